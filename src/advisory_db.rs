@@ -0,0 +1,272 @@
+//! Support for local advisory databases.
+//!
+//! A local advisory database lets `known-vulnerable-actions` run without
+//! access to GitHub's advisories API, e.g. in air-gapped CI. The database
+//! is a JSON object keyed by `owner/repo`, where each key maps to a list
+//! of advisories affecting that action:
+//!
+//! ```json
+//! {
+//!   "actions/download-artifact": [
+//!     {
+//!       "ghsa_id": "GHSA-cxww-7g56-2vh6",
+//!       "severity": "high",
+//!       "vulnerable_version_range": ">= 4.0.0, < 4.1.7"
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! Version ranges use the same syntax as GitHub's advisories: a
+//! comma-separated list of constraints, each of which is a version
+//! optionally preceded by one of `<`, `<=`, `>`, `>=`, or `=`.
+
+use std::{cmp::Ordering, collections::HashMap, fs, str::FromStr};
+
+use anyhow::{anyhow, Context as _, Result};
+use camino::Utf8Path;
+use serde::{de, Deserialize};
+
+use crate::github_api::Advisory;
+
+/// A dotted numeric version, e.g. `1.2.3` or `v1.2.3`.
+#[derive(Clone, Debug)]
+struct Version(Vec<u64>);
+
+impl FromStr for Version {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let components = s
+            .strip_prefix(['v', 'V'])
+            .unwrap_or(s)
+            .split('.')
+            .map(u64::from_str)
+            .collect::<Result<Vec<_>, _>>()
+            .with_context(|| format!("invalid version: {s}"))?;
+
+        Ok(Self(components))
+    }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Version {}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Missing components are treated as zero, i.e. `1.2` == `1.2.0`.
+        let len = self.0.len().max(other.0.len());
+        let component = |v: &Self, idx| v.0.get(idx).copied().unwrap_or(0);
+
+        (0..len)
+            .map(|idx| component(self, idx).cmp(&component(other, idx)))
+            .find(|ord| ord.is_ne())
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Op {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+}
+
+/// A GHSA-style vulnerable version range, e.g. `>= 4.0.0, < 4.1.7`.
+#[derive(Clone, Debug)]
+struct VersionRange(Vec<(Op, Version)>);
+
+impl FromStr for VersionRange {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let constraints = s
+            .split(',')
+            .map(|constraint| {
+                let constraint = constraint.trim();
+                let (op, version) = [
+                    ("<=", Op::Le),
+                    (">=", Op::Ge),
+                    ("<", Op::Lt),
+                    (">", Op::Gt),
+                    ("=", Op::Eq),
+                ]
+                .into_iter()
+                .find_map(|(prefix, op)| constraint.strip_prefix(prefix).map(|rest| (op, rest)))
+                .unwrap_or((Op::Eq, constraint));
+
+                Ok((op, version.trim().parse()?))
+            })
+            .collect::<Result<Vec<_>>>()
+            .with_context(|| format!("invalid version range: {s}"))?;
+
+        Ok(Self(constraints))
+    }
+}
+
+impl<'de> Deserialize<'de> for VersionRange {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let raw = String::deserialize(deserializer)?;
+        VersionRange::from_str(&raw).map_err(|e| de::Error::custom(format!("{e:#}")))
+    }
+}
+
+impl VersionRange {
+    /// Returns whether the given version satisfies every constraint
+    /// in this range.
+    fn contains(&self, version: &Version) -> bool {
+        self.0.iter().all(|(op, bound)| match op {
+            Op::Lt => version < bound,
+            Op::Le => version <= bound,
+            Op::Gt => version > bound,
+            Op::Ge => version >= bound,
+            Op::Eq => version == bound,
+        })
+    }
+}
+
+#[derive(Clone, Deserialize)]
+struct Entry {
+    #[serde(flatten)]
+    advisory: Advisory,
+    vulnerable_version_range: VersionRange,
+}
+
+/// A local database of GitHub Actions advisories.
+#[derive(Clone, Default)]
+pub struct AdvisoryDb {
+    /// Advisories, keyed by lowercased `owner/repo`.
+    advisories: HashMap<String, Vec<Entry>>,
+}
+
+impl AdvisoryDb {
+    /// Loads the advisory database at the given path.
+    pub fn load(path: &Utf8Path) -> Result<Self> {
+        let contents = fs::read_to_string(path).with_context(|| format!("couldn't read {path}"))?;
+
+        Self::parse(&contents).with_context(|| format!("invalid advisory database: {path}"))
+    }
+
+    fn parse(contents: &str) -> Result<Self> {
+        let advisories = serde_json::from_str::<HashMap<String, Vec<Entry>>>(contents)?
+            .into_iter()
+            .map(|(slug, entries)| {
+                if slug.split('/').count() != 2 {
+                    return Err(anyhow!("invalid action slug: {slug} (expected owner/repo)"));
+                }
+
+                Ok((slug.to_lowercase(), entries))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self { advisories })
+    }
+
+    /// Returns the advisories that affect the given action at the given
+    /// version.
+    ///
+    /// Only full versions (like `v1.2.3`) can match. Partial versions
+    /// (like `v1`) typically move between releases, and non-versions
+    /// (like branch names) can't be compared at all.
+    pub(crate) fn advisories(&self, owner: &str, repo: &str, version: &str) -> Vec<Advisory> {
+        let Ok(version) = version.parse::<Version>() else {
+            return vec![];
+        };
+
+        if version.0.len() < 3 {
+            return vec![];
+        }
+
+        self.advisories
+            .get(&format!("{owner}/{repo}").to_lowercase())
+            .into_iter()
+            .flatten()
+            .filter(|entry| entry.vulnerable_version_range.contains(&version))
+            .map(|entry| entry.advisory.clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AdvisoryDb, Version, VersionRange};
+
+    #[test]
+    fn test_version_range() {
+        for (range, version, expected) in [
+            (">= 4.0.0, < 4.1.7", "4.0.0", true),
+            (">= 4.0.0, < 4.1.7", "v4.1.6", true),
+            (">= 4.0.0, < 4.1.7", "4.1.7", false),
+            (">= 4.0.0, < 4.1.7", "3.9.9", false),
+            ("< 2", "1.99.0", true),
+            ("< 2", "2.0.0", false),
+            ("<= 1.2", "1.2.0", true),
+            ("= 1.0.1", "1.0.1", true),
+            ("1.0.1", "1.0.2", false),
+            ("> 1.0.0", "1.0.0", false),
+        ] {
+            let range = range.parse::<VersionRange>().unwrap();
+            let version = version.parse::<Version>().unwrap();
+            assert_eq!(range.contains(&version), expected, "failed: {range:?}");
+        }
+
+        assert!("< 1.x".parse::<VersionRange>().is_err());
+        assert!(">= 1.0.0,".parse::<VersionRange>().is_err());
+    }
+
+    #[test]
+    fn test_advisories() {
+        let db = AdvisoryDb::parse(
+            r#"
+{
+  "Actions/Download-Artifact": [
+    {
+      "ghsa_id": "GHSA-cxww-7g56-2vh6",
+      "severity": "high",
+      "vulnerable_version_range": ">= 4.0.0, < 4.1.7"
+    }
+  ]
+}
+"#,
+        )
+        .unwrap();
+
+        let ids = |owner, repo, version| {
+            db.advisories(owner, repo, version)
+                .into_iter()
+                .map(|advisory| advisory.ghsa_id)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            ids("actions", "download-artifact", "v4.1.0"),
+            ["GHSA-cxww-7g56-2vh6"]
+        );
+        assert!(ids("actions", "download-artifact", "v4.1.7").is_empty());
+        assert!(ids("actions", "download-artifact", "v4").is_empty());
+        assert!(ids("actions", "download-artifact", "main").is_empty());
+        assert!(ids("actions", "checkout", "v4.1.0").is_empty());
+
+        assert!(AdvisoryDb::parse(r#"{"checkout": []}"#).is_err());
+        assert!(AdvisoryDb::parse(
+            r#"{"a/b": [{"ghsa_id": "x", "severity": "low", "vulnerable_version_range": "?"}]}"#
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,273 @@
+//! Detects `if:` conditions whose only security gate is a check against
+//! `github.actor` or `github.triggering_actor`.
+//!
+//! The actor is whoever last acted on the triggering context, which isn't
+//! necessarily the person whose changes are being run. Depending on the
+//! trigger, an attacker can get a trusted actor to re-run or otherwise act
+//! on their changes, which makes actor checks a weak access control on
+//! their own.
+
+use github_actions_models::common::{expr::ExplicitExpr, If};
+
+use super::{audit_meta, Audit};
+use crate::{
+    expr::{self, Context, Expr},
+    finding::{Confidence, Finding, Persona, Severity},
+    models::{JobExt as _, NormalJob},
+    state::AuditState,
+    utils::{is_trusted_comparison, TRUSTED_CONDITION_CONTEXTS},
+};
+
+/// Contexts that identify the actor of a workflow run.
+const ACTOR_CONTEXTS: &[&str] = &["github.actor", "github.triggering_actor"];
+
+pub(crate) struct ActorConditions;
+
+audit_meta!(
+    ActorConditions,
+    "actor-conditions",
+    "condition gated only on the triggering actor",
+    rationale = "
+        Flags if: conditions whose only security-relevant check compares
+        github.actor or github.triggering_actor against a fixed user.
+
+        The actor is whoever last acted on the triggering context, not necessarily
+        the author of the changes being run. Depending on the trigger, an attacker
+        can get a trusted user to re-run or otherwise act on their changes, making
+        the condition true while running the attacker's code.
+
+        Findings are low severity, since the impact depends on what's being
+        guarded. Confidence is high when the actor check is the entire condition,
+        and medium when it's part of a larger expression. This audit is only shown
+        with the pedantic persona.
+    ",
+    example = r#"
+        on: pull_request_target
+
+        jobs:
+          deploy:
+            runs-on: ubuntu-latest
+            if: github.actor == 'some-maintainer'
+            steps:
+              - run: ./deploy.sh
+    "#,
+    remediation = "
+        Gate privileged jobs on a deployment environment with required reviewers,
+        or on a check that only maintainers can satisfy, such as a label or the
+        author's association with the repository.
+    ",
+);
+
+impl ActorConditions {
+    fn is_actor(ctx: &Context) -> bool {
+        ACTOR_CONTEXTS.iter().any(|actor| ctx == *actor)
+    }
+
+    /// Returns the actor context checked by the given expression, if the
+    /// expression itself is an actor check.
+    ///
+    /// Actor checks are either comparisons against a literal user, or
+    /// allowlist checks like `contains(fromJSON('[...]'), github.actor)`.
+    fn actor_check<'a>(expr: &'a Expr) -> Option<&'a str> {
+        match expr {
+            Expr::BinOp {
+                lhs,
+                op: expr::BinOp::Eq | expr::BinOp::Neq,
+                rhs,
+            } => match (lhs.as_ref(), rhs.as_ref()) {
+                (Expr::Context(ctx), Expr::String(user))
+                | (Expr::String(user), Expr::Context(ctx))
+                    // Checks against bots are bot-conditions' concern.
+                    if Self::is_actor(ctx) && !user.ends_with("[bot]") =>
+                {
+                    Some(ctx.as_str())
+                }
+                _ => None,
+            },
+            Expr::Call { func, args } if func.eq_ignore_ascii_case("contains") => {
+                match args.as_slice() {
+                    [_, Expr::Context(ctx)] if Self::is_actor(ctx) => Some(ctx.as_str()),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Walks the given expression, collecting each actor check.
+    ///
+    /// Returns whether the expression also checks something that establishes
+    /// trust independently of the actor, i.e. a context that only maintainers
+    /// can influence, or a comparison that `controllable-conditions` considers
+    /// trusted (like `github.event.pull_request.head.repo.full_name ==
+    /// github.repository`).
+    fn walk_tree<'a>(expr: &'a Expr, checks: &mut Vec<&'a str>) -> bool {
+        if let Some(actor) = Self::actor_check(expr) {
+            checks.push(actor);
+            return false;
+        }
+
+        match expr {
+            Expr::Call { func: _, args } => {
+                // Every argument needs to be walked, so don't short-circuit.
+                let mut trusted = false;
+                for arg in args {
+                    trusted |= Self::walk_tree(arg, checks);
+                }
+
+                trusted
+            }
+            Expr::Context(ctx) => match ctx.components().first() {
+                // Like `Expr::contexts`, only the call's arguments are
+                // well-known contexts in `foo(args).a.b.c`.
+                Some(call @ Expr::Call { .. }) => Self::walk_tree(call, checks),
+                _ => TRUSTED_CONDITION_CONTEXTS
+                    .iter()
+                    .any(|trusted| ctx == *trusted),
+            },
+            Expr::Index(expr) => Self::walk_tree(expr, checks),
+            Expr::BinOp {
+                lhs,
+                op: expr::BinOp::Eq | expr::BinOp::Neq,
+                rhs,
+            } if is_trusted_comparison(lhs, rhs) => true,
+            Expr::BinOp { lhs, op: _, rhs } => {
+                let trusted_lhs = Self::walk_tree(lhs, checks);
+                let trusted_rhs = Self::walk_tree(rhs, checks);
+
+                trusted_lhs || trusted_rhs
+            }
+            Expr::UnOp { op: _, expr } => Self::walk_tree(expr, checks),
+            _ => false,
+        }
+    }
+
+    /// Returns the actor context that solely gates the given condition,
+    /// along with a confidence.
+    fn actor_condition(cond: &str) -> Option<(String, Confidence)> {
+        let bare = match ExplicitExpr::from_curly(cond) {
+            Some(raw_expr) => raw_expr.as_bare().to_string(),
+            None => cond.to_string(),
+        };
+
+        let Ok(expr) = Expr::parse(&bare) else {
+            tracing::warn!("couldn't parse expression: {cond}");
+            return None;
+        };
+
+        let mut checks = vec![];
+        if Self::walk_tree(&expr, &mut checks) {
+            return None;
+        }
+
+        let actor = checks.first()?;
+        let confidence = if Self::actor_check(&expr).is_some() {
+            Confidence::High
+        } else {
+            Confidence::Medium
+        };
+
+        Some((actor.to_string(), confidence))
+    }
+}
+
+impl Audit for ActorConditions {
+    fn new(_state: AuditState) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        Ok(Self)
+    }
+
+    fn audit_normal_job<'w>(&self, job: &NormalJob<'w>) -> anyhow::Result<Vec<Finding<'w>>> {
+        let mut findings = vec![];
+
+        let mut conds = vec![];
+        if let Some(If::Expr(expr)) = &job.r#if {
+            conds.push((expr, job.location()));
+        }
+
+        for step in job.steps() {
+            if let Some(If::Expr(expr)) = &step.r#if {
+                conds.push((expr, step.location()));
+            }
+        }
+
+        for (expr, loc) in conds {
+            if let Some((actor, confidence)) = Self::actor_condition(expr) {
+                findings.push(
+                    Self::finding()
+                        .severity(Severity::Low)
+                        .confidence(confidence)
+                        .persona(Persona::Pedantic)
+                        .add_location(
+                            loc.with_keys(&["if".into()])
+                                .primary()
+                                .annotated(format!("condition is only gated on {actor}")),
+                        )
+                        .build(job.parent())?,
+                );
+            }
+        }
+
+        Ok(findings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ActorConditions;
+    use crate::finding::Confidence;
+
+    #[test]
+    fn test_actor_condition() {
+        for (cond, expected) in &[
+            ("github.event_name == 'push'", None),
+            ("github.actor == 'dependabot[bot]'", None),
+            (
+                "github.actor == 'octocat'",
+                Some(("github.actor", Confidence::High)),
+            ),
+            (
+                "${{ 'octocat' != github.triggering_actor }}",
+                Some(("github.triggering_actor", Confidence::High)),
+            ),
+            (
+                "contains(fromJSON('[\"octocat\", \"hubot\"]'), github.actor)",
+                Some(("github.actor", Confidence::High)),
+            ),
+            (
+                "github.event_name == 'pull_request_target' && github.actor == 'octocat'",
+                Some(("github.actor", Confidence::Medium)),
+            ),
+            (
+                "github.actor == 'octocat' && github.event.pull_request.author_association == 'OWNER'",
+                None,
+            ),
+            (
+                "github.actor == 'octocat' && !github.event.pull_request.head.repo.fork",
+                None,
+            ),
+            (
+                "contains(github.event.pull_request.labels.*.name, 'safe') || github.actor == 'octocat'",
+                None,
+            ),
+            (
+                "github.actor == 'octocat' && github.event.pull_request.head.repo.full_name == github.repository",
+                None,
+            ),
+            // The head repository's name is attacker-controllable on its own.
+            (
+                "github.actor == 'octocat' && contains(github.event.pull_request.head.repo.full_name, 'octo')",
+                Some(("github.actor", Confidence::Medium)),
+            ),
+        ] {
+            let expected = expected.map(|(ctx, confidence)| (ctx.to_string(), confidence));
+            assert_eq!(
+                ActorConditions::actor_condition(cond),
+                expected,
+                "failed: {cond}"
+            );
+        }
+    }
+}
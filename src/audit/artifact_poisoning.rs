@@ -0,0 +1,139 @@
+//! Detects `workflow_run` workflows that download artifacts from the
+//! triggering workflow run and then execute code.
+//!
+//! `workflow_run` workflows run in the privileged context of the base
+//! repository, even when the triggering run was for a pull request from
+//! a fork. Artifacts produced by that triggering run are fully controlled
+//! by the pull request's author, so unpacking them and then running a
+//! build step or local action can execute attacker-controlled code with
+//! access to the base repository's secrets and a write-scoped token.
+
+use github_actions_models::common::{EnvValue, Uses};
+
+use super::{audit_meta, pull_request_target_checkout::PullRequestTargetCheckout, Audit};
+use crate::{
+    finding::{Confidence, Finding, Severity},
+    models::{uses::RepositoryUsesExt as _, JobExt as _, NormalJob, StepBodyCommon, StepCommon},
+    state::AuditState,
+};
+
+pub(crate) struct ArtifactPoisoning;
+
+audit_meta!(
+    ArtifactPoisoning,
+    "artifact-poisoning",
+    "execution of artifacts from a triggering workflow run",
+    rationale = "
+        Flags workflow_run workflows that download artifacts from another workflow
+        run, and then run a build command or local action.
+
+        workflow_run workflows run with the base repository's secrets and a
+        potentially write-scoped token, even when the triggering run was for a pull
+        request from a fork. The triggering run's artifacts are fully controlled by
+        the pull request's author, so executing anything after downloading them can
+        run attacker-controlled code.
+
+        Findings are high severity, since exploitation leads to code execution in a
+        privileged context. They're medium confidence, since zizmor can't tell
+        whether the later step actually executes the downloaded artifacts.
+    ",
+    example = r#"
+        on: workflow_run
+
+        jobs:
+          publish:
+            runs-on: ubuntu-latest
+            steps:
+              - uses: actions/download-artifact@v4
+                with:
+                  run-id: ${{ github.event.workflow_run.id }}
+                  github-token: ${{ secrets.GITHUB_TOKEN }}
+              - run: make publish
+    "#,
+    remediation = "
+        Treat downloaded artifacts as untrusted data. Download them outside of the
+        workspace (e.g. into $RUNNER_TEMP), validate them before use, and never
+        execute them or run build tools alongside them.
+    ",
+);
+
+impl ArtifactPoisoning {
+    /// Returns whether the given step body downloads artifacts from
+    /// another workflow run.
+    fn downloads_artifacts(body: &StepBodyCommon) -> bool {
+        match body {
+            StepBodyCommon::Uses {
+                uses: Uses::Repository(uses),
+                with,
+            } => {
+                // actions/download-artifact only downloads from another
+                // run when explicitly given a `run-id`.
+                (uses.matches("actions/download-artifact") && with.contains_key("run-id"))
+                    || uses.matches("dawidd6/action-download-artifact")
+                    || (uses.matches("actions/github-script")
+                        && matches!(
+                            with.get("script"),
+                            Some(EnvValue::String(script)) if script.contains("downloadArtifact")
+                        ))
+            }
+            StepBodyCommon::Run { run, .. } => run.contains("gh run download"),
+            _ => false,
+        }
+    }
+}
+
+impl Audit for ArtifactPoisoning {
+    fn new(_state: AuditState) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        Ok(Self)
+    }
+
+    fn audit_normal_job<'w>(&self, job: &NormalJob<'w>) -> anyhow::Result<Vec<Finding<'w>>> {
+        let mut findings = vec![];
+
+        if !job.parent().has_workflow_run() {
+            return Ok(findings);
+        }
+
+        for download in job.steps() {
+            if !Self::downloads_artifacts(&download.body()) {
+                continue;
+            }
+
+            // Like with pull-request-target-checkout, local actions and
+            // build commands both execute code from the working tree,
+            // which now contains the downloaded artifacts.
+            let Some(execution) = PullRequestTargetCheckout::next_build_step(job, &download) else {
+                continue;
+            };
+
+            findings.push(
+                Self::finding()
+                    .severity(Severity::High)
+                    .confidence(Confidence::Medium)
+                    .add_location(
+                        download
+                            .location_with_name()
+                            .primary()
+                            .annotated("downloads artifacts from another workflow run"),
+                    )
+                    .add_location(
+                        execution
+                            .location_with_name()
+                            .annotated("downloaded artifacts may be executed here"),
+                    )
+                    .add_location(
+                        job.parent()
+                            .location()
+                            .with_keys(&["on".into()])
+                            .annotated("privileged trigger"),
+                    )
+                    .build(job.parent())?,
+            );
+        }
+
+        Ok(findings)
+    }
+}
@@ -1,26 +1,67 @@
-use std::ops::Deref as _;
+use std::{ops::Deref as _, sync::LazyLock};
 
 use anyhow::Result;
 use github_actions_models::{
     common::{expr::ExplicitExpr, EnvValue, Uses},
     workflow::job::StepBody,
 };
-use itertools::Itertools as _;
+use regex::Regex;
 
 use super::{audit_meta, Audit};
 use crate::utils::split_patterns;
 use crate::{
     finding::{Confidence, Finding, Persona, Severity},
-    models::{uses::RepositoryUsesExt as _, JobExt},
+    models::{uses::RepositoryUsesExt as _, JobExt, NormalJob, Step},
     state::AuditState,
 };
 
+/// Matches commands that push to the repository, i.e. `git push` (including
+/// with global options like `git -C dir push`) and `gh` subcommands that
+/// create or update refs on the remote.
+static PUSH_INDICATOR: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"\bgit(?:[ \t]+(?:-[cC][ \t]+\S+|--?[\w-]+(?:=\S+)?))*[ \t]+push\b|\bgh[ \t]+(?:pr[ \t]+(?:create|merge)|release[ \t]+(?:create|upload)|repo[ \t]+sync)\b",
+    )
+    .unwrap()
+});
+
 pub(crate) struct Artipacked;
 
 audit_meta!(
     Artipacked,
     "artipacked",
-    "credential persistence through GitHub Actions artifacts"
+    "credential persistence through GitHub Actions artifacts",
+    rationale = "
+        Flags uses of actions/checkout that don't set persist-credentials: false.
+
+        By default, actions/checkout persists the workflow's credentials in the
+        checked-out repository's .git/config. If the repository is then uploaded as
+        an artifact (e.g. with a path of '.'), those credentials are leaked to
+        anyone who can download the artifact.
+
+        Findings are high severity and high confidence when a vulnerable checkout is
+        followed by an upload of the checkout's directory, and medium severity with
+        low confidence when there's only a checkout. When a later run: step pushes
+        to the repository (e.g. with git push), the persisted credentials are
+        available to every step in between, so confidence is raised to medium.
+
+        Separately, uploads of the entire workspace are flagged regardless of any
+        checkout, since they can expose .env files, build outputs and other secrets.
+        These are low severity, or medium when include-hidden-files: true.
+    ",
+    example = r#"
+        steps:
+          - uses: actions/checkout@v4
+          - uses: actions/upload-artifact@v4
+            with:
+              path: .
+    "#,
+    remediation = "
+        Set persist-credentials: false on every actions/checkout step. If a later
+        step needs git credentials, pass them to that step explicitly.
+
+        Upload only the specific files or directories that the artifact needs.
+    ",
 );
 
 impl Artipacked {
@@ -43,6 +84,35 @@ impl Artipacked {
 
         patterns
     }
+
+    /// Returns whether the given `run:` block pushes to the repository.
+    fn pushes(run: &str) -> bool {
+        PUSH_INDICATOR.is_match(run)
+    }
+
+    fn push_finding<'w>(
+        job: &NormalJob<'w>,
+        checkout: &Step<'w>,
+        push: &Step<'w>,
+        persona: Persona,
+    ) -> Result<Finding<'w>> {
+        Self::finding()
+            .severity(Severity::Medium)
+            .confidence(Confidence::Medium)
+            .persona(persona)
+            .add_location(
+                checkout
+                    .location()
+                    .primary()
+                    .annotated("does not set persist-credentials: false"),
+            )
+            .add_location(
+                push.location()
+                    .with_keys(&["run".into()])
+                    .annotated("pushes with the credentials persisted above"),
+            )
+            .build(job.parent())
+    }
 }
 
 impl Audit for Artipacked {
@@ -50,13 +120,21 @@ impl Audit for Artipacked {
         Ok(Self)
     }
 
-    fn audit_normal_job<'w>(&self, job: &super::NormalJob<'w>) -> Result<Vec<Finding<'w>>> {
+    fn audit_normal_job<'w>(&self, job: &NormalJob<'w>) -> Result<Vec<Finding<'w>>> {
         let mut findings = vec![];
 
         // First, collect all vulnerable checkouts and upload steps independently.
         let mut vulnerable_checkouts = vec![];
         let mut vulnerable_uploads = vec![];
+        let mut pushes = vec![];
         for step in job.steps() {
+            if let StepBody::Run { run, .. } = &step.deref().body {
+                if Self::pushes(run) {
+                    pushes.push(step);
+                }
+                continue;
+            }
+
             let StepBody::Uses {
                 uses: Uses::Repository(ref uses),
                 ref with,
@@ -88,16 +166,60 @@ impl Audit for Artipacked {
 
                 let dangerous_paths = self.dangerous_artifact_patterns(path);
                 if !dangerous_paths.is_empty() {
+                    // Independent of any persisted credentials, uploading
+                    // the entire workspace exposes everything else in it.
+                    // Newer versions of upload-artifact exclude hidden files
+                    // (like .git and .env) unless explicitly included.
+                    let includes_hidden = with
+                        .get("include-hidden-files")
+                        .is_some_and(|v| v.to_string() == "true");
+
+                    findings.push(
+                        Self::finding()
+                            .severity(if includes_hidden {
+                                Severity::Medium
+                            } else {
+                                Severity::Low
+                            })
+                            .confidence(Confidence::High)
+                            .add_location(step.location_with_name())
+                            .add_location(
+                                step.location()
+                                    .primary()
+                                    .with_keys(&["with".into(), "path".into()])
+                                    .annotated(if includes_hidden {
+                                        "uploads the entire workspace, including hidden files"
+                                    } else {
+                                        "uploads the entire workspace"
+                                    }),
+                            )
+                            .build(job.parent())?,
+                    );
+
                     // TODO: plumb dangerous_paths into the annotation here.
-                    vulnerable_uploads.push(step)
+                    vulnerable_uploads.push(step);
                 }
             }
         }
 
+        // A checkout's persisted credentials are also available to every
+        // step between it and a later step that pushes with them.
+        let push_after = |checkout: &Step<'w>| {
+            pushes
+                .iter()
+                .find(|push| checkout.index < push.index)
+                .cloned()
+        };
+
         if vulnerable_uploads.is_empty() {
             // If we have no vulnerable uploads, then emit lower-confidence
             // findings for just the checkout steps.
             for (checkout, persona) in vulnerable_checkouts {
+                if let Some(push) = push_after(&checkout) {
+                    findings.push(Self::push_finding(job, &checkout, &push, persona)?);
+                    continue;
+                }
+
                 findings.push(
                     Self::finding()
                         .severity(Severity::Medium)
@@ -114,13 +236,21 @@ impl Audit for Artipacked {
             }
         } else {
             // Select only pairs where the vulnerable checkout precedes the
-            // vulnerable upload. There are more efficient ways to do this than
-            // a cartesian product, but this way is simple.
-            for ((checkout, persona), upload) in vulnerable_checkouts
-                .into_iter()
-                .cartesian_product(vulnerable_uploads.into_iter())
-            {
-                if checkout.index < upload.index {
+            // vulnerable upload.
+            for (checkout, persona) in vulnerable_checkouts {
+                let uploads = vulnerable_uploads
+                    .iter()
+                    .filter(|upload| checkout.index < upload.index)
+                    .collect::<Vec<_>>();
+
+                if uploads.is_empty() {
+                    if let Some(push) = push_after(&checkout) {
+                        findings.push(Self::push_finding(job, &checkout, &push, persona)?);
+                    }
+                    continue;
+                }
+
+                for upload in uploads {
                     findings.push(
                         Self::finding()
                             .severity(Severity::High)
@@ -146,3 +276,28 @@ impl Audit for Artipacked {
         Ok(findings)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Artipacked;
+
+    #[test]
+    fn test_pushes() {
+        for (run, pushes) in &[
+            ("echo hello", false),
+            ("git pull", false),
+            ("git commit -m 'push it'", false),
+            ("git push", true),
+            ("git push origin HEAD:main", true),
+            ("git add . && git commit -m update && git push", true),
+            ("git -C docs push", true),
+            ("git -c user.name=bot --no-pager push --force", true),
+            ("gh pr create --fill", true),
+            ("gh release upload v1.0.0 dist/*", true),
+            ("gh pr view 123", false),
+            ("github push", false),
+        ] {
+            assert_eq!(Artipacked::pushes(run), *pushes, "failed: {run}");
+        }
+    }
+}
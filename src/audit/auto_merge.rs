@@ -0,0 +1,207 @@
+//! Detects steps that merge (or enable auto-merge for) pull requests.
+//!
+//! Workflows that merge pull requests automatically, e.g. for Dependabot
+//! updates, are only as safe as the conditions that gate them. When such
+//! a workflow can be triggered by a pull request, an attacker who can
+//! satisfy (or spoof) those conditions can get their own changes merged.
+
+use github_actions_models::common::Uses;
+use serde::Deserialize;
+
+use super::{audit_meta, Audit};
+use crate::{
+    finding::{Confidence, Persona, Severity, SymbolicLocation},
+    models::{uses::RepositoryUsesExt as _, StepBodyCommon, StepCommon},
+    state::AuditState,
+};
+
+/// Actions that merge pull requests, or enable auto-merge for them.
+const DEFAULT_ACTIONS: &[&str] = &[
+    "ahmadnassri/action-dependabot-auto-merge",
+    "fastify/github-action-merge-dependabot",
+    "pascalgn/automerge-action",
+    "peter-evans/enable-pull-request-automerge",
+];
+
+/// Commands that merge pull requests, or enable auto-merge for them.
+const DEFAULT_COMMANDS: &[&str] = &["gh pr merge"];
+
+/// Audit-specific settings, under `rules.auto-merge.config`.
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct AutoMergeConfig {
+    /// Additional actions that merge pull requests.
+    #[serde(default)]
+    actions: Vec<String>,
+    /// Additional commands that merge pull requests.
+    #[serde(default)]
+    commands: Vec<String>,
+}
+
+pub(crate) struct AutoMerge {
+    actions: Vec<String>,
+    commands: Vec<Vec<String>>,
+}
+
+audit_meta!(
+    AutoMerge,
+    "auto-merge",
+    "step merges or auto-merges pull requests",
+    rationale = "
+        Flags steps that merge pull requests or enable auto-merge for them, e.g.
+        with gh pr merge or peter-evans/enable-pull-request-automerge.
+
+        Automatic merges are only as safe as the conditions that gate them. When
+        the workflow can be triggered by a pull request, an attacker who can
+        satisfy (or spoof) those conditions can get their own changes merged.
+
+        Findings are medium severity in workflows with pull request triggers (or
+        workflow_run, which is commonly chained from them), and low severity
+        otherwise. They have low confidence, and are only shown with the auditor
+        persona. Additional actions and commands can be configured under
+        rules.auto-merge.config.
+    ",
+    example = r#"
+        on: pull_request_target
+
+        jobs:
+          automerge:
+            if: github.actor == 'dependabot[bot]'
+            runs-on: ubuntu-latest
+            steps:
+              - run: gh pr merge --auto --squash "$PR_URL"
+    "#,
+    remediation = "
+        Make sure that automatic merges are gated on conditions that an attacker
+        can't satisfy, e.g. by checking github.event.pull_request.user.login rather
+        than github.actor, and by requiring a human review through branch
+        protection.
+    ",
+);
+
+impl AutoMerge {
+    /// Returns whether `line` runs the given command, i.e. contains the
+    /// command's words in sequence.
+    fn runs_command(line: &str, command: &[String]) -> bool {
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        !command.is_empty() && words.windows(command.len()).any(|window| window == command)
+    }
+
+    /// Returns an annotated location for the step if it uses a merging
+    /// action, or for each line in its `run:` script that runs a merging
+    /// command.
+    fn merge_locations<'s>(&self, step: &impl StepCommon<'s>) -> Vec<SymbolicLocation<'s>> {
+        match step.body() {
+            StepBodyCommon::Uses {
+                uses: Uses::Repository(uses),
+                ..
+            } => self
+                .actions
+                .iter()
+                .find(|action| uses.matches(action))
+                .map(|action| {
+                    step.location()
+                        .with_keys(&["uses".into()])
+                        .annotated(format!("{action} merges pull requests"))
+                })
+                .into_iter()
+                .collect(),
+            StepBodyCommon::Run { run, .. } => run
+                .lines()
+                .filter(|line| {
+                    self.commands
+                        .iter()
+                        .any(|command| Self::runs_command(line, command))
+                })
+                .map(|line| {
+                    step.location()
+                        .with_keys(&["run".into()])
+                        .annotated(format!("`{line}` merges pull requests", line = line.trim()))
+                })
+                .collect(),
+            _ => vec![],
+        }
+    }
+}
+
+impl Audit for AutoMerge {
+    fn new(state: AuditState) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        let config = state
+            .config
+            .rule_config::<AutoMergeConfig>(Self::ident())?
+            .unwrap_or_default();
+
+        let actions = DEFAULT_ACTIONS
+            .iter()
+            .map(|action| action.to_string())
+            .chain(config.actions)
+            .collect();
+
+        let commands = DEFAULT_COMMANDS
+            .iter()
+            .map(|command| command.to_string())
+            .chain(config.commands)
+            .map(|command| command.split_whitespace().map(Into::into).collect())
+            .collect();
+
+        Ok(Self { actions, commands })
+    }
+
+    fn audit_step<'w>(&self, step: &super::Step<'w>) -> anyhow::Result<Vec<super::Finding<'w>>> {
+        let mut findings = vec![];
+
+        let workflow = step.workflow();
+        let severity = if workflow.has_pull_request()
+            || workflow.has_pull_request_target()
+            || workflow.has_fork_pull_request()
+            || workflow.has_workflow_run()
+        {
+            Severity::Medium
+        } else {
+            Severity::Low
+        };
+
+        for location in self.merge_locations(step) {
+            findings.push(
+                Self::finding()
+                    .severity(severity)
+                    .confidence(Confidence::Low)
+                    .persona(Persona::Auditor)
+                    .add_location(step.location_with_name())
+                    .add_location(location.primary())
+                    .build(workflow)?,
+            );
+        }
+
+        Ok(findings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AutoMerge;
+
+    #[test]
+    fn test_runs_command() {
+        let command = ["gh", "pr", "merge"].map(String::from);
+
+        for (line, expected) in [
+            ("gh pr merge --auto --squash \"$PR_URL\"", true),
+            ("  gh   pr merge 123", true),
+            ("if gh pr merge \"$PR\"; then", true),
+            ("gh pr view \"$PR\"", false),
+            ("gh pr review --approve \"$PR\"", false),
+            ("echo gh pr", false),
+            ("git merge main", false),
+        ] {
+            assert_eq!(
+                AutoMerge::runs_command(line, &command),
+                expected,
+                "failed: {line}"
+            );
+        }
+    }
+}
@@ -9,7 +9,38 @@ use crate::{
 
 pub(crate) struct BotConditions;
 
-audit_meta!(BotConditions, "bot-conditions", "spoofable bot actor check");
+audit_meta!(
+    BotConditions,
+    "bot-conditions",
+    "spoofable bot actor check",
+    rationale = "
+        Flags if: conditions that check github.actor against a bot's name, such as
+        dependabot[bot].
+
+        github.actor refers to the last actor to act on the triggering context,
+        not necessarily the one who created it. An attacker can cause a bot to act
+        on their pull request (e.g. by asking Dependabot to rebase it), making the
+        condition true while running the attacker's changes.
+
+        Findings are high severity, since these conditions usually guard privileged
+        operations like auto-merging. Confidence is high when the check is the
+        entire condition, and medium when it's part of a larger expression.
+    ",
+    example = r#"
+        on: pull_request_target
+
+        jobs:
+          automerge:
+            runs-on: ubuntu-latest
+            if: github.actor == 'dependabot[bot]'
+            steps:
+              - run: gh pr merge --auto --merge "$PR_URL"
+    "#,
+    remediation = "
+        Check a context that can't be influenced by other actors, such as
+        github.event.pull_request.user.login, instead of github.actor.
+    ",
+);
 
 impl Audit for BotConditions {
     fn new(_state: super::AuditState) -> anyhow::Result<Self>
@@ -0,0 +1,172 @@
+//! Detects composite action steps that invoke scripts bundled with the
+//! action (via `${{ github.action_path }}`) with attacker-controllable
+//! arguments.
+//!
+//! Bundled scripts frequently live outside of the repository being audited,
+//! so their handling of arguments is opaque to us. Passing them
+//! attacker-controllable values directly is a smell, since any injection
+//! or argument confusion in the script is reachable by the attacker.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use super::{audit_meta, Audit};
+use crate::{
+    finding::{Confidence, Severity},
+    models::{StepBodyCommon, StepCommon as _},
+    state::AuditState,
+    utils::{expanded_contexts, is_controllable},
+};
+
+/// Matches a reference to the action's own path, either via the
+/// `github.action_path` context or the `GITHUB_ACTION_PATH` variable.
+static ACTION_PATH: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\$\{\{\s*(?i:github\.action_path)\s*\}\}|\$\{?GITHUB_ACTION_PATH\b\}?").unwrap()
+});
+
+pub(crate) struct BundledScriptArgs;
+
+audit_meta!(
+    BundledScriptArgs,
+    "bundled-script-args",
+    "attacker-controllable arguments to bundled script",
+    rationale = "
+        Flags composite action steps that pass potentially attacker-controllable
+        values, such as action inputs, as arguments to a script bundled with the
+        action (i.e. via github.action_path).
+
+        Depending on how the bundled script handles its arguments, this can lead to
+        argument or code injection.
+
+        Findings are informational with low confidence, since zizmor can't analyze
+        how the script handles its arguments.
+    ",
+    example = r#"
+        runs:
+          using: composite
+          steps:
+            - run: ${{ github.action_path }}/script.sh ${{ inputs.name }}
+              shell: bash
+    "#,
+    remediation = "
+        Pass the value through an environment variable instead, and confirm that
+        the script treats it as data rather than as options or code.
+    ",
+);
+
+impl BundledScriptArgs {
+    /// Returns each attacker-controllable context that's expanded into
+    /// the arguments of a bundled script invocation within `run`.
+    fn controllable_script_args(run: &str) -> Vec<String> {
+        let mut contexts = vec![];
+
+        // Join shell line continuations, so that each line is a
+        // single logical command.
+        for line in run.replace("\\\n", " ").lines() {
+            let Some(action_path) = ACTION_PATH.find(line) else {
+                continue;
+            };
+
+            // Unlike in workflows, an action's own inputs are also
+            // attacker-controllable, since any caller can set them.
+            contexts.extend(expanded_contexts(&line[action_path.end()..], |ctx| {
+                ctx.child_of("inputs") || is_controllable(ctx)
+            }));
+        }
+
+        contexts
+    }
+}
+
+impl Audit for BundledScriptArgs {
+    fn new(_state: AuditState) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        Ok(Self)
+    }
+
+    fn audit_composite_step<'a>(
+        &self,
+        step: &super::CompositeStep<'a>,
+    ) -> anyhow::Result<Vec<super::Finding<'a>>> {
+        let mut findings = vec![];
+
+        let StepBodyCommon::Run { run, .. } = step.body() else {
+            return Ok(findings);
+        };
+
+        for context in Self::controllable_script_args(run) {
+            findings.push(
+                Self::finding()
+                    .severity(Severity::Informational)
+                    .confidence(Confidence::Low)
+                    .add_location(step.location_with_name())
+                    .add_location(
+                        step.location()
+                            .with_keys(&["run".into()])
+                            .primary()
+                            .annotated(format!("{context} is passed to a bundled script")),
+                    )
+                    .build(step.action())?,
+            );
+        }
+
+        Ok(findings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BundledScriptArgs;
+
+    #[test]
+    fn test_controllable_script_args() {
+        for (run, expected) in &[
+            ("${{ github.action_path }}/run.sh", vec![]),
+            ("${{ github.action_path }}/run.sh \"$INPUT\"", vec![]),
+            (
+                "${{ github.action_path }}/run.sh ${{ inputs.foo }}",
+                vec!["inputs.foo"],
+            ),
+            (
+                "bash ${{ GitHub.Action_Path }}/run.sh '${{ inputs.foo }}'",
+                vec!["inputs.foo"],
+            ),
+            (
+                "$GITHUB_ACTION_PATH/run.sh ${{ inputs.foo }}",
+                vec!["inputs.foo"],
+            ),
+            (
+                "${GITHUB_ACTION_PATH}/run.sh \\\n  --title ${{ github.event.issue.title }}",
+                vec!["github.event.issue.title"],
+            ),
+            (
+                "${{ github.action_path }}/run.sh ${{ github.event.issue.number }}",
+                vec![],
+            ),
+            (
+                "${{ github.action_path }}/run.sh ${{ github.head_ref }}",
+                vec!["github.head_ref"],
+            ),
+            // Expressions before the script path aren't arguments to it.
+            (
+                "FOO=${{ inputs.foo }} ${{ github.action_path }}/run.sh",
+                vec![],
+            ),
+            // Not a bundled script.
+            ("./run.sh ${{ inputs.foo }}", vec![]),
+            (
+                "echo ${{ inputs.foo }}\n${{ github.action_path }}/run.sh",
+                vec![],
+            ),
+        ] {
+            assert_eq!(
+                &BundledScriptArgs::controllable_script_args(run),
+                expected,
+                "failed: {run}"
+            );
+        }
+    }
+}
@@ -6,10 +6,12 @@ use github_actions_models::workflow::event::{BareEvent, BranchFilters, OptionalB
 use github_actions_models::workflow::Trigger;
 
 use crate::audit::{audit_meta, Audit};
-use crate::finding::{Confidence, Finding, Severity};
+use crate::expr::Expr;
+use crate::finding::{Confidence, Finding, Persona, Severity};
 use crate::models::coordinate::{ActionCoordinate, Control, ControlFieldType, Toggle, Usage};
-use crate::models::{JobExt as _, NormalJob, Step, StepCommon, Steps};
+use crate::models::{JobExt as _, NormalJob, Step, StepBodyCommon, StepCommon, Steps};
 use crate::state::AuditState;
+use crate::utils::{controllable_contexts, extract_expressions};
 
 /// The list of know cache-aware actions
 /// In the future we can easily retrieve this list from the static API,
@@ -129,6 +131,26 @@ static KNOWN_CACHE_AWARE_ACTIONS: LazyLock<Vec<ActionCoordinate>> = LazyLock::ne
     ]
 });
 
+/// Actions whose cache entries are addressed by explicit `key` and
+/// `restore-keys` inputs.
+static KNOWN_CACHE_KEYED_ACTIONS: LazyLock<Vec<ActionCoordinate>> = LazyLock::new(|| {
+    vec![
+        // https://github.com/actions/cache/blob/main/action.yml
+        ActionCoordinate::NotConfigurable(Uses::from_str("actions/cache").unwrap()),
+        // https://github.com/actions/cache/blob/main/save/action.yml
+        ActionCoordinate::NotConfigurable(Uses::from_str("actions/cache/save").unwrap()),
+        // https://github.com/actions/cache/blob/main/restore/action.yml
+        ActionCoordinate::NotConfigurable(Uses::from_str("actions/cache/restore").unwrap()),
+    ]
+});
+
+/// The inputs that make up a keyed action's cache key.
+const CACHE_KEY_INPUTS: &[&str] = &["key", "restore-keys"];
+
+/// Contexts whose values can't be seen statically, and that may therefore
+/// already contain a hash of the dependency lockfile.
+const OPAQUE_KEY_CONTEXTS: &[&str] = &["env", "inputs", "needs", "steps", "vars"];
+
 /// A list of well-know publisher actions
 /// In the future we can retrieve this list from the static API
 static KNOWN_PUBLISHER_ACTIONS: LazyLock<Vec<ActionCoordinate>> = LazyLock::new(|| {
@@ -191,7 +213,51 @@ pub(crate) struct CachePoisoning;
 audit_meta!(
     CachePoisoning,
     "cache-poisoning",
-    "runtime artifacts potentially vulnerable to a cache poisoning attack"
+    "runtime artifacts potentially vulnerable to a cache poisoning attack",
+    rationale = "
+        Flags caching in workflows that publish release artifacts.
+
+        Caches are shared across a repository's workflows. An attacker who can
+        populate a cache from a less privileged workflow (e.g. one triggered by a
+        pull request) can poison the dependencies or build outputs that a release
+        workflow later restores and publishes.
+
+        Findings are high severity, since a poisoned release affects every
+        downstream user. Confidence ranges from low to medium, depending on how
+        clearly the workflow publishes artifacts and enables caching.
+
+        Separately, any workflow gets a high severity finding for each
+        actions/cache key or restore-keys value that expands an
+        attacker-controllable context, such as a pull request's branch name.
+        An attacker who controls the key can choose which cache entry a run
+        restores, or write an entry that a more privileged run later restores.
+
+        The pedantic persona also gets a low severity finding for each
+        actions/cache key that doesn't include a hashFiles() component, since
+        such keys keep serving the same (possibly stale or poisoned) cache
+        entry across dependency changes.
+    ",
+    example = r#"
+        on:
+          release:
+            types: [published]
+
+        jobs:
+          publish:
+            runs-on: ubuntu-latest
+            steps:
+              - uses: actions/setup-node@v4
+                with:
+                  cache: npm
+              - run: npm ci && npm publish
+    "#,
+    remediation = "
+        Disable caching in workflows that produce release artifacts, e.g. by
+        removing cache: from setup actions, or by setting it to false.
+
+        Build cache keys only from values that the attacker can't control, such
+        as runner.os and a hashFiles() of the lockfile.
+    ",
 );
 
 impl CachePoisoning {
@@ -301,6 +367,112 @@ impl CachePoisoning {
 
         finding.ok()
     }
+
+    /// Returns whether `expr` calls `hashFiles()` anywhere.
+    fn calls_hashfiles(expr: &Expr) -> bool {
+        match expr {
+            Expr::Call { func, args } => {
+                func.eq_ignore_ascii_case("hashFiles") || args.iter().any(Self::calls_hashfiles)
+            }
+            Expr::Index(expr) => Self::calls_hashfiles(expr),
+            Expr::Context(ctx) => ctx.components().iter().any(Self::calls_hashfiles),
+            Expr::BinOp { lhs, op: _, rhs } => {
+                Self::calls_hashfiles(lhs) || Self::calls_hashfiles(rhs)
+            }
+            Expr::UnOp { op: _, expr } => Self::calls_hashfiles(expr),
+            _ => false,
+        }
+    }
+
+    /// Returns whether the given cache key is missing a `hashFiles()`
+    /// component, i.e. doesn't change when the dependency lockfile does.
+    ///
+    /// Keys that expand opaque contexts (like `env.*` or `steps.*`) aren't
+    /// considered to be missing one, since those may contain a hash.
+    fn key_missing_hashfiles(key: &str) -> bool {
+        for (expr, _) in extract_expressions(key) {
+            let Ok(parsed) = Expr::parse(expr.as_bare()) else {
+                tracing::warn!("couldn't parse expression: {expr}", expr = expr.as_bare());
+                return false;
+            };
+
+            if Self::calls_hashfiles(&parsed)
+                || parsed.contexts().iter().any(|ctx| {
+                    OPAQUE_KEY_CONTEXTS
+                        .iter()
+                        .any(|opaque| ctx.child_of(*opaque))
+                })
+            {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    fn uses_controllable_cache_key<'w>(&self, step: &Step<'w>) -> anyhow::Result<Vec<Finding<'w>>> {
+        let mut findings = vec![];
+
+        if !KNOWN_CACHE_KEYED_ACTIONS
+            .iter()
+            .any(|coord| coord.usage(step).is_some())
+        {
+            return Ok(findings);
+        }
+
+        let StepBodyCommon::Uses { with, .. } = step.body() else {
+            return Ok(findings);
+        };
+
+        // Restore keys are prefixes that are meant to match older entries,
+        // so only the primary key needs to change with the lockfile.
+        if let Some(key) = with.get("key") {
+            if Self::key_missing_hashfiles(&key.to_string()) {
+                findings.push(
+                    Self::finding()
+                        .confidence(Confidence::Medium)
+                        .severity(Severity::Low)
+                        .persona(Persona::Pedantic)
+                        .add_location(step.location_with_name())
+                        .add_location(
+                            step.location()
+                                .primary()
+                                .with_keys(&["with".into(), "key".into()])
+                                .annotated(
+                                    "cache key doesn't include a hashFiles() of the lockfile",
+                                ),
+                        )
+                        .build(step.workflow())?,
+                );
+            }
+        }
+
+        for input in CACHE_KEY_INPUTS {
+            let Some(key) = with.get(*input) else {
+                continue;
+            };
+
+            for context in controllable_contexts(&key.to_string()) {
+                findings.push(
+                    Self::finding()
+                        .confidence(Confidence::Medium)
+                        .severity(Severity::High)
+                        .add_location(step.location_with_name())
+                        .add_location(
+                            step.location()
+                                .primary()
+                                .with_keys(&["with".into(), (*input).into()])
+                                .annotated(format!(
+                                    "cache key may be controlled by an attacker via {context}"
+                                )),
+                        )
+                        .build(step.workflow())?,
+                );
+            }
+        }
+
+        Ok(findings)
+    }
 }
 
 impl Audit for CachePoisoning {
@@ -313,6 +485,11 @@ impl Audit for CachePoisoning {
 
     fn audit_normal_job<'w>(&self, job: &NormalJob<'w>) -> anyhow::Result<Vec<Finding<'w>>> {
         let mut findings = vec![];
+
+        for step in job.steps() {
+            findings.extend(self.uses_controllable_cache_key(&step)?);
+        }
+
         let steps = job.steps();
         let trigger = &job.parent().on;
 
@@ -329,3 +506,33 @@ impl Audit for CachePoisoning {
         Ok(findings)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::CachePoisoning;
+
+    #[test]
+    fn test_key_missing_hashfiles() {
+        for (key, missing) in &[
+            ("deps", true),
+            ("deps-${{ runner.os }}", true),
+            ("deps-${{ runner.os }}-${{ github.sha }}", true),
+            (
+                "deps-${{ runner.os }}-${{ hashFiles('**/Cargo.lock') }}",
+                false,
+            ),
+            ("${{ runner.os }}-${{ HASHFILES('go.sum') }}", false),
+            ("deps-${{ format('{0}', hashFiles('go.sum')) }}", false),
+            // Opaque contexts may already contain a hash.
+            ("deps-${{ env.LOCK_HASH }}", false),
+            ("${{ steps.key.outputs.key }}", false),
+            ("${{ inputs.cache-key }}", false),
+        ] {
+            assert_eq!(
+                CachePoisoning::key_missing_hashfiles(key),
+                *missing,
+                "failed: {key}"
+            );
+        }
+    }
+}
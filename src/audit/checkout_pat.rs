@@ -0,0 +1,165 @@
+//! Detects `actions/checkout` steps that use a personal access token
+//! (or other non-default credential) in pull request workflows.
+//!
+//! `actions/checkout` persists its `token:` into the checked-out
+//! repository's git configuration, where any later step (including
+//! one that runs code from the pull request) can read it. Unlike the
+//! default `GITHUB_TOKEN`, a PAT is typically scoped to every repository
+//! its owner can access, and doesn't expire when the job completes.
+
+use github_actions_models::common::Uses;
+
+use super::{audit_meta, Audit};
+use crate::{
+    expr::Expr,
+    finding::{Confidence, Finding, Severity},
+    models::{uses::RepositoryUsesExt as _, JobExt as _, NormalJob, StepBodyCommon, StepCommon},
+    state::AuditState,
+    utils::extract_expressions,
+};
+
+pub(crate) struct CheckoutPat;
+
+audit_meta!(
+    CheckoutPat,
+    "checkout-pat",
+    "personal access token used for checkout in a pull request workflow",
+    rationale = "
+        Flags actions/checkout steps that use a personal access token in workflows
+        triggered by pull requests.
+
+        A checkout token is persisted in the repository's git configuration by
+        default, where it's accessible to every later step, including any that run
+        code from the pull request. Personal access tokens are usually far broader
+        than the workflow's own GITHUB_TOKEN.
+
+        Findings are medium severity and high confidence.
+    ",
+    example = r#"
+        on: pull_request_target
+
+        jobs:
+          build:
+            runs-on: ubuntu-latest
+            steps:
+              - uses: actions/checkout@v4
+                with:
+                  token: ${{ secrets.MY_PAT }}
+    "#,
+    remediation = "
+        Use the workflow's GITHUB_TOKEN with narrowly scoped permissions: instead.
+        If a personal access token is unavoidable, set persist-credentials: false
+        and use a fine-grained token with the minimum necessary access.
+    ",
+);
+
+impl CheckoutPat {
+    /// Returns each `secrets.*` context, other than `secrets.GITHUB_TOKEN`,
+    /// that's expanded into the given `token:` value.
+    fn token_secrets(token: &str) -> Vec<String> {
+        let mut secrets = vec![];
+
+        for (expr, _) in extract_expressions(token) {
+            let Ok(parsed) = Expr::parse(expr.as_bare()) else {
+                tracing::warn!("couldn't parse expression: {expr}", expr = expr.as_bare());
+                continue;
+            };
+
+            secrets.extend(
+                parsed
+                    .contexts()
+                    .into_iter()
+                    .filter(|ctx| ctx.child_of("secrets") && **ctx != *"secrets.GITHUB_TOKEN")
+                    .map(|ctx| ctx.as_str().to_string()),
+            );
+        }
+
+        secrets
+    }
+}
+
+impl Audit for CheckoutPat {
+    fn new(_state: AuditState) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        Ok(Self)
+    }
+
+    fn audit_normal_job<'w>(&self, job: &NormalJob<'w>) -> anyhow::Result<Vec<Finding<'w>>> {
+        let mut findings = vec![];
+
+        let workflow = job.parent();
+        if !workflow.has_pull_request() && !workflow.has_pull_request_target() {
+            return Ok(findings);
+        }
+
+        for step in job.steps() {
+            let StepBodyCommon::Uses {
+                uses: Uses::Repository(uses),
+                with,
+            } = step.body()
+            else {
+                continue;
+            };
+
+            if !uses.matches("actions/checkout") {
+                continue;
+            }
+
+            let Some(token) = with.get("token") else {
+                continue;
+            };
+
+            for secret in Self::token_secrets(&token.to_string()) {
+                findings.push(
+                    Self::finding()
+                        .severity(Severity::Medium)
+                        .confidence(Confidence::High)
+                        .add_location(step.location_with_name())
+                        .add_location(
+                            step.location()
+                                .primary()
+                                .with_keys(&["with".into(), "token".into()])
+                                .annotated(format!("{secret} is used as the checkout token")),
+                        )
+                        .add_location(
+                            workflow
+                                .location()
+                                .with_keys(&["on".into()])
+                                .annotated("pull request trigger"),
+                        )
+                        .build(workflow)?,
+                );
+            }
+        }
+
+        Ok(findings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CheckoutPat;
+
+    #[test]
+    fn test_token_secrets() {
+        for (token, expected) in &[
+            ("${{ secrets.GITHUB_TOKEN }}", vec![]),
+            ("${{ secrets.github_token }}", vec![]),
+            ("${{ github.token }}", vec![]),
+            ("${{ secrets.MY_PAT }}", vec!["secrets.MY_PAT"]),
+            (
+                "${{ secrets.BOT_PAT || secrets.GITHUB_TOKEN }}",
+                vec!["secrets.BOT_PAT"],
+            ),
+            ("${{ steps.app-token.outputs.token }}", vec![]),
+        ] {
+            assert_eq!(
+                &CheckoutPat::token_secrets(token),
+                expected,
+                "failed: {token}"
+            );
+        }
+    }
+}
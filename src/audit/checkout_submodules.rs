@@ -0,0 +1,123 @@
+//! Detects `actions/checkout` steps that fetch submodules in pull request
+//! workflows.
+//!
+//! With `submodules: true` (or `recursive`), `actions/checkout` also fetches
+//! every submodule listed in the checked-out `.gitmodules`. When the
+//! checked-out code comes from a pull request, so does `.gitmodules`,
+//! which means that the pull request's author controls which repositories
+//! are fetched into the workspace, and at which commits.
+
+use github_actions_models::common::Uses;
+
+use super::{audit_meta, Audit};
+use crate::{
+    finding::{Confidence, Finding, Severity},
+    models::{uses::RepositoryUsesExt as _, JobExt as _, NormalJob, StepBodyCommon, StepCommon},
+    state::AuditState,
+};
+
+pub(crate) struct CheckoutSubmodules;
+
+audit_meta!(
+    CheckoutSubmodules,
+    "checkout-submodules",
+    "submodules fetched by checkout in a pull request workflow",
+    rationale = "
+        Flags actions/checkout steps with submodules: true or submodules: recursive
+        in workflows triggered by pull requests.
+
+        When a pull request is checked out, its .gitmodules is too, so the pull
+        request's author chooses which repositories are fetched into the workspace
+        and at which commits. Any later step that builds, tests, or otherwise runs
+        the workspace's contents may run that code.
+
+        Findings are medium severity and medium confidence, since whether the
+        submodules' contents are ever run depends on the rest of the job.
+    ",
+    example = r#"
+        on: pull_request_target
+
+        jobs:
+          build:
+            runs-on: ubuntu-latest
+            steps:
+              - uses: actions/checkout@v4
+                with:
+                  submodules: recursive
+    "#,
+    remediation = "
+        Remove submodules: from the checkout step, or set it to false. If the job
+        needs a submodule, fetch it explicitly, pinned to a known commit, rather
+        than trusting the checked-out .gitmodules.
+    ",
+);
+
+impl CheckoutSubmodules {
+    /// Returns whether the given `submodules:` value enables fetching
+    /// submodules.
+    fn fetches_submodules(submodules: &str) -> bool {
+        matches!(submodules, "true" | "recursive")
+    }
+}
+
+impl Audit for CheckoutSubmodules {
+    fn new(_state: AuditState) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        Ok(Self)
+    }
+
+    fn audit_normal_job<'w>(&self, job: &NormalJob<'w>) -> anyhow::Result<Vec<Finding<'w>>> {
+        let mut findings = vec![];
+
+        let workflow = job.parent();
+        if !workflow.has_pull_request() && !workflow.has_pull_request_target() {
+            return Ok(findings);
+        }
+
+        for step in job.steps() {
+            let StepBodyCommon::Uses {
+                uses: Uses::Repository(uses),
+                with,
+            } = step.body()
+            else {
+                continue;
+            };
+
+            if !uses.matches("actions/checkout") {
+                continue;
+            }
+
+            let Some(submodules) = with.get("submodules") else {
+                continue;
+            };
+
+            if !Self::fetches_submodules(&submodules.to_string()) {
+                continue;
+            }
+
+            findings.push(
+                Self::finding()
+                    .severity(Severity::Medium)
+                    .confidence(Confidence::Medium)
+                    .add_location(step.location_with_name())
+                    .add_location(
+                        step.location()
+                            .primary()
+                            .with_keys(&["with".into(), "submodules".into()])
+                            .annotated("submodules are fetched from the checked-out .gitmodules"),
+                    )
+                    .add_location(
+                        workflow
+                            .location()
+                            .with_keys(&["on".into()])
+                            .annotated("pull request trigger"),
+                    )
+                    .build(workflow)?,
+            );
+        }
+
+        Ok(findings)
+    }
+}
@@ -0,0 +1,156 @@
+//! Detects `concurrency:` groups that are keyed on attacker-controllable
+//! values.
+//!
+//! Runs in the same concurrency group replace each other: a new run
+//! cancels any pending run in its group and, with `cancel-in-progress`,
+//! any running one too. When the group is derived from a value that an
+//! attacker controls (like a pull request's branch name), the attacker
+//! can pick a value that collides with another run's group, and cancel
+//! that run at will.
+
+use github_actions_models::workflow::Concurrency;
+
+use super::{audit_meta, Audit};
+use crate::{
+    finding::{Confidence, Finding, Severity, SymbolicLocation},
+    models::{Job, JobExt as _, Workflow},
+    state::AuditState,
+    utils::controllable_contexts,
+};
+
+pub(crate) struct ControllableConcurrency;
+
+audit_meta!(
+    ControllableConcurrency,
+    "controllable-concurrency",
+    "concurrency group keyed on attacker-controllable value",
+    rationale = "
+        Flags workflow and job concurrency groups that expand attacker-controllable
+        contexts, such as github.head_ref.
+
+        A new run cancels any pending run in the same concurrency group, and with
+        cancel-in-progress, any running one too. An attacker who controls part of
+        the group can choose a value that collides with another run's group, and
+        cancel that run.
+
+        Findings are medium severity with cancel-in-progress, and low severity
+        otherwise. They have low confidence, since colliding groups aren't always
+        reachable by an attacker.
+    ",
+    example = r#"
+        concurrency:
+          group: ${{ github.workflow }}-${{ github.head_ref }}
+          cancel-in-progress: true
+    "#,
+    remediation = "
+        Key concurrency groups on values that an attacker can't choose, such as
+        github.event.pull_request.number or github.ref.
+    ",
+);
+
+impl ControllableConcurrency {
+    /// Returns whether the `concurrency:` block under the given location
+    /// sets `cancel-in-progress`, or might via an expression.
+    ///
+    /// NOTE: `Concurrency::Rich::cancel_in_progress` is never populated by
+    /// the current workflow models, since they only match the field's
+    /// snake_case name. Instead, we look the key up in the workflow's
+    /// already-parsed document.
+    fn cancels_in_progress<'w>(location: &SymbolicLocation<'w>, workflow: &'w Workflow) -> bool {
+        let Ok(cancel) = location
+            .with_keys(&["concurrency".into(), "cancel-in-progress".into()])
+            .concretize(workflow)
+        else {
+            return false;
+        };
+
+        // The queried feature is the whole `cancel-in-progress: ...` pair.
+        let value = cancel.concrete.feature.trim();
+        match value
+            .strip_prefix("cancel-in-progress:")
+            .unwrap_or(value)
+            .trim()
+        {
+            "true" => true,
+            value => value.starts_with("${{"),
+        }
+    }
+
+    fn audit_concurrency<'w>(
+        concurrency: &Concurrency,
+        location: SymbolicLocation<'w>,
+        workflow: &'w Workflow,
+    ) -> anyhow::Result<Vec<Finding<'w>>> {
+        let cancels = Self::cancels_in_progress(&location, workflow);
+        let (group, location) = match concurrency {
+            Concurrency::Bare(group) => (group, location.with_keys(&["concurrency".into()])),
+            Concurrency::Rich { group, .. } => (
+                group,
+                location.with_keys(&["concurrency".into(), "group".into()]),
+            ),
+        };
+
+        let severity = match cancels {
+            true => Severity::Medium,
+            false => Severity::Low,
+        };
+
+        controllable_contexts(group)
+            .into_iter()
+            .map(|context| {
+                Self::finding()
+                    .severity(severity)
+                    .confidence(Confidence::Low)
+                    .add_location(
+                        location
+                            .clone()
+                            .primary()
+                            .annotated(format!("{context} may be attacker-controllable")),
+                    )
+                    .build(workflow)
+            })
+            .collect()
+    }
+}
+
+impl Audit for ControllableConcurrency {
+    fn new(_state: AuditState) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        Ok(Self)
+    }
+
+    fn audit_workflow<'w>(
+        &self,
+        workflow: &'w Workflow,
+        _workflows: &super::LocalWorkflows<'w>,
+    ) -> anyhow::Result<Vec<Finding<'w>>> {
+        let mut findings = vec![];
+
+        if let Some(concurrency) = &workflow.concurrency {
+            findings.extend(Self::audit_concurrency(
+                concurrency,
+                workflow.location(),
+                workflow,
+            )?);
+        }
+
+        for job in workflow.jobs() {
+            // Only normal jobs can have their own concurrency groups.
+            let Job::NormalJob(job) = job else {
+                continue;
+            };
+
+            if let Some(concurrency) = &job.concurrency {
+                findings.extend(Self::audit_concurrency(
+                    concurrency,
+                    job.location(),
+                    workflow,
+                )?);
+            }
+        }
+
+        Ok(findings)
+    }
+}
@@ -0,0 +1,219 @@
+//! Detects attacker-controllable contexts in `if:` conditions.
+//!
+//! Unlike `run:` blocks, `if:` conditions aren't directly injectable.
+//! However, a condition that depends on attacker-controlled text (like a
+//! pull request's title or branch name) can be satisfied (or bypassed)
+//! at will by the attacker, which makes it a poor security gate. This is
+//! especially true of substring matches like `contains(...)`.
+
+use github_actions_models::common::{expr::ExplicitExpr, If};
+
+use super::{audit_meta, Audit};
+use crate::{
+    expr::{self, Expr},
+    finding::{Confidence, Finding, Persona, Severity},
+    models::{JobExt as _, NormalJob},
+    state::AuditState,
+    utils::{is_controllable, is_trusted_comparison},
+};
+
+/// Functions that match against substrings of their arguments.
+const MATCHING_FUNCTIONS: &[&str] = &["contains", "startsWith", "endsWith"];
+
+pub(crate) struct ControllableConditions;
+
+audit_meta!(
+    ControllableConditions,
+    "controllable-conditions",
+    "attacker-controllable context in condition",
+    rationale = "
+        Flags if: conditions that depend on attacker-controllable contexts, such as
+        a pull request's title or labels.
+
+        A condition that an attacker can satisfy doesn't protect the job or step it
+        guards.
+
+        Findings are low severity, since the impact depends on what's being
+        guarded. Substring checks like contains() are easy to satisfy, and are
+        reported with medium confidence. This audit is only shown with the
+        pedantic persona.
+    ",
+    example = r#"
+        jobs:
+          deploy:
+            if: contains(github.event.pull_request.title, '[deploy]')
+            runs-on: ubuntu-latest
+            steps:
+              - run: ./deploy.sh
+    "#,
+    remediation = "
+        Base conditions on contexts that an attacker can't control, such as the
+        repository, the event type, or the permissions of the actor.
+    ",
+);
+
+impl ControllableConditions {
+    /// Walks the given expression, collecting each attacker-controllable
+    /// context along with a confidence.
+    ///
+    /// `matching` indicates whether the expression is an argument to a
+    /// substring-matching function, which is trivially bypassable.
+    fn walk_tree<'a>(expr: &'a Expr, matching: bool, contexts: &mut Vec<(&'a str, Confidence)>) {
+        match expr {
+            Expr::Call { func, args } => {
+                // Arguments to other calls (e.g. `toJSON(...)`) keep
+                // the outer matching state.
+                let matching = matching
+                    || MATCHING_FUNCTIONS
+                        .iter()
+                        .any(|f| f.eq_ignore_ascii_case(func));
+
+                for arg in args {
+                    Self::walk_tree(arg, matching, contexts);
+                }
+            }
+            Expr::Context(ctx) => {
+                if let Some(call @ Expr::Call { .. }) = ctx.components().first() {
+                    // Like `Expr::contexts`, only the call's arguments are
+                    // well-known contexts in `foo(args).a.b.c`.
+                    Self::walk_tree(call, false, contexts);
+                } else if is_controllable(ctx) {
+                    let confidence = if matching {
+                        Confidence::Medium
+                    } else {
+                        Confidence::Low
+                    };
+                    contexts.push((ctx.as_str(), confidence));
+                }
+            }
+            Expr::Index(expr) => Self::walk_tree(expr, matching, contexts),
+            Expr::BinOp { lhs, op, rhs } => match op {
+                expr::BinOp::Eq | expr::BinOp::Neq if is_trusted_comparison(lhs, rhs) => {}
+                _ => {
+                    Self::walk_tree(lhs, matching, contexts);
+                    Self::walk_tree(rhs, matching, contexts);
+                }
+            },
+            Expr::UnOp { op: _, expr } => Self::walk_tree(expr, matching, contexts),
+            _ => (),
+        }
+    }
+
+    fn condition_contexts(cond: &str) -> Vec<(String, Confidence)> {
+        let bare = match ExplicitExpr::from_curly(cond) {
+            Some(raw_expr) => raw_expr.as_bare().to_string(),
+            None => cond.to_string(),
+        };
+
+        let Ok(expr) = Expr::parse(&bare) else {
+            tracing::warn!("couldn't parse expression: {cond}");
+            return vec![];
+        };
+
+        let mut contexts = vec![];
+        Self::walk_tree(&expr, false, &mut contexts);
+
+        contexts
+            .into_iter()
+            .map(|(ctx, confidence)| (ctx.to_string(), confidence))
+            .collect()
+    }
+}
+
+impl Audit for ControllableConditions {
+    fn new(_state: AuditState) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        Ok(Self)
+    }
+
+    fn audit_normal_job<'w>(&self, job: &NormalJob<'w>) -> anyhow::Result<Vec<Finding<'w>>> {
+        let mut findings = vec![];
+
+        let mut conds = vec![];
+        if let Some(If::Expr(expr)) = &job.r#if {
+            conds.push((expr, job.location()));
+        }
+
+        for step in job.steps() {
+            if let Some(If::Expr(expr)) = &step.r#if {
+                conds.push((expr, step.location()));
+            }
+        }
+
+        for (expr, loc) in conds {
+            for (context, confidence) in Self::condition_contexts(expr) {
+                findings.push(
+                    Self::finding()
+                        .severity(Severity::Low)
+                        .confidence(confidence)
+                        .persona(Persona::Pedantic)
+                        .add_location(
+                            loc.with_keys(&["if".into()])
+                                .primary()
+                                .annotated(format!("{context} may be attacker-controllable")),
+                        )
+                        .build(job.parent())?,
+                );
+            }
+        }
+
+        Ok(findings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ControllableConditions;
+    use crate::finding::Confidence;
+
+    #[test]
+    fn test_condition_contexts() {
+        for (cond, expected) in &[
+            ("github.event_name == 'push'", vec![]),
+            ("github.event.action == 'opened'", vec![]),
+            ("github.event.pull_request.merged", vec![]),
+            ("github.event.pull_request.number == 1", vec![]),
+            (
+                "github.event.pull_request.head.repo.full_name == github.repository",
+                vec![],
+            ),
+            (
+                "github.repository != github.event.pull_request.head.repo.full_name",
+                vec![],
+            ),
+            (
+                "github.event.pull_request.title == 'foo'",
+                vec![("github.event.pull_request.title", Confidence::Low)],
+            ),
+            (
+                "${{ github.head_ref == 'main' }}",
+                vec![("github.head_ref", Confidence::Low)],
+            ),
+            (
+                "contains(github.event.pull_request.labels.*.name, 'safe-to-test')",
+                vec![],
+            ),
+            (
+                "contains(github.event.pull_request.body, 'LGTM')",
+                vec![("github.event.pull_request.body", Confidence::Medium)],
+            ),
+            (
+                "startsWith(github.event.comment.body, '/deploy') && github.event.action == 'created'",
+                vec![("github.event.comment.body", Confidence::Medium)],
+            ),
+            (
+                "!contains(toJSON(github.event.commits.*.message), '[skip ci]')",
+                vec![("github.event.commits.*.message", Confidence::Medium)],
+            ),
+        ] {
+            let actual = ControllableConditions::condition_contexts(cond);
+            let expected = expected
+                .iter()
+                .map(|(ctx, confidence)| (ctx.to_string(), *confidence))
+                .collect::<Vec<_>>();
+            assert_eq!(actual, expected, "failed: {cond}");
+        }
+    }
+}
@@ -0,0 +1,169 @@
+//! Detects `setup-*` actions whose version or registry inputs expand
+//! attacker-controllable contexts.
+//!
+//! Setup actions resolve their version inputs against remote indices,
+//! and some (like `actions/setup-node`) also take the package registry to
+//! configure. An attacker who controls either can steer the job towards
+//! a tool version or registry of their choosing.
+
+use github_actions_models::common::Uses;
+
+use super::{audit_meta, Audit};
+use crate::{
+    finding::{Confidence, Finding, Severity, SymbolicLocation},
+    models::{uses::RepositoryUsesExt as _, StepBodyCommon, StepCommon},
+    state::AuditState,
+    utils::controllable_contexts,
+};
+
+/// Each setup action, along with its version and registry inputs.
+const SETUP_ACTION_INPUTS: &[(&str, &[&str])] = &[
+    // https://github.com/actions/setup-dotnet/blob/main/action.yml
+    ("actions/setup-dotnet", &["dotnet-version"]),
+    // https://github.com/actions/setup-go/blob/main/action.yml
+    ("actions/setup-go", &["go-version"]),
+    // https://github.com/actions/setup-java/blob/main/action.yml
+    ("actions/setup-java", &["java-version", "distribution"]),
+    // https://github.com/actions/setup-node/blob/main/action.yml
+    ("actions/setup-node", &["node-version", "registry-url"]),
+    // https://github.com/actions/setup-python/blob/main/action.yml
+    ("actions/setup-python", &["python-version"]),
+    // https://github.com/astral-sh/setup-uv/blob/main/action.yml
+    ("astral-sh/setup-uv", &["version"]),
+    // https://github.com/dtolnay/rust-toolchain/blob/master/action.yml
+    ("dtolnay/rust-toolchain", &["toolchain"]),
+    // https://github.com/oven-sh/setup-bun/blob/main/action.yml
+    ("oven-sh/setup-bun", &["bun-version", "registry-url"]),
+    // https://github.com/pnpm/action-setup/blob/master/action.yml
+    ("pnpm/action-setup", &["version"]),
+    // https://github.com/ruby/setup-ruby/blob/master/action.yml
+    ("ruby/setup-ruby", &["ruby-version", "bundler"]),
+];
+
+pub(crate) struct ControllableSetupInputs;
+
+audit_meta!(
+    ControllableSetupInputs,
+    "controllable-setup-inputs",
+    "setup action's version or registry controlled by an attacker",
+    rationale = "
+        Flags setup actions (like actions/setup-node or actions/setup-python)
+        whose version or registry inputs expand attacker-controllable contexts,
+        like github.event.inputs.* or github.head_ref.
+
+        Setup actions resolve version specifications against remote indices,
+        and some configure a package registry as well. An attacker who controls
+        these inputs can steer the job towards a tool version or registry of
+        their choosing, e.g. one with known vulnerabilities or one that they
+        operate.
+
+        Findings are medium severity with medium confidence.
+    ",
+    example = r#"
+        on:
+          workflow_dispatch:
+            inputs:
+              version:
+                type: string
+
+        jobs:
+          build:
+            runs-on: ubuntu-latest
+            steps:
+              - uses: actions/setup-node@v4
+                with:
+                  node-version: ${{ github.event.inputs.version }}
+    "#,
+    remediation = "
+        Pin tool versions and registries in the workflow itself, e.g. in a version
+        file like .nvmrc or .python-version. If a version must be chosen at runtime,
+        validate it against an allowlist first.
+    ",
+);
+
+impl ControllableSetupInputs {
+    /// Returns the annotated location of each attacker-controllable
+    /// context that's expanded into the step's setup inputs.
+    fn controllable_inputs<'s>(step: &impl StepCommon<'s>) -> Vec<SymbolicLocation<'s>> {
+        let StepBodyCommon::Uses {
+            uses: Uses::Repository(uses),
+            with,
+        } = step.body()
+        else {
+            return vec![];
+        };
+
+        let Some((_, inputs)) = SETUP_ACTION_INPUTS
+            .iter()
+            .find(|(action, _)| uses.matches(action))
+        else {
+            return vec![];
+        };
+
+        let mut locations = vec![];
+        for input in *inputs {
+            let Some(value) = with.get(*input) else {
+                continue;
+            };
+
+            for context in controllable_contexts(&value.to_string()) {
+                locations.push(
+                    step.location()
+                        .primary()
+                        .with_keys(&["with".into(), (*input).into()])
+                        .annotated(format!(
+                            "{input} may be controlled by an attacker via {context}"
+                        )),
+                );
+            }
+        }
+
+        locations
+    }
+}
+
+impl Audit for ControllableSetupInputs {
+    fn new(_state: AuditState) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        Ok(Self)
+    }
+
+    fn audit_step<'w>(&self, step: &super::Step<'w>) -> anyhow::Result<Vec<Finding<'w>>> {
+        let mut findings = vec![];
+
+        for location in Self::controllable_inputs(step) {
+            findings.push(
+                Self::finding()
+                    .severity(Severity::Medium)
+                    .confidence(Confidence::Medium)
+                    .add_location(step.location_with_name())
+                    .add_location(location)
+                    .build(step.workflow())?,
+            );
+        }
+
+        Ok(findings)
+    }
+
+    fn audit_composite_step<'a>(
+        &self,
+        step: &super::CompositeStep<'a>,
+    ) -> anyhow::Result<Vec<Finding<'a>>> {
+        let mut findings = vec![];
+
+        for location in Self::controllable_inputs(step) {
+            findings.push(
+                Self::finding()
+                    .severity(Severity::Medium)
+                    .confidence(Confidence::Medium)
+                    .add_location(step.location_with_name())
+                    .add_location(location)
+                    .build(step.action())?,
+            );
+        }
+
+        Ok(findings)
+    }
+}
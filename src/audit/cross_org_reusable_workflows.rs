@@ -0,0 +1,183 @@
+//! Detects reusable workflow calls that cross organization boundaries.
+//!
+//! A reusable workflow from another organization runs with whatever
+//! permissions and secrets its caller gives it, but is maintained by
+//! someone else. That's a trust boundary worth reviewing, especially when
+//! the call uses `secrets: inherit`.
+
+use github_actions_models::{common::Uses, workflow::job::Secrets};
+
+use super::{audit_meta, Audit, ReusableWorkflowCallJob};
+use crate::{
+    finding::{Confidence, Finding, Persona, Severity},
+    models::JobExt as _,
+    state::AuditState,
+};
+
+pub(crate) struct CrossOrgReusableWorkflows;
+
+audit_meta!(
+    CrossOrgReusableWorkflows,
+    "cross-org-reusable-workflows",
+    "reusable workflow called from another organization",
+    rationale = "
+        Flags reusable workflow calls whose uses: owner differs from the owner
+        of the calling workflow's repository.
+
+        A reusable workflow from another organization is maintained outside of
+        the caller's control, but runs with the permissions and secrets that the
+        caller gives it.
+
+        The calling repository's owner is only known for remote inputs, e.g.
+        when auditing owner/repo, so local inputs are never flagged. Findings
+        are only shown with the pedantic persona, and are low severity with high
+        confidence, or medium severity when the call uses secrets: inherit.
+    ",
+    example = r#"
+        jobs:
+          call:
+            uses: other-org/workflows/.github/workflows/build.yml@v1
+            secrets: inherit
+    "#,
+    remediation = "
+        Review the called workflow and its maintainers, and pass it only the
+        secrets that it needs.
+    ",
+);
+
+impl Audit for CrossOrgReusableWorkflows {
+    fn new(_state: AuditState) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        Ok(Self)
+    }
+
+    fn audit_reusable_job<'w>(
+        &self,
+        job: &ReusableWorkflowCallJob<'w>,
+    ) -> anyhow::Result<Vec<Finding<'w>>> {
+        let mut findings = vec![];
+
+        let Uses::Repository(uses) = &job.uses else {
+            return Ok(findings);
+        };
+
+        // Without the caller's owner, there's no boundary to compare against.
+        let Some(owner) = job.parent().key.owner() else {
+            return Ok(findings);
+        };
+
+        // GitHub owners are case-insensitive.
+        if uses.owner.eq_ignore_ascii_case(owner) {
+            return Ok(findings);
+        }
+
+        let mut finding = Self::finding()
+            .confidence(Confidence::High)
+            .persona(Persona::Pedantic)
+            .add_location(
+                job.location()
+                    .primary()
+                    .with_keys(&["uses".into()])
+                    .annotated(format!(
+                        "reusable workflow is owned by {callee}, not {owner}",
+                        callee = uses.owner
+                    )),
+            );
+
+        finding = if matches!(job.secrets, Some(Secrets::Inherit)) {
+            finding.severity(Severity::Medium).add_location(
+                job.location()
+                    .with_keys(&["secrets".into()])
+                    .annotated("all parent secrets are passed across the boundary"),
+            )
+        } else {
+            finding.severity(Severity::Low)
+        };
+
+        findings.push(finding.build(job.parent())?);
+
+        Ok(findings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use github_actions_models::common::Uses;
+
+    use super::CrossOrgReusableWorkflows;
+    use crate::{
+        audit::Audit,
+        finding::Severity,
+        models::{Job, JobExt as _, Workflow},
+        registry::InputKey,
+        state::AuditState,
+    };
+
+    const WORKFLOW: &str = r#"
+on: push
+
+jobs:
+  same-org:
+    uses: example/workflows/.github/workflows/build.yml@v1
+
+  same-org-different-case:
+    uses: Example/workflows/.github/workflows/build.yml@v1
+
+  other-org:
+    uses: other-org/workflows/.github/workflows/build.yml@v1
+
+  other-org-inherit:
+    uses: other-org/workflows/.github/workflows/build.yml@v1
+    secrets: inherit
+
+  local:
+    uses: ./.github/workflows/build.yml
+"#;
+
+    fn audit(key: InputKey) -> Vec<(String, Severity)> {
+        let workflow = Workflow::from_string(WORKFLOW.into(), key).unwrap();
+        let audit = CrossOrgReusableWorkflows::new(AuditState::default()).unwrap();
+
+        workflow
+            .jobs()
+            .filter_map(|job| match job {
+                Job::ReusableWorkflowCallJob(job) => Some(job),
+                _ => None,
+            })
+            .flat_map(|job| {
+                let id = job.id().to_string();
+                audit
+                    .audit_reusable_job(&job)
+                    .unwrap()
+                    .into_iter()
+                    .map(move |finding| (id.clone(), finding.determinations.severity))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_remote_caller() {
+        let Uses::Repository(slug) = Uses::from_str("example/repo").unwrap() else {
+            panic!()
+        };
+        let key = InputKey::remote(&slug, ".github/workflows/ci.yml".into()).unwrap();
+
+        assert_eq!(
+            audit(key),
+            [
+                ("other-org".into(), Severity::Low),
+                ("other-org-inherit".into(), Severity::Medium),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_local_caller() {
+        let key = InputKey::local("ci.yml", None).unwrap();
+        assert!(audit(key).is_empty());
+    }
+}
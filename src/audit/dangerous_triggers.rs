@@ -10,7 +10,35 @@ pub(crate) struct DangerousTriggers;
 audit_meta!(
     DangerousTriggers,
     "dangerous-triggers",
-    "use of fundamentally insecure workflow trigger"
+    "use of fundamentally insecure workflow trigger",
+    rationale = "
+        Flags workflows triggered by pull_request_target or workflow_run.
+
+        These triggers run in the context of the base repository, with access to
+        its secrets and a potentially write-scoped token, even when triggered by a
+        pull request from a fork. They're almost always used insecurely, e.g. by
+        checking out and running the pull request's code.
+
+        Findings are high severity with medium confidence, since some uses of these
+        triggers are safe.
+    ",
+    example = r#"
+        on: pull_request_target
+
+        jobs:
+          test:
+            runs-on: ubuntu-latest
+            steps:
+              - uses: actions/checkout@v4
+                with:
+                  ref: ${{ github.event.pull_request.head.sha }}
+              - run: make test
+    "#,
+    remediation = "
+        Use pull_request instead, which doesn't have access to the base
+        repository's secrets. If a privileged operation is needed, isolate it in a
+        separate workflow that never runs the pull request's code.
+    ",
 );
 
 impl Audit for DangerousTriggers {
@@ -18,7 +46,11 @@ impl Audit for DangerousTriggers {
         Ok(Self)
     }
 
-    fn audit_workflow<'w>(&self, workflow: &'w Workflow) -> Result<Vec<Finding<'w>>> {
+    fn audit_workflow<'w>(
+        &self,
+        workflow: &'w Workflow,
+        _workflows: &super::LocalWorkflows<'w>,
+    ) -> Result<Vec<Finding<'w>>> {
         let mut findings = vec![];
         if workflow.has_pull_request_target() {
             findings.push(
@@ -0,0 +1,125 @@
+//! Detects steps that share an `id:` with an earlier step in the same job
+//! or composite action.
+//!
+//! GitHub rejects workflows with duplicate step IDs, but the parsed models
+//! accept them, so zizmor would otherwise audit the workflow as if it were
+//! valid. Duplicate job IDs and output names are mapping keys, which are
+//! collapsed during parsing and can't be detected here.
+
+use super::{audit_meta, Audit};
+use crate::{
+    finding::{Confidence, Finding, Severity, SymbolicLocation},
+    models::{Action, JobExt as _, NormalJob},
+    state::AuditState,
+};
+
+pub(crate) struct DuplicateStepIds;
+
+audit_meta!(
+    DuplicateStepIds,
+    "duplicate-step-ids",
+    "step ID used more than once",
+    rationale = "
+        Flags steps whose id: is already used by an earlier step in the same job
+        or composite action. Step IDs are compared case-insensitively, like the
+        steps.<id> contexts that reference them.
+
+        GitHub rejects workflows with duplicate step IDs, so these findings
+        usually indicate a copy-paste mistake. References to the duplicated ID
+        are ambiguous, and may not refer to the step that the author intended.
+
+        Findings are low severity with high confidence.
+    ",
+    example = r#"
+        steps:
+          - id: build
+            run: make
+          - id: build
+            run: make install
+    "#,
+    remediation = "
+        Give each step a unique id:, and update any steps.<id> references to
+        match.
+    ",
+);
+
+/// A step's `id:`, along with the location of its definition.
+type StepId<'w> = (&'w str, SymbolicLocation<'w>);
+
+impl DuplicateStepIds {
+    /// Returns each step ID in `ids` that duplicates an earlier one, along
+    /// with the earlier ID.
+    fn duplicates<'w>(ids: Vec<StepId<'w>>) -> Vec<(StepId<'w>, StepId<'w>)> {
+        let mut seen: Vec<StepId<'w>> = vec![];
+        let mut duplicates = vec![];
+
+        for (id, location) in ids {
+            match seen
+                .iter()
+                .find(|(other, _)| other.eq_ignore_ascii_case(id))
+            {
+                Some(first) => duplicates.push(((id, location), first.clone())),
+                None => seen.push((id, location)),
+            }
+        }
+
+        duplicates
+    }
+
+    fn duplicate_finding<'w>(
+        (id, location): StepId<'w>,
+        (first, first_location): StepId<'w>,
+        document: &'w impl AsRef<yamlpath::Document>,
+    ) -> anyhow::Result<Finding<'w>> {
+        Self::finding()
+            .severity(Severity::Low)
+            .confidence(Confidence::High)
+            .add_location(
+                location
+                    .primary()
+                    .annotated(format!("{id} is already used as a step ID")),
+            )
+            .add_location(first_location.annotated(format!("{first} is first used here")))
+            .build(document)
+    }
+}
+
+impl Audit for DuplicateStepIds {
+    fn new(_state: AuditState) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        Ok(Self)
+    }
+
+    fn audit_normal_job<'w>(&self, job: &NormalJob<'w>) -> anyhow::Result<Vec<Finding<'w>>> {
+        let ids = job
+            .steps()
+            .filter_map(|step| {
+                let inner: &'w _ = *step;
+                let id = inner.id.as_deref()?;
+                Some((id, step.location().with_keys(&["id".into()])))
+            })
+            .collect();
+
+        Self::duplicates(ids)
+            .into_iter()
+            .map(|(duplicate, first)| Self::duplicate_finding(duplicate, first, job.parent()))
+            .collect()
+    }
+
+    fn audit_action<'a>(&self, action: &'a Action) -> anyhow::Result<Vec<Finding<'a>>> {
+        let ids = action
+            .steps()
+            .filter_map(|step| {
+                let id = step.inner.id.as_deref()?;
+                Some((id, step.location().with_keys(&["id".into()])))
+            })
+            .collect();
+
+        Self::duplicates(ids)
+            .into_iter()
+            .map(|(duplicate, first)| Self::duplicate_finding(duplicate, first, action))
+            .collect()
+    }
+}
@@ -0,0 +1,197 @@
+//! Detects `runs-on:` values that are selected by non-static expressions.
+//!
+//! A job's runner is chosen before any of its steps run, so a `runs-on:`
+//! that expands attacker-controllable input (e.g. via a matrix generated
+//! from a previous job's outputs) can direct the job onto an unexpected
+//! runner, such as a self-hosted runner with access to internal resources.
+
+use github_actions_models::{
+    common::expr::LoE,
+    workflow::job::{RunsOn, Strategy},
+};
+
+use super::{audit_meta, Audit, Job};
+use crate::{
+    expr::Expr,
+    finding::{Confidence, Finding, Severity},
+    models::{JobExt as _, Matrix, Workflow},
+    state::AuditState,
+    utils::extract_expressions,
+};
+
+pub(crate) struct DynamicRunsOn;
+
+audit_meta!(
+    DynamicRunsOn,
+    "dynamic-runs-on",
+    "runner selected by a non-static expression",
+    rationale = "
+        Flags runs-on: values that are selected by expressions.
+
+        A job's runner is selected before any of its steps run. A runs-on: that
+        expands attacker-controllable input, such as a matrix generated from a
+        previous job's outputs, can direct the job onto an unexpected runner, such
+        as a self-hosted runner with access to internal resources.
+
+        Findings are medium severity with medium confidence when the runner is
+        selected by a non-static matrix, and informational with low confidence for
+        other expressions.
+    ",
+    example = r#"
+        jobs:
+          setup:
+            runs-on: ubuntu-latest
+            outputs:
+              runners: ${{ steps.runners.outputs.runners }}
+            steps:
+              - id: runners
+                run: echo "runners=$(cat runners.json)" >> "$GITHUB_OUTPUT"
+
+          build:
+            needs: setup
+            strategy:
+              matrix:
+                runner: ${{ fromJSON(needs.setup.outputs.runners) }}
+            runs-on: ${{ matrix.runner }}
+    "#,
+    remediation = "
+        Select runners with static labels, or with a matrix whose values are all
+        listed literally in the workflow.
+    ",
+);
+
+impl DynamicRunsOn {
+    /// Returns each context that's expanded within the given `runs-on:`.
+    fn runs_on_contexts(runs_on: &LoE<RunsOn>) -> Vec<String> {
+        let values = match runs_on {
+            LoE::Expr(expr) => vec![expr.as_curly().to_string()],
+            LoE::Literal(RunsOn::Target(labels)) => labels.clone(),
+            LoE::Literal(RunsOn::Group { group, labels }) => {
+                group.iter().chain(labels).cloned().collect()
+            }
+        };
+
+        let mut contexts = vec![];
+        for value in values {
+            for (expr, _) in extract_expressions(&value) {
+                let Ok(parsed) = Expr::parse(expr.as_bare()) else {
+                    tracing::warn!("couldn't parse expression: {expr}", expr = expr.as_bare());
+                    continue;
+                };
+
+                contexts.extend(parsed.contexts().iter().map(|ctx| ctx.as_str().to_string()));
+            }
+        }
+
+        contexts
+    }
+}
+
+impl Audit for DynamicRunsOn {
+    fn new(_state: AuditState) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        Ok(Self)
+    }
+
+    fn audit_workflow<'w>(
+        &self,
+        workflow: &'w Workflow,
+        _workflows: &super::LocalWorkflows<'w>,
+    ) -> anyhow::Result<Vec<Finding<'w>>> {
+        let mut findings = vec![];
+
+        for job in workflow.jobs() {
+            let Job::NormalJob(job) = job else {
+                continue;
+            };
+
+            for context in Self::runs_on_contexts(&job.runs_on) {
+                let runs_on = job.location().primary().with_keys(&["runs-on".into()]);
+
+                let is_matrix = context
+                    .split(['.', '['])
+                    .next()
+                    .is_some_and(|head| head.eq_ignore_ascii_case("matrix"));
+
+                if is_matrix {
+                    let Some(Strategy {
+                        matrix: Some(matrix),
+                        ..
+                    }) = &job.strategy
+                    else {
+                        // Context specifies a matrix, but there is no matrix defined.
+                        // This is an invalid workflow so there's no point in flagging it.
+                        continue;
+                    };
+
+                    if Matrix::new(matrix).is_static(&context) {
+                        continue;
+                    }
+
+                    findings.push(
+                        Self::finding()
+                            .severity(Severity::Medium)
+                            .confidence(Confidence::Medium)
+                            .add_location(
+                                job.location()
+                                    .with_keys(&["strategy".into(), "matrix".into()])
+                                    .annotated("matrix is not static"),
+                            )
+                            .add_location(
+                                runs_on.annotated(format!(
+                                    "runner is selected by non-static {context}"
+                                )),
+                            )
+                            .build(workflow)?,
+                    );
+                } else {
+                    findings.push(
+                        Self::finding()
+                            .severity(Severity::Informational)
+                            .confidence(Confidence::Low)
+                            .add_location(
+                                runs_on.annotated(format!("runner is selected by {context}")),
+                            )
+                            .build(workflow)?,
+                    );
+                }
+            }
+        }
+
+        Ok(findings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use github_actions_models::{common::expr::LoE, workflow::job::RunsOn};
+
+    use super::DynamicRunsOn;
+
+    #[test]
+    fn test_runs_on_contexts() {
+        for (runs_on, expected) in [
+            ("ubuntu-latest", vec![]),
+            ("[self-hosted, linux]", vec![]),
+            ("${{ matrix.os }}", vec!["matrix.os"]),
+            ("[self-hosted, '${{ inputs.arch }}']", vec!["inputs.arch"]),
+            (
+                "{group: '${{ vars.GROUP }}', labels: ['${{ matrix.label }}']}",
+                vec!["vars.GROUP", "matrix.label"],
+            ),
+            (
+                "${{ fromJSON(needs.setup.outputs.runner) }}",
+                vec!["needs.setup.outputs.runner"],
+            ),
+        ] {
+            let runs_on = serde_yaml::from_str::<LoE<RunsOn>>(runs_on).unwrap();
+            assert_eq!(
+                DynamicRunsOn::runs_on_contexts(&runs_on),
+                expected,
+                "failed: {runs_on:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,162 @@
+//! Detects `env:` keys that differ only by case within a single
+//! workflow, job, and step scope chain.
+//!
+//! Environment variable names are case-sensitive on Linux and macOS
+//! runners, but case-insensitive on Windows runners. A workflow that
+//! defines both `Path` and `PATH` therefore behaves differently depending
+//! on where it runs, and is confusing to read either way.
+
+use github_actions_models::{
+    common::{expr::LoE, Env},
+    workflow::job::StepBody,
+};
+
+use super::{audit_meta, Audit};
+use crate::{
+    finding::{Confidence, Finding, Persona, Severity, SymbolicLocation},
+    models::{Job, JobExt as _, Workflow},
+    state::AuditState,
+};
+
+pub(crate) struct EnvCaseCollision;
+
+audit_meta!(
+    EnvCaseCollision,
+    "env-case-collision",
+    "environment variables that differ only by case",
+    rationale = "
+        Flags env: keys that differ only by case, across a workflow's, job's, and
+        step's env: blocks.
+
+        Environment variable names are case-sensitive on Linux and macOS, but
+        case-insensitive on Windows. A workflow that defines both Path and PATH
+        behaves differently depending on where it runs.
+
+        Findings are low severity with high confidence, and are only shown with
+        the pedantic persona.
+    ",
+    example = r#"
+        env:
+          PATH: /usr/bin
+
+        jobs:
+          build:
+            runs-on: ubuntu-latest
+            env:
+              Path: /opt/bin
+    "#,
+    remediation = "
+        Rename one of the variables so that every name is unique regardless of
+        case.
+    ",
+);
+
+/// An `env:` key that's in scope, along with the location of its definition.
+type ScopedKey<'w> = (&'w str, SymbolicLocation<'w>);
+
+impl EnvCaseCollision {
+    /// Checks each key in `env` (defined at `location`) against the keys
+    /// already in `scope`, and then adds it to `scope`.
+    fn check_env<'w>(
+        env: &'w LoE<Env>,
+        location: SymbolicLocation<'w>,
+        scope: &mut Vec<ScopedKey<'w>>,
+        workflow: &'w Workflow,
+        findings: &mut Vec<Finding<'w>>,
+    ) -> anyhow::Result<()> {
+        let LoE::Literal(env) = env else {
+            return Ok(());
+        };
+
+        for key in env.keys() {
+            let definition = location.with_keys(&["env".into(), key.as_str().into()]);
+
+            for (other, other_definition) in scope.iter() {
+                if *other == key || !other.eq_ignore_ascii_case(key) {
+                    continue;
+                }
+
+                findings.push(
+                    Self::finding()
+                        .severity(Severity::Low)
+                        .confidence(Confidence::High)
+                        .persona(Persona::Pedantic)
+                        .add_location(
+                            definition
+                                .clone()
+                                .primary()
+                                .annotated(format!("{key} differs from {other} only by case")),
+                        )
+                        .add_location(
+                            other_definition
+                                .clone()
+                                .annotated(format!("{other} is defined here")),
+                        )
+                        .build(workflow)?,
+                );
+            }
+
+            scope.push((key, definition));
+        }
+
+        Ok(())
+    }
+}
+
+impl Audit for EnvCaseCollision {
+    fn new(_state: AuditState) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        Ok(Self)
+    }
+
+    fn audit_workflow<'w>(
+        &self,
+        workflow: &'w Workflow,
+        _workflows: &super::LocalWorkflows<'w>,
+    ) -> anyhow::Result<Vec<Finding<'w>>> {
+        let mut findings = vec![];
+
+        let mut workflow_scope = vec![];
+        Self::check_env(
+            &workflow.env,
+            workflow.location(),
+            &mut workflow_scope,
+            workflow,
+            &mut findings,
+        )?;
+
+        for job in workflow.jobs() {
+            let Job::NormalJob(job) = job else {
+                continue;
+            };
+
+            let mut job_scope = workflow_scope.clone();
+            Self::check_env(
+                &job.env,
+                job.location(),
+                &mut job_scope,
+                workflow,
+                &mut findings,
+            )?;
+
+            for step in job.steps() {
+                let StepBody::Run { env, .. } = &step.body else {
+                    continue;
+                };
+
+                let mut step_scope = job_scope.clone();
+                Self::check_env(
+                    env,
+                    step.location(),
+                    &mut step_scope,
+                    workflow,
+                    &mut findings,
+                )?;
+            }
+        }
+
+        Ok(findings)
+    }
+}
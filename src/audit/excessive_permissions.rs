@@ -10,7 +10,7 @@ use crate::{
 };
 
 // Subjective mapping of permissions to severities, when given `write` access.
-static KNOWN_PERMISSIONS: LazyLock<HashMap<&str, Severity>> = LazyLock::new(|| {
+pub(crate) static KNOWN_PERMISSIONS: LazyLock<HashMap<&str, Severity>> = LazyLock::new(|| {
     [
         ("actions", Severity::High),
         ("attestations", Severity::High),
@@ -20,6 +20,8 @@ static KNOWN_PERMISSIONS: LazyLock<HashMap<&str, Severity>> = LazyLock::new(|| {
         ("discussions", Severity::Medium),
         ("id-token", Severity::High),
         ("issues", Severity::High),
+        // Only `read` access is supported.
+        ("models", Severity::Low),
         ("packages", Severity::High),
         ("pages", Severity::High),
         ("pull-requests", Severity::High),
@@ -34,7 +36,35 @@ static KNOWN_PERMISSIONS: LazyLock<HashMap<&str, Severity>> = LazyLock::new(|| {
 audit_meta!(
     ExcessivePermissions,
     "excessive-permissions",
-    "overly broad permissions"
+    "overly broad permissions",
+    rationale = "
+        Flags overly broad permissions: blocks, as well as workflows and jobs that
+        rely on the default permissions.
+
+        Every job receives a GITHUB_TOKEN, whose permissions are inherited from the
+        workflow or the repository's defaults when not specified. A compromised
+        step can use that token to do anything its permissions allow, such as
+        pushing to the repository or publishing packages.
+
+        Findings range from low to high severity, depending on which permissions
+        are granted. Confidence is high for explicit permissions, and medium for
+        defaults, since zizmor can't see the repository's default setting.
+    ",
+    example = r#"
+        on: push
+
+        permissions: write-all
+
+        jobs:
+          test:
+            runs-on: ubuntu-latest
+            steps:
+              - run: make test
+    "#,
+    remediation = "
+        Set permissions: {} at the workflow level, and grant each job only the
+        individual permissions it needs.
+    ",
 );
 
 pub(crate) struct ExcessivePermissions {
@@ -52,6 +82,7 @@ impl Audit for ExcessivePermissions {
     fn audit_workflow<'w>(
         &self,
         workflow: &'w crate::models::Workflow,
+        _workflows: &super::LocalWorkflows<'w>,
     ) -> anyhow::Result<Vec<crate::finding::Finding<'w>>> {
         let mut findings = vec![];
 
@@ -63,11 +94,6 @@ impl Audit for ExcessivePermissions {
             })
             .all(|perm| !matches!(perm, Permissions::Base(BasePermission::Default)));
 
-        let explicit_parent_permissions = !matches!(
-            &workflow.permissions,
-            Permissions::Base(BasePermission::Default)
-        );
-
         let workflow_is_reusable_only =
             workflow.has_workflow_call() && workflow.has_single_trigger();
 
@@ -84,24 +110,9 @@ impl Audit for ExcessivePermissions {
                 Persona::Regular
             };
 
-        // Handle top-level permissions.
-        let location = workflow.location().primary();
-
-        for (severity, confidence, perm_location) in
-            self.check_workflow_permissions(&workflow.permissions, location)
-        {
-            findings.push(
-                Self::finding()
-                    .severity(severity)
-                    .confidence(confidence)
-                    .persona(workflow_finding_persona)
-                    .add_location(perm_location)
-                    .build(workflow)?,
-            );
-        }
-
-        for job in workflow.jobs() {
-            let (permissions, job_location, job_finding_persona) = match job {
+        let jobs = workflow
+            .jobs()
+            .map(|job| match job {
                 Job::NormalJob(job) => {
                     // For normal jobs: if the workflow is reusable-only, we
                     // emit pedantic findings.
@@ -119,22 +130,121 @@ impl Audit for ExcessivePermissions {
                     // the workflow is reusable-only.
                     (&job.permissions, job.location(), Persona::Regular)
                 }
-            };
+            })
+            .collect::<Vec<_>>();
+
+        // When neither the workflow nor some of its jobs declare any
+        // permissions, those jobs silently receive the default token.
+        // We emit a single finding for this, rather than one for the
+        // workflow and another for each job.
+        let undeclared = match &workflow.permissions {
+            Permissions::Base(BasePermission::Default) => jobs
+                .iter()
+                .filter(|(permissions, _, _)| {
+                    matches!(permissions, Permissions::Base(BasePermission::Default))
+                })
+                .collect::<Vec<_>>(),
+            _ => vec![],
+        };
 
-            if let Some((severity, confidence, perm_location)) = self.check_job_permissions(
-                permissions,
-                explicit_parent_permissions,
-                job_location.clone(),
-            ) {
+        // The finding is as visible as its most visible job, and the first
+        // such job holds its primary location.
+        let persona = undeclared.iter().map(|(_, _, persona)| *persona).max();
+        if let Some(primary_idx) = undeclared
+            .iter()
+            .position(|(_, _, job_persona)| Some(*job_persona) == persona)
+        {
+            let (_, primary, persona) = undeclared[primary_idx];
+            let mut finding = Self::finding()
+                .severity(Severity::Medium)
+                .confidence(Confidence::Medium)
+                .persona(*persona)
+                .add_location(primary.clone().primary().annotated(
+                    "no permissions: block here or at the workflow level; \
+                     declare least-privilege permissions explicitly",
+                ));
+
+            for (idx, (_, job_location, _)) in undeclared.iter().enumerate() {
+                if idx == primary_idx {
+                    continue;
+                }
+
+                finding = finding.add_location(
+                    job_location
+                        .clone()
+                        .annotated("this job also has no permissions: block"),
+                );
+            }
+
+            findings.push(finding.build(workflow)?);
+        }
+
+        // Handle top-level permissions. A workflow without permissions
+        // is already covered above, unless all of its jobs declare their own.
+        if undeclared.is_empty() {
+            let location = workflow.location().primary();
+
+            for (severity, confidence, perm_location) in
+                self.check_workflow_permissions(&workflow.permissions, location)
+            {
                 findings.push(
                     Self::finding()
                         .severity(severity)
                         .confidence(confidence)
-                        .persona(job_finding_persona)
-                        .add_location(job_location)
-                        .add_location(perm_location.primary())
+                        .persona(workflow_finding_persona)
+                        .add_location(perm_location)
                         .build(workflow)?,
-                )
+                );
+            }
+        }
+
+        for (permissions, job_location, job_finding_persona) in jobs {
+            // `pull_request` workflows get a read-only token by default, so
+            // explicit write grants within them are almost always unneeded.
+            if workflow.has_pull_request() {
+                for (severity, confidence, perm_location) in
+                    self.check_pull_request_permissions(permissions, job_location.clone())
+                {
+                    findings.push(
+                        Self::finding()
+                            .severity(severity)
+                            .confidence(confidence)
+                            .persona(job_finding_persona)
+                            .add_location(job_location.clone())
+                            .add_location(perm_location.primary())
+                            .add_location(
+                                workflow
+                                    .location()
+                                    .with_keys(&["on".into()])
+                                    .annotated("workflow is triggered by pull_request"),
+                            )
+                            .build(workflow)?,
+                    )
+                }
+            }
+
+            if let Some((severity, confidence, perm_location)) =
+                self.check_job_permissions(permissions, &workflow.permissions, job_location.clone())
+            {
+                let mut finding = Self::finding()
+                    .severity(severity)
+                    .confidence(confidence)
+                    .persona(job_finding_persona)
+                    .add_location(job_location)
+                    .add_location(perm_location.primary());
+
+                // If the workflow restricts its permissions, the job's
+                // broader permissions make that restriction illusory.
+                if Self::broadens(&workflow.permissions, permissions) {
+                    finding = finding.add_location(
+                        workflow
+                            .location()
+                            .with_keys(&["permissions".into()])
+                            .annotated("workflow restricts permissions here"),
+                    );
+                }
+
+                findings.push(finding.build(workflow)?)
             }
         }
 
@@ -200,36 +310,91 @@ impl ExcessivePermissions {
         results
     }
 
+    fn check_pull_request_permissions<'a>(
+        &self,
+        permissions: &'a Permissions,
+        location: SymbolicLocation<'a>,
+    ) -> Vec<(Severity, Confidence, SymbolicLocation<'a>)> {
+        let mut results = vec![];
+
+        // `write-all` is already flagged unconditionally.
+        let Permissions::Explicit(perms) = permissions else {
+            return results;
+        };
+
+        for (name, perm) in perms {
+            if *perm != Permission::Write {
+                continue;
+            }
+
+            results.push((
+                Severity::Medium,
+                Confidence::High,
+                location
+                    .with_keys(&["permissions".into(), name.as_str().into()])
+                    .annotated(format!(
+                        "{name}: write is granted to a pull_request-triggered job"
+                    )),
+            ));
+        }
+
+        results
+    }
+
+    /// Returns whether the `child` (i.e. job-level) permissions broaden
+    /// the restrictions set by the `parent` (i.e. workflow-level)
+    /// permissions.
+    ///
+    /// Only blanket grants (`read-all` and `write-all`) are considered:
+    /// explicit per-scope grants beneath a restrictive parent (like
+    /// `permissions: {}`) are the recommended way to scope each job.
+    fn broadens(parent: &Permissions, child: &Permissions) -> bool {
+        match (parent, child) {
+            // A parent without a `permissions:` block doesn't restrict anything.
+            (Permissions::Base(BasePermission::Default), _) => false,
+            (Permissions::Base(BasePermission::WriteAll), _) => false,
+            (_, Permissions::Base(BasePermission::WriteAll)) => true,
+            (Permissions::Explicit(perms), Permissions::Base(BasePermission::ReadAll)) => {
+                KNOWN_PERMISSIONS
+                    .keys()
+                    .any(|name| matches!(perms.get(*name), None | Some(Permission::None)))
+            }
+            _ => false,
+        }
+    }
+
     fn check_job_permissions<'a>(
         &self,
         permissions: &Permissions,
-        explicit_parent_permissions: bool,
+        parent_permissions: &Permissions,
         location: SymbolicLocation<'a>,
     ) -> Option<(Severity, Confidence, SymbolicLocation<'a>)> {
+        // Blanket permissions are always flagged, but are especially
+        // notable when they silently override a restrictive parent.
+        let overrides = match Self::broadens(parent_permissions, permissions) {
+            true => ", overriding the workflow's permissions",
+            false => "",
+        };
+
         match permissions {
             Permissions::Base(base) => match base {
-                // The job has no explicit permissions, meaning it gets
-                // the default $GITHUB_TOKEN *if* the workflow doesn't
-                // set any permissions.
-                BasePermission::Default if !explicit_parent_permissions => Some((
-                    Severity::Medium,
-                    Confidence::Medium,
-                    location.annotated("default permissions used due to no permissions: block"),
-                )),
+                // The job has no explicit permissions, meaning it inherits
+                // the workflow's. If the workflow doesn't set any either,
+                // that's flagged once for the whole workflow instead.
                 BasePermission::Default => None,
                 BasePermission::ReadAll => Some((
                     Severity::Medium,
                     Confidence::High,
                     location
                         .with_keys(&["permissions".into()])
-                        .annotated("uses read-all permissions"),
+                        .annotated(format!("uses read-all permissions{overrides}")),
                 )),
                 BasePermission::WriteAll => Some((
                     Severity::High,
                     Confidence::High,
                     location
                         .with_keys(&["permissions".into()])
-                        .annotated("uses write-all permissions"),
+                        .annotated(format!("uses write-all permissions{overrides}")),
                 )),
             },
             // In the general case, it's impossible to tell whether a job-level
@@ -0,0 +1,183 @@
+//! Detects explicit uses of the workflow's `GITHUB_TOKEN` in workflows
+//! that can run for pull requests from forks.
+//!
+//! For pull requests from forks, the `GITHUB_TOKEN` is read-only and
+//! secrets aren't available. Steps that explicitly pass the token along
+//! (e.g. to an action's `with:` or a script's `env:`) may fail or behave
+//! differently for fork pull requests, and some actions enable additional
+//! behavior whenever they're given a token.
+
+use std::ops::Deref as _;
+
+use github_actions_models::{common::expr::LoE, workflow::job::StepBody};
+
+use super::{audit_meta, Audit};
+use crate::{
+    expr::Expr,
+    finding::{Confidence, Finding, Severity},
+    models::{JobExt as _, NormalJob},
+    state::AuditState,
+    utils::extract_expressions,
+};
+
+/// Contexts that expand to the workflow's `GITHUB_TOKEN`.
+const TOKEN_CONTEXTS: &[&str] = &["github.token", "secrets.GITHUB_TOKEN"];
+
+pub(crate) struct ForkPrToken;
+
+audit_meta!(
+    ForkPrToken,
+    "fork-pr-token",
+    "explicit GITHUB_TOKEN use in a workflow that runs for fork pull requests",
+    rationale = "
+        Flags explicit uses of github.token or secrets.GITHUB_TOKEN in workflows
+        triggered by pull_request, pull_request_review, or
+        pull_request_review_comment.
+
+        These triggers run for pull requests from forks, where the GITHUB_TOKEN is
+        read-only. Steps that explicitly pass the token along may fail or behave
+        unexpectedly for fork pull requests, and some actions enable additional
+        behavior whenever they're given a token.
+
+        Findings are informational with medium confidence, since zizmor can't tell
+        whether the workflow actually runs for pull requests from forks.
+    ",
+    example = r#"
+        on: pull_request
+
+        jobs:
+          lint:
+            runs-on: ubuntu-latest
+            steps:
+              - uses: some/linter@v1
+                with:
+                  github-token: ${{ secrets.GITHUB_TOKEN }}
+    "#,
+    remediation = "
+        Only pass the token to steps that need it, and make sure those steps
+        tolerate a read-only token. Steps that need more than read access should
+        run in a separate workflow, e.g. one triggered by workflow_run.
+    ",
+);
+
+impl ForkPrToken {
+    /// Returns each `GITHUB_TOKEN` context that's expanded into `value`.
+    fn token_contexts(value: &str) -> Vec<String> {
+        let mut contexts = vec![];
+
+        for (expr, _) in extract_expressions(value) {
+            let Ok(parsed) = Expr::parse(expr.as_bare()) else {
+                tracing::warn!("couldn't parse expression: {expr}", expr = expr.as_bare());
+                continue;
+            };
+
+            contexts.extend(
+                parsed
+                    .contexts()
+                    .into_iter()
+                    .filter(|ctx| TOKEN_CONTEXTS.iter().any(|token| **ctx == **token))
+                    .map(|ctx| ctx.as_str().to_string()),
+            );
+        }
+
+        contexts
+    }
+}
+
+impl Audit for ForkPrToken {
+    fn new(_state: AuditState) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        Ok(Self)
+    }
+
+    fn audit_normal_job<'w>(&self, job: &NormalJob<'w>) -> anyhow::Result<Vec<Finding<'w>>> {
+        let mut findings = vec![];
+
+        let workflow = job.parent();
+        if !workflow.has_fork_pull_request() {
+            return Ok(findings);
+        }
+
+        for step in job.steps() {
+            // Each (route, value) in this step that may expand the token.
+            let mut values = vec![];
+
+            match &step.deref().body {
+                StepBody::Uses { with, .. } => {
+                    values.extend(
+                        with.iter()
+                            .map(|(k, v)| (vec!["with".into(), k.as_str().into()], v.to_string())),
+                    );
+                }
+                StepBody::Run { run, env, .. } => {
+                    values.push((vec!["run".into()], run.clone()));
+
+                    if let LoE::Literal(env) = env {
+                        values.extend(
+                            env.iter().map(|(k, v)| {
+                                (vec!["env".into(), k.as_str().into()], v.to_string())
+                            }),
+                        );
+                    }
+                }
+            }
+
+            for (route, value) in values {
+                for context in Self::token_contexts(&value) {
+                    findings.push(
+                        Self::finding()
+                            .severity(Severity::Informational)
+                            .confidence(Confidence::Medium)
+                            .add_location(step.location_with_name())
+                            .add_location(
+                                step.location()
+                                    .primary()
+                                    .with_keys(&route)
+                                    .annotated(format!("{context} is used here")),
+                            )
+                            .add_location(
+                                workflow
+                                    .location()
+                                    .with_keys(&["on".into()])
+                                    .annotated("may run for pull requests from forks"),
+                            )
+                            .build(workflow)?,
+                    );
+                }
+            }
+        }
+
+        Ok(findings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ForkPrToken;
+
+    #[test]
+    fn test_token_contexts() {
+        for (value, expected) in &[
+            ("hello", vec![]),
+            ("${{ secrets.MY_PAT }}", vec![]),
+            ("${{ github.token }}", vec!["github.token"]),
+            ("${{ secrets.github_token }}", vec!["secrets.github_token"]),
+            (
+                "${{ secrets.MY_PAT || secrets.GITHUB_TOKEN }}",
+                vec!["secrets.GITHUB_TOKEN"],
+            ),
+            (
+                "gh pr view --json title -q .title ${{ github.event.number }} # ${{ github.token }}",
+                vec!["github.token"],
+            ),
+        ] {
+            assert_eq!(
+                &ForkPrToken::token_contexts(value),
+                expected,
+                "failed: {value}"
+            );
+        }
+    }
+}
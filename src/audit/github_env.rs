@@ -10,7 +10,7 @@ use streaming_iterator::StreamingIterator;
 use tree_sitter::{Language, Parser, Query, QueryCapture, QueryCursor, QueryMatches, Tree};
 
 use super::{audit_meta, Audit};
-use crate::finding::{Confidence, Finding, Severity};
+use crate::finding::{Confidence, Finding, Severity, SymbolicLocation};
 use crate::models::{JobExt as _, Step};
 use crate::state::AuditState;
 use crate::utils;
@@ -19,6 +19,30 @@ static GITHUB_ENV_WRITE_CMD: LazyLock<Regex> = LazyLock::new(|| {
     Regex::new(r#"(?mi)^.+\s*>>?\s*"?%(?<destination>GITHUB_ENV|GITHUB_PATH)%"?.*$"#).unwrap()
 });
 
+/// Matches `echo "NAME=..." >> $GITHUB_ENV` and similar, capturing `NAME`.
+///
+/// This is deliberately shell-agnostic: it covers the bash/sh
+/// (`$GITHUB_ENV`, `${GITHUB_ENV}`), pwsh (`$env:GITHUB_ENV`) and cmd
+/// (`%GITHUB_ENV%`) spellings, as well as `| tee [-a] $GITHUB_ENV`.
+static GITHUB_ENV_ECHO_WRITE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?m)(?:^|&&|;)[ \t]*echo[ \t]+(?:-[a-zA-Z]+[ \t]+)*["']?(?<name>[A-Za-z_][A-Za-z0-9_]*)=.*?(?:>>?|\|[ \t]*tee(?:[ \t]+-a)?)[ \t]*["']?(?i:\$\{?GITHUB_ENV\b\}?|\$env:GITHUB_ENV\b|%GITHUB_ENV%)"#,
+    )
+    .unwrap()
+});
+
+/// Environment variables that control which code later steps load or
+/// execute. Writing these to `GITHUB_ENV` is dangerous even when the
+/// written value looks static, since it's trivially escalated into
+/// code execution in every subsequent step.
+const SENSITIVE_ENV_VARS: &[&str] = &[
+    "PATH",
+    "LD_PRELOAD",
+    "LD_LIBRARY_PATH",
+    "NODE_OPTIONS",
+    "PYTHONPATH",
+];
+
 pub(crate) struct GitHubEnv {
     // NOTE: interior mutability used since Parser::parse requires &mut self
     bash_parser: RefCell<Parser>,
@@ -31,7 +55,37 @@ pub(crate) struct GitHubEnv {
     pwsh_pipeline_query: SpannedQuery,
 }
 
-audit_meta!(GitHubEnv, "github-env", "dangerous use of environment file");
+audit_meta!(
+    GitHubEnv,
+    "github-env",
+    "dangerous use of environment file",
+    rationale = "
+        Flags writes to the GITHUB_ENV and GITHUB_PATH environment files in
+        workflows with dangerous triggers.
+
+        Writes to these files affect every later step in the job. If an attacker
+        controls any part of what's written, they can set variables like
+        LD_PRELOAD or BASH_ENV, or prepend to the PATH, to execute arbitrary code.
+
+        Findings are high severity. Confidence is high when the workflow uses a
+        dangerous trigger, and low otherwise.
+    ",
+    example = r#"
+        on: pull_request_target
+
+        jobs:
+          build:
+            runs-on: ubuntu-latest
+            steps:
+              - run: echo "TITLE=${PR_TITLE}" >> "$GITHUB_ENV"
+                env:
+                  PR_TITLE: ${{ github.event.pull_request.title }}
+    "#,
+    remediation = "
+        Avoid GITHUB_ENV and GITHUB_PATH in privileged workflows. To pass values
+        between steps, use step outputs (GITHUB_OUTPUT) instead.
+    ",
+);
 
 /// Holds a tree-sitter query that contains a `@span` capture that
 /// covers the entire range of the query.
@@ -313,6 +367,68 @@ impl GitHubEnv {
         Ok(matching_spans)
     }
 
+    /// Returns each write of a [`SENSITIVE_ENV_VARS`] variable to
+    /// `GITHUB_ENV` in the given `run:` body, along with its span.
+    fn sensitive_env_writes(run_step_body: &str) -> Vec<(&str, Range<usize>)> {
+        GITHUB_ENV_ECHO_WRITE
+            .captures_iter(run_step_body)
+            .filter_map(|c| {
+                let name = c.name("name").unwrap().as_str();
+                let span = c.get(0).unwrap().range();
+
+                SENSITIVE_ENV_VARS.contains(&name).then_some((name, span))
+            })
+            .collect()
+    }
+
+    fn github_env_findings<'w>(
+        &self,
+        run: &str,
+        shell: &str,
+        location: SymbolicLocation<'w>,
+        document: &'w impl AsRef<yamlpath::Document>,
+    ) -> Result<Vec<Finding<'w>>> {
+        let mut findings = vec![];
+
+        let sensitive_writes = Self::sensitive_env_writes(run);
+        for (name, _span) in &sensitive_writes {
+            findings.push(
+                Self::finding()
+                    .severity(Severity::High)
+                    .confidence(Confidence::High)
+                    .add_location(location.clone().primary().annotated(format!(
+                        "write to {name} via GITHUB_ENV may allow code execution"
+                    )))
+                    .build(document)?,
+            )
+        }
+
+        for (dest, span) in self.uses_github_env(run, shell)? {
+            // Writes of sensitive variables have already been flagged above.
+            if sensitive_writes
+                .iter()
+                .any(|(_, sensitive)| sensitive.start < span.end && span.start < sensitive.end)
+            {
+                continue;
+            }
+
+            findings.push(
+                Self::finding()
+                    .severity(Severity::High)
+                    .confidence(Confidence::Low)
+                    .add_location(
+                        location
+                            .clone()
+                            .primary()
+                            .annotated(format!("write to {dest} may allow code execution")),
+                    )
+                    .build(document)?,
+            )
+        }
+
+        Ok(findings)
+    }
+
     fn uses_github_env<'hay>(
         &self,
         run_step_body: &'hay str,
@@ -394,20 +510,12 @@ impl Audit for GitHubEnv {
             });
 
             // TODO: actually use the spanning information here.
-            for (dest, _span) in self.uses_github_env(run, shell)? {
-                findings.push(
-                    Self::finding()
-                        .severity(Severity::High)
-                        .confidence(Confidence::Low)
-                        .add_location(
-                            step.location()
-                                .primary()
-                                .with_keys(&["run".into()])
-                                .annotated(format!("write to {dest} may allow code execution")),
-                        )
-                        .build(step.workflow())?,
-                )
-            }
+            findings.extend(self.github_env_findings(
+                run,
+                shell,
+                step.location().with_keys(&["run".into()]),
+                step.workflow(),
+            )?);
         }
 
         Ok(findings)
@@ -424,20 +532,12 @@ impl Audit for GitHubEnv {
         };
 
         // TODO: actually use the spanning information here.
-        for (dest, _span) in self.uses_github_env(run, shell)? {
-            findings.push(
-                Self::finding()
-                    .severity(Severity::High)
-                    .confidence(Confidence::Low)
-                    .add_location(
-                        step.location()
-                            .primary()
-                            .with_keys(&["run".into()])
-                            .annotated(format!("write to {dest} may allow code execution")),
-                    )
-                    .build(step.action())?,
-            )
-        }
+        findings.extend(self.github_env_findings(
+            run,
+            shell,
+            step.location().with_keys(&["run".into()]),
+            step.action(),
+        )?);
 
         Ok(findings)
     }
@@ -506,9 +606,16 @@ mod tests {
         ] {
             let audit_state = AuditState {
                 no_online_audits: false,
+                fix: false,
+                persona: Default::default(),
                 cache_dir: "/tmp/zizmor".into(),
                 gh_token: None,
                 gh_hostname: GitHubHost::Standard("github.com".into()),
+                config: Default::default(),
+                advisory_db: None,
+                rate_limit: Default::default(),
+                request_timeout: AuditState::DEFAULT_REQUEST_TIMEOUT,
+                ca_bundle: None,
             };
 
             let sut = GitHubEnv::new(audit_state).expect("failed to create audit");
@@ -519,6 +626,45 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_sensitive_env_writes() {
+        for (case, expected) in &[
+            ("echo \"PATH=$PATH:/opt/bin\" >> $GITHUB_ENV", Some("PATH")),
+            ("echo PATH=/opt/bin >> \"${GITHUB_ENV}\"", Some("PATH")),
+            (
+                "echo 'LD_PRELOAD=./libfoo.so' >> $GITHUB_ENV",
+                Some("LD_PRELOAD"),
+            ),
+            (
+                "echo -e \"NODE_OPTIONS=--require ./x.js\">>$GITHUB_ENV",
+                Some("NODE_OPTIONS"),
+            ),
+            (
+                "echo \"PYTHONPATH=src\" | tee -a $GITHUB_ENV",
+                Some("PYTHONPATH"),
+            ),
+            (
+                "cd foo && echo LD_LIBRARY_PATH=lib >> $GITHUB_ENV",
+                Some("LD_LIBRARY_PATH"),
+            ),
+            ("echo \"PATH=$env:PATH\" >> $env:GITHUB_ENV", Some("PATH")),
+            ("echo PATH=C:\\bin>>%GITHUB_ENV%", Some("PATH")),
+            // negative cases
+            ("echo \"FOO=bar\" >> $GITHUB_ENV", None),
+            ("echo \"MYPATH=bar\" >> $GITHUB_ENV", None),
+            ("echo \"PATH=bar\" >> $GITHUB_OUTPUT", None),
+            ("echo \"PATH=bar\" >> $GITHUB_ENVX", None),
+            ("echo \"PATH=bar\"", None),
+        ] {
+            let writes = GitHubEnv::sensitive_env_writes(case);
+            assert_eq!(
+                writes.first().map(|(name, _)| *name),
+                *expected,
+                "failed: {case}"
+            );
+        }
+    }
+
     #[test]
     fn test_exploitable_cmd_patterns() {
         for (case, expected) in &[
@@ -592,9 +738,16 @@ mod tests {
         ] {
             let audit_state = AuditState {
                 no_online_audits: false,
+                fix: false,
+                persona: Default::default(),
                 cache_dir: "/tmp/zizmor".into(),
                 gh_token: None,
                 gh_hostname: GitHubHost::Standard("github.com".into()),
+                config: Default::default(),
+                advisory_db: None,
+                rate_limit: Default::default(),
+                request_timeout: AuditState::DEFAULT_REQUEST_TIMEOUT,
+                ca_bundle: None,
             };
 
             let sut = GitHubEnv::new(audit_state).expect("failed to create audit");
@@ -0,0 +1,269 @@
+//! Detects step outputs that are set from attacker-controllable values
+//! and then expanded by later steps in the same job.
+//!
+//! Writing a value to `GITHUB_OUTPUT` doesn't sanitize it: a later step
+//! that expands `${{ steps.<id>.outputs.<name> }}` in its `run:` block or
+//! `if:` condition is just as injectable as one that expands the original
+//! attacker-controllable context directly. Like `github-env`, this uses a
+//! shell-agnostic pattern to find writes to the output file.
+
+use std::sync::LazyLock;
+
+use github_actions_models::{
+    common::{
+        expr::{ExplicitExpr, LoE},
+        If,
+    },
+    workflow::job::StepBody,
+};
+use regex::Regex;
+
+use super::{audit_meta, Audit};
+use crate::{
+    expr::Expr,
+    finding::{Confidence, Finding, Severity},
+    models::{JobExt as _, NormalJob, Step},
+    state::AuditState,
+    utils::{controllable_contexts, extract_expressions},
+};
+
+/// Matches `echo "name=..." >> $GITHUB_OUTPUT` and similar, capturing the
+/// output's `name` and written `value`.
+///
+/// Like `github-env`'s pattern, this covers the bash/sh, pwsh and cmd
+/// spellings of the output file, as well as `| tee [-a] $GITHUB_OUTPUT`.
+static GITHUB_OUTPUT_ECHO_WRITE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?m)(?:^|&&|;)[ \t]*echo[ \t]+(?:-[a-zA-Z]+[ \t]+)*["']?(?<name>[A-Za-z_][A-Za-z0-9_-]*)=(?<value>.*?)["']?[ \t]*(?:>>?|\|[ \t]*tee(?:[ \t]+-a)?)[ \t]*["']?(?i:\$\{?GITHUB_OUTPUT\b\}?|\$env:GITHUB_OUTPUT\b|%GITHUB_OUTPUT%)"#,
+    )
+    .unwrap()
+});
+
+/// Matches a shell variable reference, e.g. `$FOO`, `${FOO}` or `$env:FOO`.
+static SHELL_VARIABLE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\$(?:env:)?\{?(?<name>[A-Za-z_][A-Za-z0-9_]*)\}?"#).unwrap());
+
+pub(crate) struct GitHubOutput;
+
+audit_meta!(
+    GitHubOutput,
+    "github-output",
+    "attacker-controllable value passed through a step output",
+    rationale = "
+        Flags step outputs that are set from attacker-controllable values, and
+        then expanded in the run: block or if: condition of a later step in the
+        same job.
+
+        Passing a value through GITHUB_OUTPUT doesn't sanitize it. A later step
+        that expands the output with ${{ steps.<id>.outputs.<name> }} is just as
+        injectable as one that expands the original context directly.
+
+        Findings are high severity with medium confidence, since the written
+        value may be transformed before it's written.
+    ",
+    example = r#"
+        steps:
+          - id: title
+            run: echo "title=${{ github.event.pull_request.title }}" >> "$GITHUB_OUTPUT"
+          - run: echo "${{ steps.title.outputs.title }}"
+    "#,
+    remediation = "
+        Pass step outputs to later steps through environment variables, and
+        quote them when they're expanded, e.g. env: TITLE: ${{
+        steps.title.outputs.title }} and then \"$TITLE\".
+    ",
+);
+
+/// An attacker-controllable value written to a step's output.
+struct TaintedOutput<'w> {
+    /// The writing step.
+    step: Step<'w>,
+    /// The `steps.<id>.outputs.<name>` context for the output.
+    context: String,
+    /// The attacker-controllable context that's written.
+    source: String,
+}
+
+impl GitHubOutput {
+    /// Returns each output written by `run`, along with the
+    /// attacker-controllable contexts that its value expands.
+    ///
+    /// Values are controllable when they expand a controllable context
+    /// directly, or via a shell variable that's set from one in `env`.
+    fn tainted_writes(run: &str, env: impl Fn(&str) -> Option<String>) -> Vec<(&str, Vec<String>)> {
+        GITHUB_OUTPUT_ECHO_WRITE
+            .captures_iter(run)
+            .filter_map(|c| {
+                let name = c.name("name")?.as_str();
+                let value = c.name("value")?.as_str();
+
+                let mut sources = controllable_contexts(value);
+                for var in SHELL_VARIABLE.captures_iter(value) {
+                    if let Some(env_value) = env(&var["name"]) {
+                        sources.extend(controllable_contexts(&env_value));
+                    }
+                }
+
+                (!sources.is_empty()).then_some((name, sources))
+            })
+            .collect()
+    }
+
+    /// Returns each context in `contexts` that's expanded by `expr`.
+    fn expanded_contexts<'c>(expr: &str, contexts: &'c [&'c str]) -> Vec<&'c str> {
+        let Ok(parsed) = Expr::parse(expr) else {
+            tracing::warn!("couldn't parse expression: {expr}");
+            return vec![];
+        };
+
+        contexts
+            .iter()
+            .filter(|context| parsed.contexts().iter().any(|ctx| **ctx == ***context))
+            .copied()
+            .collect()
+    }
+}
+
+impl Audit for GitHubOutput {
+    fn new(_state: AuditState) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        Ok(Self)
+    }
+
+    fn audit_normal_job<'w>(&self, job: &NormalJob<'w>) -> anyhow::Result<Vec<Finding<'w>>> {
+        let mut findings = vec![];
+        let mut tainted: Vec<TaintedOutput<'w>> = vec![];
+
+        for step in job.steps() {
+            // First, check whether this step consumes any previously
+            // tainted outputs in a dangerous position.
+            if !tainted.is_empty() {
+                let contexts = tainted
+                    .iter()
+                    .map(|t| t.context.as_str())
+                    .collect::<Vec<_>>();
+
+                let mut consumers = vec![];
+                if let StepBody::Run { run, .. } = &step.body {
+                    for (expr, _) in extract_expressions(run) {
+                        for context in Self::expanded_contexts(expr.as_bare(), &contexts) {
+                            consumers.push(("run", context));
+                        }
+                    }
+                }
+                if let Some(If::Expr(cond)) = &step.r#if {
+                    let bare = match ExplicitExpr::from_curly(cond) {
+                        Some(raw_expr) => raw_expr.as_bare().to_string(),
+                        None => cond.to_string(),
+                    };
+                    for context in Self::expanded_contexts(&bare, &contexts) {
+                        consumers.push(("if", context));
+                    }
+                }
+
+                for (key, context) in consumers {
+                    let output = tainted.iter().find(|t| t.context == context).unwrap();
+
+                    findings.push(
+                        Self::finding()
+                            .severity(Severity::High)
+                            .confidence(Confidence::Medium)
+                            .add_location(
+                                output.step.location().with_keys(&["run".into()]).annotated(
+                                    format!(
+                                        "{source} is written to {context}",
+                                        source = output.source
+                                    ),
+                                ),
+                            )
+                            .add_location(
+                                step.location()
+                                    .primary()
+                                    .with_keys(&[key.into()])
+                                    .annotated(format!(
+                                        "{context} may expand into attacker-controllable code"
+                                    )),
+                            )
+                            .build(job.parent())?,
+                    );
+                }
+            }
+
+            // Then, record any tainted outputs that this step writes.
+            // Outputs can only be referenced by later steps via the
+            // writing step's `id:`.
+            let (Some(id), StepBody::Run { run, env, .. }) = (&step.id, &step.body) else {
+                continue;
+            };
+
+            let env = |name: &str| match env {
+                LoE::Literal(env) => env.get(name).map(|v| v.to_string()),
+                LoE::Expr(_) => None,
+            };
+
+            for (name, sources) in Self::tainted_writes(run, env) {
+                tainted.push(TaintedOutput {
+                    step: step.clone(),
+                    context: format!("steps.{id}.outputs.{name}"),
+                    source: sources[0].clone(),
+                });
+            }
+        }
+
+        Ok(findings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GitHubOutput;
+
+    #[test]
+    fn test_tainted_writes() {
+        let env = |name: &str| {
+            (name == "TITLE").then(|| "${{ github.event.pull_request.title }}".to_string())
+        };
+
+        for (run, expected) in &[
+            ("echo \"foo=bar\" >> $GITHUB_OUTPUT", vec![]),
+            (
+                "echo \"number=${{ github.event.pull_request.number }}\" >> $GITHUB_OUTPUT",
+                vec![],
+            ),
+            (
+                "echo \"title=${{ github.event.pull_request.title }}\" >> $GITHUB_OUTPUT",
+                vec![("title", vec!["github.event.pull_request.title"])],
+            ),
+            (
+                "echo \"title=$TITLE\" >> \"$GITHUB_OUTPUT\"",
+                vec![("title", vec!["github.event.pull_request.title"])],
+            ),
+            (
+                "echo head-ref=${{ github.head_ref }} | tee -a ${GITHUB_OUTPUT}",
+                vec![("head-ref", vec!["github.head_ref"])],
+            ),
+            (
+                "echo \"body=${{ github.event.issue.body }}\" >> $GITHUB_ENV",
+                vec![],
+            ),
+            (
+                "echo \"branch=$env:TITLE\" >> $env:GITHUB_OUTPUT",
+                vec![("branch", vec!["github.event.pull_request.title"])],
+            ),
+        ] {
+            let actual = GitHubOutput::tainted_writes(run, env);
+            let expected = expected
+                .iter()
+                .map(|(name, sources)| {
+                    (
+                        *name,
+                        sources.iter().map(|s| s.to_string()).collect::<Vec<_>>(),
+                    )
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(actual, expected, "failed: {run}");
+        }
+    }
+}
@@ -15,7 +15,30 @@ pub(crate) struct HardcodedContainerCredentials {}
 audit_meta!(
     HardcodedContainerCredentials,
     "hardcoded-container-credentials",
-    "hardcoded credential in GitHub Actions container configurations"
+    "hardcoded credential in GitHub Actions container configurations",
+    rationale = "
+        Flags container: and services: blocks with hardcoded registry credentials.
+
+        Credentials in a workflow file are visible to anyone who can read the
+        repository, and persist in its history.
+
+        Findings are high severity with high confidence.
+    ",
+    example = r#"
+        jobs:
+          test:
+            runs-on: ubuntu-latest
+            container:
+              image: registry.example.com/app
+              credentials:
+                username: user
+                password: hackme
+    "#,
+    remediation = "
+        Store the credentials as secrets, and reference them with expressions like
+        ${{ secrets.REGISTRY_PASSWORD }}. Rotate any credentials that have been
+        committed.
+    ",
 );
 
 impl Audit for HardcodedContainerCredentials {
@@ -29,6 +52,7 @@ impl Audit for HardcodedContainerCredentials {
     fn audit_workflow<'w>(
         &self,
         workflow: &'w crate::models::Workflow,
+        _workflows: &super::LocalWorkflows<'w>,
     ) -> anyhow::Result<Vec<crate::finding::Finding<'w>>> {
         let mut findings = vec![];
 
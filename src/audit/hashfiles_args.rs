@@ -0,0 +1,162 @@
+use crate::{
+    expr::{Context, Expr},
+    finding::{Confidence, Feature, Location, Severity},
+    utils::extract_expressions,
+};
+
+use super::{audit_meta, Audit, AuditInput};
+
+pub(crate) struct HashFilesArgs;
+
+audit_meta!(
+    HashFilesArgs,
+    "hashfiles-args",
+    "suspicious hashFiles() arguments",
+    rationale = "
+        Flags hashFiles() calls whose arguments can't match any files: calls
+        without any patterns, and calls with absolute paths (hashFiles() only
+        matches files under GITHUB_WORKSPACE).
+
+        hashFiles() returns an empty string when nothing matches, which silently
+        produces the same cache key for every run.
+
+        Findings are low severity, and are only shown with the developer persona
+        or more sensitive personas.
+    ",
+    example = r#"
+        steps:
+          - uses: actions/cache@v4
+            with:
+              path: ~/.npm
+              key: npm-${{ hashFiles('/package-lock.json') }}
+    "#,
+    remediation = "
+        Pass patterns relative to the workspace, such as '**/package-lock.json'.
+    ",
+);
+
+impl Audit for HashFilesArgs {
+    fn new(_state: super::AuditState) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        Ok(Self)
+    }
+
+    fn audit_raw<'w>(&self, input: &'w AuditInput) -> anyhow::Result<Vec<super::Finding<'w>>> {
+        let mut findings = vec![];
+        let raw = input.document().source();
+
+        for (expr, span) in extract_expressions(raw) {
+            let Ok(parsed) = Expr::parse(expr.as_bare()) else {
+                tracing::warn!("couldn't parse expression: {expr}", expr = expr.as_bare());
+                continue;
+            };
+
+            for (annotation, confidence) in Self::suspicious_hashfiles(&parsed) {
+                findings.push(
+                    Self::finding()
+                        .confidence(confidence)
+                        .severity(Severity::Low)
+                        .developer()
+                        .add_raw_location(Location::new(
+                            input.location().annotated(annotation).primary(),
+                            Feature::from_span(&span, input),
+                        ))
+                        .build(input)?,
+                );
+            }
+        }
+
+        Ok(findings)
+    }
+}
+
+impl HashFilesArgs {
+    /// Returns whether the given `hashFiles()` pattern is an absolute path,
+    /// which `hashFiles()` can never match, since it only matches files
+    /// under `GITHUB_WORKSPACE`.
+    fn is_absolute_pattern(pattern: &str) -> bool {
+        // Exclusion patterns (`!foo`) are still resolved like any other.
+        let pattern = pattern.strip_prefix('!').unwrap_or(pattern);
+
+        pattern.starts_with('/')
+            || pattern.starts_with('\\')
+            || pattern.starts_with('~')
+            // Windows drive paths, e.g. `C:\foo` or `C:/foo`.
+            || matches!(
+                pattern.as_bytes(),
+                [drive, b':', b'/' | b'\\', ..] if drive.is_ascii_alphabetic()
+            )
+    }
+
+    /// Returns an annotation and confidence for each suspicious
+    /// `hashFiles()` call in the given expression.
+    fn suspicious_hashfiles(expr: &Expr) -> Vec<(&'static str, Confidence)> {
+        let mut results = vec![];
+
+        match expr {
+            Expr::Call { func, args } => {
+                if func.eq_ignore_ascii_case("hashFiles") {
+                    if args.is_empty() {
+                        results.push((
+                            "hashFiles() without any patterns always hashes nothing",
+                            Confidence::High,
+                        ));
+                    } else if args
+                        .iter()
+                        .any(|arg| matches!(arg, Expr::String(pattern) if Self::is_absolute_pattern(pattern)))
+                    {
+                        results.push((
+                            "hashFiles() can't match absolute paths, only paths under the workspace",
+                            Confidence::Medium,
+                        ));
+                    }
+                }
+
+                results.extend(args.iter().flat_map(Self::suspicious_hashfiles));
+            }
+            Expr::Index(expr) => results.extend(Self::suspicious_hashfiles(expr)),
+            Expr::Context(Context { raw: _, components }) => {
+                results.extend(components.iter().flat_map(Self::suspicious_hashfiles))
+            }
+            Expr::BinOp { lhs, op: _, rhs } => {
+                results.extend(Self::suspicious_hashfiles(lhs));
+                results.extend(Self::suspicious_hashfiles(rhs));
+            }
+            Expr::UnOp { op: _, expr } => results.extend(Self::suspicious_hashfiles(expr)),
+            _ => (),
+        }
+
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_suspicious_hashfiles() {
+        for (expr, count) in &[
+            ("hashFiles('**/package-lock.json')", 0),
+            ("hashFiles('**/go.sum', '!vendor/**')", 0),
+            ("hashFiles(format('{0}/go.sum', matrix.dir))", 0),
+            ("hashFiles()", 1),
+            ("HASHFILES()", 1),
+            ("hashFiles('/home/runner/go.sum')", 1),
+            ("hashFiles('**/go.sum', '!/tmp/**')", 1),
+            ("hashFiles('~/.cargo/registry')", 1),
+            ("hashFiles('C:\\cache\\foo')", 1),
+            ("hashFiles('c:/cache/foo')", 1),
+            ("format('{0}-{1}', runner.os, hashFiles())", 1),
+            ("hashFiles() || hashFiles('/foo')", 2),
+            ("format('{0}', hashFiles())", 1),
+        ] {
+            let expr = crate::expr::Expr::parse(expr).unwrap();
+            assert_eq!(
+                super::HashFilesArgs::suspicious_hashfiles(&expr).len(),
+                *count,
+                "failed: {expr:?}"
+            );
+        }
+    }
+}
@@ -25,7 +25,27 @@ pub(crate) struct ImpostorCommit {
 audit_meta!(
     ImpostorCommit,
     "impostor-commit",
-    "commit with no history in referenced repository"
+    "commit with no history in referenced repository",
+    rationale = "
+        Flags uses: clauses pinned to commits that don't exist in the history of
+        the referenced repository.
+
+        GitHub shares a commit namespace between a repository and its forks, so a
+        commit in an attacker's fork can be referenced as if it belongs to the
+        upstream repository. An impostor commit looks like a properly pinned
+        action, but runs the attacker's code.
+
+        Findings are high severity with high confidence. This audit requires
+        GitHub API access.
+    ",
+    example = r#"
+        steps:
+          - uses: actions/checkout@c7d749a2d57b4b375d1ebcd17cfbfb60c676f18e
+    "#,
+    remediation = "
+        Pin to a commit that's reachable from one of the repository's branches or
+        tags, and check any unfamiliar pins against the upstream history.
+    ",
 );
 
 impl ImpostorCommit {
@@ -125,7 +145,11 @@ impl Audit for ImpostorCommit {
         Ok(ImpostorCommit { client })
     }
 
-    fn audit_workflow<'w>(&self, workflow: &'w Workflow) -> Result<Vec<Finding<'w>>> {
+    fn audit_workflow<'w>(
+        &self,
+        workflow: &'w Workflow,
+        _workflows: &super::LocalWorkflows<'w>,
+    ) -> Result<Vec<Finding<'w>>> {
         let mut findings = vec![];
 
         for job in workflow.jobs() {
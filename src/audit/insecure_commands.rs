@@ -17,7 +17,28 @@ pub(crate) struct InsecureCommands;
 audit_meta!(
     InsecureCommands,
     "insecure-commands",
-    "execution of insecure workflow commands is enabled"
+    "execution of insecure workflow commands is enabled",
+    rationale = "
+        Flags workflows that set ACTIONS_ALLOW_UNSECURE_COMMANDS.
+
+        This re-enables the deprecated set-env and add-path workflow commands,
+        which let any process that can write to the job's output modify the
+        environment and PATH of later steps.
+
+        Findings are high severity. Confidence is high when the variable is set
+        literally, and low when it's set by an expression.
+    ",
+    example = r#"
+        jobs:
+          build:
+            runs-on: ubuntu-latest
+            env:
+              ACTIONS_ALLOW_UNSECURE_COMMANDS: true
+    "#,
+    remediation = "
+        Remove ACTIONS_ALLOW_UNSECURE_COMMANDS, and use the GITHUB_ENV and
+        GITHUB_PATH environment files instead of the legacy commands.
+    ",
 );
 
 impl InsecureCommands {
@@ -104,7 +125,11 @@ impl Audit for InsecureCommands {
         Ok(Self {})
     }
 
-    fn audit_workflow<'w>(&self, workflow: &'w Workflow) -> anyhow::Result<Vec<Finding<'w>>> {
+    fn audit_workflow<'w>(
+        &self,
+        workflow: &'w Workflow,
+        _workflows: &super::LocalWorkflows<'w>,
+    ) -> anyhow::Result<Vec<Finding<'w>>> {
         let mut results = vec![];
 
         match &workflow.env {
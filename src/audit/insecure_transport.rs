@@ -0,0 +1,265 @@
+//! Detects `run:` scripts that disable TLS certificate verification.
+//!
+//! Commands like `curl -k` or `npm config set strict-ssl false` make
+//! subsequent downloads vulnerable to MITM attacks, which in CI typically
+//! means an attacker can substitute dependencies or tools that are then
+//! executed with the job's credentials.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use super::{audit_meta, Audit};
+use crate::{
+    finding::{Confidence, Severity, SymbolicLocation},
+    models::{StepBodyCommon, StepCommon},
+    state::AuditState,
+};
+
+/// Each pattern that disables TLS verification, along with a short
+/// description of what it disables.
+///
+/// To detect a new toggle, add it here.
+static INSECURE_TRANSPORT_PATTERNS: LazyLock<Vec<(Regex, &str)>> = LazyLock::new(|| {
+    [
+        // Each command's flags are only matched up to the next shell operator,
+        // so that e.g. `curl ... | tar -xzkf -` isn't attributed to curl.
+        //
+        // `-k` may be bundled with other short flags, e.g. `curl -fsSLk`.
+        (
+            r"\bcurl\b[^|;&]*\s(?:--insecure|-[a-zA-Z]*k[a-zA-Z]*)\b",
+            "curl certificate verification",
+        ),
+        (
+            r"\bwget\b[^|;&]*\s--no-check-certificate\b",
+            "wget certificate verification",
+        ),
+        (
+            r"(?i)\bhttp\.sslVerify(?:\s*=\s*|\s+)false\b",
+            "git certificate verification",
+        ),
+        (r"\bGIT_SSL_NO_VERIFY\s*=", "git certificate verification"),
+        (
+            r"\b(?:npm|yarn|pnpm)\b[^|;&]*\bstrict-ssl(?:\s*=\s*|\s+)false\b",
+            "npm certificate verification",
+        ),
+        (
+            r"\bNODE_TLS_REJECT_UNAUTHORIZED\s*=\s*['\x22]?0\b",
+            "Node.js TLS verification",
+        ),
+        (r"\bPIP_TRUSTED_HOST\s*=", "pip certificate verification"),
+        (
+            r"\bpip3?\b[^|;&]*\s--trusted-host\b",
+            "pip certificate verification",
+        ),
+    ]
+    .into_iter()
+    .map(|(pattern, desc)| (Regex::new(pattern).unwrap(), desc))
+    .collect()
+});
+
+pub(crate) struct InsecureTransport;
+
+audit_meta!(
+    InsecureTransport,
+    "insecure-transport",
+    "run: step disables TLS verification",
+    rationale = "
+        Flags run: scripts that disable TLS certificate verification, e.g. with
+        curl -k, git -c http.sslVerify=false, npm config set strict-ssl false, or
+        PIP_TRUSTED_HOST.
+
+        Without certificate verification, anyone who can intercept the runner's
+        traffic can substitute the downloaded dependencies or tools, which are
+        then executed with the job's credentials.
+
+        Findings are medium severity with high confidence.
+    ",
+    example = r#"
+        steps:
+          - run: curl -k https://example.com/install.sh -o install.sh
+    "#,
+    remediation = "
+        Leave TLS verification enabled. If a host uses a private certificate
+        authority, trust that authority explicitly instead (e.g. with curl
+        --cacert or NODE_EXTRA_CA_CERTS).
+    ",
+);
+
+impl InsecureTransport {
+    /// Returns each line of `script` that disables TLS verification, along
+    /// with a description of what it disables.
+    fn insecure_lines(script: &str) -> Vec<(&str, &'static str)> {
+        script
+            .lines()
+            .filter_map(|line| {
+                let (_, desc) = INSECURE_TRANSPORT_PATTERNS
+                    .iter()
+                    .find(|(pattern, _)| pattern.is_match(line))?;
+                Some((line.trim(), *desc))
+            })
+            .collect()
+    }
+
+    /// Returns an annotated location for each line in the step's `run:`
+    /// script that disables TLS verification.
+    fn insecure_locations<'s>(step: &impl StepCommon<'s>) -> Vec<SymbolicLocation<'s>> {
+        let StepBodyCommon::Run { run, .. } = step.body() else {
+            return vec![];
+        };
+
+        Self::insecure_lines(run)
+            .into_iter()
+            .map(|(line, desc)| {
+                step.location()
+                    .with_keys(&["run".into()])
+                    .annotated(format!("`{line}` disables {desc}"))
+            })
+            .collect()
+    }
+}
+
+impl Audit for InsecureTransport {
+    fn new(_state: AuditState) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        Ok(Self)
+    }
+
+    fn audit_step<'w>(&self, step: &super::Step<'w>) -> anyhow::Result<Vec<super::Finding<'w>>> {
+        let mut findings = vec![];
+
+        for location in Self::insecure_locations(step) {
+            findings.push(
+                Self::finding()
+                    .severity(Severity::Medium)
+                    .confidence(Confidence::High)
+                    .add_location(step.location_with_name())
+                    .add_location(location.primary())
+                    .build(step.workflow())?,
+            );
+        }
+
+        Ok(findings)
+    }
+
+    fn audit_composite_step<'a>(
+        &self,
+        step: &super::CompositeStep<'a>,
+    ) -> anyhow::Result<Vec<super::Finding<'a>>> {
+        let mut findings = vec![];
+
+        for location in Self::insecure_locations(step) {
+            findings.push(
+                Self::finding()
+                    .severity(Severity::Medium)
+                    .confidence(Confidence::High)
+                    .add_location(step.location_with_name())
+                    .add_location(location.primary())
+                    .build(step.action())?,
+            );
+        }
+
+        Ok(findings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InsecureTransport;
+
+    #[test]
+    fn test_insecure_lines() {
+        for (script, expected) in &[
+            ("curl -fsSL https://example.com", vec![]),
+            ("curl -K config.txt https://example.com", vec![]),
+            ("curl --keepalive-time 5 https://example.com", vec![]),
+            ("wget https://example.com", vec![]),
+            ("git config http.sslVerify true", vec![]),
+            ("npm config set strict-ssl true", vec![]),
+            ("pip install -r requirements.txt", vec![]),
+            ("curl -sSL https://example.com | tar -xzkf -", vec![]),
+            ("curl -fsSL https://example.com -o x && make -k", vec![]),
+            ("curl -fsSL https://example.com; rsync -avk src dst", vec![]),
+            ("wget https://example.com | foo --no-check-certificate", vec![]),
+            ("npm ci && echo strict-ssl false", vec![]),
+            (
+                "curl -k https://example.com",
+                vec![("curl -k https://example.com", "curl certificate verification")],
+            ),
+            (
+                "curl -fsSLk https://example.com | sh",
+                vec![(
+                    "curl -fsSLk https://example.com | sh",
+                    "curl certificate verification",
+                )],
+            ),
+            (
+                "curl --insecure -o out https://example.com",
+                vec![(
+                    "curl --insecure -o out https://example.com",
+                    "curl certificate verification",
+                )],
+            ),
+            (
+                "wget --no-check-certificate https://example.com",
+                vec![(
+                    "wget --no-check-certificate https://example.com",
+                    "wget certificate verification",
+                )],
+            ),
+            (
+                "git -c http.sslVerify=false clone https://example.com/repo",
+                vec![(
+                    "git -c http.sslVerify=false clone https://example.com/repo",
+                    "git certificate verification",
+                )],
+            ),
+            (
+                "git config --global http.sslverify false",
+                vec![(
+                    "git config --global http.sslverify false",
+                    "git certificate verification",
+                )],
+            ),
+            (
+                "export GIT_SSL_NO_VERIFY=1",
+                vec![("export GIT_SSL_NO_VERIFY=1", "git certificate verification")],
+            ),
+            (
+                "make\n  npm config set strict-ssl false\n",
+                vec![(
+                    "npm config set strict-ssl false",
+                    "npm certificate verification",
+                )],
+            ),
+            (
+                "export NODE_TLS_REJECT_UNAUTHORIZED=0",
+                vec![(
+                    "export NODE_TLS_REJECT_UNAUTHORIZED=0",
+                    "Node.js TLS verification",
+                )],
+            ),
+            (
+                "export PIP_TRUSTED_HOST=pypi.example.com\npip install --trusted-host pypi.example.com foo",
+                vec![
+                    (
+                        "export PIP_TRUSTED_HOST=pypi.example.com",
+                        "pip certificate verification",
+                    ),
+                    (
+                        "pip install --trusted-host pypi.example.com foo",
+                        "pip certificate verification",
+                    ),
+                ],
+            ),
+        ] {
+            assert_eq!(
+                &InsecureTransport::insecure_lines(script),
+                expected,
+                "failed: {script}"
+            );
+        }
+    }
+}
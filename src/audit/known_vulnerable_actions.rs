@@ -1,7 +1,8 @@
 //! Detects publicly disclosed action vulnerabilities.
 //!
 //! This audit uses GitHub's security advisories API as a source of
-//! ground truth.
+//! ground truth. When online audits aren't available, it can instead use
+//! a local advisory database (see [`crate::advisory_db`]).
 //!
 //! See: <https://docs.github.com/en/rest/security-advisories/global-advisories?apiVersion=2022-11-28>
 
@@ -12,27 +13,53 @@ use super::{audit_meta, Audit};
 use crate::finding::Finding;
 use crate::models::CompositeStep;
 use crate::{
+    advisory_db::AdvisoryDb,
     finding::{Confidence, Severity},
-    github_api,
+    github_api::{self, Advisory},
     models::uses::RepositoryUsesExt as _,
     state::AuditState,
 };
 
+/// Where advisories are looked up.
+enum AdvisorySource {
+    /// GitHub's advisories API.
+    Online(github_api::Client),
+    /// A local advisory database, for use when online audits aren't
+    /// available.
+    Offline(AdvisoryDb),
+}
+
 pub(crate) struct KnownVulnerableActions {
-    client: github_api::Client,
+    source: AdvisorySource,
 }
 
 audit_meta!(
     KnownVulnerableActions,
     "known-vulnerable-actions",
-    "action has a known vulnerability"
+    "action has a known vulnerability",
+    rationale = "
+        Flags uses: clauses that reference action versions with known security
+        advisories.
+
+        Findings take the severity of the matching advisory, with high confidence.
+        This audit requires GitHub API access, or a local advisory database passed
+        with --advisory-db.
+    ",
+    example = r#"
+        steps:
+          - uses: tj-actions/changed-files@v44
+    "#,
+    remediation = "
+        Upgrade to a version of the action that isn't affected by the advisory,
+        or remove the action.
+    ",
 );
 
 impl KnownVulnerableActions {
-    fn action_known_vulnerabilities(
-        &self,
+    fn online_advisories(
+        client: &github_api::Client,
         uses: &RepositoryUses,
-    ) -> Result<Vec<(Severity, String)>> {
+    ) -> Result<Vec<Advisory>> {
         let version = match &uses.git_ref {
             // If `uses` is pinned to a symbolic ref, we need to perform
             // feats of heroism to figure out what's going on.
@@ -51,19 +78,14 @@ impl KnownVulnerableActions {
             // To handle all of the above, we convert the ref into a commit
             // and then find the longest tag for that commit.
             Some(version) if !uses.ref_is_commit() => {
-                let Some(commit_ref) =
-                    self.client
-                        .commit_for_ref(&uses.owner, &uses.repo, version)?
+                let Some(commit_ref) = client.commit_for_ref(&uses.owner, &uses.repo, version)?
                 else {
                     // No `ref -> commit` means that the action's version
                     // is probably just outright invalid.
                     return Ok(vec![]);
                 };
 
-                match self
-                    .client
-                    .longest_tag_for_commit(&uses.owner, &uses.repo, &commit_ref)?
-                {
+                match client.longest_tag_for_commit(&uses.owner, &uses.repo, &commit_ref)? {
                     Some(tag) => tag.name,
                     // Somehow we've round-tripped through a commit and ended
                     // up without a tag, which suggests we went
@@ -76,8 +98,7 @@ impl KnownVulnerableActions {
             // tag matching that ref. In theory the action's repo could do
             // something annoying like use branches for versions instead,
             // which we should also probably support.
-            Some(commit_ref) => match self
-                .client
+            Some(commit_ref) => match client
                 .longest_tag_for_commit(&uses.owner, &uses.repo, commit_ref)
                 .with_context(|| {
                     format!(
@@ -101,9 +122,31 @@ impl KnownVulnerableActions {
             None => return Ok(vec![]),
         };
 
-        let vulns = self
-            .client
-            .gha_advisories(&uses.owner, &uses.repo, &version)?;
+        client.gha_advisories(&uses.owner, &uses.repo, &version)
+    }
+
+    /// Like [`Self::online_advisories`], but using a local advisory database.
+    ///
+    /// Without network access, symbolic refs and commits can't be resolved
+    /// to tags, so only actions that are pinned to an exact version tag
+    /// can be matched.
+    fn offline_advisories(db: &AdvisoryDb, uses: &RepositoryUses) -> Vec<Advisory> {
+        match &uses.git_ref {
+            Some(version) if !uses.ref_is_commit() => {
+                db.advisories(&uses.owner, &uses.repo, version)
+            }
+            _ => vec![],
+        }
+    }
+
+    fn action_known_vulnerabilities(
+        &self,
+        uses: &RepositoryUses,
+    ) -> Result<Vec<(Severity, String)>> {
+        let vulns = match &self.source {
+            AdvisorySource::Online(client) => Self::online_advisories(client, uses)?,
+            AdvisorySource::Offline(db) => Self::offline_advisories(db, uses),
+        };
 
         let mut results = vec![];
 
@@ -128,15 +171,21 @@ impl Audit for KnownVulnerableActions {
     where
         Self: Sized,
     {
-        if state.no_online_audits {
-            return Err(anyhow!("offline audits only requested"));
-        }
+        let client = match state.no_online_audits {
+            true => None,
+            false => state.github_client(),
+        };
 
-        let Some(client) = state.github_client() else {
-            return Err(anyhow!("can't run without a GitHub API token"));
+        let source = match (client, state.advisory_db) {
+            (Some(client), _) => AdvisorySource::Online(client),
+            (None, Some(db)) => AdvisorySource::Offline(db),
+            (None, None) if state.no_online_audits => {
+                return Err(anyhow!("offline audits only requested"))
+            }
+            (None, None) => return Err(anyhow!("can't run without a GitHub API token")),
         };
 
-        Ok(Self { client })
+        Ok(Self { source })
     }
 
     fn audit_step<'w>(&self, step: &super::Step<'w>) -> Result<Vec<super::Finding<'w>>> {
@@ -0,0 +1,162 @@
+//! Detects `run:` scripts that emit the legacy `::set-env::` and
+//! `::add-path::` workflow commands.
+//!
+//! These commands are disabled by default, and only work when
+//! `ACTIONS_ALLOW_UNSECURE_COMMANDS` is set. A script that emits them
+//! therefore either doesn't work, or depends on the insecure behavior
+//! that `insecure-commands` flags.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use super::{audit_meta, Audit};
+use crate::{
+    finding::{Confidence, Severity, SymbolicLocation},
+    models::{StepBodyCommon, StepCommon},
+    state::AuditState,
+};
+
+/// Matches a legacy `::set-env` or `::add-path` workflow command,
+/// capturing the command's name.
+static LEGACY_COMMAND: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"::(?<command>set-env|add-path)\b").unwrap());
+
+pub(crate) struct LegacyCommands;
+
+audit_meta!(
+    LegacyCommands,
+    "legacy-commands",
+    "use of legacy set-env or add-path workflow commands",
+    rationale = "
+        Flags run: scripts that emit the legacy ::set-env and ::add-path workflow
+        commands.
+
+        These commands are disabled by default, and only work when
+        ACTIONS_ALLOW_UNSECURE_COMMANDS is set. A script that emits them either
+        doesn't work as intended, or depends on that insecure setting.
+
+        Findings are medium severity with high confidence.
+    ",
+    example = r#"
+        steps:
+          - run: echo "::set-env name=VERSION::1.2.3"
+    "#,
+    remediation = "
+        Write to the GITHUB_ENV and GITHUB_PATH environment files instead, e.g.
+        echo \"VERSION=1.2.3\" >> \"$GITHUB_ENV\".
+    ",
+);
+
+impl LegacyCommands {
+    /// Returns each line of `script` that emits a legacy workflow command,
+    /// along with that command's name.
+    fn legacy_commands(script: &str) -> Vec<(&str, &str)> {
+        script
+            .lines()
+            .filter_map(|line| {
+                let command = LEGACY_COMMAND.captures(line)?.name("command")?.as_str();
+                Some((line.trim(), command))
+            })
+            .collect()
+    }
+
+    /// Returns an annotated location for each line in the step's `run:`
+    /// script that emits a legacy workflow command.
+    fn command_locations<'s>(step: &impl StepCommon<'s>) -> Vec<SymbolicLocation<'s>> {
+        let StepBodyCommon::Run { run, .. } = step.body() else {
+            return vec![];
+        };
+
+        Self::legacy_commands(run)
+            .into_iter()
+            .map(|(line, command)| {
+                step.location()
+                    .with_keys(&["run".into()])
+                    .annotated(format!("`{line}` emits the legacy {command} command"))
+            })
+            .collect()
+    }
+}
+
+impl Audit for LegacyCommands {
+    fn new(_state: AuditState) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        Ok(Self)
+    }
+
+    fn audit_step<'w>(&self, step: &super::Step<'w>) -> anyhow::Result<Vec<super::Finding<'w>>> {
+        let mut findings = vec![];
+
+        for location in Self::command_locations(step) {
+            findings.push(
+                Self::finding()
+                    .severity(Severity::Medium)
+                    .confidence(Confidence::High)
+                    .add_location(step.location_with_name())
+                    .add_location(location.primary())
+                    .build(step.workflow())?,
+            );
+        }
+
+        Ok(findings)
+    }
+
+    fn audit_composite_step<'a>(
+        &self,
+        step: &super::CompositeStep<'a>,
+    ) -> anyhow::Result<Vec<super::Finding<'a>>> {
+        let mut findings = vec![];
+
+        for location in Self::command_locations(step) {
+            findings.push(
+                Self::finding()
+                    .severity(Severity::Medium)
+                    .confidence(Confidence::High)
+                    .add_location(step.location_with_name())
+                    .add_location(location.primary())
+                    .build(step.action())?,
+            );
+        }
+
+        Ok(findings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LegacyCommands;
+
+    #[test]
+    fn test_legacy_commands() {
+        for (script, expected) in &[
+            ("echo hello", vec![]),
+            ("echo \"FOO=bar\" >> $GITHUB_ENV", vec![]),
+            ("echo ::set-output name=foo::bar", vec![]),
+            (
+                "echo \"::set-env name=FOO::bar\"",
+                vec![("echo \"::set-env name=FOO::bar\"", "set-env")],
+            ),
+            (
+                "make\n  echo ::add-path::/opt/bin\n",
+                vec![("echo ::add-path::/opt/bin", "add-path")],
+            ),
+            (
+                "Write-Output '::set-env name=A::1'\necho ::add-path::C:\\bin",
+                vec![
+                    ("Write-Output '::set-env name=A::1'", "set-env"),
+                    ("echo ::add-path::C:\\bin", "add-path"),
+                ],
+            ),
+            ("echo ::set-environment", vec![]),
+        ] {
+            assert_eq!(
+                &LegacyCommands::legacy_commands(script),
+                expected,
+                "failed: {script}"
+            );
+        }
+    }
+}
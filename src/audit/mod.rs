@@ -9,57 +9,97 @@ use yamlpath::Document;
 use crate::{
     finding::{Finding, FindingBuilder, SymbolicLocation},
     models::{Action, CompositeStep, Job, NormalJob, ReusableWorkflowCallJob, Step, Workflow},
-    registry::InputKey,
+    registry::{InputKey, LocalWorkflows},
     state::AuditState,
 };
 
+pub(crate) mod actor_conditions;
+pub(crate) mod artifact_poisoning;
 pub(crate) mod artipacked;
+pub(crate) mod auto_merge;
 pub(crate) mod bot_conditions;
+pub(crate) mod bundled_script_args;
 pub(crate) mod cache_poisoning;
+pub(crate) mod checkout_pat;
+pub(crate) mod checkout_submodules;
+pub(crate) mod controllable_concurrency;
+pub(crate) mod controllable_conditions;
+pub(crate) mod controllable_setup_inputs;
+pub(crate) mod cross_org_reusable_workflows;
 pub(crate) mod dangerous_triggers;
+pub(crate) mod duplicate_step_ids;
+pub(crate) mod dynamic_runs_on;
+pub(crate) mod env_case_collision;
 pub(crate) mod excessive_permissions;
+pub(crate) mod fork_pr_token;
 pub(crate) mod github_env;
+pub(crate) mod github_output;
 pub(crate) mod hardcoded_container_credentials;
+pub(crate) mod hashfiles_args;
 pub(crate) mod impostor_commit;
 pub(crate) mod insecure_commands;
+pub(crate) mod insecure_transport;
 pub(crate) mod known_vulnerable_actions;
+pub(crate) mod legacy_commands;
 pub(crate) mod overprovisioned_secrets;
+pub(crate) mod pull_request_target_checkout;
+pub(crate) mod reducible_permissions;
 pub(crate) mod ref_confusion;
+pub(crate) mod secret_exfiltration;
+pub(crate) mod secret_in_args;
+pub(crate) mod secret_in_inputs;
+pub(crate) mod secret_in_logs;
+pub(crate) mod secret_in_url;
 pub(crate) mod secrets_inherit;
 pub(crate) mod self_hosted_runner;
+pub(crate) mod stale_branch_workflows;
+pub(crate) mod tainted_env;
 pub(crate) mod template_injection;
+pub(crate) mod unknown_permissions;
+pub(crate) mod unnecessary_sudo;
+pub(crate) mod unpinned_images;
+pub(crate) mod unpinned_reusable_workflows;
 pub(crate) mod unpinned_uses;
+pub(crate) mod untrusted_checkout_secrets;
+pub(crate) mod unused_id_token;
 pub(crate) mod use_trusted_publishing;
 
 #[derive(Debug)]
-pub(crate) enum AuditInput {
+pub enum AuditInput {
     Workflow(Workflow),
     Action(Action),
 }
 
 impl AuditInput {
-    pub(crate) fn key(&self) -> &InputKey {
+    pub fn key(&self) -> &InputKey {
         match self {
             AuditInput::Workflow(workflow) => &workflow.key,
             AuditInput::Action(action) => &action.key,
         }
     }
 
-    pub(crate) fn document(&self) -> &yamlpath::Document {
+    pub(crate) fn key_mut(&mut self) -> &mut InputKey {
+        match self {
+            AuditInput::Workflow(workflow) => &mut workflow.key,
+            AuditInput::Action(action) => &mut action.key,
+        }
+    }
+
+    pub fn document(&self) -> &yamlpath::Document {
         match self {
             AuditInput::Workflow(workflow) => &workflow.document,
             AuditInput::Action(action) => &action.document,
         }
     }
 
-    pub(crate) fn line_index(&self) -> &LineIndex {
+    pub fn line_index(&self) -> &LineIndex {
         match self {
             AuditInput::Workflow(workflow) => &workflow.line_index,
             AuditInput::Action(action) => &action.line_index,
         }
     }
 
-    pub(crate) fn link(&self) -> Option<&str> {
+    pub fn link(&self) -> Option<&str> {
         match self {
             AuditInput::Workflow(workflow) => workflow.link.as_deref(),
             AuditInput::Action(action) => action.link.as_deref(),
@@ -92,6 +132,56 @@ impl From<Action> for AuditInput {
     }
 }
 
+/// A long-form explanation of an audit, as shown by `--explain`.
+pub struct Explanation {
+    /// The audit's identifier, e.g. `template-injection`.
+    pub ident: &'static str,
+    /// The audit's brief description.
+    pub desc: &'static str,
+    /// The URL for the audit's documentation.
+    pub url: &'static str,
+    rationale: &'static str,
+    example: &'static str,
+    remediation: &'static str,
+}
+
+impl Explanation {
+    /// Removes the common leading indentation from each line of `text`,
+    /// along with any surrounding blank lines.
+    fn dedent(text: &str) -> String {
+        let indent = text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| line.len() - line.trim_start().len())
+            .min()
+            .unwrap_or(0);
+
+        text.trim_matches('\n')
+            .lines()
+            .map(|line| line.get(indent..).unwrap_or("").trim_end())
+            .collect::<Vec<_>>()
+            .join("\n")
+            .trim()
+            .to_string()
+    }
+
+    /// What the audit detects, and why its findings have the severities
+    /// and confidences that they do.
+    pub fn rationale(&self) -> String {
+        Self::dedent(self.rationale)
+    }
+
+    /// A canonical example of a workflow that the audit flags.
+    pub fn example(&self) -> String {
+        Self::dedent(self.example)
+    }
+
+    /// How to remediate the audit's findings.
+    pub fn remediation(&self) -> String {
+        Self::dedent(self.remediation)
+    }
+}
+
 /// A supertrait for all audits.
 ///
 /// Workflow audits, action audits, and all future audit types
@@ -110,6 +200,10 @@ pub(crate) trait AuditCore {
     where
         Self: Sized;
 
+    fn explanation() -> Explanation
+    where
+        Self: Sized;
+
     fn finding<'w>() -> FindingBuilder<'w>
     where
         Self: Sized,
@@ -122,13 +216,30 @@ pub(crate) trait AuditCore {
 ///
 /// Example use:
 ///
-/// ```no_run
+/// ```ignore
 /// struct SomeAudit;
 ///
-/// audit_meta!(SomeAudit, "some-audit", "brief description");
+/// audit_meta!(
+///     SomeAudit,
+///     "some-audit",
+///     "brief description",
+///     rationale = "why this is bad, and how bad it is",
+///     example = "a workflow that the audit flags",
+///     remediation = "how to fix it",
+/// );
 /// ```
+///
+/// Each of `rationale`, `example`, and `remediation` is shown by
+/// `--explain`, and can be written as an indented multi-line string.
 macro_rules! audit_meta {
-    ($t:ty, $id:literal, $desc:expr) => {
+    (
+        $t:ty,
+        $id:literal,
+        $desc:expr,
+        rationale = $rationale:expr,
+        example = $example:expr,
+        remediation = $remediation:expr $(,)?
+    ) => {
         use crate::audit::AuditCore;
 
         impl AuditCore for $t {
@@ -146,6 +257,17 @@ macro_rules! audit_meta {
             fn url() -> &'static str {
                 concat!("https://woodruffw.github.io/zizmor/audits/#", $id)
             }
+
+            fn explanation() -> crate::audit::Explanation {
+                crate::audit::Explanation {
+                    ident: $id,
+                    desc: $desc,
+                    url: Self::url(),
+                    rationale: $rationale,
+                    example: $example,
+                    remediation: $remediation,
+                }
+            }
         }
     };
 }
@@ -202,7 +324,11 @@ pub(crate) trait Audit: AuditCore {
         Ok(vec![])
     }
 
-    fn audit_workflow<'w>(&self, workflow: &'w Workflow) -> Result<Vec<Finding<'w>>> {
+    fn audit_workflow<'w>(
+        &self,
+        workflow: &'w Workflow,
+        workflows: &LocalWorkflows<'w>,
+    ) -> Result<Vec<Finding<'w>>> {
         let mut results = vec![];
 
         for job in workflow.jobs() {
@@ -211,7 +337,7 @@ pub(crate) trait Audit: AuditCore {
                     results.extend(self.audit_normal_job(&normal)?);
                 }
                 Job::ReusableWorkflowCallJob(reusable) => {
-                    results.extend(self.audit_reusable_job(&reusable)?);
+                    results.extend(self.audit_reusable_job(&reusable.with_callee(workflows))?);
                 }
             }
         }
@@ -243,10 +369,17 @@ pub(crate) trait Audit: AuditCore {
     ///
     /// Implementors **should not** override this blanket implementation,
     /// since it's marked with tracing instrumentation.
-    #[instrument(skip(self))]
-    fn audit<'w>(&self, input: &'w AuditInput) -> Result<Vec<Finding<'w>>> {
+    ///
+    /// `workflows` contains every local workflow being audited, and is
+    /// used to resolve local reusable workflow calls to their callees.
+    #[instrument(skip(self, workflows))]
+    fn audit<'w>(
+        &self,
+        input: &'w AuditInput,
+        workflows: &LocalWorkflows<'w>,
+    ) -> Result<Vec<Finding<'w>>> {
         let mut results = match input {
-            AuditInput::Workflow(workflow) => self.audit_workflow(workflow),
+            AuditInput::Workflow(workflow) => self.audit_workflow(workflow, workflows),
             AuditInput::Action(action) => self.audit_action(action),
         }?;
 
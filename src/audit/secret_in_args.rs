@@ -0,0 +1,325 @@
+//! Detects secrets that are passed to commands as arguments.
+//!
+//! A process's arguments are visible to every other process on the same
+//! machine (e.g. via `ps` or `/proc/*/cmdline`), and are frequently echoed
+//! by tools that log the commands they run. Secrets passed via environment
+//! variables aren't exposed in either way.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use super::{audit_meta, secret_in_url::SecretInUrl, Audit};
+use crate::{
+    expr::Expr,
+    finding::{Confidence, Severity, SymbolicLocation},
+    models::{StepBodyCommon, StepCommon},
+    state::AuditState,
+    utils::{extract_expressions, mask_expressions},
+};
+
+/// Matches a shell variable assignment word, e.g. `FOO=` or `FOO+="`.
+static ASSIGNMENT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[A-Za-z_][A-Za-z0-9_]*\+?=").unwrap());
+
+/// Matches the start of a heredoc, capturing its delimiter.
+static HEREDOC: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"<<-?\s*['"]?([A-Za-z_][A-Za-z0-9_]*)['"]?"#).unwrap());
+
+/// Shell builtins, which run in the shell itself rather than in a new
+/// process, and therefore don't expose their arguments.
+const BUILTINS: &[&str] = &[
+    "[", "[[", "declare", "echo", "export", "local", "printf", "read", "readonly", "test",
+    "typeset",
+];
+
+pub(crate) struct SecretInArgs;
+
+audit_meta!(
+    SecretInArgs,
+    "secret-in-args",
+    "secret passed as a command-line argument",
+    rationale = "
+        Flags run: scripts that pass secrets as command-line arguments.
+
+        Command-line arguments are visible to other processes on the runner, such
+        as through ps or /proc, and may be logged by the invoked program.
+
+        Findings are low severity with medium confidence.
+    ",
+    example = r#"
+        steps:
+          - run: deploy --token ${{ secrets.DEPLOY_TOKEN }}
+    "#,
+    remediation = "
+        Pass secrets through environment variables or standard input instead.
+    ",
+);
+
+impl SecretInArgs {
+    /// Blanks out the bodies of any heredocs in `script`, returning
+    /// the blanked script along with the byte range of each body.
+    ///
+    /// Blanking preserves byte offsets and newlines.
+    fn blank_heredocs(script: &str) -> (String, Vec<std::ops::Range<usize>>) {
+        let mut blanked = String::with_capacity(script.len());
+        let mut bodies = vec![];
+        let mut delimiter: Option<String> = None;
+        let mut body_start = 0;
+        let mut offset = 0;
+
+        for line in script.split_inclusive('\n') {
+            match &delimiter {
+                Some(delim) => {
+                    if line.trim() == delim {
+                        bodies.push(body_start..offset);
+                        delimiter = None;
+                        blanked.push_str(line);
+                    } else {
+                        blanked.extend(line.chars().map(|c| if c == '\n' { c } else { ' ' }));
+                    }
+                }
+                None => {
+                    if let Some(captures) = HEREDOC.captures(line) {
+                        delimiter = Some(captures[1].to_string());
+                        body_start = offset + line.len();
+                    }
+                    blanked.push_str(line);
+                }
+            }
+
+            offset += line.len();
+        }
+
+        // An unterminated heredoc runs to the end of the script.
+        if delimiter.is_some() {
+            bodies.push(body_start..offset);
+        }
+
+        (blanked, bodies)
+    }
+
+    /// Returns whether the given offset into `script` falls in argument
+    /// position of a command that runs as its own process.
+    ///
+    /// This is a best-effort approximation of POSIX shell word splitting:
+    /// it understands quoting, escapes, line continuations, and command
+    /// separators, but not much else.
+    fn is_argument(script: &str, offset: usize) -> bool {
+        let mut words: Vec<String> = vec![];
+        let mut current = String::new();
+        let mut quote: Option<char> = None;
+
+        let mut chars = script[..offset].chars();
+        while let Some(c) = chars.next() {
+            match (quote, c) {
+                (Some(q), c) if c == q => {
+                    current.push(c);
+                    quote = None;
+                }
+                (Some('"'), '\\') => {
+                    current.push(c);
+                    current.extend(chars.next());
+                }
+                (Some(_), c) => current.push(c),
+                (None, '\\') => match chars.next() {
+                    // A line continuation acts like whitespace.
+                    Some('\n') | None => words.extend(Some(std::mem::take(&mut current))),
+                    Some(next) => {
+                        current.push(c);
+                        current.push(next);
+                    }
+                },
+                (None, '\'' | '"') => {
+                    current.push(c);
+                    quote = Some(c);
+                }
+                (None, '\n' | ';' | '&' | '|' | '(' | ')' | '`') => {
+                    words.clear();
+                    current.clear();
+                }
+                (None, c) if c.is_whitespace() => words.extend(Some(std::mem::take(&mut current))),
+                (None, c) => current.push(c),
+            }
+
+            words.retain(|word| !word.is_empty());
+        }
+
+        // Leading assignments (`FOO=bar cmd ...`) only affect the command's
+        // environment, and aren't arguments.
+        let mut command = words.iter().skip_while(|word| ASSIGNMENT.is_match(word));
+
+        match (command.next(), command.next()) {
+            // No command yet, so we're in an assignment or are the
+            // command itself.
+            (None, _) => false,
+            (Some(command), _) if BUILTINS.contains(&command.as_str()) => false,
+            // PowerShell-style assignments, e.g. `$env:FOO = ...`.
+            (Some(command), Some(next)) if command.starts_with('$') && next.starts_with('=') => {
+                false
+            }
+            _ => true,
+        }
+    }
+
+    /// Returns each `secrets.*` context that's expanded in argument
+    /// position within the given `run:` script.
+    fn secret_arguments(script: &str) -> Vec<String> {
+        let exprs = extract_expressions(script);
+
+        let (masked, heredocs) = Self::blank_heredocs(&mask_expressions(script));
+
+        // Secrets in URL queries are already flagged by `secret-in-url`.
+        let leaky_urls = SecretInUrl::leaky_urls(script);
+
+        let mut results = vec![];
+        for (expr, span) in &exprs {
+            let Ok(parsed) = Expr::parse(expr.as_bare()) else {
+                tracing::warn!("couldn't parse expression: {expr}", expr = expr.as_bare());
+                continue;
+            };
+
+            let secrets = parsed
+                .contexts()
+                .into_iter()
+                .filter(|ctx| ctx.child_of("secrets"))
+                .collect::<Vec<_>>();
+
+            if secrets.is_empty()
+                || heredocs.iter().any(|body| body.contains(&span.start))
+                || leaky_urls.iter().any(|url| url.expansions.contains(span))
+                || !Self::is_argument(&masked, span.start)
+            {
+                continue;
+            }
+
+            results.extend(secrets.into_iter().map(|ctx| ctx.as_str().to_string()));
+        }
+
+        results
+    }
+
+    /// Returns an annotated location for each secret passed as an
+    /// argument in the step's `run:` script.
+    fn argument_locations<'s>(step: &impl StepCommon<'s>) -> Vec<SymbolicLocation<'s>> {
+        let StepBodyCommon::Run { run, .. } = step.body() else {
+            return vec![];
+        };
+
+        Self::secret_arguments(run)
+            .into_iter()
+            .map(|secret| {
+                step.location()
+                    .with_keys(&["run".into()])
+                    .annotated(format!("{secret} is passed as a command-line argument"))
+            })
+            .collect()
+    }
+}
+
+impl Audit for SecretInArgs {
+    fn new(_state: AuditState) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        Ok(Self)
+    }
+
+    fn audit_step<'w>(&self, step: &super::Step<'w>) -> anyhow::Result<Vec<super::Finding<'w>>> {
+        let mut findings = vec![];
+
+        for location in Self::argument_locations(step) {
+            findings.push(
+                Self::finding()
+                    .severity(Severity::Low)
+                    .confidence(Confidence::Medium)
+                    .add_location(step.location_with_name())
+                    .add_location(location.primary())
+                    .build(step.workflow())?,
+            );
+        }
+
+        Ok(findings)
+    }
+
+    fn audit_composite_step<'a>(
+        &self,
+        step: &super::CompositeStep<'a>,
+    ) -> anyhow::Result<Vec<super::Finding<'a>>> {
+        let mut findings = vec![];
+
+        for location in Self::argument_locations(step) {
+            findings.push(
+                Self::finding()
+                    .severity(Severity::Low)
+                    .confidence(Confidence::Medium)
+                    .add_location(step.location_with_name())
+                    .add_location(location.primary())
+                    .build(step.action())?,
+            );
+        }
+
+        Ok(findings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SecretInArgs;
+
+    #[test]
+    fn test_secret_arguments() {
+        for (script, expected) in &[
+            ("mytool --token ${{ secrets.FOO }}", vec!["secrets.FOO"]),
+            ("mytool --token=${{ secrets.FOO }}", vec!["secrets.FOO"]),
+            ("mytool -p \"${{ secrets.FOO }}\"", vec!["secrets.FOO"]),
+            (
+                "mytool \\\n  --user ${{ secrets.USER }} \\\n  --password ${{ secrets.PASS }}",
+                vec!["secrets.USER", "secrets.PASS"],
+            ),
+            (
+                "cd app && FOO=bar ./deploy.sh ${{ secrets.FOO }}",
+                vec!["secrets.FOO"],
+            ),
+            // `env` is a command like any other.
+            ("env FOO=${{ secrets.FOO }} mytool", vec!["secrets.FOO"]),
+            (
+                "mytool ${{ secrets[format('{0}_TOKEN', matrix.env)] }}",
+                vec!["secrets[format('{0}_TOKEN', matrix.env)]"],
+            ),
+            // Environment assignments and builtins.
+            ("FOO=${{ secrets.FOO }} mytool", vec![]),
+            ("FOO=bar BAR=\"${{ secrets.FOO }}\" mytool", vec![]),
+            ("export FOO=\"${{ secrets.FOO }}\"\nmytool", vec![]),
+            (
+                "echo \"${{ secrets.FOO }}\" | docker login --password-stdin",
+                vec![],
+            ),
+            ("echo \"token=${{ secrets.FOO }}\" >> $GITHUB_ENV", vec![]),
+            ("$env:FOO = \"${{ secrets.FOO }}\"", vec![]),
+            ("FOO=\"prefix ${{ secrets.FOO }}\" mytool", vec![]),
+            // Heredoc bodies aren't arguments.
+            (
+                "cat <<EOF > config.ini\ntoken = ${{ secrets.FOO }}\nEOF\nmytool",
+                vec![],
+            ),
+            (
+                "cat <<'EOF' > config.ini\ntoken: ${{ secrets.FOO }}\nEOF\nmytool --x ${{ secrets.BAR }}",
+                vec!["secrets.BAR"],
+            ),
+            // Secrets in URL queries are left to `secret-in-url`.
+            (
+                "curl \"https://example.com/?token=${{ secrets.FOO }}\" -u ${{ secrets.BAR }}",
+                vec!["secrets.BAR"],
+            ),
+            // Not a secret.
+            ("mytool --ref ${{ github.ref }}", vec![]),
+        ] {
+            assert_eq!(
+                &SecretInArgs::secret_arguments(script),
+                expected,
+                "failed: {script}"
+            );
+        }
+    }
+}
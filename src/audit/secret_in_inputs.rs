@@ -0,0 +1,83 @@
+//! Detects secrets that are passed to reusable workflows via `with:`.
+//!
+//! Reusable workflows receive secrets through a dedicated `secrets:`
+//! mechanism. Secrets that are passed through `with:` instead become
+//! ordinary inputs to the called workflow, which are treated as plaintext.
+
+use super::{audit_meta, Audit};
+use crate::{
+    finding::{Confidence, Finding, Severity},
+    models::{JobExt as _, ReusableWorkflowCallJob},
+    state::AuditState,
+    utils::secret_contexts,
+};
+
+pub(crate) struct SecretInInputs;
+
+audit_meta!(
+    SecretInInputs,
+    "secret-in-inputs",
+    "secret passed to a reusable workflow as an input",
+    rationale = "
+        Flags reusable workflow calls that pass secrets through with: rather than
+        secrets:.
+
+        Values passed through with: become ordinary inputs to the called workflow.
+        Inputs are treated as plaintext, so the secret may end up in logs, in the
+        workflow run's UI, or anywhere else the called workflow uses the input.
+
+        Findings are medium severity with high confidence.
+    ",
+    example = r#"
+        jobs:
+          call:
+            uses: example/workflows/.github/workflows/deploy.yml@v1
+            with:
+              deploy-token: ${{ secrets.DEPLOY_TOKEN }}
+    "#,
+    remediation = "
+        Declare the value as a secret in the called workflow's on.workflow_call,
+        and pass it with secrets: instead, e.g. secrets: { deploy-token: ${{
+        secrets.DEPLOY_TOKEN }} }.
+    ",
+);
+
+impl Audit for SecretInInputs {
+    fn new(_state: AuditState) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        Ok(Self)
+    }
+
+    fn audit_reusable_job<'w>(
+        &self,
+        job: &ReusableWorkflowCallJob<'w>,
+    ) -> anyhow::Result<Vec<Finding<'w>>> {
+        let mut findings = vec![];
+
+        for (input, value) in &job.with {
+            for context in secret_contexts(&value.to_string()) {
+                findings.push(
+                    Self::finding()
+                        .severity(Severity::Medium)
+                        .confidence(Confidence::High)
+                        .add_location(
+                            job.location()
+                                .with_keys(&["uses".into()])
+                                .annotated("this reusable workflow"),
+                        )
+                        .add_location(
+                            job.location()
+                                .primary()
+                                .with_keys(&["with".into(), input.as_str().into()])
+                                .annotated(format!("{context} is passed as an input")),
+                        )
+                        .build(job.parent())?,
+                );
+            }
+        }
+
+        Ok(findings)
+    }
+}
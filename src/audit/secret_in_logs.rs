@@ -0,0 +1,346 @@
+//! Detects `actions/github-script` scripts that log secrets.
+//!
+//! GitHub masks secrets in workflow logs, but only on a best-effort basis:
+//! values that are transformed (e.g. encoded, split, or embedded in JSON)
+//! before they're logged aren't recognized. Values that the script itself
+//! registers with `core.setSecret` are only masked from that point on,
+//! so logging them beforehand leaks them too.
+
+use std::ops::Range;
+
+use super::{
+    audit_meta,
+    template_injection::{ScriptKind, TemplateInjection},
+    Audit,
+};
+use crate::{
+    expr::Expr,
+    finding::{Confidence, Severity, SymbolicLocation},
+    models::StepCommon,
+    state::AuditState,
+    utils::{extract_expressions, mask_expressions},
+};
+
+/// Functions that write their arguments to the workflow log.
+const LOGGING_SINKS: &[&str] = &[
+    "console.debug",
+    "console.error",
+    "console.info",
+    "console.log",
+    "console.warn",
+    "core.debug",
+    "core.error",
+    "core.info",
+    "core.notice",
+    "core.startGroup",
+    "core.warning",
+];
+
+pub(crate) struct SecretInLogs;
+
+audit_meta!(
+    SecretInLogs,
+    "secret-in-logs",
+    "secret written to the log by actions/github-script",
+    rationale = "
+        Flags actions/github-script scripts that pass secrets to logging functions
+        like core.info or console.log, as well as values that are logged before
+        they're registered with core.setSecret.
+
+        GitHub masks secrets in workflow logs, but only on a best-effort basis:
+        values that are transformed (e.g. encoded, split, or embedded in JSON)
+        before they're logged aren't recognized. Values registered with
+        core.setSecret are only masked from that point on.
+
+        Secrets passed to logging functions are medium severity with low
+        confidence, since they may still be masked. Values that are logged before
+        they're registered are medium severity with high confidence.
+    ",
+    example = r#"
+        steps:
+          - uses: actions/github-script@v7
+            with:
+              script: |
+                const token = await getToken();
+                core.info(`token: ${token}`);
+                core.setSecret(token);
+    "#,
+    remediation = "
+        Don't log secrets. Register runtime secrets with core.setSecret before
+        doing anything else with them.
+    ",
+);
+
+impl SecretInLogs {
+    /// Returns each call to one of the given functions in `script`, along
+    /// with the byte range of its arguments.
+    ///
+    /// This is a small scanner rather than a parser: it understands string
+    /// literals well enough to find each call's closing parenthesis, but
+    /// doesn't skip over comments.
+    fn calls<'n>(script: &str, names: &[&'n str]) -> Vec<(&'n str, Range<usize>)> {
+        let mut calls = vec![];
+
+        for name in names {
+            for (start, _) in script.match_indices(name) {
+                let preceded_by_ident = script[..start]
+                    .chars()
+                    .next_back()
+                    .is_some_and(|c| c.is_alphanumeric() || matches!(c, '_' | '$' | '.'));
+                if preceded_by_ident {
+                    continue;
+                }
+
+                let rest = &script[start + name.len()..];
+                let Some(args) = rest.trim_start().strip_prefix('(') else {
+                    continue;
+                };
+                let args_start = script.len() - args.len();
+
+                let mut depth = 0;
+                let mut quote = None;
+                let mut chars = args.char_indices();
+                while let Some((offset, c)) = chars.next() {
+                    match (quote, c) {
+                        (Some(_), '\\') => {
+                            chars.next();
+                        }
+                        (Some(q), c) if c == q => quote = None,
+                        (Some(_), _) => {}
+                        (None, '\'' | '"' | '`') => quote = Some(c),
+                        (None, '(' | '[' | '{') => depth += 1,
+                        (None, ')') if depth == 0 => {
+                            calls.push((*name, args_start..args_start + offset));
+                            break;
+                        }
+                        (None, ')' | ']' | '}') => depth -= 1,
+                        (None, _) => {}
+                    }
+                }
+            }
+        }
+
+        calls.sort_by_key(|(_, args)| args.start);
+        calls
+    }
+
+    /// Returns whether `haystack` contains `ident` as a whole identifier.
+    fn contains_ident(haystack: &str, ident: &str) -> bool {
+        let is_ident = |c: char| c.is_alphanumeric() || matches!(c, '_' | '$');
+
+        haystack.match_indices(ident).any(|(start, _)| {
+            let end = start + ident.len();
+            !haystack[..start].ends_with(is_ident) && !haystack[end..].starts_with(is_ident)
+        })
+    }
+
+    /// Returns each secret-bearing context that's expanded into the
+    /// arguments of a logging function, along with the function.
+    fn logged_secrets(script: &str) -> Vec<(String, &'static str)> {
+        let exprs = extract_expressions(script);
+
+        let calls = Self::calls(&mask_expressions(script), LOGGING_SINKS);
+
+        let mut results = vec![];
+        for (expr, span) in &exprs {
+            let Some((sink, _)) = calls.iter().find(|(_, args)| args.contains(&span.start)) else {
+                continue;
+            };
+
+            let Ok(parsed) = Expr::parse(expr.as_bare()) else {
+                tracing::warn!("couldn't parse expression: {expr}", expr = expr.as_bare());
+                continue;
+            };
+
+            results.extend(
+                parsed
+                    .contexts()
+                    .into_iter()
+                    .filter(|ctx| ctx.child_of("secrets") || *ctx == "github.token")
+                    .map(|ctx| (ctx.as_str().to_string(), *sink)),
+            );
+        }
+
+        results
+    }
+
+    /// Returns each identifier that's passed to a logging function before
+    /// it's registered with `core.setSecret`, along with the function.
+    fn logged_before_registration(script: &str) -> Vec<(&str, &'static str)> {
+        let sinks = Self::calls(script, LOGGING_SINKS);
+
+        let mut results = vec![];
+        for (_, args) in Self::calls(script, &["core.setSecret"]) {
+            let secret = script[args.clone()].trim();
+
+            // Only plain identifiers (like `token` or `creds.password`)
+            // can be reliably matched against earlier calls.
+            let is_identifier = !secret.is_empty()
+                && !secret.starts_with(|c: char| c.is_ascii_digit())
+                && secret
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || matches!(c, '_' | '$' | '.'));
+            if !is_identifier {
+                continue;
+            }
+
+            results.extend(
+                sinks
+                    .iter()
+                    .filter(|(_, sink_args)| sink_args.end < args.start)
+                    .filter(|(_, sink_args)| {
+                        Self::contains_ident(&script[sink_args.clone()], secret)
+                    })
+                    .map(|(sink, _)| (secret, *sink)),
+            );
+        }
+
+        results
+    }
+
+    /// Returns the severity, confidence, and annotated location of each
+    /// secret logged by the step's `actions/github-script` script.
+    fn leaks<'s>(step: &impl StepCommon<'s>) -> Vec<(Severity, Confidence, SymbolicLocation<'s>)> {
+        let Some((script, location, ScriptKind::JavaScript)) =
+            TemplateInjection::script_with_location(step)
+        else {
+            return vec![];
+        };
+
+        let logged = Self::logged_secrets(&script)
+            .into_iter()
+            .map(|(secret, sink)| {
+                (
+                    Severity::Medium,
+                    Confidence::Low,
+                    location
+                        .clone()
+                        .annotated(format!("{secret} is passed to {sink}")),
+                )
+            });
+
+        let unregistered =
+            Self::logged_before_registration(&script)
+                .into_iter()
+                .map(|(secret, sink)| {
+                    (
+                        Severity::Medium,
+                        Confidence::High,
+                        location.clone().annotated(format!(
+                        "{secret} is passed to {sink} before it's registered with core.setSecret"
+                    )),
+                    )
+                });
+
+        logged.chain(unregistered).collect()
+    }
+}
+
+impl Audit for SecretInLogs {
+    fn new(_state: AuditState) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        Ok(Self)
+    }
+
+    fn audit_step<'w>(&self, step: &super::Step<'w>) -> anyhow::Result<Vec<super::Finding<'w>>> {
+        let mut findings = vec![];
+
+        for (severity, confidence, location) in Self::leaks(step) {
+            findings.push(
+                Self::finding()
+                    .severity(severity)
+                    .confidence(confidence)
+                    .add_location(step.location_with_name())
+                    .add_location(location.primary())
+                    .build(step.workflow())?,
+            );
+        }
+
+        Ok(findings)
+    }
+
+    fn audit_composite_step<'a>(
+        &self,
+        step: &super::CompositeStep<'a>,
+    ) -> anyhow::Result<Vec<super::Finding<'a>>> {
+        let mut findings = vec![];
+
+        for (severity, confidence, location) in Self::leaks(step) {
+            findings.push(
+                Self::finding()
+                    .severity(severity)
+                    .confidence(confidence)
+                    .add_location(step.location_with_name())
+                    .add_location(location.primary())
+                    .build(step.action())?,
+            );
+        }
+
+        Ok(findings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SecretInLogs;
+
+    #[test]
+    fn test_logged_secrets() {
+        for (script, expected) in &[
+            ("core.info('hello')", vec![]),
+            (
+                "core.info('token: ${{ secrets.TOKEN }}')",
+                vec![("secrets.TOKEN", "core.info")],
+            ),
+            (
+                "console.log(btoa(\"${{ secrets.TOKEN }}\"))",
+                vec![("secrets.TOKEN", "console.log")],
+            ),
+            (
+                "core.warning(`a (${ '${{ github.token }}' })`)",
+                vec![("github.token", "core.warning")],
+            ),
+            // Not a logging function.
+            ("core.setSecret('${{ secrets.TOKEN }}')", vec![]),
+            ("mycore.info('${{ secrets.TOKEN }}')", vec![]),
+            // Outside of the call's arguments.
+            ("core.info('hello'); foo('${{ secrets.TOKEN }}')", vec![]),
+            // Not a secret.
+            ("core.info('${{ github.actor }}')", vec![]),
+        ] {
+            let actual = SecretInLogs::logged_secrets(script);
+            let actual = actual
+                .iter()
+                .map(|(secret, sink)| (secret.as_str(), *sink))
+                .collect::<Vec<_>>();
+            assert_eq!(actual, *expected, "failed: {script}");
+        }
+    }
+
+    #[test]
+    fn test_logged_before_registration() {
+        for (script, expected) in &[
+            ("core.setSecret(token); core.info(token)", vec![]),
+            (
+                "core.info(`token: ${token}`); core.setSecret(token)",
+                vec![("token", "core.info")],
+            ),
+            (
+                "console.log(creds.password)\ncore.setSecret( creds.password )",
+                vec![("creds.password", "console.log")],
+            ),
+            // A different identifier that merely contains the secret's name.
+            ("core.info(tokenCount); core.setSecret(token)", vec![]),
+            // Not an identifier.
+            ("core.info(x); core.setSecret(get(x))", vec![]),
+        ] {
+            assert_eq!(
+                SecretInLogs::logged_before_registration(script),
+                *expected,
+                "failed: {script}"
+            );
+        }
+    }
+}
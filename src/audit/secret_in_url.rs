@@ -0,0 +1,367 @@
+//! Detects secrets that are interpolated into URL query strings.
+//!
+//! Query strings are routinely recorded in server and proxy access logs,
+//! and are echoed by tools like `curl -v` and `git` when tracing. A secret
+//! in a query string therefore has many more opportunities to leak than
+//! one passed via a header or environment variable.
+
+use std::{ops::Range, sync::LazyLock};
+
+use github_actions_models::common::{expr::LoE, Env};
+use indexmap::IndexMap;
+use regex::Regex;
+
+use super::{audit_meta, Audit};
+use crate::{
+    expr::Expr,
+    finding::{Confidence, Severity, SymbolicLocation},
+    models::{StepBodyCommon, StepCommon},
+    state::AuditState,
+    utils::{extract_expressions, mask_expressions},
+};
+
+/// Matches a URL-like token that ends inside of a query parameter's value,
+/// e.g. `https://example.com/?foo=bar&token=`.
+static URL_QUERY_VALUE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^[A-Za-z][A-Za-z0-9+.-]*://[^?#]*\?(?:[^#]*&)?[^=&#]*=[^&#]*$").unwrap()
+});
+
+pub(crate) struct SecretInUrl;
+
+/// A URL whose query string contains one or more secret expansions.
+pub(crate) struct LeakyUrl {
+    /// The URL's span within the text it was found in.
+    pub(crate) span: Range<usize>,
+    /// The spans of each secret-bearing expansion in the URL's query.
+    pub(crate) expansions: Vec<Range<usize>>,
+    /// The `secrets.*` contexts expanded in the URL's query.
+    pub(crate) secrets: Vec<String>,
+}
+
+audit_meta!(
+    SecretInUrl,
+    "secret-in-url",
+    "secret in URL query string",
+    rationale = "
+        Flags secrets that are embedded in URL query strings.
+
+        Query strings are routinely logged by servers and proxies, and are
+        included in error messages.
+
+        Findings are medium severity with medium confidence.
+    ",
+    example = r#"
+        steps:
+          - run: curl "https://example.com/api?token=${{ secrets.API_TOKEN }}"
+    "#,
+    remediation = "
+        Send secrets in a request header instead, e.g.
+        curl -H \"Authorization: Bearer ${API_TOKEN}\" https://example.com/api.
+    ",
+);
+
+impl SecretInUrl {
+    /// Returns whether the given character terminates a URL-like token.
+    fn is_url_boundary(c: char) -> bool {
+        c.is_whitespace() || matches!(c, '"' | '\'' | '`' | '<' | '>')
+    }
+
+    /// Returns the span of each expression in `text` that expands one or
+    /// more `secrets.*` contexts, along with those contexts.
+    fn secret_expressions(text: &str) -> Vec<(Range<usize>, Vec<String>)> {
+        extract_expressions(text)
+            .into_iter()
+            .filter_map(|(expr, span)| {
+                let secrets = Expr::parse(expr.as_bare())
+                    .ok()?
+                    .contexts()
+                    .into_iter()
+                    .filter(|ctx| ctx.child_of("secrets"))
+                    .map(|ctx| ctx.as_str().to_string())
+                    .collect::<Vec<_>>();
+
+                (!secrets.is_empty()).then_some((span, secrets))
+            })
+            .collect()
+    }
+
+    /// Returns each URL in `text` that contains `secrets.*` expansions
+    /// within its query string.
+    pub(crate) fn leaky_urls(text: &str) -> Vec<LeakyUrl> {
+        let secret_exprs = Self::secret_expressions(text);
+
+        if secret_exprs.is_empty() {
+            return vec![];
+        }
+
+        let masked = mask_expressions(text);
+
+        let mut results: Vec<LeakyUrl> = vec![];
+        for (span, secrets) in &secret_exprs {
+            let start = masked[..span.start]
+                .char_indices()
+                .rev()
+                .find(|(_, c)| Self::is_url_boundary(*c))
+                .map_or(0, |(idx, c)| idx + c.len_utf8());
+
+            if !URL_QUERY_VALUE.is_match(&masked[start..span.start]) {
+                continue;
+            }
+
+            let end = masked[span.end..]
+                .find(Self::is_url_boundary)
+                .map_or(text.len(), |idx| span.end + idx);
+
+            // Expansions are visited in order, so any earlier expansion in
+            // the same URL is in the last result.
+            match results.last_mut() {
+                Some(url) if url.span == (start..end) => {
+                    url.expansions.push(span.clone());
+                    for secret in secrets {
+                        if !url.secrets.contains(secret) {
+                            url.secrets.push(secret.clone());
+                        }
+                    }
+                }
+                _ => results.push(LeakyUrl {
+                    span: start..end,
+                    expansions: vec![span.clone()],
+                    secrets: secrets.clone(),
+                }),
+            }
+        }
+
+        results
+    }
+
+    /// Returns a redacted rendering of each URL in `text` that contains
+    /// `secrets.*` expansions within its query string, along with the
+    /// secrets in question.
+    fn secrets_in_url_queries(text: &str) -> Vec<(String, Vec<String>)> {
+        let secret_spans = Self::secret_expressions(text)
+            .into_iter()
+            .map(|(span, _)| span)
+            .collect::<Vec<_>>();
+
+        Self::leaky_urls(text)
+            .into_iter()
+            .map(|url| (Self::redact(text, url.span, &secret_spans), url.secrets))
+            .collect()
+    }
+
+    /// Renders `text[url]`, replacing each secret expansion with `***`.
+    fn redact(text: &str, url: Range<usize>, secret_spans: &[Range<usize>]) -> String {
+        let mut redacted = String::new();
+        let mut cursor = url.start;
+
+        for span in secret_spans
+            .iter()
+            .filter(|span| span.start >= url.start && span.end <= url.end)
+        {
+            redacted.push_str(&text[cursor..span.start]);
+            redacted.push_str("***");
+            cursor = span.end;
+        }
+        redacted.push_str(&text[cursor..url.end]);
+
+        redacted
+    }
+
+    /// Returns the `env:` block of a `uses:` step.
+    ///
+    /// The step models only retain `env:` for `run:` steps, so we look it
+    /// up in the document instead.
+    fn uses_env<'s>(
+        step: &impl StepCommon<'s>,
+        document: &'s impl AsRef<yamlpath::Document>,
+    ) -> Option<Env> {
+        let env = step
+            .location()
+            .with_keys(&["env".into()])
+            .concretize(document)
+            .ok()?;
+
+        // NOTE: The feature includes the `env:` key itself.
+        match serde_yaml::from_str::<IndexMap<String, LoE<Env>>>(env.concrete.feature)
+            .ok()?
+            .shift_remove("env")?
+        {
+            LoE::Literal(env) => Some(env),
+            LoE::Expr(_) => None,
+        }
+    }
+
+    /// Returns an annotated location for each URL in the step's `run:`,
+    /// `with:`, or `env:` values that may leak secrets via its query.
+    fn leaky_locations<'s>(
+        step: &impl StepCommon<'s>,
+        document: &'s impl AsRef<yamlpath::Document>,
+    ) -> Vec<SymbolicLocation<'s>> {
+        let mut values = vec![];
+
+        let uses_env;
+        let env = match step.body() {
+            StepBodyCommon::Uses { with, .. } => {
+                for (key, value) in with {
+                    values.push((
+                        value.to_string(),
+                        step.location()
+                            .with_keys(&["with".into(), key.clone().into()]),
+                    ));
+                }
+
+                uses_env = Self::uses_env(step, document);
+                uses_env.as_ref()
+            }
+            StepBodyCommon::Run { run, env, .. } => {
+                values.push((run.to_string(), step.location().with_keys(&["run".into()])));
+
+                match env {
+                    LoE::Literal(env) => Some(env),
+                    LoE::Expr(_) => None,
+                }
+            }
+        };
+
+        for (key, value) in env.into_iter().flatten() {
+            values.push((
+                value.to_string(),
+                step.location()
+                    .with_keys(&["env".into(), key.clone().into()]),
+            ));
+        }
+
+        values
+            .into_iter()
+            .flat_map(|(value, location)| {
+                Self::secrets_in_url_queries(&value)
+                    .into_iter()
+                    .map(move |(url, secrets)| {
+                        location.clone().annotated(format!(
+                            "{secrets} may leak via URL query: {url}",
+                            secrets = secrets.join(", ")
+                        ))
+                    })
+            })
+            .collect()
+    }
+}
+
+impl Audit for SecretInUrl {
+    fn new(_state: AuditState) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        Ok(Self)
+    }
+
+    fn audit_step<'w>(&self, step: &super::Step<'w>) -> anyhow::Result<Vec<super::Finding<'w>>> {
+        let mut findings = vec![];
+
+        for location in Self::leaky_locations(step, step.workflow()) {
+            findings.push(
+                Self::finding()
+                    .severity(Severity::Medium)
+                    .confidence(Confidence::Medium)
+                    .add_location(step.location_with_name())
+                    .add_location(location.primary())
+                    .build(step.workflow())?,
+            );
+        }
+
+        Ok(findings)
+    }
+
+    fn audit_composite_step<'a>(
+        &self,
+        step: &super::CompositeStep<'a>,
+    ) -> anyhow::Result<Vec<super::Finding<'a>>> {
+        let mut findings = vec![];
+
+        for location in Self::leaky_locations(step, step.action()) {
+            findings.push(
+                Self::finding()
+                    .severity(Severity::Medium)
+                    .confidence(Confidence::Medium)
+                    .add_location(step.location_with_name())
+                    .add_location(location.primary())
+                    .build(step.action())?,
+            );
+        }
+
+        Ok(findings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SecretInUrl;
+
+    #[test]
+    fn test_secrets_in_url_queries() {
+        for (text, expected) in &[
+            ("curl https://example.com/", vec![]),
+            (
+                "curl https://example.com/?token=${{ secrets.FOO }}",
+                vec![("https://example.com/?token=***", vec!["secrets.FOO"])],
+            ),
+            (
+                "curl 'https://example.com/?a=b&token=${{ secrets.FOO }}'",
+                vec![("https://example.com/?a=b&token=***", vec!["secrets.FOO"])],
+            ),
+            (
+                "curl \"https://${{ env.HOST }}/x?token=${{ secrets.FOO }}&y=z\" -o out",
+                vec![("https://${{ env.HOST }}/x?token=***&y=z", vec!["secrets.FOO"])],
+            ),
+            (
+                "https://example.com/?token=prefix-${{ secrets.FOO }}",
+                vec![("https://example.com/?token=prefix-***", vec!["secrets.FOO"])],
+            ),
+            (
+                "https://example.com/?a=${{ secrets.A }}&b=${{ secrets.B }}",
+                vec![("https://example.com/?a=***&b=***", vec!["secrets.A", "secrets.B"])],
+            ),
+            (
+                "https://example.com/?a=${{ secrets.A }}&b=${{ secrets.A }} https://example.com/?c=${{ secrets.C }}",
+                vec![
+                    ("https://example.com/?a=***&b=***", vec!["secrets.A"]),
+                    ("https://example.com/?c=***", vec!["secrets.C"]),
+                ],
+            ),
+            // Non-ASCII whitespace before the URL.
+            (
+                "curl\u{a0}https://example.com/?token=${{ secrets.FOO }}",
+                vec![("https://example.com/?token=***", vec!["secrets.FOO"])],
+            ),
+            (
+                "curl\u{3000}https://example.com/?token=${{ secrets.FOO }}",
+                vec![("https://example.com/?token=***", vec!["secrets.FOO"])],
+            ),
+            // Not in a query string.
+            ("curl https://${{ secrets.HOST }}/", vec![]),
+            (
+                "curl -H 'Authorization: ${{ secrets.FOO }}' https://example.com/?a=b",
+                vec![],
+            ),
+            ("echo token=${{ secrets.FOO }}", vec![]),
+            // Not a secret.
+            ("curl https://example.com/?ref=${{ github.ref }}", vec![]),
+        ] {
+            let expected = expected
+                .iter()
+                .map(|(url, secrets)| {
+                    (
+                        url.to_string(),
+                        secrets.iter().map(|s| s.to_string()).collect::<Vec<_>>(),
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                SecretInUrl::secrets_in_url_queries(text),
+                expected,
+                "failed: {text}"
+            );
+        }
+    }
+}
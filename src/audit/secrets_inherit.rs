@@ -1,16 +1,133 @@
-use github_actions_models::workflow::job::Secrets;
+use std::collections::BTreeSet;
+
+use github_actions_models::{common::Uses, workflow::job::Secrets};
 
 use super::{audit_meta, Audit};
-use crate::{finding::Confidence, models::JobExt as _};
+use crate::{
+    expr::Expr,
+    finding::{Confidence, Persona, Severity},
+    models::{Job, JobExt as _, ReusableWorkflowCallJob, Workflow},
+    registry::LocalWorkflows,
+    utils::extract_expressions,
+};
 
 pub(crate) struct SecretsInherit;
 
 audit_meta!(
     SecretsInherit,
     "secrets-inherit",
-    "secrets unconditionally inherited by called workflow"
+    "secrets unconditionally inherited by called workflow",
+    rationale = "
+        Flags reusable workflow calls that use secrets: inherit.
+
+        This passes every secret available to the calling workflow to the called
+        workflow, rather than only the secrets it needs.
+
+        Findings are medium severity with high confidence. When the called workflow
+        is being audited and itself forwards its secrets with secrets: inherit,
+        secrets propagate through every workflow in the chain, and findings are
+        high severity instead.
+
+        Calls to remote workflows can't be checked for further forwarding, so the
+        pedantic persona also gets a low confidence note for each of them.
+    ",
+    example = r#"
+        jobs:
+          call:
+            uses: example/workflows/.github/workflows/deploy.yml@v1
+            secrets: inherit
+    "#,
+    remediation = "
+        Pass only the secrets that the called workflow needs, e.g.
+        secrets: { deploy-token: ${{ secrets.DEPLOY_TOKEN }} }.
+    ",
 );
 
+impl SecretsInherit {
+    /// Returns a description of each workflow that `callee` transitively
+    /// forwards its inherited secrets to, via its own `secrets: inherit`
+    /// calls.
+    ///
+    /// The chain is followed through every forwarding workflow that's
+    /// being audited, and ends at the first workflow that isn't.
+    fn forwarding_chain<'w>(callee: &'w Workflow, workflows: &LocalWorkflows<'w>) -> Vec<String> {
+        let mut chain = vec![];
+        let mut visited = vec![callee];
+        let mut current = callee;
+
+        while let Some(forwarding) = current.jobs().find_map(|job| match job {
+            Job::ReusableWorkflowCallJob(job) if matches!(job.secrets, Some(Secrets::Inherit)) => {
+                Some(job.with_callee(workflows))
+            }
+            _ => None,
+        }) {
+            let Some(next) = forwarding.callee() else {
+                chain.push(Self::describe_callee(&forwarding));
+                break;
+            };
+
+            // Guard against (invalid) cycles between local workflows.
+            if visited.iter().any(|w| std::ptr::eq(*w, next)) {
+                break;
+            }
+
+            chain.push(next.key.filename().to_string());
+            visited.push(next);
+            current = next;
+        }
+
+        chain
+    }
+
+    /// Returns a short description of the workflow called by `job`, for
+    /// callees that aren't being audited.
+    fn describe_callee(job: &ReusableWorkflowCallJob) -> String {
+        match &job.uses {
+            Uses::Local(local) => local.path.clone(),
+            Uses::Repository(remote) => format!(
+                "{owner}/{repo} (remote)",
+                owner = remote.owner,
+                repo = remote.repo
+            ),
+            Uses::Docker(_) => unreachable!("reusable workflows can't be Docker images"),
+        }
+    }
+
+    /// Returns the secrets that the given workflow uses, other than
+    /// `secrets.GITHUB_TOKEN`.
+    ///
+    /// Returns `None` if the workflow accesses its secrets dynamically
+    /// (e.g. via `toJSON(secrets)` or `secrets[format(...)]`), since any
+    /// of the inherited secrets may be used.
+    fn used_secrets(workflow: &Workflow) -> Option<BTreeSet<String>> {
+        let mut secrets = BTreeSet::new();
+
+        for (expr, _) in extract_expressions(workflow.document.source()) {
+            let Ok(parsed) = Expr::parse(expr.as_bare()) else {
+                tracing::warn!("couldn't parse expression: {expr}", expr = expr.as_bare());
+                continue;
+            };
+
+            for context in parsed.contexts() {
+                if !context.child_of("secrets") {
+                    continue;
+                }
+
+                match context.components() {
+                    [_, Expr::Identifier(name)] => {
+                        if !name.eq_ignore_ascii_case("GITHUB_TOKEN") {
+                            secrets.insert(format!("secrets.{name}"));
+                        }
+                    }
+                    _ => return None,
+                }
+            }
+        }
+
+        Some(secrets)
+    }
+}
+
 impl Audit for SecretsInherit {
     fn new(_state: super::AuditState) -> anyhow::Result<Self>
     where
@@ -19,13 +136,49 @@ impl Audit for SecretsInherit {
         Ok(Self)
     }
 
-    fn audit_reusable_job<'w>(
+    fn audit_workflow<'w>(
         &self,
-        job: &super::ReusableWorkflowCallJob<'w>,
+        workflow: &'w Workflow,
+        workflows: &LocalWorkflows<'w>,
     ) -> anyhow::Result<Vec<super::Finding<'w>>> {
         let mut findings = vec![];
 
-        if matches!(job.secrets, Some(Secrets::Inherit)) {
+        for job in workflow.jobs() {
+            let Job::ReusableWorkflowCallJob(job) = job else {
+                continue;
+            };
+            let job = job.with_callee(workflows);
+
+            if !matches!(job.secrets, Some(Secrets::Inherit)) {
+                continue;
+            }
+
+            let chain = job
+                .callee()
+                .map(|callee| Self::forwarding_chain(callee, workflows))
+                .unwrap_or_default();
+
+            // If we can see the called workflow, we can also tell the user
+            // which (if any) of the inherited secrets it actually needs.
+            let annotation = if !chain.is_empty() {
+                format!(
+                    "inherits all parent secrets, and the called workflow forwards them to {chain}",
+                    chain = chain.join(" → ")
+                )
+            } else {
+                match job.callee().and_then(Self::used_secrets) {
+                    Some(used) if used.is_empty() => {
+                        "inherits all parent secrets, but the called workflow doesn't use any"
+                            .into()
+                    }
+                    Some(used) => format!(
+                        "inherits all parent secrets, but the called workflow only uses {used}",
+                        used = used.into_iter().collect::<Vec<_>>().join(", ")
+                    ),
+                    None => "inherits all parent secrets".into(),
+                }
+            };
+
             findings.push(
                 Self::finding()
                     .add_location(
@@ -37,12 +190,33 @@ impl Audit for SecretsInherit {
                     .add_location(
                         job.location()
                             .with_keys(&["secrets".into()])
-                            .annotated("inherits all parent secrets"),
+                            .annotated(annotation),
                     )
                     .confidence(Confidence::High)
-                    .severity(crate::finding::Severity::Medium)
-                    .build(job.parent())?,
+                    .severity(match chain.is_empty() {
+                        true => Severity::Medium,
+                        false => Severity::High,
+                    })
+                    .build(workflow)?,
             );
+
+            // We can't see into remote workflows, so we can only note that
+            // they might forward the inherited secrets further.
+            if matches!(job.uses, Uses::Repository(_)) {
+                findings.push(
+                    Self::finding()
+                        .add_location(
+                            job.location()
+                                .primary()
+                                .with_keys(&["uses".into()])
+                                .annotated("remote workflow may forward inherited secrets further"),
+                        )
+                        .confidence(Confidence::Low)
+                        .severity(Severity::Informational)
+                        .persona(Persona::Pedantic)
+                        .build(workflow)?,
+                );
+            }
         }
 
         Ok(findings)
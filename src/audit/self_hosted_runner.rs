@@ -14,8 +14,9 @@ use github_actions_models::{
 use super::{audit_meta, Audit, Job};
 use crate::models::Matrix;
 use crate::{
-    finding::{Confidence, Persona, Severity},
-    models::JobExt as _,
+    expr::Expr,
+    finding::{Confidence, Finding, Persona, Severity},
+    models::{JobExt as _, NormalJob, Workflow},
     AuditState,
 };
 
@@ -24,9 +25,108 @@ pub(crate) struct SelfHostedRunner;
 audit_meta!(
     SelfHostedRunner,
     "self-hosted-runner",
-    "runs on a self-hosted runner"
+    "runs on a self-hosted runner",
+    rationale = "
+        Flags jobs that run on self-hosted runners.
+
+        Self-hosted runners are often not ephemeral, so a compromised job can
+        persist on the runner and attack later jobs, including ones from other
+        repositories sharing the runner.
+
+        Findings are of unknown severity, since the risk depends on how the runner
+        is configured. They're only shown with the auditor persona.
+    ",
+    example = r#"
+        jobs:
+          build:
+            runs-on: [self-hosted, linux]
+    "#,
+    remediation = "
+        Use GitHub-hosted runners where possible. Otherwise, use ephemeral,
+        isolated self-hosted runners, and never run workflows from untrusted pull
+        requests on them.
+    ",
 );
 
+/// How a `runs-on:` expression expands, with respect to self-hosted runners.
+#[derive(Debug, PartialEq)]
+enum Expansion {
+    /// The expression expands into a self-hosted label for at least one
+    /// row of the job's matrix.
+    SelfHosted,
+    /// The expression only expands into static labels, none of which
+    /// are self-hosted.
+    NotSelfHosted,
+    /// The expression can't be statically expanded.
+    Unknown,
+}
+
+impl SelfHostedRunner {
+    /// Expands the given `runs-on:` expression against the job's matrix.
+    ///
+    /// Only expressions that are a bare `matrix.*` context can be expanded,
+    /// e.g. `${{ matrix.runner }}`, including when `runner` is only added by
+    /// `matrix.include` rows.
+    fn expansion(job: &NormalJob, expr: &ExplicitExpr) -> Expansion {
+        let Ok(Expr::Context(ctx)) = Expr::parse(expr.as_bare()) else {
+            return Expansion::Unknown;
+        };
+
+        if !ctx.child_of("matrix") {
+            return Expansion::Unknown;
+        }
+
+        let Ok(matrix) = Matrix::try_from(job) else {
+            return Expansion::Unknown;
+        };
+
+        let Some(path) = matrix
+            .expanded_values
+            .iter()
+            .map(|(path, _)| path)
+            .find(|path| ctx == *path.as_str())
+        else {
+            // The context doesn't expand into any scalar values, e.g.
+            // because it's not in the matrix or is an object.
+            return Expansion::Unknown;
+        };
+
+        if !matrix.is_static(path) {
+            return Expansion::Unknown;
+        }
+
+        let self_hosted = matrix
+            .expanded_values
+            .iter()
+            .any(|(p, value)| p == path && value.eq_ignore_ascii_case("self-hosted"));
+
+        if self_hosted {
+            Expansion::SelfHosted
+        } else {
+            Expansion::NotSelfHosted
+        }
+    }
+
+    fn matrix_finding<'w>(job: &NormalJob<'w>, workflow: &'w Workflow) -> Result<Finding<'w>> {
+        Self::finding()
+            .confidence(Confidence::High)
+            .severity(Severity::Unknown)
+            .persona(Persona::Auditor)
+            .add_location(
+                job.location()
+                    .with_keys(&["strategy".into()])
+                    .annotated("matrix declares self-hosted runner"),
+            )
+            .add_location(
+                job.location()
+                    .primary()
+                    .with_keys(&["runs-on".into()])
+                    .annotated("expression may expand into a self-hosted runner"),
+            )
+            .build(workflow)
+    }
+}
+
 impl Audit for SelfHostedRunner {
     fn new(_state: AuditState) -> anyhow::Result<Self>
     where
@@ -37,8 +137,9 @@ impl Audit for SelfHostedRunner {
 
     fn audit_workflow<'w>(
         &self,
-        workflow: &'w crate::models::Workflow,
-    ) -> Result<Vec<crate::finding::Finding<'w>>> {
+        workflow: &'w Workflow,
+        _workflows: &super::LocalWorkflows<'w>,
+    ) -> Result<Vec<Finding<'w>>> {
         let mut results = vec![];
 
         for job in workflow.jobs() {
@@ -48,48 +149,57 @@ impl Audit for SelfHostedRunner {
 
             match &job.runs_on {
                 LoE::Literal(RunsOn::Target(labels)) => {
-                    {
-                        let Some(label) = labels.first() else {
-                            continue;
-                        };
-
-                        if label == "self-hosted" {
-                            // All self-hosted runners start with the 'self-hosted'
-                            // label followed by any specifiers.
-                            results.push(
-                                Self::finding()
-                                    .confidence(Confidence::High)
-                                    .severity(Severity::Unknown)
-                                    .persona(Persona::Auditor)
-                                    .add_location(
-                                        job.location()
-                                            .primary()
-                                            .with_keys(&["runs-on".into()])
-                                            .annotated("self-hosted runner used here"),
-                                    )
-                                    .build(workflow)?,
-                            );
-                        } else if ExplicitExpr::from_curly(label).is_some() {
-                            // The job might also have its runner expanded via an
-                            // expression. Long-term we should perform this evaluation
-                            // to increase our confidence, but for now we flag it as
-                            // potentially expanding to self-hosted.
-                            results.push(
-                                Self::finding()
-                                    .confidence(Confidence::Low)
-                                    .severity(Severity::Unknown)
-                                    .persona(Persona::Auditor)
-                                    .add_location(
-                                        job.location()
-                                            .primary()
-                                            .with_keys(&["runs-on".into()])
-                                            .annotated(
-                                                "expression may expand into a self-hosted runner",
-                                            ),
-                                    )
-                                    .build(workflow)?,
-                            );
-                        }
+                    let Some(label) = labels.first() else {
+                        continue;
+                    };
+
+                    if label == "self-hosted" {
+                        // All self-hosted runners start with the 'self-hosted'
+                        // label followed by any specifiers.
+                        results.push(
+                            Self::finding()
+                                .confidence(Confidence::High)
+                                .severity(Severity::Unknown)
+                                .persona(Persona::Auditor)
+                                .add_location(
+                                    job.location()
+                                        .primary()
+                                        .with_keys(&["runs-on".into()])
+                                        .annotated("self-hosted runner used here"),
+                                )
+                                .build(workflow)?,
+                        );
+                        continue;
+                    }
+
+                    // The job might also have its runner labels expanded via
+                    // expressions. Matrix expressions can be expanded statically;
+                    // anything else is flagged as potentially expanding to
+                    // self-hosted.
+                    let expansions = labels
+                        .iter()
+                        .filter_map(ExplicitExpr::from_curly)
+                        .map(|expr| Self::expansion(&job, &expr))
+                        .collect::<Vec<_>>();
+
+                    if expansions.contains(&Expansion::SelfHosted) {
+                        results.push(Self::matrix_finding(&job, workflow)?);
+                    } else if expansions.contains(&Expansion::Unknown) {
+                        results.push(
+                            Self::finding()
+                                .confidence(Confidence::Low)
+                                .severity(Severity::Unknown)
+                                .persona(Persona::Auditor)
+                                .add_location(
+                                    job.location()
+                                        .primary()
+                                        .with_keys(&["runs-on".into()])
+                                        .annotated(
+                                            "expression may expand into a self-hosted runner",
+                                        ),
+                                )
+                                .build(workflow)?,
+                        );
                     }
                 }
                 // NOTE: GHA docs are unclear on whether runner groups always
@@ -112,38 +222,10 @@ impl Audit for SelfHostedRunner {
                 ),
                 // The entire `runs-on:` is an expression, which may or may
                 // not be a self-hosted runner when expanded, like above.
+                // Non-matrix expressions are left to `dynamic-runs-on`.
                 LoE::Expr(exp) => {
-                    let Ok(matrix) = Matrix::try_from(&job) else {
-                        continue;
-                    };
-
-                    let expansions = matrix.expanded_values;
-
-                    let self_hosted = expansions.iter().any(|(path, expansion)| {
-                        exp.as_bare() == path && expansion.contains("self-hosted")
-                    });
-
-                    if self_hosted {
-                        results.push(
-                            Self::finding()
-                                .confidence(Confidence::High)
-                                .severity(Severity::Unknown)
-                                .persona(Persona::Auditor)
-                                .add_location(
-                                    job.location()
-                                        .with_keys(&["strategy".into()])
-                                        .annotated("matrix declares self-hosted runner"),
-                                )
-                                .add_location(
-                                    job.location()
-                                        .primary()
-                                        .with_keys(&["runs-on".into()])
-                                        .annotated(
-                                            "expression may expand into a self-hosted runner",
-                                        ),
-                                )
-                                .build(workflow)?,
-                        )
+                    if Self::expansion(&job, exp) == Expansion::SelfHosted {
+                        results.push(Self::matrix_finding(&job, workflow)?);
                     }
                 }
             }
@@ -0,0 +1,181 @@
+//! Detects `pull_request_target` triggers that are gated on branch filters.
+//!
+//! For `pull_request_target`, GitHub runs the copy of the workflow on the
+//! pull request's *base* branch, including that copy's branch filters.
+//! A filter like `branches: [main]` therefore doesn't prevent an attacker
+//! from targeting a stale branch that still carries an older, vulnerable
+//! copy of the workflow.
+//!
+//! When auditing a remote repository in online mode with any persona but
+//! the regular one, this audit also lists the repository's other branches
+//! whose copy of the workflow differs.
+
+use anyhow::Result;
+use github_actions_models::workflow::{
+    event::{BranchFilters, OptionalBody},
+    Trigger,
+};
+
+use super::{audit_meta, Audit};
+use crate::{
+    finding::{Confidence, Finding, Persona, Severity},
+    github_api,
+    models::Workflow,
+    registry::InputKey,
+    state::AuditState,
+};
+
+/// The maximum number of differing branches to list in a finding.
+const MAX_LISTED_BRANCHES: usize = 5;
+
+pub(crate) struct StaleBranchWorkflows {
+    client: Option<github_api::Client>,
+}
+
+audit_meta!(
+    StaleBranchWorkflows,
+    "stale-branch-workflows",
+    "pull_request_target gated on branch filters",
+    rationale = "
+        Flags pull_request_target triggers that use branches: or branches-ignore:
+        filters.
+
+        For pull_request_target, GitHub runs the copy of the workflow on the pull
+        request's base branch, including that copy's filters. Branch filters on
+        the default branch therefore don't stop an attacker from opening a pull
+        request against a stale branch whose copy of the workflow is still
+        vulnerable.
+
+        Findings are low severity, and are only shown with the pedantic persona.
+        When auditing a remote repository in online mode with any persona but
+        the regular one, findings also list the other branches with a differing
+        copy of the workflow, and have medium confidence when there are any.
+        This also makes them visible to the developer persona.
+    ",
+    example = r#"
+        on:
+          pull_request_target:
+            branches: [main]
+    "#,
+    remediation = "
+        Don't rely on branch filters to limit where pull_request_target runs.
+        Delete stale branches, or fix every branch's copy of the workflow.
+    ",
+);
+
+impl StaleBranchWorkflows {
+    /// Returns the other branches in the workflow's repository whose copy
+    /// of the workflow differs from this one.
+    ///
+    /// Only remote workflows can be compared, since local workflows aren't
+    /// tied to a repository.
+    fn differing_branches(&self, workflow: &Workflow) -> Result<Option<Vec<String>>> {
+        let (Some(client), InputKey::Remote(key)) = (&self.client, &workflow.key) else {
+            return Ok(None);
+        };
+
+        let branches = client.list_branches(&key.owner, &key.repo)?;
+        let others = branches
+            .iter()
+            .map(|branch| branch.name.as_str())
+            .filter(|name| key.git_ref.as_deref() != Some(*name))
+            .collect::<Vec<_>>();
+
+        let copies =
+            client.fetch_file_at_refs(&key.owner, &key.repo, key.path.as_str(), &others)?;
+
+        Ok(Some(
+            others
+                .into_iter()
+                .zip(copies)
+                // Branches without a copy of the workflow can't run it.
+                .filter(|(_, copy)| {
+                    copy.as_deref()
+                        .is_some_and(|copy| copy != workflow.document.source())
+                })
+                .map(|(name, _)| name.to_string())
+                .collect(),
+        ))
+    }
+}
+
+impl Audit for StaleBranchWorkflows {
+    fn new(state: AuditState) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        // NOTE: Comparing branches costs an API request per branch, so we
+        // skip it for the regular persona, which never shows these findings.
+        // The developer persona shows them once comparing raises their
+        // confidence.
+        let client = match state.no_online_audits || state.persona == Persona::Regular {
+            true => None,
+            false => state.github_client(),
+        };
+
+        Ok(Self { client })
+    }
+
+    fn audit_workflow<'w>(
+        &self,
+        workflow: &'w Workflow,
+        _workflows: &super::LocalWorkflows<'w>,
+    ) -> Result<Vec<Finding<'w>>> {
+        let mut findings = vec![];
+
+        // Bare events can't have filters.
+        let Trigger::Events(events) = &workflow.on else {
+            return Ok(findings);
+        };
+
+        let OptionalBody::Body(pull_request_target) = &events.pull_request_target else {
+            return Ok(findings);
+        };
+
+        let filter = match &pull_request_target.branch_filters {
+            Some(BranchFilters::Branches(_)) => "branches",
+            Some(BranchFilters::BranchesIgnore(_)) => "branches-ignore",
+            None => return Ok(findings),
+        };
+
+        let mut finding = Self::finding()
+            .severity(Severity::Low)
+            .confidence(Confidence::Low)
+            .persona(Persona::Pedantic)
+            .add_location(
+                workflow
+                    .location()
+                    .primary()
+                    .with_keys(&["on".into(), "pull_request_target".into(), filter.into()])
+                    .annotated("branch filters don't apply to stale branches"),
+            );
+
+        if let Some(branches) = self.differing_branches(workflow)? {
+            if !branches.is_empty() {
+                let mut listed = branches
+                    .iter()
+                    .take(MAX_LISTED_BRANCHES)
+                    .map(String::as_str)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                if branches.len() > MAX_LISTED_BRANCHES {
+                    listed.push_str(&format!(
+                        ", and {more} more",
+                        more = branches.len() - MAX_LISTED_BRANCHES
+                    ));
+                }
+
+                finding = finding.confidence(Confidence::Medium).add_location(
+                    workflow
+                        .location()
+                        .with_keys(&["on".into()])
+                        .annotated(format!("differing copies on other branches: {listed}")),
+                );
+            }
+        }
+
+        findings.push(finding.build(workflow)?);
+
+        Ok(findings)
+    }
+}
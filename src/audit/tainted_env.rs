@@ -0,0 +1,240 @@
+//! Detects `env:` values that expand attacker-controllable contexts, and
+//! that are then expanded by later steps in the same job.
+//!
+//! Assigning an attacker-controllable context to an environment variable
+//! doesn't sanitize it: a step that expands `${{ env.NAME }}` into its
+//! `run:` block or an action's `with:` inputs is just as exposed as one
+//! that expands the original context directly. Because the assignment and
+//! the expansion are often in different places (e.g. a job-level `env:`
+//! and a later step), per-step analysis doesn't see the connection.
+//!
+//! `template-injection` only checks whether an `env.NAME` expansion is
+//! statically known, and reports the rest at low severity; this audit
+//! resolves the variable back to the context that taints it.
+
+use github_actions_models::{
+    common::{expr::LoE, Env},
+    workflow::job::StepBody,
+};
+
+use super::{audit_meta, Audit};
+use crate::{
+    finding::{Confidence, Finding, Severity, SymbolicLocation},
+    models::{JobExt as _, NormalJob},
+    state::AuditState,
+    utils::{controllable_contexts, expanded_contexts},
+};
+
+pub(crate) struct TaintedEnv;
+
+audit_meta!(
+    TaintedEnv,
+    "tainted-env",
+    "attacker-controllable value passed through an environment variable",
+    rationale = "
+        Flags environment variables that are set from attacker-controllable
+        contexts, and then expanded with ${{ env.NAME }} in a run: block or an
+        action's with: inputs.
+
+        Assigning a value to an environment variable doesn't sanitize it.
+        Expanding the variable with ${{ env.NAME }} is just as injectable as
+        expanding the original context, but the two are often far apart: e.g.
+        the variable may be set in a job-level env: block and expanded by a
+        later step.
+
+        Expansions into run: blocks are high severity with high confidence.
+        Expansions into with: inputs are medium severity with low confidence,
+        since they're only exploitable if the action uses the input unsafely.
+
+        template-injection also flags ${{ env.NAME }} in run: blocks, but only
+        at low severity, since it doesn't look at where the variable is set.
+    ",
+    example = r#"
+        env:
+          TITLE: ${{ github.event.pull_request.title }}
+
+        jobs:
+          greet:
+            runs-on: ubuntu-latest
+            steps:
+              - run: echo "${{ env.TITLE }}"
+    "#,
+    remediation = "
+        Reference environment variables with the shell's own syntax instead,
+        e.g. \"$TITLE\", and don't pass attacker-controllable values to actions
+        that expand them into code.
+    ",
+);
+
+/// An environment variable whose value expands an attacker-controllable
+/// context, along with the location of its definition.
+#[derive(Clone)]
+struct Tainted<'w> {
+    name: &'w str,
+    /// The attacker-controllable context that the value expands.
+    source: String,
+    definition: SymbolicLocation<'w>,
+}
+
+impl TaintedEnv {
+    /// Returns each attacker-controllable context expanded in `value`, along
+    /// with the `NAME` of each `env.NAME` context that it expands.
+    fn expanded_contexts(value: &str) -> (Vec<String>, Vec<String>) {
+        let controllable = controllable_contexts(value);
+        let env = expanded_contexts(value, |ctx| ctx.pop_if("env").is_some())
+            .into_iter()
+            .filter_map(|ctx| Some(ctx.split_once('.')?.1.to_string()))
+            .collect();
+
+        (controllable, env)
+    }
+
+    /// Returns the tainted variable in `scope` with the given name, if any.
+    ///
+    /// Like other contexts, `env.NAME` is looked up case-insensitively.
+    fn lookup<'a, 'w>(scope: &'a [Tainted<'w>], name: &str) -> Option<&'a Tainted<'w>> {
+        scope
+            .iter()
+            .rev()
+            .find(|tainted| tainted.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Updates `scope` with the variables in `env` (defined at `location`).
+    ///
+    /// Variables that are set from an attacker-controllable context (or from
+    /// another tainted variable) are added to `scope`, while variables that
+    /// are set from anything else shadow any tainted variable of the same name.
+    fn taint<'w>(env: &'w LoE<Env>, location: SymbolicLocation<'w>, scope: &mut Vec<Tainted<'w>>) {
+        let LoE::Literal(env) = env else {
+            return;
+        };
+
+        let outer = scope.clone();
+        for (name, value) in env {
+            scope.retain(|tainted| !tainted.name.eq_ignore_ascii_case(name));
+
+            let (controllable, env) = Self::expanded_contexts(&value.to_string());
+            let source = controllable.into_iter().next().or_else(|| {
+                env.iter()
+                    .find_map(|name| Self::lookup(&outer, name))
+                    .map(|tainted| tainted.source.clone())
+            });
+
+            if let Some(source) = source {
+                scope.push(Tainted {
+                    name,
+                    source,
+                    definition: location.with_keys(&["env".into(), name.as_str().into()]),
+                });
+            }
+        }
+    }
+}
+
+impl Audit for TaintedEnv {
+    fn new(_state: AuditState) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        Ok(Self)
+    }
+
+    fn audit_normal_job<'w>(&self, job: &NormalJob<'w>) -> anyhow::Result<Vec<Finding<'w>>> {
+        let mut findings = vec![];
+        let workflow = job.parent();
+
+        let mut job_scope = vec![];
+        Self::taint(&workflow.env, workflow.location(), &mut job_scope);
+        Self::taint(&job.env, job.location(), &mut job_scope);
+
+        for step in job.steps() {
+            // Each expansion site, along with how it's exposed.
+            let mut sites = vec![];
+            let mut scope = job_scope.clone();
+
+            match &step.body {
+                StepBody::Uses { with, .. } => {
+                    for (input, value) in with {
+                        sites.push((
+                            value.to_string(),
+                            step.location()
+                                .with_keys(&["with".into(), input.as_str().into()]),
+                            format!("is passed to this action's {input} input"),
+                            Severity::Medium,
+                            Confidence::Low,
+                        ));
+                    }
+                }
+                StepBody::Run { run, env, .. } => {
+                    Self::taint(env, step.location(), &mut scope);
+
+                    sites.push((
+                        run.clone(),
+                        step.location().with_keys(&["run".into()]),
+                        "may expand into attacker-controllable code".to_string(),
+                        Severity::High,
+                        Confidence::High,
+                    ));
+                }
+            }
+
+            for (value, location, exposure, severity, confidence) in sites {
+                let (_, mut names) = Self::expanded_contexts(&value);
+                names.sort();
+                names.dedup();
+
+                for tainted in names.iter().filter_map(|name| Self::lookup(&scope, name)) {
+                    findings.push(
+                        Self::finding()
+                            .severity(severity)
+                            .confidence(confidence)
+                            .add_location(step.location_with_name())
+                            .add_location(tainted.definition.clone().annotated(format!(
+                                "{name} is set from {source}",
+                                name = tainted.name,
+                                source = tainted.source
+                            )))
+                            .add_location(
+                                location.clone().primary().annotated(format!(
+                                    "env.{name} {exposure}",
+                                    name = tainted.name
+                                )),
+                            )
+                            .build(workflow)?,
+                    );
+                }
+            }
+        }
+
+        Ok(findings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TaintedEnv;
+
+    #[test]
+    fn test_expanded_contexts() {
+        for (value, controllable, env) in [
+            ("plain", &[][..], &[][..]),
+            (
+                "${{ github.event.pull_request.title }}",
+                &["github.event.pull_request.title"],
+                &[],
+            ),
+            ("${{ github.event.pull_request.number }}", &[], &[]),
+            ("prefix-${{ github.head_ref }}", &["github.head_ref"], &[]),
+            ("${{ env.TITLE }}", &[], &["TITLE"]),
+            (
+                "${{ format('{0}-{1}', env.A, github.event.issue.body) }}",
+                &["github.event.issue.body"],
+                &["A"],
+            ),
+        ] {
+            let (actual_controllable, actual_env) = TaintedEnv::expanded_contexts(value);
+            assert_eq!(actual_controllable, controllable, "failed: {value}");
+            assert_eq!(actual_env, env, "failed: {value}");
+        }
+    }
+}
@@ -9,11 +9,17 @@
 //!
 //! A small amount of additional processing is done to remove template
 //! expressions that an attacker can't control.
+//!
+//! For `actions/github-script`, expansions into code positions (as opposed
+//! to string literals) are reported with higher confidence, since they
+//! don't require the attacker to break out of a string first.
+//!
+//! Shell scripts get similar treatment, depending on the step's `shell:`:
+//! expansions outside of single quotes are reported with higher confidence,
+//! since both bash and PowerShell evaluate command substitutions within
+//! double-quoted strings.
 
-use github_actions_models::{
-    common::{expr::LoE, Uses},
-    workflow::job::Strategy,
-};
+use github_actions_models::{common::Uses, workflow::job::Strategy};
 
 use super::{audit_meta, Audit};
 use crate::{
@@ -21,69 +27,143 @@ use crate::{
     finding::{Confidence, Persona, Severity, SymbolicLocation},
     models::{self, uses::RepositoryUsesExt as _, StepCommon},
     state::AuditState,
-    utils::extract_expressions,
+    utils::{extract_expressions, mask_expressions, normalize_shell, SAFE_CONTEXTS},
 };
 
 pub(crate) struct TemplateInjection;
 
+/// The language of a script that template expressions are expanded into.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum ScriptKind {
+    /// JavaScript, e.g. via `actions/github-script`.
+    JavaScript,
+    /// A POSIX shell script, i.e. `shell: bash` or `shell: sh`.
+    Bash,
+    /// A PowerShell script, i.e. `shell: pwsh` or `shell: powershell`.
+    PowerShell,
+    /// Anything else, e.g. `shell: cmd` or `shell: python`.
+    Other,
+}
+
+impl ScriptKind {
+    /// Returns the kind of script that the given `shell:` runs.
+    pub(crate) fn from_shell(shell: Option<&str>) -> Self {
+        match shell.map(normalize_shell) {
+            Some("bash" | "sh") => Self::Bash,
+            Some("pwsh" | "powershell") => Self::PowerShell,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// Lexical states for [`TemplateInjection::js_code_position`].
+enum JsState {
+    /// Code, tracking the depth of any braces opened within it.
+    Code(usize),
+    /// A `'` or `"` quoted string.
+    String(char),
+    /// A template literal, outside of any `${...}` substitution.
+    Template,
+    LineComment,
+    BlockComment,
+}
+
+/// Lexical states for [`TemplateInjection::shell_state`].
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum ShellState {
+    Code,
+    /// A single-quoted string, within which nothing is expanded.
+    SingleQuoted,
+    /// A double-quoted string, within which substitutions are still expanded.
+    DoubleQuoted,
+    LineComment,
+    /// A PowerShell `<# ... #>` comment.
+    BlockComment,
+}
+
 audit_meta!(
     TemplateInjection,
     "template-injection",
-    "code injection via template expansion"
+    "code injection via template expansion",
+    rationale = "
+        Flags expressions that are expanded into code, such as run: scripts and
+        actions/github-script scripts.
+
+        Expressions are expanded before the code runs, so an attacker-controllable
+        value like a pull request's title can inject arbitrary commands.
+
+        Findings are high severity with high confidence for known
+        attacker-controllable contexts, with lower severities and confidences for
+        contexts that may or may not be controllable. Some findings are only shown
+        with the pedantic or auditor personas.
+    ",
+    example = r#"
+        steps:
+          - run: echo "${{ github.event.pull_request.title }}"
+    "#,
+    remediation = "
+        Pass the value through an environment variable, and reference the
+        variable with the shell's own syntax instead, e.g.
+        env: { TITLE: ${{ github.event.pull_request.title }} } with
+        run: echo \"${TITLE}\".
+    ",
 );
 
-/// Contexts that are believed to be always safe.
-const SAFE_CONTEXTS: &[&str] = &[
-    // The action path is always safe.
-    "github.action_path",
-    // The GitHub event name (i.e. trigger) is itself safe.
-    "github.event_name",
-    // Safe keys within the otherwise generally unsafe github.event context.
-    "github.event.after",  // hexadecimal SHA ref
-    "github.event.before", // hexadecimal SHA ref
-    "github.event.issue.number",
-    "github.event.merge_group.base_sha",
-    "github.event.number",
-    "github.event.pull_request.base.sha",
-    "github.event.pull_request.commits", // number of commits in PR
-    "github.event.pull_request.number",  // the PR's own number
-    "github.event.workflow_run.id",
-    // Information about the GitHub repository
-    "github.repository",
-    "github.repository_id",
-    "github.repositoryUrl",
-    // Information about the GitHub repository owner (account/org or ID)
-    "github.repository_owner",
-    "github.repository_owner_id",
-    // Unique numbers assigned by GitHub for workflow runs
-    "github.run_attempt",
-    "github.run_id",
-    "github.run_number",
-    // Typically something like `https://github.com`; you have bigger problems if
-    // this is attacker-controlled.
-    "github.server_url",
-    // Always a 40-char SHA-1 reference.
-    "github.sha",
-    // Like `secrets.*`: not safe to expose, but safe to interpolate.
-    "github.token",
-    // GitHub Actions-controlled local directory.
-    "github.workspace",
-    // GitHub Actions-controller runner architecture.
-    "runner.arch",
-    // Debug logging is (1) or is not (0) enabled on GitHub Actions runner.
-    "runner.debug",
-    // GitHub Actions runner operating system.
-    "runner.os",
-    // GitHub Actions temporary directory, value controlled by the runner itself.
-    "runner.temp",
-    // GitHub Actions cached tool directory, value controlled by the runner itself.
-    "runner.tool_cache",
+/// Events whose payloads supply attacker-controllable values, keyed by
+/// the context (or `github.event` payload key) that they supply.
+const TAINTING_EVENTS: &[(&str, &[&str])] = &[
+    (
+        "github.event.comment",
+        &[
+            "issue_comment",
+            "pull_request_review_comment",
+            "discussion_comment",
+            "commit_comment",
+        ],
+    ),
+    ("github.event.commits", &["push"]),
+    (
+        "github.event.discussion",
+        &["discussion", "discussion_comment"],
+    ),
+    ("github.event.head_commit", &["push"]),
+    ("github.event.issue", &["issues", "issue_comment"]),
+    ("github.event.pages", &["gollum"]),
+    (
+        "github.event.pull_request",
+        &[
+            "pull_request",
+            "pull_request_target",
+            "pull_request_review",
+            "pull_request_review_comment",
+        ],
+    ),
+    ("github.event.review", &["pull_request_review"]),
+    ("github.event.workflow_run", &["workflow_run"]),
+    ("github.head_ref", &["pull_request", "pull_request_target"]),
 ];
 
 impl TemplateInjection {
-    fn script_with_location<'s>(
+    /// Returns the events that can supply an attacker-controllable value
+    /// for the given context.
+    fn tainting_events(context: &str) -> &'static [&'static str] {
+        let context = context.to_ascii_lowercase();
+
+        TAINTING_EVENTS
+            .iter()
+            .find(|(prefix, _)| {
+                context
+                    .strip_prefix(prefix)
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with(['.', '[']))
+            })
+            .map_or(&[], |(_, events)| events)
+    }
+
+    /// Returns the script that the given step expands template expressions
+    /// into, if any, along with its location and language.
+    pub(crate) fn script_with_location<'s>(
         step: &impl StepCommon<'s>,
-    ) -> Option<(String, SymbolicLocation<'s>)> {
+    ) -> Option<(String, SymbolicLocation<'s>, ScriptKind)> {
         match step.body() {
             models::StepBodyCommon::Uses {
                 uses: Uses::Repository(uses),
@@ -94,30 +174,180 @@ impl TemplateInjection {
                         (
                             script.to_string(),
                             step.location().with_keys(&["with".into(), "script".into()]),
+                            ScriptKind::JavaScript,
                         )
                     })
                 } else if uses.matches("azure/powershell") || uses.matches("azure/cli") {
                     // Both `azure/powershell` and `azure/cli` uses the same `inlineScript`
                     // option to feed arbitrary code.
+                    let kind = if uses.matches("azure/powershell") {
+                        ScriptKind::PowerShell
+                    } else {
+                        ScriptKind::Bash
+                    };
 
                     with.get("inlineScript").map(|script| {
                         (
                             script.to_string(),
                             step.location()
                                 .with_keys(&["with".into(), "inlineScript".into()]),
+                            kind,
                         )
                     })
                 } else {
                     None
                 }
             }
-            models::StepBodyCommon::Run { run, .. } => {
-                Some((run.to_string(), step.location().with_keys(&["run".into()])))
-            }
+            models::StepBodyCommon::Run { run, .. } => Some((
+                run.to_string(),
+                step.location().with_keys(&["run".into()]),
+                ScriptKind::from_shell(step.shell()),
+            )),
             _ => None,
         }
     }
 
+    /// Returns whether the given offset into a JavaScript `script` is in a
+    /// code position, i.e. not within a string literal or comment.
+    ///
+    /// This is a small lexer rather than a parser: it understands strings,
+    /// template literals (including nested `${...}` substitutions), and
+    /// comments, but not regular expression literals.
+    fn js_code_position(script: &str, offset: usize) -> bool {
+        let mut stack = vec![JsState::Code(0)];
+        let mut chars = script[..offset].chars().peekable();
+
+        while let Some(c) = chars.next() {
+            let nested = stack.len() > 1;
+            // NOTE: Safe unwrap, since the bottom of the stack is never popped.
+            let state = stack.last_mut().unwrap();
+            match state {
+                JsState::Code(depth) => match c {
+                    '\'' | '"' => stack.push(JsState::String(c)),
+                    '`' => stack.push(JsState::Template),
+                    '/' if chars.next_if_eq(&'/').is_some() => stack.push(JsState::LineComment),
+                    '/' if chars.next_if_eq(&'*').is_some() => stack.push(JsState::BlockComment),
+                    '{' => *depth += 1,
+                    '}' if *depth == 0 && nested => {
+                        // The end of a template literal's substitution.
+                        stack.pop();
+                    }
+                    '}' => *depth = depth.saturating_sub(1),
+                    _ => (),
+                },
+                JsState::String(quote) => match c {
+                    '\\' => {
+                        chars.next();
+                    }
+                    // Unterminated strings end at the end of the line.
+                    '\n' => {
+                        stack.pop();
+                    }
+                    c if c == *quote => {
+                        stack.pop();
+                    }
+                    _ => (),
+                },
+                JsState::Template => match c {
+                    '\\' => {
+                        chars.next();
+                    }
+                    '`' => {
+                        stack.pop();
+                    }
+                    '$' if chars.next_if_eq(&'{').is_some() => stack.push(JsState::Code(0)),
+                    _ => (),
+                },
+                JsState::LineComment => {
+                    if c == '\n' {
+                        stack.pop();
+                    }
+                }
+                JsState::BlockComment => {
+                    if c == '*' && chars.next_if_eq(&'/').is_some() {
+                        stack.pop();
+                    }
+                }
+            }
+        }
+
+        matches!(stack.last(), Some(JsState::Code(_)))
+    }
+
+    /// Returns whether the given offset into a bash or PowerShell `script`
+    /// is in a position where the shell evaluates it, i.e. outside of
+    /// single-quoted strings and comments.
+    ///
+    /// Double-quoted strings count as evaluated, since both shells expand
+    /// command substitutions (`$(...)`) within them. Like
+    /// [`TemplateInjection::js_code_position`], this is a small lexer: it
+    /// doesn't understand heredocs or here-strings.
+    fn shell_code_position(script: &str, offset: usize, kind: ScriptKind) -> bool {
+        matches!(
+            Self::shell_state(script, offset, kind),
+            ShellState::Code | ShellState::DoubleQuoted
+        )
+    }
+
+    /// Returns the lexical state of a bash or PowerShell `script` at the
+    /// given offset, e.g. whether the offset is within a comment.
+    pub(crate) fn shell_state(script: &str, offset: usize, kind: ScriptKind) -> ShellState {
+        let escape = match kind {
+            ScriptKind::PowerShell => '`',
+            _ => '\\',
+        };
+
+        let mut state = ShellState::Code;
+        // Whether the next character begins a new word, i.e. whether a `#`
+        // there begins a comment.
+        let mut word_start = true;
+        let mut chars = script[..offset].chars().peekable();
+        while let Some(c) = chars.next() {
+            match state {
+                ShellState::Code => match c {
+                    c if c == escape => {
+                        chars.next();
+                    }
+                    '\'' => state = ShellState::SingleQuoted,
+                    '"' => state = ShellState::DoubleQuoted,
+                    '#' if word_start => state = ShellState::LineComment,
+                    '<' if kind == ScriptKind::PowerShell && chars.next_if_eq(&'#').is_some() => {
+                        state = ShellState::BlockComment
+                    }
+                    _ => {}
+                },
+                // PowerShell's `''` escape is equivalent to closing and
+                // immediately reopening the string, so it needs no handling.
+                ShellState::SingleQuoted => {
+                    if c == '\'' {
+                        state = ShellState::Code;
+                    }
+                }
+                ShellState::DoubleQuoted => match c {
+                    c if c == escape => {
+                        chars.next();
+                    }
+                    '"' => state = ShellState::Code,
+                    _ => {}
+                },
+                ShellState::LineComment => {
+                    if c == '\n' {
+                        state = ShellState::Code;
+                    }
+                }
+                ShellState::BlockComment => {
+                    if c == '#' && chars.next_if_eq(&'>').is_some() {
+                        state = ShellState::Code;
+                    }
+                }
+            }
+
+            word_start = c.is_whitespace() || matches!(c, ';' | '|' | '&' | '(' | ')');
+        }
+
+        state
+    }
+
     /// Checks whether an expression is "safe" for the purposes of template
     /// injection.
     ///
@@ -165,10 +395,15 @@ impl TemplateInjection {
     fn injectable_template_expressions<'s>(
         &self,
         run: &str,
+        kind: ScriptKind,
         step: &impl StepCommon<'s>,
     ) -> Vec<(String, Severity, Confidence, Persona)> {
+        let exprs = extract_expressions(run);
+
+        let masked = mask_expressions(run);
+
         let mut bad_expressions = vec![];
-        for (expr, _) in extract_expressions(run) {
+        for (expr, span) in exprs {
             let Ok(parsed) = Expr::parse(expr.as_bare()) else {
                 tracing::warn!("couldn't parse expression: {expr}", expr = expr.as_bare());
                 continue;
@@ -186,6 +421,8 @@ impl TemplateInjection {
                 continue;
             }
 
+            let first = bad_expressions.len();
+
             for context in parsed.contexts() {
                 if context.child_of("secrets") {
                     // While not ideal, secret expansion is typically not exploitable.
@@ -193,13 +430,22 @@ impl TemplateInjection {
                 } else if SAFE_CONTEXTS.iter().any(|safe| *context == **safe) {
                     continue;
                 } else if context.child_of("inputs") {
-                    // TODO: Currently low confidence because we don't check the
-                    // input's type. In the future, we should index back into
-                    // the workflow's triggers and exclude input expansions
-                    // from innocuous types, e.g. booleans.
+                    // Inputs that are declared with an innocuous type can't
+                    // contain arbitrary text, so expanding them is a smell
+                    // rather than an injection. Everything else (including
+                    // inputs of unknown type) is low confidence, since we
+                    // can't see how the input is provided.
+                    let severity = match context
+                        .pop_if("inputs")
+                        .and_then(|input| step.input_type(input))
+                    {
+                        Some("boolean" | "number" | "choice") => Severity::Informational,
+                        _ => Severity::High,
+                    };
+
                     bad_expressions.push((
                         context.as_str().into(),
-                        Severity::High,
+                        severity,
                         Confidence::Low,
                         Persona::default(),
                     ));
@@ -226,12 +472,7 @@ impl TemplateInjection {
                 } else if context.child_of("matrix") || context == "matrix" {
                     if let Some(Strategy { matrix, .. }) = step.strategy() {
                         let matrix_is_static = match matrix {
-                            // The matrix is generated by an expression, meaning
-                            // that it's trivially not static.
-                            Some(LoE::Expr(_)) => false,
-                            // The matrix may expand to static values according to the context
-                            Some(inner) => models::Matrix::new(inner)
-                                .expands_to_static_values(context.as_str()),
+                            Some(inner) => models::Matrix::new(inner).is_static(context.as_str()),
                             // Context specifies a matrix, but there is no matrix defined.
                             // This is an invalid workflow so there's no point in flagging it.
                             None => continue,
@@ -258,6 +499,22 @@ impl TemplateInjection {
                     ));
                 }
             }
+
+            // Expanding into code directly (rather than into a string
+            // literal) doesn't require any escaping to exploit.
+            let code_position = match kind {
+                ScriptKind::JavaScript => Self::js_code_position(&masked, span.start),
+                ScriptKind::Bash | ScriptKind::PowerShell => {
+                    Self::shell_code_position(&masked, span.start, kind)
+                }
+                ScriptKind::Other => false,
+            };
+
+            if code_position {
+                for (_, _, confidence, _) in &mut bad_expressions[first..] {
+                    *confidence = confidence.raised();
+                }
+            }
         }
 
         bad_expressions
@@ -278,12 +535,12 @@ impl Audit for TemplateInjection {
     ) -> anyhow::Result<Vec<super::Finding<'a>>> {
         let mut findings = vec![];
 
-        let Some((script, script_loc)) = Self::script_with_location(step) else {
+        let Some((script, script_loc, kind)) = Self::script_with_location(step) else {
             return Ok(findings);
         };
 
         for (expr, severity, confidence, persona) in
-            self.injectable_template_expressions(&script, step)
+            self.injectable_template_expressions(&script, kind, step)
         {
             findings.push(
                 Self::finding()
@@ -306,26 +563,37 @@ impl Audit for TemplateInjection {
     fn audit_step<'w>(&self, step: &super::Step<'w>) -> anyhow::Result<Vec<super::Finding<'w>>> {
         let mut findings = vec![];
 
-        let Some((script, script_loc)) = Self::script_with_location(step) else {
+        let Some((script, script_loc, kind)) = Self::script_with_location(step) else {
             return Ok(findings);
         };
 
+        let workflow = step.workflow();
         for (expr, severity, confidence, persona) in
-            self.injectable_template_expressions(&script, step)
+            self.injectable_template_expressions(&script, kind, step)
         {
-            findings.push(
-                Self::finding()
-                    .severity(severity)
-                    .confidence(confidence)
-                    .persona(persona)
-                    .add_location(step.location_with_name())
-                    .add_location(
-                        script_loc.clone().primary().annotated(format!(
-                            "{expr} may expand into attacker-controllable code"
-                        )),
-                    )
-                    .build(step.workflow())?,
-            )
+            let mut finding = Self::finding()
+                .severity(severity)
+                .confidence(confidence)
+                .persona(persona)
+                .add_location(step.location_with_name())
+                .add_location(
+                    script_loc
+                        .clone()
+                        .primary()
+                        .annotated(format!("{expr} may expand into attacker-controllable code")),
+                );
+
+            // Point at each trigger that makes the expression
+            // attacker-controllable, so that the flow is visible from
+            // the finding itself.
+            for event in Self::tainting_events(&expr) {
+                if let Some(trigger) = workflow.trigger_location(event) {
+                    finding =
+                        finding.add_location(trigger.annotated(format!("{event} supplies {expr}")));
+                }
+            }
+
+            findings.push(finding.build(workflow)?)
         }
 
         Ok(findings)
@@ -334,7 +602,7 @@ impl Audit for TemplateInjection {
 
 #[cfg(test)]
 mod tests {
-    use super::Expr;
+    use super::{Expr, ScriptKind};
     use crate::audit::template_injection::TemplateInjection;
 
     #[test]
@@ -387,4 +655,115 @@ mod tests {
             assert_eq!(TemplateInjection::expr_is_safe(&expr), *safe, "{expr:#?}");
         }
     }
+
+    #[test]
+    fn test_js_code_position() {
+        for (script, expected) in &[
+            ("return ${{ github.event.issue.title }}", true),
+            ("const x = ${{ inputs.foo }};", true),
+            ("return \"${{ github.event.issue.title }}\"", false),
+            ("return 'it\\'s ${{ github.event.issue.title }}'", false),
+            ("return `title: ${{ github.event.issue.title }}`", false),
+            ("return `title: ${ ${{ github.event.issue.title }} }`", true),
+            ("return `${ {a: 1}[${{ inputs.foo }}] }`", true),
+            ("if (x) { foo(${{ inputs.foo }}) }", true),
+            ("// ${{ inputs.foo }}", false),
+            ("/* ${{ inputs.foo }} */", false),
+            ("/* */ ${{ inputs.foo }}", true),
+            ("console.log(\"a\")\n${{ inputs.foo }}", true),
+            // Earlier expressions don't affect the lexer state.
+            ("foo('${{ inputs.a }}', ${{ inputs.b == 'x' }})", true),
+        ] {
+            let start = script.rfind("${{").unwrap();
+            assert_eq!(
+                TemplateInjection::js_code_position(script, start),
+                *expected,
+                "failed: {script}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_shell_code_position() {
+        for (script, kind, expected) in &[
+            ("echo ${{ inputs.foo }}", ScriptKind::Bash, true),
+            ("echo \"hello ${{ inputs.foo }}\"", ScriptKind::Bash, true),
+            ("echo 'hello ${{ inputs.foo }}'", ScriptKind::Bash, false),
+            ("echo \"it's ${{ inputs.foo }}\"", ScriptKind::Bash, true),
+            ("echo 'a' \"b\" ${{ inputs.foo }}", ScriptKind::Bash, true),
+            ("echo \\'${{ inputs.foo }}", ScriptKind::Bash, true),
+            ("echo \"\\\"'${{ inputs.foo }}\"", ScriptKind::Bash, true),
+            ("# ${{ inputs.foo }}", ScriptKind::Bash, false),
+            ("echo $# ${{ inputs.foo }}", ScriptKind::Bash, true),
+            ("# comment\necho ${{ inputs.foo }}", ScriptKind::Bash, true),
+            (
+                "Write-Output ${{ inputs.foo }}",
+                ScriptKind::PowerShell,
+                true,
+            ),
+            (
+                "Write-Output \"${{ inputs.foo }}\"",
+                ScriptKind::PowerShell,
+                true,
+            ),
+            (
+                "Write-Output '${{ inputs.foo }}'",
+                ScriptKind::PowerShell,
+                false,
+            ),
+            (
+                "Write-Output 'it''s ${{ inputs.foo }}'",
+                ScriptKind::PowerShell,
+                false,
+            ),
+            (
+                "Write-Output \"`\"${{ inputs.foo }}\"",
+                ScriptKind::PowerShell,
+                true,
+            ),
+            // Backslashes aren't escapes in PowerShell.
+            (
+                "Write-Output 'C:\\' ${{ inputs.foo }}",
+                ScriptKind::PowerShell,
+                true,
+            ),
+            ("<# ${{ inputs.foo }} #>", ScriptKind::PowerShell, false),
+            ("<# #> ${{ inputs.foo }}", ScriptKind::PowerShell, true),
+        ] {
+            let start = script.rfind("${{").unwrap();
+            assert_eq!(
+                TemplateInjection::shell_code_position(script, start, *kind),
+                *expected,
+                "failed: {script}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_tainting_events() {
+        for (context, expected) in [
+            ("github.event.issue.title", &["issues", "issue_comment"][..]),
+            ("GITHUB.EVENT.ISSUE.TITLE", &["issues", "issue_comment"]),
+            (
+                "github.event.comment.body",
+                &[
+                    "issue_comment",
+                    "pull_request_review_comment",
+                    "discussion_comment",
+                    "commit_comment",
+                ],
+            ),
+            ("github.event.head_commit.message", &["push"]),
+            ("github.head_ref", &["pull_request", "pull_request_target"]),
+            ("github.event.issues", &[]),
+            ("github.event.inputs.name", &[]),
+            ("github.actor", &[]),
+        ] {
+            assert_eq!(
+                TemplateInjection::tainting_events(context),
+                expected,
+                "failed: {context}"
+            );
+        }
+    }
 }
@@ -0,0 +1,145 @@
+//! Detects `permissions:` blocks with unknown scope names.
+//!
+//! GitHub silently ignores scopes that it doesn't recognize, so a typo
+//! like `content: write` (rather than `contents: write`) grants nothing,
+//! while looking like a deliberate, narrowly scoped grant.
+
+use github_actions_models::common::Permissions;
+
+use super::{audit_meta, excessive_permissions::KNOWN_PERMISSIONS, Audit, Job};
+use crate::{
+    finding::{Confidence, Finding, Severity, SymbolicLocation},
+    models::{JobExt as _, Workflow},
+    registry::LocalWorkflows,
+    state::AuditState,
+};
+
+pub(crate) struct UnknownPermissions;
+
+audit_meta!(
+    UnknownPermissions,
+    "unknown-permissions",
+    "permissions: block with an unknown scope",
+    rationale = "
+        Flags permissions: blocks that contain scopes that GitHub doesn't
+        recognize, like content: (rather than contents:).
+
+        GitHub ignores unknown scopes, so a typo grants nothing at all. This
+        usually breaks the workflow, but can also give a false sense of which
+        permissions a job has been granted.
+
+        Findings are low severity with high confidence, and suggest the closest
+        known scope when there is one.
+    ",
+    example = r#"
+        permissions:
+          content: read
+    "#,
+    remediation = "
+        Use one of GitHub's documented permission scopes, e.g. contents: read.
+    ",
+);
+
+impl UnknownPermissions {
+    /// Returns the known scope that `scope` is most likely a typo of, if any.
+    fn suggestion(scope: &str) -> Option<&'static str> {
+        let scope = scope.to_ascii_lowercase().replace('_', "-");
+
+        KNOWN_PERMISSIONS
+            .keys()
+            .map(|known| (*known, strsim::levenshtein(&scope, known)))
+            .filter(|(_, distance)| *distance <= 3)
+            .min_by_key(|(known, distance)| (*distance, *known))
+            .map(|(known, _)| known)
+    }
+
+    /// Returns an annotated location for each unknown scope in the
+    /// given permissions.
+    fn unknown_scopes<'w>(
+        permissions: &'w Permissions,
+        location: SymbolicLocation<'w>,
+    ) -> Vec<SymbolicLocation<'w>> {
+        let Permissions::Explicit(perms) = permissions else {
+            return vec![];
+        };
+
+        perms
+            .keys()
+            .filter(|scope| !KNOWN_PERMISSIONS.contains_key(scope.as_str()))
+            .map(|scope| {
+                let annotation = match Self::suggestion(scope) {
+                    Some(known) => {
+                        format!("unknown permission scope: {scope} (did you mean {known}?)")
+                    }
+                    None => format!("unknown permission scope: {scope}"),
+                };
+
+                location
+                    .with_keys(&["permissions".into(), scope.as_str().into()])
+                    .primary()
+                    .annotated(annotation)
+            })
+            .collect()
+    }
+}
+
+impl Audit for UnknownPermissions {
+    fn new(_state: AuditState) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        Ok(Self)
+    }
+
+    fn audit_workflow<'w>(
+        &self,
+        workflow: &'w Workflow,
+        _workflows: &LocalWorkflows<'w>,
+    ) -> anyhow::Result<Vec<Finding<'w>>> {
+        let mut locations = Self::unknown_scopes(&workflow.permissions, workflow.location());
+
+        for job in workflow.jobs() {
+            locations.extend(match job {
+                Job::NormalJob(job) => Self::unknown_scopes(&job.permissions, job.location()),
+                Job::ReusableWorkflowCallJob(job) => {
+                    Self::unknown_scopes(&job.permissions, job.location())
+                }
+            });
+        }
+
+        locations
+            .into_iter()
+            .map(|location| {
+                Self::finding()
+                    .severity(Severity::Low)
+                    .confidence(Confidence::High)
+                    .add_location(location)
+                    .build(workflow)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UnknownPermissions;
+
+    #[test]
+    fn test_suggestion() {
+        for (scope, expected) in [
+            ("content", Some("contents")),
+            ("pull-request", Some("pull-requests")),
+            ("pull_requests", Some("pull-requests")),
+            ("ID-TOKEN", Some("id-token")),
+            ("issue", Some("issues")),
+            ("workflows", None),
+            ("administration", None),
+        ] {
+            assert_eq!(
+                UnknownPermissions::suggestion(scope),
+                expected,
+                "failed: {scope}"
+            );
+        }
+    }
+}
@@ -0,0 +1,226 @@
+//! Detects `run:` steps that invoke `sudo` on GitHub-hosted runners.
+//!
+//! GitHub-hosted runners give the runner user passwordless `sudo`, so
+//! scripts that use it work in CI even when they don't need it. This
+//! tends to mask permission issues (e.g. root-owned files in the
+//! workspace) and makes scripts fragile on runners that don't allow it.
+
+use github_actions_models::{common::expr::LoE, workflow::job::RunsOn};
+use serde::Deserialize;
+
+use super::{
+    audit_meta,
+    template_injection::{ScriptKind, ShellState, TemplateInjection},
+    Audit,
+};
+use crate::{
+    finding::{Confidence, Persona, Severity},
+    models::{NormalJob, StepBodyCommon, StepCommon as _},
+    state::AuditState,
+};
+
+/// Words after which a command begins, in addition to the start of a line
+/// and shell operators like `;` and `&&`.
+const COMMAND_PREFIXES: &[&str] = &["do", "else", "exec", "then", "time"];
+
+/// Audit-specific settings, under `rules.unnecessary-sudo.config`.
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct UnnecessarySudoConfig {
+    /// Runner labels that are known to need `sudo`. Jobs that run on
+    /// any of these labels aren't flagged.
+    #[serde(default)]
+    runners: Vec<String>,
+}
+
+pub(crate) struct UnnecessarySudo {
+    runners: Vec<String>,
+}
+
+audit_meta!(
+    UnnecessarySudo,
+    "unnecessary-sudo",
+    "run: step invokes sudo",
+    rationale = "
+        Flags run: steps that invoke sudo on GitHub-hosted runners.
+
+        GitHub-hosted runners allow passwordless sudo, so scripts that use it
+        work in CI even when they don't need it. This tends to mask permission
+        issues (e.g. root-owned files left in the workspace), and makes scripts
+        fragile on runners that don't allow it.
+
+        Findings are informational with low confidence, since many uses of sudo
+        (e.g. installing system packages) are legitimate, and are only shown with
+        the pedantic persona.
+    ",
+    example = r#"
+        steps:
+          - run: sudo make install
+    "#,
+    remediation = "
+        Remove sudo from commands that don't need it. If a job's runners are
+        known to need sudo, list their labels under rules.unnecessary-sudo.config.
+    ",
+);
+
+impl UnnecessarySudo {
+    /// Returns whether `prefix`, i.e. the part of a script before a word,
+    /// ends in a position where a new command begins.
+    fn command_position(prefix: &str) -> bool {
+        let prefix = prefix.trim_end_matches([' ', '\t']);
+
+        if prefix.is_empty()
+            || prefix.ends_with(['\n', ';', '|', '&', '(', '{', '!', '`'])
+            || prefix.ends_with("$(")
+        {
+            return true;
+        }
+
+        let last_word = prefix
+            .rsplit(|c: char| c.is_whitespace() || matches!(c, ';' | '|' | '&' | '('))
+            .next()
+            .unwrap_or_default();
+
+        COMMAND_PREFIXES.contains(&last_word)
+            && Self::command_position(&prefix[..prefix.len() - last_word.len()])
+    }
+
+    /// Returns each line of the given shell `script` that invokes `sudo`.
+    ///
+    /// `sudo` within comments or quoted strings, or in any position other
+    /// than a command's (e.g. `apt-get install sudo`), isn't an invocation.
+    fn sudo_lines(script: &str) -> Vec<&str> {
+        let is_word = |c: char| c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '/');
+
+        let mut lines = vec![];
+        for (start, _) in script.match_indices("sudo") {
+            let end = start + "sudo".len();
+            if script[end..].starts_with(is_word) || script[..start].ends_with(is_word) {
+                continue;
+            }
+
+            if !Self::command_position(&script[..start])
+                || TemplateInjection::shell_state(script, start, ScriptKind::Bash)
+                    != ShellState::Code
+            {
+                continue;
+            }
+
+            let line_start = script[..start].rfind('\n').map_or(0, |i| i + 1);
+            let line_end = script[end..].find('\n').map_or(script.len(), |i| end + i);
+            let line = script[line_start..line_end].trim();
+            if !lines.contains(&line) {
+                lines.push(line);
+            }
+        }
+
+        lines
+    }
+
+    /// Returns whether the given job runs on a GitHub-hosted runner that
+    /// isn't known to need `sudo`.
+    fn runs_on_github_hosted(&self, job: &NormalJob) -> bool {
+        // Runner groups and expressions may or may not be GitHub-hosted,
+        // so we only consider plain labels.
+        let LoE::Literal(RunsOn::Target(labels)) = &job.runs_on else {
+            return false;
+        };
+
+        !labels.is_empty()
+            && !labels.iter().any(|label| {
+                label.eq_ignore_ascii_case("self-hosted")
+                    || self
+                        .runners
+                        .iter()
+                        .any(|runner| label.eq_ignore_ascii_case(runner))
+            })
+    }
+}
+
+impl Audit for UnnecessarySudo {
+    fn new(state: AuditState) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        let config = state
+            .config
+            .rule_config::<UnnecessarySudoConfig>(Self::ident())?
+            .unwrap_or_default();
+
+        Ok(Self {
+            runners: config.runners,
+        })
+    }
+
+    fn audit_step<'w>(&self, step: &super::Step<'w>) -> anyhow::Result<Vec<super::Finding<'w>>> {
+        let StepBodyCommon::Run { run, .. } = step.body() else {
+            return Ok(vec![]);
+        };
+
+        if !self.runs_on_github_hosted(step.job())
+            || ScriptKind::from_shell(step.shell()) != ScriptKind::Bash
+        {
+            return Ok(vec![]);
+        }
+
+        let lines = Self::sudo_lines(run);
+        let Some(first) = lines.first() else {
+            return Ok(vec![]);
+        };
+
+        let annotation = match lines.len() {
+            1 => format!("`{first}` runs with sudo"),
+            2 => format!("`{first}` (and 1 other line) run with sudo"),
+            n => format!("`{first}` (and {n} other lines) run with sudo", n = n - 1),
+        };
+
+        Ok(vec![Self::finding()
+            .severity(Severity::Informational)
+            .confidence(Confidence::Low)
+            .persona(Persona::Pedantic)
+            .add_location(step.location_with_name())
+            .add_location(
+                step.location()
+                    .with_keys(&["run".into()])
+                    .primary()
+                    .annotated(annotation),
+            )
+            .build(step.workflow())?])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UnnecessarySudo;
+
+    #[test]
+    fn test_sudo_lines() {
+        for (script, expected) in [
+            ("sudo make install", &["sudo make install"][..]),
+            (
+                "make\nsudo apt-get update && sudo apt-get install -y jq",
+                &["sudo apt-get update && sudo apt-get install -y jq"],
+            ),
+            (
+                "./configure; sudo make install",
+                &["./configure; sudo make install"],
+            ),
+            ("if true; then sudo ls; fi", &["if true; then sudo ls; fi"]),
+            ("echo $(sudo whoami)", &["echo $(sudo whoami)"]),
+            // Not invocations.
+            ("# sudo make install", &[]),
+            ("make # needs sudo", &[]),
+            ("echo 'run sudo make install'", &[]),
+            ("echo \"then sudo\"", &[]),
+            ("apt-get install sudo", &[]),
+            ("sudoku --solve", &[]),
+            ("/usr/bin/pseudo-sudo", &[]),
+        ] {
+            assert_eq!(
+                UnnecessarySudo::sudo_lines(script),
+                expected,
+                "failed: {script}"
+            );
+        }
+    }
+}
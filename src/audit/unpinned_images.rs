@@ -0,0 +1,151 @@
+//! Detects job `container:` and `services:` images that aren't pinned
+//! to a digest.
+//!
+//! Like `docker://` uses, image tags are mutable: `node:18` or
+//! `postgres:latest` can be repointed at entirely different images by
+//! whoever controls the image's repository.
+
+use std::str::FromStr as _;
+
+use github_actions_models::{common::DockerUses, workflow::job::Container};
+
+use super::{audit_meta, Audit};
+use crate::{
+    finding::{Confidence, Finding, Severity},
+    models::{JobExt as _, NormalJob},
+    state::AuditState,
+};
+
+pub(crate) struct UnpinnedImages;
+
+audit_meta!(
+    UnpinnedImages,
+    "unpinned-images",
+    "unpinned image references",
+    rationale = "
+        Flags container: and services: images that aren't pinned by digest.
+
+        A tag like :latest (or no tag at all) can be moved to a different image at
+        any time.
+
+        Findings are medium severity with high confidence.
+    ",
+    example = r#"
+        jobs:
+          test:
+            runs-on: ubuntu-latest
+            container:
+              image: node:latest
+    "#,
+    remediation = "
+        Pin each image by its SHA-256 digest, e.g. node@sha256:<digest>.
+    ",
+);
+
+impl UnpinnedImages {
+    /// Returns the given container's image, along with the route to it
+    /// relative to the container itself.
+    fn image(container: &Container) -> (&str, Option<&'static str>) {
+        match container {
+            Container::Name(image) => (image, None),
+            Container::Container { image, .. } => (image, Some("image")),
+        }
+    }
+
+    /// Returns whether the given image reference is pinned to a digest.
+    fn image_is_pinned(image: &str) -> bool {
+        // We can't evaluate images that are computed from expressions,
+        // e.g. `image: ${{ matrix.image }}`.
+        if image.is_empty() || image.contains("${{") {
+            return true;
+        }
+
+        let image = image.strip_prefix("docker://").unwrap_or(image);
+        match DockerUses::from_str(image) {
+            Ok(image) => image.hash.is_some(),
+            Err(_) => {
+                tracing::warn!("couldn't parse image reference: {image}");
+                true
+            }
+        }
+    }
+}
+
+impl Audit for UnpinnedImages {
+    fn new(_state: AuditState) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        Ok(Self)
+    }
+
+    fn audit_normal_job<'w>(&self, job: &NormalJob<'w>) -> anyhow::Result<Vec<Finding<'w>>> {
+        let mut findings = vec![];
+
+        let containers = job
+            .container
+            .iter()
+            .map(|container| (None, vec!["container".into()], container))
+            .chain(job.services.iter().map(|(service, container)| {
+                (
+                    Some(service),
+                    vec!["services".into(), service.as_str().into()],
+                    container,
+                )
+            }));
+
+        for (service, mut keys, container) in containers {
+            let (image, key) = Self::image(container);
+            if Self::image_is_pinned(image) {
+                continue;
+            }
+
+            keys.extend(key.map(Into::into));
+
+            let annotation = match service {
+                Some(service) => format!("service {service}: image is not pinned to a SHA256 hash"),
+                None => "container image is not pinned to a SHA256 hash".into(),
+            };
+
+            findings.push(
+                Self::finding()
+                    .severity(Severity::Medium)
+                    .confidence(Confidence::High)
+                    .add_location(
+                        job.location()
+                            .with_keys(&keys)
+                            .primary()
+                            .annotated(annotation),
+                    )
+                    .build(job.parent())?,
+            );
+        }
+
+        Ok(findings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UnpinnedImages;
+
+    #[test]
+    fn test_image_is_pinned() {
+        for (image, pinned) in [
+            ("node", false),
+            ("node:18", false),
+            ("postgres:latest", false),
+            ("ghcr.io/org/img:v1", false),
+            ("localhost:5000/img", false),
+            ("docker://ubuntu:24.04", false),
+            ("node@sha256:b7d7e4bbd8a1b0f9b0e6b4d3c7f7b3d3a1b0f9b0e6b4d3c7f7b3d3a1b0f9b0e6", true),
+            ("ghcr.io/org/img@sha256:b7d7e4bbd8a1b0f9b0e6b4d3c7f7b3d3a1b0f9b0e6b4d3c7f7b3d3a1b0f9b0e6", true),
+            // Images computed from expressions can't be evaluated.
+            ("${{ matrix.image }}", true),
+            ("node:${{ matrix.version }}", true),
+            ("", true),
+        ] {
+            assert_eq!(UnpinnedImages::image_is_pinned(image), pinned, "failed: {image}");
+        }
+    }
+}
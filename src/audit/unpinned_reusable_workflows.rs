@@ -0,0 +1,94 @@
+//! Detects reusable workflow calls that aren't pinned to a commit.
+//!
+//! Unlike `unpinned-uses`, which covers step-level actions, this covers
+//! job-level `uses:` clauses. A reusable workflow runs with whatever
+//! secrets its caller passes it, so a branch or tag that gets repointed
+//! at malicious code gives those secrets away.
+
+use github_actions_models::{common::Uses, workflow::job::Secrets};
+
+use super::{audit_meta, Audit, ReusableWorkflowCallJob};
+use crate::{
+    finding::{Confidence, Finding, Severity},
+    models::{uses::RepositoryUsesExt as _, JobExt as _},
+    state::AuditState,
+};
+
+pub(crate) struct UnpinnedReusableWorkflows;
+
+audit_meta!(
+    UnpinnedReusableWorkflows,
+    "unpinned-reusable-workflows",
+    "reusable workflow not pinned to a commit",
+    rationale = "
+        Flags reusable workflow calls that aren't pinned to a commit.
+
+        Branch and tag refs can be moved, so the called workflow can change
+        without any change to the calling workflow.
+
+        Findings are high confidence, and are medium severity when the call passes
+        secrets to the called workflow, or low severity otherwise.
+    ",
+    example = r#"
+        jobs:
+          call:
+            uses: example/workflows/.github/workflows/build.yml@main
+    "#,
+    remediation = "
+        Pin the call to a full commit SHA, e.g.
+        example/workflows/.github/workflows/build.yml@<sha> # v1.
+    ",
+);
+
+impl Audit for UnpinnedReusableWorkflows {
+    fn new(_state: AuditState) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        Ok(Self)
+    }
+
+    fn audit_reusable_job<'w>(
+        &self,
+        job: &ReusableWorkflowCallJob<'w>,
+    ) -> anyhow::Result<Vec<Finding<'w>>> {
+        let mut findings = vec![];
+
+        // Local reusable workflows are fully controlled by the calling
+        // repository, and can't be pinned anyways.
+        let Uses::Repository(uses) = &job.uses else {
+            return Ok(findings);
+        };
+
+        if uses.ref_is_commit() {
+            return Ok(findings);
+        }
+
+        let passes_secrets = match &job.secrets {
+            Some(Secrets::Inherit) => true,
+            Some(Secrets::Env(secrets)) => !secrets.is_empty(),
+            None => false,
+        };
+
+        let mut finding = Self::finding().confidence(Confidence::High).add_location(
+            job.location()
+                .primary()
+                .with_keys(&["uses".into()])
+                .annotated("reusable workflow is not pinned to a commit"),
+        );
+
+        finding = if passes_secrets {
+            finding.severity(Severity::Medium).add_location(
+                job.location()
+                    .with_keys(&["secrets".into()])
+                    .annotated("secrets are passed to the reusable workflow"),
+            )
+        } else {
+            finding.severity(Severity::Low)
+        };
+
+        findings.push(finding.build(job.parent())?);
+
+        Ok(findings)
+    }
+}
@@ -1,12 +1,45 @@
+use anyhow::Result;
 use github_actions_models::common::Uses;
 
 use super::{audit_meta, Audit, AuditState, Finding, Step};
-use crate::finding::{Confidence, Persona, Severity};
-use crate::models::{uses::UsesExt as _, CompositeStep};
+use crate::finding::{Confidence, Fix, Persona, Severity, SymbolicLocation};
+use crate::github_api;
+use crate::models::{
+    uses::{RepositoryUsesExt as _, UsesExt as _},
+    CompositeStep,
+};
 
-pub(crate) struct UnpinnedUses;
+pub(crate) struct UnpinnedUses {
+    /// A GitHub API client, present only when fixes have been requested.
+    client: Option<github_api::Client>,
+}
+
+audit_meta!(
+    UnpinnedUses,
+    "unpinned-uses",
+    "unpinned action reference",
+    rationale = "
+        Flags uses: clauses that aren't pinned to any ref, as well as Docker
+        actions that aren't pinned by digest.
+
+        An unpinned action runs whatever is on the referenced repository's
+        default branch (or the image's :latest tag) at the time the workflow runs.
 
-audit_meta!(UnpinnedUses, "unpinned-uses", "unpinned action reference");
+        Findings are medium severity with high confidence. Actions pinned to an
+        abbreviated commit SHA (e.g. @abc1234) are also flagged with medium
+        severity, since GitHub may resolve them ambiguously. Actions pinned to a
+        branch or tag rather than a commit SHA are also flagged with the pedantic
+        persona, with low severity.
+    ",
+    example = r#"
+        steps:
+          - uses: actions/checkout
+    "#,
+    remediation = "
+        Pin each action to a full commit SHA, with the intended version in a
+        comment, e.g. actions/checkout@<sha> # v4.
+    ",
+);
 
 impl UnpinnedUses {
     pub fn evaluate_pinning<'u>(&self, uses: &Uses) -> Option<(&'u str, Severity, Persona)> {
@@ -17,12 +50,29 @@ impl UnpinnedUses {
             return None;
         }
 
+        // Docker image tags are mutable, so only a digest actually pins
+        // an image: `docker://ubuntu` and `docker://ubuntu:24.04` are
+        // equally unpinned.
+        if let Uses::Docker(docker) = uses {
+            return docker.hash.is_none().then_some((
+                "image is not pinned to a SHA256 hash",
+                Severity::Medium,
+                Persona::default(),
+            ));
+        }
+
         if uses.unpinned() {
             Some((
                 "action is not pinned to a tag, branch, or hash ref",
                 Severity::Medium,
                 Persona::default(),
             ))
+        } else if matches!(uses, Uses::Repository(repo) if repo.ref_is_abbreviated_commit()) {
+            Some((
+                "action is pinned to an abbreviated commit SHA; pin to the full 40-character SHA",
+                Severity::Medium,
+                Persona::default(),
+            ))
         } else if uses.unhashed() {
             Some((
                 "action is not pinned to a hash ref",
@@ -33,14 +83,62 @@ impl UnpinnedUses {
             None
         }
     }
+
+    /// Returns a fix that pins the given `uses:` to the commit that its
+    /// symbolic ref currently resolves to, if fixes have been requested
+    /// and the ref can be resolved.
+    fn pinning_fix<'w>(
+        &self,
+        uses: &Uses,
+        location: SymbolicLocation<'w>,
+    ) -> Result<Option<Fix<'w>>> {
+        let Some(client) = &self.client else {
+            return Ok(None);
+        };
+
+        let Uses::Repository(uses) = uses else {
+            return Ok(None);
+        };
+
+        let Some(git_ref) = uses.symbolic_ref() else {
+            return Ok(None);
+        };
+
+        let Some(sha) = client.commit_for_ref(&uses.owner, &uses.repo, git_ref)? else {
+            tracing::warn!(
+                "couldn't resolve {owner}/{repo}@{git_ref} to a commit",
+                owner = uses.owner,
+                repo = uses.repo,
+            );
+            return Ok(None);
+        };
+
+        Ok(Some(Fix {
+            title: format!("pin to {sha} ({git_ref})"),
+            location,
+            old: Some(format!("@{git_ref}")),
+            new: format!("@{sha}"),
+            // Preserve the human-readable ref as a trailing comment,
+            // e.g. `uses: actions/checkout@<sha> # v4.2.2`. An abbreviated
+            // commit SHA isn't any more readable than the full one.
+            comment: (!uses.ref_is_abbreviated_commit()).then(|| git_ref.into()),
+        }))
+    }
 }
 
 impl Audit for UnpinnedUses {
-    fn new(_state: AuditState) -> anyhow::Result<Self>
+    fn new(state: AuditState) -> anyhow::Result<Self>
     where
         Self: Sized,
     {
-        Ok(Self)
+        // NOTE: This audit always works offline; we only need a client
+        // when we've been asked to resolve refs for fixes.
+        let client = match state.fix {
+            true => state.github_client(),
+            false => None,
+        };
+
+        Ok(Self { client })
     }
 
     fn audit_step<'w>(&self, step: &Step<'w>) -> anyhow::Result<Vec<Finding<'w>>> {
@@ -51,19 +149,19 @@ impl Audit for UnpinnedUses {
         };
 
         if let Some((annotation, severity, persona)) = self.evaluate_pinning(uses) {
-            findings.push(
-                Self::finding()
-                    .confidence(Confidence::High)
-                    .severity(severity)
-                    .persona(persona)
-                    .add_location(
-                        step.location()
-                            .primary()
-                            .with_keys(&["uses".into()])
-                            .annotated(annotation),
-                    )
-                    .build(step.workflow())?,
-            );
+            let location = step.location().with_keys(&["uses".into()]);
+
+            let mut finding = Self::finding()
+                .confidence(Confidence::High)
+                .severity(severity)
+                .persona(persona)
+                .add_location(location.clone().primary().annotated(annotation));
+
+            if let Some(fix) = self.pinning_fix(uses, location)? {
+                finding = finding.fix(fix);
+            }
+
+            findings.push(finding.build(step.workflow())?);
         };
 
         Ok(findings)
@@ -80,19 +178,19 @@ impl Audit for UnpinnedUses {
         };
 
         if let Some((annotation, severity, persona)) = self.evaluate_pinning(uses) {
-            findings.push(
-                Self::finding()
-                    .confidence(Confidence::High)
-                    .severity(severity)
-                    .persona(persona)
-                    .add_location(
-                        step.location()
-                            .primary()
-                            .with_keys(&["uses".into()])
-                            .annotated(annotation),
-                    )
-                    .build(step.action())?,
-            );
+            let location = step.location().with_keys(&["uses".into()]);
+
+            let mut finding = Self::finding()
+                .confidence(Confidence::High)
+                .severity(severity)
+                .persona(persona)
+                .add_location(location.clone().primary().annotated(annotation));
+
+            if let Some(fix) = self.pinning_fix(uses, location)? {
+                finding = finding.fix(fix);
+            }
+
+            findings.push(finding.build(step.action())?);
         };
 
         Ok(findings)
@@ -0,0 +1,232 @@
+//! Detects secrets that are exposed to steps that run after a checkout
+//! of untrusted (i.e. pull request) code.
+//!
+//! This is a higher-confidence companion to `overprovisioned-secrets`:
+//! once a job has checked out a pull request's head, any later `run:`
+//! step may execute attacker-controlled code (build scripts, test
+//! harnesses, package manager hooks, etc.). Secrets expanded into those
+//! steps are then directly reachable by the attacker.
+
+use github_actions_models::common::{expr::LoE, Env, Uses};
+
+use super::{audit_meta, Audit};
+use crate::{
+    expr::Expr,
+    finding::{Confidence, Finding, Severity},
+    models::{uses::RepositoryUsesExt as _, JobExt as _, NormalJob, StepBodyCommon, StepCommon},
+    state::AuditState,
+    utils::{extract_expressions, secret_contexts},
+};
+
+/// Contexts that refer to the head of a pull request, or to a
+/// `workflow_run` triggered by one.
+const UNTRUSTED_REF_CONTEXTS: &[&str] = &[
+    "github.head_ref",
+    "github.event.pull_request.head",
+    "github.event.pull_request.merge_commit_sha",
+    "github.event.workflow_run.head_branch",
+    "github.event.workflow_run.head_sha",
+    "github.event.workflow_run.head_repository",
+];
+
+pub(crate) struct UntrustedCheckoutSecrets;
+
+audit_meta!(
+    UntrustedCheckoutSecrets,
+    "untrusted-checkout-secrets",
+    "secrets exposed to untrusted code",
+    rationale = "
+        Flags steps that run code from a checked-out pull request while secrets
+        are available to them.
+
+        In privileged workflows like pull_request_target, checking out the pull
+        request's head and then running its code gives the pull request's author
+        access to any secrets exposed to that code.
+
+        Findings are high severity with high confidence.
+    ",
+    example = r#"
+        on: pull_request_target
+
+        jobs:
+          test:
+            runs-on: ubuntu-latest
+            steps:
+              - uses: actions/checkout@v4
+                with:
+                  ref: ${{ github.event.pull_request.head.sha }}
+              - run: make test
+                env:
+                  API_TOKEN: ${{ secrets.API_TOKEN }}
+    "#,
+    remediation = "
+        Don't expose secrets to steps that run untrusted code. Test pull requests
+        with the pull_request trigger instead.
+    ",
+);
+
+impl UntrustedCheckoutSecrets {
+    /// Returns the `actions/checkout` input that causes a pull request's
+    /// code to be checked out, rather than the base repository's, if any.
+    pub(crate) fn untrusted_checkout_input(with: &Env) -> Option<&'static str> {
+        ["ref", "repository"].into_iter().find(|input| {
+            let Some(value) = with.get(*input) else {
+                return false;
+            };
+            let value = value.to_string();
+
+            // e.g. `ref: refs/pull/${{ github.event.number }}/merge`
+            if value.contains("refs/pull/") {
+                return true;
+            }
+
+            extract_expressions(&value).iter().any(|(expr, _)| {
+                let Ok(parsed) = Expr::parse(expr.as_bare()) else {
+                    tracing::warn!("couldn't parse expression: {expr}", expr = expr.as_bare());
+                    return false;
+                };
+
+                parsed.contexts().iter().any(|ctx| {
+                    UNTRUSTED_REF_CONTEXTS
+                        .iter()
+                        .any(|untrusted| ctx.child_of(*untrusted))
+                })
+            })
+        })
+    }
+}
+
+impl Audit for UntrustedCheckoutSecrets {
+    fn new(_state: AuditState) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        Ok(Self)
+    }
+
+    fn audit_normal_job<'w>(&self, job: &NormalJob<'w>) -> anyhow::Result<Vec<Finding<'w>>> {
+        let mut findings = vec![];
+
+        let Some((checkout, input)) = job.steps().find_map(|step| {
+            let StepBodyCommon::Uses {
+                uses: Uses::Repository(uses),
+                with,
+            } = step.body()
+            else {
+                return None;
+            };
+
+            if !uses.matches("actions/checkout") {
+                return None;
+            }
+
+            Self::untrusted_checkout_input(with).map(|input| (step, input))
+        }) else {
+            return Ok(findings);
+        };
+
+        for step in job.steps().skip(checkout.index + 1) {
+            let StepBodyCommon::Run { run, env, .. } = step.body() else {
+                continue;
+            };
+
+            let mut exposures = vec![(
+                secret_contexts(run),
+                step.location().with_keys(&["run".into()]),
+            )];
+            match env {
+                LoE::Literal(env) => {
+                    for (key, value) in env {
+                        exposures.push((
+                            secret_contexts(&value.to_string()),
+                            step.location()
+                                .with_keys(&["env".into(), key.clone().into()]),
+                        ));
+                    }
+                }
+                LoE::Expr(expr) => exposures.push((
+                    secret_contexts(expr.as_curly()),
+                    step.location().with_keys(&["env".into()]),
+                )),
+            }
+
+            for (mut secrets, location) in exposures {
+                if secrets.is_empty() {
+                    continue;
+                }
+
+                secrets.sort();
+                secrets.dedup();
+
+                findings.push(
+                    Self::finding()
+                        .severity(Severity::High)
+                        .confidence(Confidence::High)
+                        .add_location(
+                            checkout
+                                .location()
+                                .with_keys(&["with".into(), input.into()])
+                                .annotated("pull request code is checked out here"),
+                        )
+                        .add_location(step.location_with_name())
+                        .add_location(location.primary().annotated(format!(
+                            "{secrets} exposed to a step that may run untrusted code",
+                            secrets = secrets.join(", ")
+                        )))
+                        .build(job.parent())?,
+                );
+            }
+        }
+
+        Ok(findings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use github_actions_models::common::Env;
+
+    use super::UntrustedCheckoutSecrets;
+
+    #[test]
+    fn test_untrusted_checkout_input() {
+        for (with, expected) in &[
+            ("persist-credentials: false", None),
+            ("ref: main", None),
+            ("ref: ${{ github.sha }}", None),
+            ("ref: ${{ github.event.pull_request.base.sha }}", None),
+            (
+                "ref: ${{ github.event.pull_request.head.sha }}",
+                Some("ref"),
+            ),
+            (
+                "ref: ${{ github.event.pull_request.head.ref }}",
+                Some("ref"),
+            ),
+            ("ref: ${{ GitHub.Head_Ref }}", Some("ref")),
+            (
+                "ref: ${{ github.event.pull_request.merge_commit_sha }}",
+                Some("ref"),
+            ),
+            (
+                "ref: ${{ github.event.workflow_run.head_sha }}",
+                Some("ref"),
+            ),
+            (
+                "ref: refs/pull/${{ github.event.number }}/merge",
+                Some("ref"),
+            ),
+            (
+                "repository: ${{ github.event.pull_request.head.repo.full_name }}",
+                Some("repository"),
+            ),
+        ] {
+            let with = serde_yaml::from_str::<Env>(with).unwrap();
+            assert_eq!(
+                UntrustedCheckoutSecrets::untrusted_checkout_input(&with),
+                *expected,
+                "failed: {with:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,222 @@
+//! Detects `id-token: write` permissions that are granted to jobs
+//! that never consume an OIDC token.
+//!
+//! `id-token: write` allows a job to mint OIDC tokens for the workflow's
+//! identity. Granting it to a job that never uses those tokens (e.g. to
+//! log in to a cloud provider, or for Trusted Publishing) is needless
+//! attack surface.
+
+use std::str::FromStr as _;
+
+use anyhow::anyhow;
+use github_actions_models::common::{Permission, Permissions, RepositoryUses, Uses};
+use serde::Deserialize;
+
+use super::{audit_meta, Audit, Job};
+use crate::{
+    finding::{Confidence, Finding, Persona, Severity},
+    models::{
+        uses::RepositoryUsesExt as _, JobExt as _, NormalJob, StepBodyCommon, StepCommon as _,
+        Workflow,
+    },
+    state::AuditState,
+};
+
+/// Actions that are known to consume OIDC tokens.
+const KNOWN_OIDC_CONSUMERS: &[&str] = &[
+    // Cloud and secret manager logins.
+    "aws-actions/configure-aws-credentials",
+    "azure/login",
+    "google-github-actions/auth",
+    "hashicorp/vault-action",
+    "octo-sts/action",
+    "pulumi/auth-actions",
+    // Trusted Publishing.
+    "pypa/gh-action-pypi-publish",
+    "rubygems/configure-rubygems-credentials",
+    "rubygems/release-gem",
+    // Attestations and signing.
+    "actions/attest",
+    "actions/attest-build-provenance",
+    "actions/attest-sbom",
+    "sigstore/gh-action-sigstore-python",
+    // GitHub Pages deployments.
+    "actions/deploy-pages",
+];
+
+/// The environment variable that OIDC consumers read to request a token.
+const ID_TOKEN_REQUEST_VAR: &str = "ACTIONS_ID_TOKEN_REQUEST_URL";
+
+/// Audit-specific settings, under `rules.unused-id-token.config`.
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct UnusedIdTokenConfig {
+    /// Additional actions to treat as OIDC consumers, as `owner/repo`.
+    #[serde(default)]
+    oidc_consumers: Vec<String>,
+}
+
+pub(crate) struct UnusedIdToken {
+    oidc_consumers: Vec<RepositoryUses>,
+}
+
+audit_meta!(
+    UnusedIdToken,
+    "unused-id-token",
+    "id-token: write without an OIDC consumer",
+    rationale = "
+        Flags id-token: write permissions in jobs that don't appear to use an OIDC
+        token.
+
+        An unused OIDC token lets a compromised step authenticate to any cloud
+        provider or service that trusts the repository's tokens.
+
+        Findings are low severity with medium confidence, since zizmor can't see
+        every OIDC consumer, and are only shown with the pedantic persona.
+    ",
+    example = r#"
+        jobs:
+          test:
+            runs-on: ubuntu-latest
+            permissions:
+              id-token: write
+            steps:
+              - run: make test
+    "#,
+    remediation = "
+        Remove id-token: write from jobs that don't use it. If a job does use an
+        OIDC consumer that zizmor doesn't know about, configure it under
+        rules.unused-id-token.config.
+    ",
+);
+
+impl UnusedIdToken {
+    /// Returns whether the given permissions explicitly grant `id-token: write`.
+    fn grants_id_token(permissions: &Permissions) -> bool {
+        match permissions {
+            Permissions::Explicit(perms) => perms
+                .iter()
+                .any(|(name, perm)| name == "id-token" && *perm == Permission::Write),
+            Permissions::Base(_) => false,
+        }
+    }
+
+    /// Returns whether any step in the given job consumes an OIDC token.
+    fn job_consumes_id_token(&self, job: &NormalJob) -> bool {
+        job.steps().any(|step| match step.body() {
+            StepBodyCommon::Uses {
+                uses: Uses::Repository(uses),
+                ..
+            } => self
+                .oidc_consumers
+                .iter()
+                .any(|consumer| uses.matches_uses(consumer)),
+            // Local and Docker actions are opaque to us, so we assume
+            // that they might consume the token.
+            StepBodyCommon::Uses { .. } => true,
+            StepBodyCommon::Run { run, .. } => run.contains(ID_TOKEN_REQUEST_VAR),
+        })
+    }
+
+    /// Returns whether the given job might consume an OIDC token.
+    ///
+    /// Reusable workflows are opaque to us, so we assume that they
+    /// might consume the token.
+    fn consumes_id_token(&self, job: &Job) -> bool {
+        match job {
+            Job::NormalJob(job) => self.job_consumes_id_token(job),
+            Job::ReusableWorkflowCallJob(_) => true,
+        }
+    }
+}
+
+impl Audit for UnusedIdToken {
+    fn new(state: AuditState) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        let config = state
+            .config
+            .rule_config::<UnusedIdTokenConfig>(Self::ident())?
+            .unwrap_or_default();
+
+        let oidc_consumers = KNOWN_OIDC_CONSUMERS
+            .iter()
+            .map(|consumer| consumer.to_string())
+            .chain(config.oidc_consumers)
+            .map(|consumer| match Uses::from_str(&consumer) {
+                Ok(Uses::Repository(uses)) => Ok(uses),
+                _ => Err(anyhow!("invalid OIDC consumer: {consumer}")),
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Self { oidc_consumers })
+    }
+
+    fn audit_workflow<'w>(
+        &self,
+        workflow: &'w Workflow,
+        _workflows: &super::LocalWorkflows<'w>,
+    ) -> anyhow::Result<Vec<Finding<'w>>> {
+        let mut findings = vec![];
+
+        // Jobs without their own permissions inherit the workflow's.
+        let mut inheriting_jobs = vec![];
+
+        for job in workflow.jobs() {
+            let permissions = match &job {
+                Job::NormalJob(job) => &job.permissions,
+                Job::ReusableWorkflowCallJob(job) => &job.permissions,
+            };
+
+            if *permissions == Permissions::default() {
+                inheriting_jobs.push(job);
+                continue;
+            }
+
+            let Job::NormalJob(job) = job else {
+                continue;
+            };
+
+            if Self::grants_id_token(&job.permissions) && !self.job_consumes_id_token(&job) {
+                findings.push(
+                    Self::finding()
+                        .severity(Severity::Low)
+                        .confidence(Confidence::Medium)
+                        .persona(Persona::Pedantic)
+                        .add_location(job.location())
+                        .add_location(
+                            job.location()
+                                .with_keys(&["permissions".into(), "id-token".into()])
+                                .primary()
+                                .annotated("job never uses an OIDC token"),
+                        )
+                        .build(workflow)?,
+                );
+            }
+        }
+
+        if Self::grants_id_token(&workflow.permissions)
+            && !inheriting_jobs
+                .iter()
+                .any(|job| self.consumes_id_token(job))
+        {
+            findings.push(
+                Self::finding()
+                    .severity(Severity::Low)
+                    .confidence(Confidence::Medium)
+                    .persona(Persona::Pedantic)
+                    .add_location(
+                        workflow
+                            .location()
+                            .with_keys(&["permissions".into(), "id-token".into()])
+                            .primary()
+                            .annotated("no job that inherits this uses an OIDC token"),
+                    )
+                    .build(workflow)?,
+            );
+        }
+
+        Ok(findings)
+    }
+}
@@ -9,7 +9,7 @@ use indexmap::IndexMap;
 
 use super::{audit_meta, Audit};
 use crate::{
-    finding::{Confidence, Severity},
+    finding::{Confidence, Fix, Severity},
     models::uses::RepositoryUsesExt as _,
     state::AuditState,
 };
@@ -29,7 +29,26 @@ pub(crate) struct UseTrustedPublishing {
 audit_meta!(
     UseTrustedPublishing,
     "use-trusted-publishing",
-    "prefer trusted publishing for authentication"
+    "prefer trusted publishing for authentication",
+    rationale = "
+        Flags package publishing steps that use a long-lived token on a package
+        index that supports Trusted Publishing.
+
+        Trusted Publishing uses short-lived OIDC credentials instead, which don't
+        need to be stored as secrets and can't be leaked for later use.
+
+        Findings are informational with high confidence.
+    ",
+    example = r#"
+        steps:
+          - uses: pypa/gh-action-pypi-publish@release/v1
+            with:
+              password: ${{ secrets.PYPI_TOKEN }}
+    "#,
+    remediation = "
+        Configure a Trusted Publisher on the package index, grant the job
+        id-token: write, and remove the token.
+    ",
 );
 
 impl UseTrustedPublishing {
@@ -108,10 +127,27 @@ impl Audit for UseTrustedPublishing {
                     )
                     .build(step.workflow())?,
             );
-        } else if ((uses.matches("rubygems/release-gem"))
-            && self.release_gem_uses_manual_credentials(with))
-            || (uses.matches("rubygems/configure-rubygems-credential")
-                && self.rubygems_credential_uses_manual_credentials(with))
+        } else if uses.matches("rubygems/release-gem")
+            && self.release_gem_uses_manual_credentials(with)
+        {
+            // Trusted Publishing is `release-gem`'s default, so this
+            // one can be fixed mechanically.
+            findings.push(
+                candidate
+                    .add_location(step.location().primary().annotated(USES_MANUAL_CREDENTIAL))
+                    .fix(Fix {
+                        title: "use Trusted Publishing".into(),
+                        location: step
+                            .location()
+                            .with_keys(&["with".into(), "setup-trusted-publisher".into()]),
+                        old: None,
+                        new: "setup-trusted-publisher: true".into(),
+                        comment: None,
+                    })
+                    .build(step.workflow())?,
+            );
+        } else if uses.matches("rubygems/configure-rubygems-credential")
+            && self.rubygems_credential_uses_manual_credentials(with)
         {
             findings.push(
                 candidate
@@ -1,9 +1,12 @@
-use std::{collections::HashMap, fs, num::NonZeroUsize, str::FromStr};
+use std::{collections::HashMap, fs, num::NonZeroUsize, path::Path, str::FromStr};
 
 use anyhow::{anyhow, Context as _, Result};
-use serde::{de, Deserialize};
+use base64::{prelude::BASE64_STANDARD, Engine as _};
+use serde::{de, de::DeserializeOwned, Deserialize};
 
-use crate::{finding::Finding, App};
+use camino::Utf8Path;
+
+use crate::finding::Finding;
 
 #[derive(Clone, Debug, PartialEq)]
 pub(crate) struct WorkflowRule {
@@ -64,39 +67,50 @@ impl<'de> Deserialize<'de> for WorkflowRule {
 
 #[derive(Clone, Debug, Deserialize)]
 pub(crate) struct AuditRuleConfig {
+    #[serde(default)]
     ignore: Vec<WorkflowRule>,
+    /// Audit-specific settings, interpreted by the audit itself.
+    #[serde(default)]
+    config: Option<serde_yaml::Value>,
 }
 
+/// The config filenames that are discovered automatically, in order
+/// of precedence.
+const CONFIG_FILENAMES: &[&str] = &["zizmor.yml", "zizmor.yaml", "zizmor.toml", "zizmor.json"];
+
+/// The environment variable that `--config-from-env` loads the
+/// configuration from.
+pub const CONFIG_ENV_VAR: &str = "ZIZMOR_CONFIG";
+
 /// Runtime configuration, corresponding to a `zizmor.yml` file.
 #[derive(Clone, Debug, Default, Deserialize)]
-pub(crate) struct Config {
+pub struct Config {
     rules: HashMap<String, AuditRuleConfig>,
 }
 
 impl Config {
-    pub(crate) fn new(app: &App) -> Result<Self> {
-        if app.no_config {
-            return Ok(Self::default());
-        }
-
-        let config = match &app.config {
-            Some(path) => serde_yaml::from_str(&fs::read_to_string(path)?)?,
+    /// Loads the configuration at the given path, or discovers one
+    /// relative to `$CWD` if no path is given.
+    pub fn new(path: Option<&Utf8Path>) -> Result<Self> {
+        let config = match path {
+            Some(path) => Self::load(path.as_std_path())?,
             None => {
                 // If the user didn't pass a config path explicitly with
                 // `--config`, then we attempt to discover one relative to $CWD
-                // Our procedure is to first look for `$CWD/.github/zizmor.yml`,
-                // then `$CWD/zizmor.yml`, and then bail.
+                // Our procedure is to first look for a config in `$CWD/.github/`,
+                // then in `$CWD`, and then bail. Within each directory, the
+                // filenames in `CONFIG_FILENAMES` are tried in order.
                 let cwd = std::env::current_dir()
                     .with_context(|| "config discovery couldn't access CWD")?;
 
-                let path = cwd.join(".github").join("zizmor.yml");
-                if path.is_file() {
-                    serde_yaml::from_str(&fs::read_to_string(path)?)?
-                } else {
-                    let path = cwd.join("zizmor.yml");
-                    if path.is_file() {
-                        serde_yaml::from_str(&fs::read_to_string(path)?)?
-                    } else {
+                let discovered = [cwd.join(".github"), cwd]
+                    .into_iter()
+                    .flat_map(|dir| CONFIG_FILENAMES.iter().map(move |name| dir.join(name)))
+                    .find(|path| path.is_file());
+
+                match discovered {
+                    Some(path) => Self::load(&path)?,
+                    None => {
                         tracing::debug!("no config discovered; loading default");
                         Config::default()
                     }
@@ -109,6 +123,71 @@ impl Config {
         Ok(config)
     }
 
+    /// Loads the configuration from the [`CONFIG_ENV_VAR`] environment
+    /// variable, instead of from a file.
+    ///
+    /// No discovery is performed, and it's an error for the variable to
+    /// be unset.
+    pub fn from_env() -> Result<Self> {
+        let value = std::env::var(CONFIG_ENV_VAR)
+            .with_context(|| format!("couldn't read config from ${CONFIG_ENV_VAR}"))?;
+
+        let config = Self::parse_env(&value)
+            .with_context(|| format!("invalid config in ${CONFIG_ENV_VAR}"))?;
+
+        tracing::debug!("loaded config: {config:?}");
+
+        Ok(config)
+    }
+
+    /// Parses a configuration from an environment variable's value, which
+    /// is either base64-encoded or raw YAML (or JSON).
+    fn parse_env(value: &str) -> Result<Self> {
+        // YAML configs always contain characters (like `:`, whitespace,
+        // or braces) that aren't valid base64, so there's no ambiguity.
+        let decoded = BASE64_STANDARD
+            .decode(value.trim())
+            .ok()
+            .and_then(|decoded| String::from_utf8(decoded).ok());
+
+        Self::parse(decoded.as_deref().unwrap_or(value), None)
+    }
+
+    /// Loads the configuration at the given path, using its extension
+    /// to determine its format.
+    fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("couldn't read config file {}", path.display()))?;
+
+        Self::parse(&contents, path.extension().and_then(|ext| ext.to_str()))
+            .with_context(|| format!("invalid config file {}", path.display()))
+    }
+
+    /// Parses the given configuration, in the format indicated by
+    /// `extension`. Configurations without a recognized extension
+    /// are parsed as YAML.
+    fn parse(contents: &str, extension: Option<&str>) -> Result<Self> {
+        let config = match extension {
+            Some("toml") => toml::from_str(contents)?,
+            Some("json") => serde_json::from_str(contents)?,
+            _ => serde_yaml::from_str(contents)?,
+        };
+
+        Ok(config)
+    }
+
+    /// Returns the audit-specific settings for the given audit, if any.
+    pub(crate) fn rule_config<T: DeserializeOwned>(&self, ident: &str) -> Result<Option<T>> {
+        self.rules
+            .get(ident)
+            .and_then(|rule_config| rule_config.config.clone())
+            .map(|config| {
+                serde_yaml::from_value(config)
+                    .with_context(|| format!("invalid configuration for {ident}"))
+            })
+            .transpose()
+    }
+
     /// Returns `true` if this [`Config`] has an ignore rule for the
     /// given finding.
     pub(crate) fn ignores(&self, finding: &Finding<'_>) -> bool {
@@ -166,7 +245,9 @@ mod tests {
 
     use anyhow::Result;
 
-    use super::WorkflowRule;
+    use base64::{prelude::BASE64_STANDARD, Engine as _};
+
+    use super::{Config, WorkflowRule};
 
     #[test]
     fn test_parse_workflow_rule() -> Result<()> {
@@ -204,4 +285,105 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_rule_config() -> Result<()> {
+        let config = serde_yaml::from_str::<Config>(
+            r#"
+rules:
+  foo:
+    config:
+      bar: [baz]
+  quux:
+    ignore:
+      - quux.yml
+"#,
+        )?;
+
+        assert_eq!(
+            config.rule_config::<serde_yaml::Value>("foo")?,
+            Some(serde_yaml::from_str("bar: [baz]")?)
+        );
+        assert_eq!(config.rule_config::<serde_yaml::Value>("quux")?, None);
+        assert_eq!(config.rule_config::<serde_yaml::Value>("missing")?, None);
+        assert!(config.rule_config::<Vec<String>>("foo").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_formats() -> Result<()> {
+        let yaml = r#"
+rules:
+  foo:
+    config:
+      bar: [baz]
+    ignore:
+      - foo.yml:1
+"#;
+
+        let toml = r#"
+[rules.foo]
+ignore = ["foo.yml:1"]
+
+[rules.foo.config]
+bar = ["baz"]
+"#;
+
+        let json = r#"{"rules": {"foo": {"config": {"bar": ["baz"]}, "ignore": ["foo.yml:1"]}}}"#;
+
+        for (contents, extension) in [
+            (yaml, Some("yml")),
+            (yaml, Some("yaml")),
+            (yaml, None),
+            (toml, Some("toml")),
+            (json, Some("json")),
+        ] {
+            let config = Config::parse(contents, extension)?;
+
+            assert_eq!(
+                config.rule_config::<serde_yaml::Value>("foo")?,
+                Some(serde_yaml::from_str("bar: [baz]")?),
+                "failed: {extension:?}"
+            );
+            assert_eq!(
+                config.rules["foo"].ignore,
+                [WorkflowRule::from_str("foo.yml:1")?],
+                "failed: {extension:?}"
+            );
+        }
+
+        // Each format is parsed strictly according to its extension.
+        assert!(Config::parse(yaml, Some("toml")).is_err());
+        assert!(Config::parse(toml, Some("json")).is_err());
+        assert!(Config::parse("rules: [", Some("yml")).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_env() -> Result<()> {
+        let yaml = "rules:\n  foo:\n    ignore:\n      - foo.yml:1\n";
+        let json = r#"{"rules": {"foo": {"ignore": ["foo.yml:1"]}}}"#;
+
+        for value in [
+            yaml.to_string(),
+            json.to_string(),
+            BASE64_STANDARD.encode(yaml),
+            format!("{}\n", BASE64_STANDARD.encode(json)),
+        ] {
+            let config = Config::parse_env(&value)?;
+            assert_eq!(
+                config.rules["foo"].ignore,
+                [WorkflowRule::from_str("foo.yml:1")?],
+                "failed: {value}"
+            );
+        }
+
+        assert!(Config::parse_env("rules: [").is_err());
+        // Valid base64, but not a valid config once decoded.
+        assert!(Config::parse_env(&BASE64_STANDARD.encode("rules: [")).is_err());
+
+        Ok(())
+    }
 }
@@ -4,9 +4,10 @@ use std::{borrow::Cow, ops::Range, sync::LazyLock};
 
 use anyhow::{anyhow, Result};
 use clap::ValueEnum;
-use line_index::{LineCol, TextSize};
+use line_index::{LineCol, LineIndex, TextSize};
 use regex::Regex;
 use serde::Serialize;
+use sha2::{Digest as _, Sha256};
 use terminal_link::Link;
 
 use crate::{
@@ -21,7 +22,7 @@ use crate::{
 #[derive(
     Copy, Clone, Debug, Default, Eq, Hash, Ord, PartialOrd, PartialEq, Serialize, ValueEnum,
 )]
-pub(crate) enum Persona {
+pub enum Persona {
     /// The "auditor" persona (false positives OK).
     ///
     /// This persona wants all results, including results that are likely
@@ -34,6 +35,12 @@ pub(crate) enum Persona {
     /// but are potential "code smells".
     Pedantic,
 
+    /// The "developer" persona (some code smells OK).
+    ///
+    /// This persona wants regular findings, plus any pedantic findings
+    /// that have a confidence of medium or higher.
+    Developer,
+
     /// The "regular" persona (minimal false positives).
     ///
     /// This persona wants actionable findings, and is sensitive to
@@ -45,7 +52,7 @@ pub(crate) enum Persona {
 #[derive(
     Copy, Clone, Debug, Default, Eq, Hash, Ord, PartialOrd, PartialEq, Serialize, ValueEnum,
 )]
-pub(crate) enum Confidence {
+pub enum Confidence {
     #[default]
     Unknown,
     Low,
@@ -53,10 +60,23 @@ pub(crate) enum Confidence {
     High,
 }
 
+impl Confidence {
+    /// Returns the next confidence tier up, if any.
+    ///
+    /// `Unknown` isn't a tier, so it's never raised.
+    pub(crate) fn raised(self) -> Self {
+        match self {
+            Confidence::Low => Confidence::Medium,
+            Confidence::Medium | Confidence::High => Confidence::High,
+            Confidence::Unknown => Confidence::Unknown,
+        }
+    }
+}
+
 #[derive(
     Copy, Clone, Debug, Default, Eq, Hash, Ord, PartialOrd, PartialEq, Serialize, ValueEnum,
 )]
-pub(crate) enum Severity {
+pub enum Severity {
     #[default]
     Unknown,
     Informational,
@@ -65,6 +85,20 @@ pub(crate) enum Severity {
     High,
 }
 
+impl Severity {
+    /// Returns the exit code for a run whose highest (failing) finding
+    /// has this severity.
+    pub fn exit_code(self) -> u8 {
+        match self {
+            Severity::Unknown => 10,
+            Severity::Informational => 11,
+            Severity::Low => 12,
+            Severity::Medium => 13,
+            Severity::High => 14,
+        }
+    }
+}
+
 #[derive(Serialize, Clone, Debug)]
 pub(crate) struct StepLocation<'w> {
     pub(crate) index: usize,
@@ -83,7 +117,7 @@ impl<'w> From<&Step<'w>> for StepLocation<'w> {
 }
 
 #[derive(Serialize, Clone, Debug)]
-pub(crate) enum RouteComponent<'w> {
+pub enum RouteComponent<'w> {
     Key(Cow<'w, str>),
     Index(usize),
 }
@@ -100,8 +134,14 @@ impl<'w> From<&'w str> for RouteComponent<'w> {
     }
 }
 
+impl From<String> for RouteComponent<'_> {
+    fn from(value: String) -> Self {
+        Self::Key(Cow::Owned(value))
+    }
+}
+
 #[derive(Serialize, Clone, Debug)]
-pub(crate) struct Route<'w> {
+pub struct Route<'w> {
     components: Vec<RouteComponent<'w>>,
 }
 
@@ -121,28 +161,28 @@ impl<'w> Route<'w> {
 
 /// Represents a symbolic workflow location.
 #[derive(Serialize, Clone, Debug)]
-pub(crate) struct SymbolicLocation<'w> {
+pub struct SymbolicLocation<'w> {
     /// The unique ID of the workflow, as it appears in the workflow registry.
-    pub(crate) key: &'w InputKey,
+    pub key: &'w InputKey,
 
     /// An annotation for this location.
-    pub(crate) annotation: String,
+    pub annotation: String,
 
     /// An OSC 8 rendered link for the location's annotation, if applicable.
     ///
     /// Not serialized, since it contains ANSI escape codes.
     #[serde(skip_serializing)]
-    pub(crate) link: Option<String>,
+    pub link: Option<String>,
 
     /// A symbolic route (of keys and indices) to the final location.
-    pub(crate) route: Route<'w>,
+    pub route: Route<'w>,
 
     /// Whether this location is subjectively "primary" to a finding,
     /// or merely a "supporting" location.
     ///
     /// This distinction only matters in output formats like SARIF,
     /// where locations are split between locations and "related" locations.
-    pub(crate) primary: bool,
+    pub primary: bool,
 }
 
 impl<'w> SymbolicLocation<'w> {
@@ -228,10 +268,10 @@ impl<'w> SymbolicLocation<'w> {
 }
 
 /// Represents a `(row, column)` point within a file.
-#[derive(Serialize)]
-pub(crate) struct Point {
-    pub(crate) row: usize,
-    pub(crate) column: usize,
+#[derive(Serialize, Clone, Copy)]
+pub struct Point {
+    pub row: usize,
+    pub column: usize,
 }
 
 impl From<LineCol> for Point {
@@ -247,10 +287,10 @@ impl From<LineCol> for Point {
 /// Every concrete location contains two spans: a line-and-column span,
 /// and an offset range.
 #[derive(Serialize)]
-pub(crate) struct ConcreteLocation {
-    pub(crate) start_point: Point,
-    pub(crate) end_point: Point,
-    pub(crate) offset_span: Range<usize>,
+pub struct ConcreteLocation {
+    pub start_point: Point,
+    pub end_point: Point,
+    pub offset_span: Range<usize>,
 }
 
 impl ConcreteLocation {
@@ -284,10 +324,61 @@ static ANY_COMMENT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"#.*$").unwra
 static IGNORE_EXPR: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"# zizmor: ignore\[(.+)\]\s*$").unwrap());
 
+static IGNORE_START_EXPR: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"# zizmor: ignore-start\[(.+)\]\s*$").unwrap());
+
+static IGNORE_END_EXPR: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"# zizmor: ignore-end\[(.+)\]\s*$").unwrap());
+
+static ACK_EXPR: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"# zizmor: ack\[(.+?)\](?:\s+(.*?))?\s*$").unwrap());
+
+/// Returns whether the given comma-separated rule list (e.g. `foo,bar`)
+/// contains `rule_id`.
+fn rule_list_contains(rules: &str, rule_id: &str) -> bool {
+    rules.split(",").any(|r| r.trim() == rule_id)
+}
+
+/// Returns whether the given rule list from an ignore comment ignores
+/// `rule_id`.
+///
+/// Unlike [`rule_list_contains`], this honors the `*` and `all` wildcards,
+/// which ignore every rule.
+fn rule_list_ignores(rules: &str, rule_id: &str) -> bool {
+    matches!(rules.trim(), "*" | "all") || rule_list_contains(rules, rule_id)
+}
+
+/// Returns the row ranges in `source` that are delimited by a pair of
+/// `# zizmor: ignore-start[...]` and `# zizmor: ignore-end[...]` comments
+/// for `rule_id`.
+///
+/// Each range spans from the `ignore-start` row to the `ignore-end` row.
+/// An `ignore-start` without a matching `ignore-end` has no effect.
+fn ignored_ranges(source: &str, rule_id: &str) -> Vec<Range<usize>> {
+    let mut ranges = vec![];
+    let mut start = None;
+
+    for (row, line) in source.lines().enumerate() {
+        if let Some(caps) = IGNORE_START_EXPR.captures(line) {
+            if rule_list_ignores(caps.get(1).unwrap().as_str(), rule_id) {
+                start.get_or_insert(row);
+            }
+        } else if let Some(caps) = IGNORE_END_EXPR.captures(line) {
+            if rule_list_ignores(caps.get(1).unwrap().as_str(), rule_id) {
+                if let Some(start) = start.take() {
+                    ranges.push(start..row);
+                }
+            }
+        }
+    }
+
+    ranges
+}
+
 /// Represents a single source comment.
 #[derive(Debug, Serialize)]
 #[serde(transparent)]
-pub(crate) struct Comment<'w>(&'w str);
+pub struct Comment<'w>(&'w str);
 
 impl Comment<'_> {
     fn ignores(&self, rule_id: &str) -> bool {
@@ -296,25 +387,36 @@ impl Comment<'_> {
             return false;
         };
 
-        caps.get(1)
-            .unwrap()
-            .as_str()
-            .split(",")
-            .any(|r| r.trim() == rule_id)
+        // `# zizmor: ignore[*]` and `# zizmor: ignore[all]` ignore every rule.
+        rule_list_ignores(caps.get(1).unwrap().as_str(), rule_id)
+    }
+
+    /// Returns the acknowledgment text for `rule_id`, if this comment
+    /// is an acknowledgment of it.
+    ///
+    /// Unlike an ignore, an acknowledgment doesn't suppress the finding;
+    /// it only attaches the comment's trailing text (e.g. a ticket
+    /// reference) to it. The returned text may be empty.
+    fn acknowledgment(&self, rule_id: &str) -> Option<&str> {
+        // Extracts foo,bar and `JIRA-123` from `# zizmor: ack[foo,bar] JIRA-123`
+        let caps = ACK_EXPR.captures(self.0)?;
+
+        rule_list_contains(caps.get(1).unwrap().as_str(), rule_id)
+            .then(|| caps.get(2).map_or("", |m| m.as_str()))
     }
 }
 
 /// An extracted feature, along with its concrete location.
 #[derive(Serialize)]
-pub(crate) struct Feature<'w> {
+pub struct Feature<'w> {
     /// The feature's concrete location, as both an offset range and point span.
-    pub(crate) location: ConcreteLocation,
+    pub location: ConcreteLocation,
 
     /// The feature's textual content.
-    pub(crate) feature: &'w str,
+    pub feature: &'w str,
 
     /// Any comments within the feature's line span.
-    pub(crate) comments: Vec<Comment<'w>>,
+    pub comments: Vec<Comment<'w>>,
 }
 
 impl<'w> Feature<'w> {
@@ -362,11 +464,11 @@ impl<'w> Feature<'w> {
 
 /// A location within a GitHub Actions workflow, with both symbolic and concrete components.
 #[derive(Serialize)]
-pub(crate) struct Location<'w> {
+pub struct Location<'w> {
     /// The symbolic workflow location.
-    pub(crate) symbolic: SymbolicLocation<'w>,
+    pub symbolic: SymbolicLocation<'w>,
     /// The concrete location, including extracted feature.
-    pub(crate) concrete: Feature<'w>,
+    pub concrete: Feature<'w>,
 }
 
 impl<'w> Location<'w> {
@@ -377,20 +479,94 @@ impl<'w> Location<'w> {
 
 /// A finding's "determination," i.e. its various classifications.
 #[derive(Serialize)]
-pub(crate) struct Determinations {
-    pub(crate) confidence: Confidence,
-    pub(crate) severity: Severity,
-    pub(super) persona: Persona,
+pub struct Determinations {
+    pub confidence: Confidence,
+    pub severity: Severity,
+    pub persona: Persona,
+}
+
+/// A fix for a finding, expressed as a textual replacement within a
+/// symbolic location.
+#[derive(Serialize, Clone, Debug)]
+pub struct Fix<'w> {
+    /// A short human-readable description of the fix.
+    pub title: String,
+    /// The location whose feature the fix rewrites.
+    pub location: SymbolicLocation<'w>,
+    /// The text to replace. The last occurrence of this text within the
+    /// location's feature is replaced, or the entire feature if `None`.
+    pub old: Option<String>,
+    /// The replacement text.
+    pub new: String,
+    /// A trailing comment to add after the replacement, unless the
+    /// location already has one.
+    pub comment: Option<String>,
+}
+
+/// A fix resolved against its input, expressed as a concrete span and
+/// its replacement text.
+#[derive(Serialize)]
+pub struct Replacement<'w> {
+    /// A short human-readable description of the fix.
+    pub title: String,
+    /// The input that the replacement applies to.
+    pub key: &'w InputKey,
+    /// The span being replaced.
+    pub location: ConcreteLocation,
+    /// The replacement text.
+    pub text: String,
 }
 
 #[derive(Serialize)]
-pub(crate) struct Finding<'w> {
-    pub(crate) ident: &'static str,
-    pub(crate) desc: &'static str,
-    pub(crate) url: &'static str,
-    pub(crate) determinations: Determinations,
-    pub(crate) locations: Vec<Location<'w>>,
-    pub(crate) ignored: bool,
+pub struct Finding<'w> {
+    pub ident: &'static str,
+    pub desc: &'static str,
+    pub url: &'static str,
+    pub determinations: Determinations,
+    pub locations: Vec<Location<'w>>,
+    /// The overall span covered by this finding's locations, from the
+    /// earliest start to the latest end.
+    ///
+    /// `None` if the locations are in more than one input.
+    pub span: Option<ConcreteLocation>,
+    pub ignored: bool,
+    /// Acknowledgment text from an inline `# zizmor: ack[...]` comment, if any.
+    pub acknowledgment: Option<String>,
+    /// Fixes for this finding, resolved against its input.
+    pub fixes: Vec<Replacement<'w>>,
+    /// A stable fingerprint for this finding, which doesn't change when
+    /// unrelated parts of its input move around.
+    pub fingerprint: String,
+}
+
+impl<'w> Finding<'w> {
+    /// Returns this finding's first primary location.
+    pub(crate) fn primary_location(&self) -> &Location<'w> {
+        // NOTE: Safe unwrap, since every finding has a primary location.
+        self.locations.iter().find(|l| l.symbolic.primary).unwrap()
+    }
+
+    /// Returns a mutable reference to this finding's first primary location.
+    fn primary_location_mut(&mut self) -> &mut Location<'w> {
+        self.locations
+            .iter_mut()
+            .find(|l| l.symbolic.primary)
+            .unwrap()
+    }
+
+    /// Marks this finding as corroborated by the given audits, raising
+    /// its confidence by one tier and noting the audits in its primary
+    /// annotation.
+    pub(crate) fn corroborate(&mut self, idents: &[&str]) {
+        self.determinations.confidence = self.determinations.confidence.raised();
+
+        let primary = self.primary_location_mut();
+        primary.symbolic.annotation = format!(
+            "{annotation} (corroborated by {idents})",
+            annotation = primary.symbolic.annotation,
+            idents = idents.join(", ")
+        );
+    }
 }
 
 pub(crate) struct FindingBuilder<'w> {
@@ -402,6 +578,7 @@ pub(crate) struct FindingBuilder<'w> {
     persona: Persona,
     raw_locations: Vec<Location<'w>>,
     locations: Vec<SymbolicLocation<'w>>,
+    fixes: Vec<Fix<'w>>,
 }
 
 impl<'w> FindingBuilder<'w> {
@@ -415,6 +592,7 @@ impl<'w> FindingBuilder<'w> {
             persona: Default::default(),
             raw_locations: vec![],
             locations: vec![],
+            fixes: vec![],
         }
     }
 
@@ -433,6 +611,12 @@ impl<'w> FindingBuilder<'w> {
         self
     }
 
+    /// Places this finding in the developer persona, which shows it
+    /// regardless of its confidence.
+    pub(crate) fn developer(self) -> Self {
+        self.persona(Persona::Developer)
+    }
+
     pub(crate) fn add_raw_location(mut self, location: Location<'w>) -> Self {
         self.raw_locations.push(location);
         self
@@ -443,6 +627,11 @@ impl<'w> FindingBuilder<'w> {
         self
     }
 
+    pub(crate) fn fix(mut self, fix: Fix<'w>) -> Self {
+        self.fixes.push(fix);
+        self
+    }
+
     pub(crate) fn build(self, document: &'w impl AsRef<yamlpath::Document>) -> Result<Finding<'w>> {
         let mut locations = self
             .locations
@@ -458,7 +647,41 @@ impl<'w> FindingBuilder<'w> {
             ));
         }
 
-        let should_ignore = Self::ignored_from_inlined_comment(&locations, self.ident);
+        let span = Self::span(&locations);
+        let should_ignore = Self::ignored_from_inlined_comment(&locations, self.ident)
+            || Self::ignored_from_range(document.as_ref().source(), &locations, self.ident);
+        let acknowledgment = Self::acknowledgment_from_inlined_comment(&locations, self.ident);
+
+        let source = document.as_ref().source();
+        let mut fixes = vec![];
+        if !self.fixes.is_empty() {
+            let line_index = LineIndex::new(source);
+
+            for fix in self.fixes {
+                let location = fix.location.clone().concretize(document)?;
+
+                let Some((span, text)) = Self::resolve_fix(&fix, &location, source) else {
+                    tracing::warn!(
+                        "couldn't resolve fix for {ident}: {title}",
+                        ident = self.ident,
+                        title = fix.title
+                    );
+                    continue;
+                };
+
+                let start = line_index.line_col(TextSize::new(span.start as u32));
+                let end = line_index.line_col(TextSize::new(span.end as u32));
+
+                fixes.push(Replacement {
+                    title: fix.title,
+                    key: location.symbolic.key,
+                    location: ConcreteLocation::new(start.into(), end.into(), span),
+                    text,
+                });
+            }
+        }
+
+        let fingerprint = Self::fingerprint(self.ident, &locations);
 
         Ok(Finding {
             ident: self.ident,
@@ -470,21 +693,127 @@ impl<'w> FindingBuilder<'w> {
                 persona: self.persona,
             },
             locations,
+            span,
             ignored: should_ignore,
+            acknowledgment,
+            fixes,
+            fingerprint,
         })
     }
 
+    /// Computes a fingerprint for a finding from its audit and primary
+    /// location.
+    ///
+    /// The fingerprint covers the location's input, symbolic route,
+    /// annotation, and feature, but not its concrete position, so that
+    /// it survives edits elsewhere in the input.
+    ///
+    /// The input is identified by its repository-relative path, so that
+    /// the fingerprint doesn't depend on how the input was given (e.g.
+    /// `.` versus `.github/workflows/` versus an absolute path).
+    fn fingerprint(ident: &str, locations: &[Location]) -> String {
+        // NOTE: Safe unwrap, since we've checked for a primary location above.
+        let primary = locations.iter().find(|l| l.symbolic.primary).unwrap();
+
+        let mut hasher = Sha256::new();
+        hasher.update(ident);
+        hasher.update([0]);
+        hasher.update(primary.symbolic.key.sarif_path());
+        hasher.update([0]);
+        for component in &primary.symbolic.route.components {
+            match component {
+                RouteComponent::Key(key) => hasher.update(key.as_bytes()),
+                RouteComponent::Index(idx) => hasher.update(idx.to_string()),
+            }
+            hasher.update([0]);
+        }
+        hasher.update(&primary.symbolic.annotation);
+        hasher.update([0]);
+        hasher.update(primary.concrete.feature);
+
+        hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+
+    /// Resolves a textual fix against its concretized location, returning
+    /// the span to replace and its replacement. Returns `None` if the fix
+    /// doesn't apply.
+    fn resolve_fix(fix: &Fix, location: &Location, source: &str) -> Option<(Range<usize>, String)> {
+        let span = &location.concrete.location.offset_span;
+
+        let (start, mut end) = match &fix.old {
+            Some(old) => {
+                let start = span.start + source.get(span.clone())?.rfind(old.as_str())?;
+                (start, start + old.len())
+            }
+            None => (span.start, span.end),
+        };
+
+        let mut text = fix.new.clone();
+        if let Some(comment) = &fix.comment {
+            if location.concrete.comments.is_empty() {
+                // Extend the replacement to the end of the feature, so that
+                // the comment lands after any trailing text (e.g. a subpath).
+                text.push_str(&source[end..span.end]);
+                text.push_str(&format!(" # {comment}"));
+                end = span.end;
+            }
+        }
+
+        Some((start..end, text))
+    }
+
+    fn span(locations: &[Location]) -> Option<ConcreteLocation> {
+        let (first, rest) = locations.split_first()?;
+
+        if rest.iter().any(|l| l.symbolic.key != first.symbolic.key) {
+            return None;
+        }
+
+        let concrete = || locations.iter().map(|l| &l.concrete.location);
+        let start = concrete().min_by_key(|c| c.offset_span.start)?;
+        let end = concrete().max_by_key(|c| c.offset_span.end)?;
+
+        Some(ConcreteLocation::new(
+            start.start_point,
+            end.end_point,
+            start.offset_span.start..end.offset_span.end,
+        ))
+    }
+
     fn ignored_from_inlined_comment(locations: &[Location], id: &str) -> bool {
         locations
             .iter()
             .flat_map(|l| &l.concrete.comments)
             .any(|c| c.ignores(id))
     }
+
+    fn ignored_from_range(source: &str, locations: &[Location], id: &str) -> bool {
+        let Some(primary) = locations.iter().find(|l| l.symbolic.primary) else {
+            return false;
+        };
+
+        let location = &primary.concrete.location;
+        ignored_ranges(source, id).iter().any(|range| {
+            range.contains(&location.start_point.row) && range.contains(&location.end_point.row)
+        })
+    }
+
+    fn acknowledgment_from_inlined_comment(locations: &[Location], id: &str) -> Option<String> {
+        locations
+            .iter()
+            .flat_map(|l| &l.concrete.comments)
+            .find_map(|c| c.acknowledgment(id))
+            .map(Into::into)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::finding::Comment;
+    use crate::finding::{ignored_ranges, Comment};
 
     #[test]
     fn test_comment_ignores() {
@@ -514,6 +843,12 @@ mod tests {
             ("#zizmor: ignore[foo]", "foo", false),
             ("#  zizmor: ignore[foo]", "foo", false),
             ("#  zizmor:  ignore[foo]", "foo", false),
+            // Wildcards match any rule.
+            ("# zizmor: ignore[*]", "anything", true),
+            ("# zizmor: ignore[all]", "anything", true),
+            // Invalid wildcards: `*` must be the entire rule list.
+            ("# zizmor: ignore[*extra]", "anything", false),
+            ("# zizmor: ignore[*,foo]", "anything", false),
         ];
 
         for (comment, rule, ignores) in cases {
@@ -524,4 +859,92 @@ mod tests {
             )
         }
     }
+
+    #[test]
+    fn test_comment_acknowledgment() {
+        let cases = &[
+            // Trivial cases.
+            ("# zizmor: ack[foo] JIRA-123", "foo", Some("JIRA-123")),
+            ("# zizmor: ack[foo,bar] JIRA-123", "bar", Some("JIRA-123")),
+            // Free-form trailing text is preserved, modulo surrounding whitespace.
+            (
+                "# zizmor: ack[foo]   JIRA-123 reviewed 2024-01  ",
+                "foo",
+                Some("JIRA-123 reviewed 2024-01"),
+            ),
+            // Brackets in the trailing text don't confuse the rule list.
+            (
+                "# zizmor: ack[foo] see [JIRA-123]",
+                "foo",
+                Some("see [JIRA-123]"),
+            ),
+            // No trailing text is OK.
+            ("# zizmor: ack[foo]", "foo", Some("")),
+            ("# zizmor: ack[foo]   ", "foo", Some("")),
+            // Valid ack, but not a match.
+            ("# zizmor: ack[foo] JIRA-123", "baz", None),
+            // Invalid ack: empty rule list.
+            ("# zizmor: ack[] JIRA-123", "foo", None),
+            // Invalid ack: trailing text must be separated by whitespace.
+            ("# zizmor: ack[foo]JIRA-123", "foo", None),
+            // Invalid ack: missing opening and/or closing [].
+            ("# zizmor: ack foo JIRA-123", "foo", None),
+            ("# zizmor: ack[foo JIRA-123", "foo", None),
+            // Invalid ack: space after # and : is mandatory and fixed.
+            ("#zizmor: ack[foo] JIRA-123", "foo", None),
+            ("# zizmor:ack[foo] JIRA-123", "foo", None),
+            // Ignores aren't acks, and vice versa.
+            ("# zizmor: ignore[foo]", "foo", None),
+        ];
+
+        for (comment, rule, ack) in cases {
+            assert_eq!(
+                Comment(comment).acknowledgment(rule),
+                *ack,
+                "{comment} has unexpected acknowledgment for {rule}"
+            );
+        }
+
+        assert!(!Comment("# zizmor: ack[foo] JIRA-123").ignores("foo"));
+    }
+
+    #[test]
+    fn test_ignored_ranges() {
+        let source = r#"a
+# zizmor: ignore-start[foo,bar]
+b
+  # zizmor: ignore-start[foo]
+c
+  # zizmor: ignore-end[foo]
+d
+# zizmor: ignore-end[bar]
+# zizmor: ignore-start[baz]
+e
+"#;
+
+        for (rule, ranges) in [
+            // Nested starts don't reset the range.
+            ("foo", vec![(1, 5)]),
+            ("bar", vec![(1, 7)]),
+            // Unterminated ranges have no effect.
+            ("baz", vec![]),
+            ("quux", vec![]),
+        ] {
+            let ranges = ranges
+                .into_iter()
+                .map(|(start, end)| start..end)
+                .collect::<Vec<_>>();
+            assert_eq!(ignored_ranges(source, rule), ranges, "failed: {rule}");
+        }
+
+        // Wildcards work for ranges just like they do for inline ignores.
+        let source = "a\n# zizmor: ignore-start[*]\nb\n# zizmor: ignore-end[all]\n";
+        assert_eq!(ignored_ranges(source, "foo"), vec![1..3]);
+
+        // Inline ignores aren't ranges, and vice versa.
+        assert!(
+            ignored_ranges("# zizmor: ignore[foo]\n# zizmor: ignore-end[foo]", "foo").is_empty()
+        );
+        assert!(!Comment("# zizmor: ignore-start[foo]").ignores("foo"));
+    }
 }
@@ -0,0 +1,126 @@
+//! Applying fixes for findings to local inputs.
+
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use camino::Utf8Path;
+
+use zizmor::{
+    finding::Replacement,
+    registry::{FindingRegistry, InputKey, InputRegistry},
+};
+
+/// A single pending edit, as a byte range within an input and its replacement.
+struct Edit {
+    start: usize,
+    end: usize,
+    replacement: String,
+}
+
+impl From<&Replacement<'_>> for Edit {
+    fn from(replacement: &Replacement) -> Self {
+        let span = &replacement.location.offset_span;
+
+        Self {
+            start: span.start,
+            end: span.end,
+            replacement: replacement.text.clone(),
+        }
+    }
+}
+
+/// Applies every fix attached to a non-ignored, non-suppressed finding,
+/// rewriting each affected local input in place.
+///
+/// Remote inputs can't be rewritten, so their fixes are skipped.
+pub(crate) fn apply_fixes(registry: &InputRegistry, findings: &FindingRegistry) -> Result<()> {
+    let mut edits_by_input: HashMap<&InputKey, Vec<Edit>> = HashMap::new();
+
+    for finding in findings.findings() {
+        for fix in &finding.fixes {
+            let key = fix.key;
+            if key.local_path().is_none() {
+                tracing::warn!("can't apply fixes to remote input {key}");
+                continue;
+            }
+
+            edits_by_input.entry(key).or_default().push(fix.into());
+        }
+    }
+
+    for (key, mut edits) in edits_by_input {
+        // NOTE: Safe unwrap, since we only collect edits for local keys.
+        let path = key.local_path().unwrap();
+        let source = registry.get_input(key).document().source();
+
+        write_edits(path, source, &mut edits)?;
+    }
+
+    Ok(())
+}
+
+fn write_edits(path: &Utf8Path, source: &str, edits: &mut [Edit]) -> Result<()> {
+    let fixed = apply_edits(source, edits);
+
+    std::fs::write(path, fixed).with_context(|| format!("failed to write fixes to {path}"))?;
+    tracing::info!("applied fixes to {path}");
+
+    Ok(())
+}
+
+/// Applies the given edits to `source`, returning the edited text.
+fn apply_edits(source: &str, edits: &mut [Edit]) -> String {
+    // Apply edits back-to-front, so that earlier offsets remain valid.
+    // Overlapping edits are skipped, since they'd clobber each other.
+    edits.sort_by_key(|edit| std::cmp::Reverse(edit.start));
+
+    let mut fixed = source.to_string();
+    let mut floor = usize::MAX;
+    for edit in edits.iter() {
+        if edit.end > floor {
+            tracing::warn!("skipping overlapping fix");
+            continue;
+        }
+
+        fixed.replace_range(edit.start..edit.end, &edit.replacement);
+        floor = edit.start;
+    }
+
+    fixed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_edits, Edit};
+
+    fn edit(start: usize, end: usize, replacement: &str) -> Edit {
+        Edit {
+            start,
+            end,
+            replacement: replacement.into(),
+        }
+    }
+
+    #[test]
+    fn test_apply_edits() {
+        let source = "uses: a@v1\nuses: b@v2 # v2.0.0\n";
+
+        // No edits is a no-op.
+        assert_eq!(apply_edits(source, &mut []), source);
+
+        // Edits are applied regardless of the order they're given in.
+        assert_eq!(
+            apply_edits(
+                source,
+                &mut [edit(7, 10, "@abc # v1"), edit(18, 21, "@def")]
+            ),
+            "uses: a@abc # v1\nuses: b@def # v2.0.0\n"
+        );
+
+        // Overlapping edits are skipped.
+        assert_eq!(
+            apply_edits(source, &mut [edit(7, 10, "@abc"), edit(6, 9, "x")]),
+            "uses: a@abc\nuses: b@v2 # v2.0.0\n"
+        );
+    }
+}
@@ -3,21 +3,30 @@
 //! Build on synchronous reqwest to avoid octocrab's need to taint
 //! the whole codebase with async.
 
-use std::{io::Read, ops::Deref, path::Path};
+use std::{
+    fs,
+    io::Read,
+    ops::Deref,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context as _, Result};
 use camino::Utf8Path;
 use flate2::read::GzDecoder;
+use futures::{future::BoxFuture, stream, StreamExt as _, TryStreamExt as _};
 use github_actions_models::common::RepositoryUses;
+use http::Extensions;
 use http_cache_reqwest::{
     CACacheManager, Cache, CacheMode, CacheOptions, HttpCache, HttpCacheOptions,
 };
 use owo_colors::OwoColorize;
 use reqwest::{
-    header::{HeaderMap, ACCEPT, AUTHORIZATION, USER_AGENT},
-    StatusCode,
+    header::{HeaderMap, ACCEPT, AUTHORIZATION, LINK, USER_AGENT},
+    Certificate, Request, Response, StatusCode, Url,
 };
-use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
+use reqwest_middleware::{ClientBuilder, ClientWithMiddleware, Next};
 use serde::{de::DeserializeOwned, Deserialize};
 use tar::Archive;
 use tracing::instrument;
@@ -29,15 +38,130 @@ use crate::{
     utils::PipeSelf,
 };
 
+/// The maximum number of pages to request concurrently when paginating.
+const MAX_CONCURRENT_PAGES: usize = 8;
+
+/// The maximum number of files to fetch concurrently.
+const MAX_CONCURRENT_FETCHES: usize = 8;
+
+/// Returns the last page number from a GitHub `Link` header, if present.
+///
+/// See: <https://docs.github.com/en/rest/using-the-rest-api/using-pagination-in-the-rest-api>
+fn last_page(link: &str) -> Option<u32> {
+    link.split(',').find_map(|link| {
+        let (url, params) = link.split_once(';')?;
+        if !params
+            .split(';')
+            .any(|param| param.trim() == r#"rel="last""#)
+        {
+            return None;
+        }
+
+        let url = Url::parse(url.trim().strip_prefix('<')?.strip_suffix('>')?).ok()?;
+        url.query_pairs()
+            .find(|(key, _)| key == "page")
+            .and_then(|(_, page)| page.parse().ok())
+    })
+}
+
+/// The GitHub API's rate limit status, as reported by the `X-RateLimit-*`
+/// headers of an API response.
+///
+/// See: <https://docs.github.com/en/rest/using-the-rest-api/rate-limits-for-the-rest-api>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RateLimit {
+    /// The number of requests remaining in the current rate limit window.
+    pub remaining: u64,
+    /// When the current rate limit window resets, in seconds since the
+    /// Unix epoch.
+    pub reset: u64,
+}
+
+impl RateLimit {
+    fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let header = |name: &str| headers.get(name)?.to_str().ok()?.parse().ok();
+
+        Some(Self {
+            remaining: header("x-ratelimit-remaining")?,
+            reset: header("x-ratelimit-reset")?,
+        })
+    }
+}
+
+/// The most recent [`RateLimit`] reported by the GitHub API, shared
+/// between every [`Client`] that records into it.
+pub type SharedRateLimit = Arc<Mutex<Option<RateLimit>>>;
+
+/// Returns a middleware that records the rate limit status of each
+/// API response into `rate_limit`.
+fn record_rate_limit(
+    rate_limit: SharedRateLimit,
+) -> impl for<'a> Fn(
+    Request,
+    &'a mut Extensions,
+    Next<'a>,
+) -> BoxFuture<'a, reqwest_middleware::Result<Response>>
+       + Send
+       + Sync
+       + 'static {
+    move |req, extensions, next| {
+        let rate_limit = rate_limit.clone();
+        Box::pin(async move {
+            let resp = next.run(req, extensions).await?;
+            if let Some(status) = RateLimit::from_headers(resp.headers()) {
+                *rate_limit.lock().unwrap() = Some(status);
+            }
+            Ok(resp)
+        })
+    }
+}
+
+/// Additional root certificates to trust, e.g. for a corporate proxy
+/// that intercepts TLS with an internal CA.
+#[derive(Clone, Debug)]
+pub struct CaBundle(Vec<Certificate>);
+
+impl CaBundle {
+    /// Loads a bundle of one or more PEM-encoded certificates from the
+    /// given path.
+    pub fn load(path: &Utf8Path) -> Result<Self> {
+        let contents =
+            fs::read(path).with_context(|| format!("couldn't read CA bundle: {path}"))?;
+
+        Self::parse(&contents).with_context(|| format!("invalid CA bundle: {path}"))
+    }
+
+    fn parse(contents: &[u8]) -> Result<Self> {
+        let certs = Certificate::from_pem_bundle(contents)?;
+        if certs.is_empty() {
+            return Err(anyhow!("no PEM-encoded certificates found"));
+        }
+
+        // NOTE: PEM parsing doesn't validate the certificates themselves,
+        // which otherwise only happens when a client is built. Building
+        // one here means that a bad certificate is reported up front,
+        // rather than when the first online audit runs.
+        certs
+            .iter()
+            .cloned()
+            .fold(reqwest::Client::builder(), |builder, cert| {
+                builder.add_root_certificate(cert)
+            })
+            .build()?;
+
+        Ok(Self(certs))
+    }
+}
+
 /// Represents different types of GitHub hosts.
 #[derive(Clone, Debug, PartialEq)]
-pub(crate) enum GitHubHost {
+pub enum GitHubHost {
     Enterprise(String),
     Standard(String),
 }
 
 impl GitHubHost {
-    pub(crate) fn from_clap(hostname: &str) -> Result<Self, String> {
+    pub fn from_clap(hostname: &str) -> Result<Self, String> {
         let normalized = hostname.to_lowercase();
 
         // NOTE: ideally we'd do a full domain validity check here.
@@ -62,13 +186,20 @@ impl GitHubHost {
     }
 }
 
-pub(crate) struct Client {
+pub struct Client {
     api_base: String,
     http: ClientWithMiddleware,
 }
 
 impl Client {
-    pub(crate) fn new(hostname: &GitHubHost, token: &str, cache_dir: &Path) -> Self {
+    pub(crate) fn new(
+        hostname: &GitHubHost,
+        token: &str,
+        cache_dir: &Path,
+        rate_limit: SharedRateLimit,
+        timeout: Duration,
+        ca_bundle: Option<&CaBundle>,
+    ) -> Self {
         let mut headers = HeaderMap::new();
         headers.insert(USER_AGENT, "zizmor".parse().unwrap());
         headers.insert(
@@ -80,30 +211,45 @@ impl Client {
         headers.insert("X-GitHub-Api-Version", "2022-11-28".parse().unwrap());
         headers.insert(ACCEPT, "application/vnd.github+json".parse().unwrap());
 
-        let http = ClientBuilder::new(
-            reqwest::Client::builder()
-                .default_headers(headers)
-                .build()
-                .expect("couldn't build GitHub client?"),
-        )
-        .with(Cache(HttpCache {
-            mode: CacheMode::Default,
-            manager: CACacheManager {
-                path: cache_dir.into(),
-            },
-            options: HttpCacheOptions {
-                cache_options: Some(CacheOptions {
-                    // GitHub API requests made with an API token seem to
-                    // always have `Cache-Control: private`, so we need to
-                    // explicitly tell http-cache that our cache is not shared
-                    // in order for things to cache correctly.
-                    shared: false,
+        // NOTE: reqwest honors the standard proxy environment variables
+        // (`HTTPS_PROXY`, `ALL_PROXY`, `NO_PROXY`, etc.) by default, so
+        // there's nothing to configure for them here.
+        let builder = reqwest::Client::builder()
+            .default_headers(headers)
+            // NOTE: These bound each connection attempt and each
+            // individual read, rather than the entire request, so that
+            // large downloads (like repository tarballs) don't time out
+            // as long as they're still making progress.
+            .connect_timeout(timeout)
+            .read_timeout(timeout);
+
+        let builder = ca_bundle
+            .into_iter()
+            .flat_map(|bundle| bundle.0.iter().cloned())
+            .fold(builder, |builder, cert| builder.add_root_certificate(cert));
+
+        let http = ClientBuilder::new(builder.build().expect("couldn't build GitHub client?"))
+            .with(Cache(HttpCache {
+                mode: CacheMode::Default,
+                manager: CACacheManager {
+                    path: cache_dir.into(),
+                },
+                options: HttpCacheOptions {
+                    cache_options: Some(CacheOptions {
+                        // GitHub API requests made with an API token seem to
+                        // always have `Cache-Control: private`, so we need to
+                        // explicitly tell http-cache that our cache is not shared
+                        // in order for things to cache correctly.
+                        shared: false,
+                        ..Default::default()
+                    }),
                     ..Default::default()
-                }),
-                ..Default::default()
-            },
-        }))
-        .build();
+                },
+            }))
+            // NOTE: This comes after the cache, so that it only sees responses
+            // that actually came from the API.
+            .with(record_rate_limit(rate_limit))
+            .build();
 
         Self {
             api_base: hostname.to_api_url(),
@@ -111,34 +257,68 @@ impl Client {
         }
     }
 
+    async fn page(&self, url: &str, pageno: u32) -> reqwest_middleware::Result<reqwest::Response> {
+        Ok(self
+            .http
+            .get(url)
+            .query(&[("page", pageno), ("per_page", 100)])
+            .send()
+            .await?
+            .error_for_status()?)
+    }
+
     async fn paginate<T: DeserializeOwned>(
         &self,
         endpoint: &str,
     ) -> reqwest_middleware::Result<Vec<T>> {
-        let mut dest = vec![];
         let url = format!("{api_base}/{endpoint}", api_base = self.api_base);
 
-        // If we were nice, we would parse GitHub's `links` header and extract
-        // the remaining number of pages. But this is annoying, and we are
-        // not nice, so we simply request pages until GitHub bails on us
-        // and returns empty results.
-        let mut pageno = 0;
-        loop {
-            let resp = self
-                .http
-                .get(&url)
-                .query(&[("page", pageno), ("per_page", 100)])
-                .send()
-                .await?
-                .error_for_status()?;
+        // GitHub's pages are 1-indexed.
+        let first = self.page(&url, 1).await?;
+        let last_page = first
+            .headers()
+            .get(LINK)
+            .and_then(|link| link.to_str().ok())
+            .and_then(last_page);
+
+        let mut dest = first.json::<Vec<T>>().await?;
+        if dest.is_empty() {
+            return Ok(dest);
+        }
 
-            let page = resp.json::<Vec<T>>().await?;
-            if page.is_empty() {
-                break;
+        match last_page {
+            // We know how many pages there are, so we can request the
+            // remaining pages concurrently. `buffered` preserves the
+            // order of the pages in the results.
+            Some(last_page) => {
+                let pages = stream::iter(2..=last_page)
+                    .map(|pageno| {
+                        let url = &url;
+                        async move {
+                            let resp = self.page(url, pageno).await?;
+                            Ok::<_, reqwest_middleware::Error>(resp.json::<Vec<T>>().await?)
+                        }
+                    })
+                    .buffered(MAX_CONCURRENT_PAGES)
+                    .try_collect::<Vec<_>>()
+                    .await?;
+
+                dest.extend(pages.into_iter().flatten());
+            }
+            // Without a `Link` header, we simply request pages until
+            // GitHub bails on us and returns empty results.
+            None => {
+                let mut pageno = 2;
+                loop {
+                    let page = self.page(&url, pageno).await?.json::<Vec<T>>().await?;
+                    if page.is_empty() {
+                        break;
+                    }
+
+                    dest.extend(page);
+                    pageno += 1;
+                }
             }
-
-            dest.extend(page);
-            pageno += 1;
         }
 
         Ok(dest)
@@ -235,6 +415,54 @@ impl Client {
         }
     }
 
+    /// Fetches the file at `path` as of each of the given refs, returning
+    /// `None` for each ref that doesn't have the file.
+    ///
+    /// The results are in the same order as `git_refs`.
+    #[instrument(skip(self))]
+    #[tokio::main]
+    pub(crate) async fn fetch_file_at_refs(
+        &self,
+        owner: &str,
+        repo: &str,
+        path: &str,
+        git_refs: &[&str],
+    ) -> Result<Vec<Option<String>>> {
+        stream::iter(git_refs)
+            .map(|git_ref| self.fetch_file(owner, repo, path, git_ref))
+            .buffered(MAX_CONCURRENT_FETCHES)
+            .try_collect()
+            .await
+    }
+
+    async fn fetch_file(
+        &self,
+        owner: &str,
+        repo: &str,
+        path: &str,
+        git_ref: &str,
+    ) -> Result<Option<String>> {
+        let url = format!(
+            "{api_base}/repos/{owner}/{repo}/contents/{path}",
+            api_base = self.api_base
+        );
+
+        let resp = self
+            .http
+            .get(url)
+            .header(ACCEPT, "application/vnd.github.raw+json")
+            .query(&[("ref", git_ref)])
+            .send()
+            .await?;
+        match resp.status() {
+            StatusCode::OK => Ok(Some(resp.text().await?)),
+            StatusCode::NOT_FOUND => Ok(None),
+            s => Err(anyhow!(
+                "{owner}/{repo}: error from GitHub API while fetching {path} at {git_ref}: {s}"
+            )),
+        }
+    }
+
     #[instrument(skip(self))]
     pub(crate) fn longest_tag_for_commit(
         &self,
@@ -316,7 +544,7 @@ impl Client {
     /// collection case.
     #[instrument(skip(self))]
     #[tokio::main]
-    pub(crate) async fn fetch_workflows(&self, slug: &RepositoryUses) -> Result<Vec<Workflow>> {
+    pub async fn fetch_workflows(&self, slug: &RepositoryUses) -> Result<Vec<Workflow>> {
         let owner = &slug.owner;
         let repo = &slug.repo;
         let git_ref = &slug.git_ref;
@@ -382,10 +610,7 @@ impl Client {
     /// retrieving the entire repository archive and decompressing it.
     #[instrument(skip(self))]
     #[tokio::main]
-    pub(crate) async fn fetch_audit_inputs(
-        &self,
-        slug: &RepositoryUses,
-    ) -> Result<Vec<AuditInput>> {
+    pub async fn fetch_audit_inputs(&self, slug: &RepositoryUses) -> Result<Vec<AuditInput>> {
         let mut inputs = vec![];
 
         let url = format!(
@@ -495,7 +720,7 @@ pub(crate) struct Comparison {
 }
 
 /// Represents a GHSA advisory.
-#[derive(Deserialize)]
+#[derive(Clone, Deserialize)]
 pub(crate) struct Advisory {
     pub(crate) ghsa_id: String,
     pub(crate) severity: String,
@@ -510,7 +735,9 @@ pub(crate) struct File {
 
 #[cfg(test)]
 mod tests {
-    use crate::github_api::GitHubHost;
+    use reqwest::header::HeaderMap;
+
+    use crate::github_api::{last_page, GitHubHost, RateLimit};
 
     #[test]
     fn test_github_host() {
@@ -525,4 +752,50 @@ mod tests {
             assert_eq!(GitHubHost::from_clap(host).unwrap().to_api_url(), expected);
         }
     }
+
+    #[test]
+    fn test_last_page() {
+        for (link, expected) in [
+            ("", None),
+            (
+                r#"<https://api.github.com/repositories/1/tags?page=2&per_page=100>; rel="next""#,
+                None,
+            ),
+            (
+                r#"<https://api.github.com/repositories/1/tags?page=2&per_page=100>; rel="next", <https://api.github.com/repositories/1/tags?page=7&per_page=100>; rel="last""#,
+                Some(7),
+            ),
+            (
+                r#"<https://api.github.com/repositories/1/tags?per_page=100&page=3>; rel="last""#,
+                Some(3),
+            ),
+            (
+                r#"<https://api.github.com/repositories/1/tags?per_page=100>; rel="last""#,
+                None,
+            ),
+        ] {
+            assert_eq!(last_page(link), expected, "failed: {link}");
+        }
+    }
+
+    #[test]
+    fn test_rate_limit_from_headers() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(RateLimit::from_headers(&headers), None);
+
+        headers.insert("x-ratelimit-remaining", "4321".parse().unwrap());
+        assert_eq!(RateLimit::from_headers(&headers), None);
+
+        headers.insert("x-ratelimit-reset", "1700000000".parse().unwrap());
+        assert_eq!(
+            RateLimit::from_headers(&headers),
+            Some(RateLimit {
+                remaining: 4321,
+                reset: 1700000000
+            })
+        );
+
+        headers.insert("x-ratelimit-remaining", "lots".parse().unwrap());
+        assert_eq!(RateLimit::from_headers(&headers), None);
+    }
 }
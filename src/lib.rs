@@ -0,0 +1,210 @@
+//! zizmor's library API.
+//!
+//! This crate powers the `zizmor` CLI, and can also be used to embed
+//! `zizmor`'s audits in other tools. The main entrypoint is [`Zizmor`],
+//! which runs every available audit against a set of [`AuditInput`]s:
+//!
+//! ```no_run
+//! use zizmor::{models::Workflow, AuditInput, Persona, Zizmor};
+//!
+//! let workflow = Workflow::from_file("ci.yml", None)?;
+//! let inputs = [AuditInput::from(workflow)];
+//!
+//! let zizmor = Zizmor::new().persona(Persona::Pedantic);
+//! for finding in zizmor.audit(&inputs)? {
+//!     println!("{ident}: {desc}", ident = finding.ident, desc = finding.desc);
+//! }
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+use std::{collections::HashSet, time::Instant};
+
+use anyhow::{Context as _, Result};
+use indicatif::ProgressStyle;
+use tracing::{info_span, Span};
+use tracing_indicatif::span_ext::IndicatifSpanExt as _;
+
+use crate::registry::LocalWorkflows;
+
+pub mod advisory_db;
+pub mod audit;
+pub mod config;
+mod expr;
+pub mod finding;
+pub mod github_api;
+pub mod models;
+pub mod registry;
+pub mod state;
+mod utils;
+
+pub use audit::AuditInput;
+pub use config::Config;
+pub use finding::{Confidence, Finding, Persona, Severity};
+pub use registry::{AuditRegistry, FindingRegistry, InputRegistry};
+pub use state::AuditState;
+
+/// A builder for running `zizmor`'s audits.
+#[derive(Clone, Default)]
+pub struct Zizmor {
+    state: AuditState,
+    min_severity: Option<Severity>,
+    min_confidence: Option<Confidence>,
+    fail_on: Option<Severity>,
+    correlate: bool,
+    ignored_fingerprints: HashSet<String>,
+}
+
+impl Zizmor {
+    /// Creates a new [`Zizmor`] with the default audit state and
+    /// configuration, the default persona, and no result filtering.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the [`AuditState`] that audits are constructed with.
+    ///
+    /// This replaces any [`Config`] or [`Persona`] previously set with
+    /// [`Zizmor::config`] or [`Zizmor::persona`].
+    pub fn state(mut self, state: AuditState) -> Self {
+        self.state = state;
+        self
+    }
+
+    /// Sets the [`Config`] used to configure audits and ignore findings.
+    pub fn config(mut self, config: Config) -> Self {
+        self.state.config = config;
+        self
+    }
+
+    /// Sets the persona to audit with.
+    pub fn persona(mut self, persona: Persona) -> Self {
+        self.state.persona = persona;
+        self
+    }
+
+    /// Filters all findings below the given severity.
+    pub fn min_severity(mut self, severity: Severity) -> Self {
+        self.min_severity = Some(severity);
+        self
+    }
+
+    /// Filters all findings below the given confidence.
+    pub fn min_confidence(mut self, confidence: Confidence) -> Self {
+        self.min_confidence = Some(confidence);
+        self
+    }
+
+    /// Only fails (i.e. produces a non-success exit code) on findings at
+    /// or above the given severity.
+    ///
+    /// Unlike [`Zizmor::min_severity`], this doesn't filter any findings.
+    pub fn fail_on(mut self, severity: Severity) -> Self {
+        self.fail_on = Some(severity);
+        self
+    }
+
+    /// Raises the confidence of findings that are corroborated by a
+    /// finding from a different audit, i.e. one whose primary location
+    /// overlaps theirs.
+    pub fn correlate(mut self) -> Self {
+        self.correlate = true;
+        self
+    }
+
+    /// Ignores the finding with the given fingerprint.
+    ///
+    /// See [`Finding::fingerprint`].
+    pub fn ignore_finding(mut self, fingerprint: impl Into<String>) -> Self {
+        self.ignored_fingerprints.insert(fingerprint.into());
+        self
+    }
+
+    /// Audits the given inputs, returning all findings that aren't
+    /// ignored or suppressed.
+    pub fn audit<'a>(&'a self, inputs: &'a [AuditInput]) -> Result<Vec<Finding<'a>>> {
+        Ok(self.audit_all(inputs.iter(), None)?.into_findings())
+    }
+
+    /// Audits each input in the given [`InputRegistry`], returning a
+    /// [`FindingRegistry`] of all findings, including ignored and
+    /// suppressed ones.
+    pub fn audit_registry<'a>(
+        &'a self,
+        registry: &'a InputRegistry,
+    ) -> Result<FindingRegistry<'a>> {
+        self.audit_all(registry.iter_inputs().map(|(_, input)| input), None)
+    }
+
+    /// Like [`Zizmor::audit_registry`], but passes each input's reported
+    /// findings to `sink` as soon as that input has been audited, rather
+    /// than holding them until every input has been audited.
+    ///
+    /// The returned [`FindingRegistry`] doesn't contain the reported
+    /// findings, but still counts them.
+    pub fn audit_registry_streaming<'a>(
+        &'a self,
+        registry: &'a InputRegistry,
+        mut sink: impl FnMut(Vec<Finding<'a>>) -> Result<()>,
+    ) -> Result<FindingRegistry<'a>> {
+        self.audit_all(
+            registry.iter_inputs().map(|(_, input)| input),
+            Some(&mut sink),
+        )
+    }
+
+    fn audit_all<'a>(
+        &'a self,
+        inputs: impl ExactSizeIterator<Item = &'a AuditInput> + Clone,
+        mut sink: Option<&mut dyn FnMut(Vec<Finding<'a>>) -> Result<()>>,
+    ) -> Result<FindingRegistry<'a>> {
+        let audit_registry = AuditRegistry::default_audits(&self.state);
+        let workflows = LocalWorkflows::new(inputs.clone());
+
+        let mut results = FindingRegistry::new(
+            &self.state.config,
+            self.state.persona,
+            self.min_severity,
+            self.min_confidence,
+            self.fail_on,
+            self.correlate,
+            &self.ignored_fingerprints,
+            audit_registry.skipped().to_vec(),
+        );
+
+        // Note: the span is dropped at the end of this function, which
+        // completes the progress bar.
+        let span = info_span!("audit");
+        span.pb_set_length((inputs.len() * audit_registry.len()) as u64);
+        span.pb_set_style(
+            &ProgressStyle::with_template("[{elapsed_precise}] {bar:!30.cyan/blue} {msg}").unwrap(),
+        );
+
+        let _guard = span.enter();
+        let start = Instant::now();
+        let ninputs = inputs.len();
+
+        for input in inputs {
+            Span::current().pb_set_message(input.key().filename());
+            let mut findings = vec![];
+            for (name, audit) in audit_registry.iter_audits() {
+                findings.extend(audit.audit(input, &workflows).with_context(|| {
+                    format!("{name} failed on {input}", input = input.key().filename())
+                })?);
+                Span::current().pb_inc(1);
+            }
+            results.extend(findings);
+            if let Some(sink) = sink.as_mut() {
+                sink(results.drain_findings())?;
+            }
+            tracing::info!(
+                "🌈 completed {input}",
+                input = input.key().best_effort_relative_path()
+            );
+        }
+
+        results.sort();
+        results.record_run(ninputs, audit_registry.len(), start.elapsed());
+
+        Ok(results)
+    }
+}
@@ -1,36 +1,50 @@
-use std::{io::stdout, process::ExitCode, str::FromStr};
+use std::{
+    io::{stdout, Read as _},
+    process::ExitCode,
+    str::FromStr,
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
 use annotate_snippets::{Level, Renderer};
-use anstream::{eprintln, stream::IsTerminal};
+use anstream::{eprintln, println};
 use anyhow::{anyhow, Context, Result};
-use audit::Audit;
 use camino::{Utf8Path, Utf8PathBuf};
 use clap::{Parser, ValueEnum};
 use clap_verbosity_flag::InfoLevel;
-use config::Config;
-use finding::{Confidence, Persona, Severity};
 use github_actions_models::common::Uses;
-use github_api::GitHubHost;
-use indicatif::ProgressStyle;
-use models::Action;
+use indexmap::IndexMap;
 use owo_colors::OwoColorize;
-use registry::{AuditRegistry, FindingRegistry, InputRegistry};
-use state::AuditState;
-use tracing::{info_span, instrument, Span};
-use tracing_indicatif::{span_ext::IndicatifSpanExt, IndicatifLayer};
-use tracing_subscriber::{layer::SubscriberExt as _, util::SubscriberInitExt as _, EnvFilter};
-
-mod audit;
-mod config;
-mod expr;
-mod finding;
-mod github_api;
-mod models;
-mod registry;
+use submodules::Submodules;
+use tracing::{instrument, level_filters::LevelFilter};
+use tracing_indicatif::IndicatifLayer;
+use tracing_subscriber::{
+    fmt::writer::BoxMakeWriter, layer::SubscriberExt as _, util::SubscriberInitExt as _, EnvFilter,
+};
+use zizmor::{
+    advisory_db::AdvisoryDb,
+    github_api::{CaBundle, GitHubHost, RateLimit},
+    models::Action,
+    registry::InputKey,
+    AuditInput, AuditRegistry, AuditState, Confidence, Config, FindingRegistry, InputRegistry,
+    Persona, Severity, Zizmor,
+};
+use zizmorignore::ZizmorIgnore;
+
+/// The exit code bit that's set when one or more inputs couldn't be
+/// collected, e.g. because they failed to parse.
+const ERRORED_INPUTS_EXIT_BIT: u8 = 0b10_0000;
+
+/// The filename that stdin inputs are reported as, unless overridden
+/// with `--input-filename`.
+const STDIN_NAME: &str = "<stdin>";
+
+mod fix;
+mod output;
 mod render;
 mod sarif;
-mod state;
-mod utils;
+mod submodules;
+mod zizmorignore;
 
 /// Finds security issues in GitHub Actions setups.
 #[derive(Parser)]
@@ -77,6 +91,47 @@ struct App {
     #[arg(long, value_enum, default_value_t)]
     format: OutputFormat,
 
+    /// When to colorize output, including logging and progress bars.
+    ///
+    /// By default, output is colorized when it's sent to a terminal and
+    /// NO_COLOR isn't set.
+    #[arg(long, value_enum, value_name = "WHEN", default_value_t)]
+    color: ColorMode,
+
+    /// Disable progress bars, even when stderr is a terminal.
+    #[arg(long)]
+    no_progress: bool,
+
+    /// Show this many lines of source context around each finding.
+    ///
+    /// By default, only the annotated lines are shown. This only affects
+    /// the plain output format.
+    #[arg(long, value_name = "N")]
+    context: Option<usize>,
+
+    /// Only emit the counts of findings by severity, rather than each
+    /// finding.
+    ///
+    /// With the JSON output format, this emits only the `summary` object.
+    /// This only affects the plain and JSON output formats.
+    #[arg(long)]
+    severity_counts_only: bool,
+
+    /// The category to tag SARIF results with.
+    ///
+    /// GitHub's code scanning uses this to keep separate uploads to the
+    /// same repository apart. This only affects the SARIF output format.
+    #[arg(long, value_name = "CATEGORY", default_value = "zizmor")]
+    sarif_category: String,
+
+    /// The repository root that SARIF and GitHub annotation paths are
+    /// relative to.
+    ///
+    /// By default, this is the nearest directory above each input that
+    /// contains a `.git` directory.
+    #[arg(long, value_name = "PATH")]
+    repo_root: Option<Utf8PathBuf>,
+
     /// The configuration file to load. By default, any config will be
     /// discovered relative to $CWD.
     #[arg(short, long, group = "conf")]
@@ -86,7 +141,16 @@ struct App {
     #[arg(long, group = "conf")]
     no_config: bool,
 
-    /// Disable all error codes besides success and tool failure.
+    /// Load the configuration from the ZIZMOR_CONFIG environment variable,
+    /// instead of from a file.
+    ///
+    /// The variable can contain the configuration as YAML (or JSON), or
+    /// base64-encoded. Like --config, this disables config discovery.
+    #[arg(long, group = "conf")]
+    config_from_env: bool,
+
+    /// Disable all error codes besides success and tool failure, except
+    /// for the errored-inputs bit (see --explain-exit-codes).
     #[arg(long)]
     no_exit_codes: bool,
 
@@ -98,17 +162,101 @@ struct App {
     #[arg(long)]
     min_confidence: Option<Confidence>,
 
+    /// Only fail on results at or above this severity.
+    ///
+    /// Unlike --min-severity, this doesn't filter any results: results
+    /// below this severity are still reported, but don't produce a
+    /// non-zero exit code.
+    #[arg(long, value_name = "SEVERITY")]
+    fail_on: Option<Severity>,
+
+    /// Raise the confidence of findings that are corroborated by
+    /// another audit.
+    ///
+    /// A finding is corroborated when its primary location overlaps
+    /// the primary location of a finding from a different audit.
+    #[arg(long)]
+    correlate: bool,
+
+    /// Ignore the finding with this fingerprint. May be repeated.
+    ///
+    /// Fingerprints are included in JSON and SARIF output, and stay the
+    /// same as long as the finding's location doesn't change.
+    #[arg(long, value_name = "FINGERPRINT")]
+    ignore_finding: Vec<String>,
+
+    /// A local advisory database to use when online audits are disabled.
+    ///
+    /// This allows `known-vulnerable-actions` to run without network
+    /// access, e.g. with --offline.
+    #[arg(long, value_name = "PATH", env = "ZIZMOR_ADVISORY_DB")]
+    advisory_db: Option<Utf8PathBuf>,
+
     /// The directory to use for HTTP caching. By default, a
     /// host-appropriate user-caching directory will be used.
     #[arg(long)]
     cache_dir: Option<Utf8PathBuf>,
 
+    /// The timeout for each GitHub API request, in seconds.
+    ///
+    /// This bounds connecting to the API and each read from it, rather
+    /// than the total duration of a request.
+    #[arg(long, value_name = "SECS", default_value_t = AuditState::DEFAULT_REQUEST_TIMEOUT.as_secs())]
+    request_timeout: u64,
+
+    /// A bundle of PEM-encoded root certificates to trust for GitHub API
+    /// requests, in addition to the default roots.
+    ///
+    /// This is useful behind proxies that intercept TLS with an internal
+    /// certificate authority.
+    #[arg(long, value_name = "PATH")]
+    ca_bundle: Option<Utf8PathBuf>,
+
+    /// Abort the entire run if it takes longer than this many seconds.
+    ///
+    /// By default, there's no limit on how long a run can take.
+    #[arg(long, value_name = "SECS")]
+    timeout: Option<u64>,
+
+    /// Apply fixes for supported findings, rewriting inputs in place.
+    ///
+    /// Only `unpinned-uses` findings are currently fixed, by pinning
+    /// them to the commit that their ref currently resolves to.
+    /// This requires online mode.
+    #[arg(long, conflicts_with_all = ["offline", "no_online_audits"])]
+    fix: bool,
+
     /// Control which kinds of inputs are collected for auditing.
     ///
     /// By default, all workflows and composite actions are collected.
     #[arg(long, value_enum, default_value_t)]
     collect: CollectionMode,
 
+    /// Collect inputs from git submodules within directory inputs.
+    ///
+    /// By default, submodules (as listed in `.gitmodules`) are treated
+    /// as external repositories, and are skipped.
+    #[arg(long)]
+    include_submodules: bool,
+
+    /// Fail immediately if any input can't be parsed.
+    ///
+    /// By default, inputs that fail to parse are reported separately,
+    /// and the remaining inputs are still audited.
+    #[arg(long)]
+    strict_collection: bool,
+
+    /// Explain the given audit, and then exit.
+    ///
+    /// The explanation includes the audit's rationale, an example of what
+    /// it flags, and how to remediate its findings.
+    #[arg(long, value_name = "IDENT", conflicts_with = "inputs")]
+    explain: Option<String>,
+
+    /// Explain zizmor's exit codes, and then exit.
+    #[arg(long, conflicts_with_all = ["inputs", "explain"])]
+    explain_exit_codes: bool,
+
     /// Enable naches mode.
     #[arg(long, hide = true, env = "ZIZMOR_NACHES")]
     naches: bool,
@@ -116,11 +264,30 @@ struct App {
     /// The inputs to audit.
     ///
     /// These can be individual workflow filenames, action definitions
-    /// (typically `action.yml`), entire directories, or a `user/repo` slug
-    /// for a GitHub repository. In the latter case, a `@ref` can be appended
-    /// to audit the repository at a particular git reference state.
-    #[arg(required = true)]
+    /// (typically `action.yml`), entire directories, glob patterns matching
+    /// workflow or action files, or a `user/repo` slug for a GitHub
+    /// repository. In the latter case, a `@ref` can be appended to audit the
+    /// repository at a particular git reference state.
+    ///
+    /// `-` reads a single workflow or action definition from stdin.
+    #[arg(required_unless_present_any = ["explain", "explain_exit_codes", "inputs_from"])]
     inputs: Vec<String>,
+
+    /// Read additional input files from this file, one path per line.
+    ///
+    /// Each path is registered exactly as given, without any directory
+    /// walking. This is useful for auditing only the files that a change
+    /// touches. Paths that no longer exist (e.g. deleted files) are skipped.
+    #[arg(long, value_name = "FILE", conflicts_with = "input_filename")]
+    inputs_from: Option<Utf8PathBuf>,
+
+    /// The filename to report for the input, instead of its actual path.
+    ///
+    /// This is useful when auditing stdin or a copy of a file, so that
+    /// diagnostics point at the original file. Only valid with a single
+    /// file or stdin input.
+    #[arg(long, value_name = "NAME")]
+    input_filename: Option<Utf8PathBuf>,
 }
 
 #[derive(Debug, Default, Copy, Clone, ValueEnum)]
@@ -128,7 +295,45 @@ pub(crate) enum OutputFormat {
     #[default]
     Plain,
     Json,
+    JsonLegacy,
+    Ndjson,
     Sarif,
+    Csv,
+    Github,
+}
+
+/// When `zizmor` colorizes its output.
+#[derive(Copy, Clone, Debug, Default, ValueEnum)]
+pub(crate) enum ColorMode {
+    /// Colorize output sent to a terminal, unless NO_COLOR is set.
+    #[default]
+    Auto,
+    /// Always colorize output.
+    Always,
+    /// Never colorize output.
+    Never,
+}
+
+impl ColorMode {
+    /// Applies this mode to every output stream, and returns whether
+    /// stderr should be colorized.
+    fn apply(&self) -> bool {
+        match self {
+            ColorMode::Auto => {}
+            ColorMode::Always => anstream::ColorChoice::Always.write_global(),
+            ColorMode::Never => anstream::ColorChoice::Never.write_global(),
+        }
+
+        let stderr =
+            anstream::AutoStream::choice(&std::io::stderr()) != anstream::ColorChoice::Never;
+
+        // Progress bars are drawn with `console`'s styles rather than through
+        // `anstream`, so they're colorized separately. Uncolorized progress
+        // bars are still drawn, just without any ANSI styling.
+        console::set_colors_enabled(stderr);
+
+        stderr
+    }
 }
 
 /// How `zizmor` collects inputs from local and remote repository sources.
@@ -151,6 +356,14 @@ impl CollectionMode {
     pub(crate) fn actions(&self) -> bool {
         matches!(self, CollectionMode::All | CollectionMode::ActionsOnly)
     }
+
+    /// Returns whether the given input is collected in this mode.
+    pub(crate) fn includes(&self, input: &AuditInput) -> bool {
+        match input {
+            AuditInput::Workflow(_) => self.workflows(),
+            AuditInput::Action(_) => self.actions(),
+        }
+    }
 }
 
 fn tip(err: impl AsRef<str>, tip: impl AsRef<str>) -> String {
@@ -162,11 +375,261 @@ fn tip(err: impl AsRef<str>, tip: impl AsRef<str>) -> String {
     format!("{}", renderer.render(message))
 }
 
-#[instrument(skip(mode, registry))]
+/// Prints the explanation for the audit with the given ident.
+fn explain(ident: &str) -> Result<()> {
+    let explanations = AuditRegistry::explanations();
+    let Some(explanation) = explanations.iter().find(|e| e.ident == ident) else {
+        return Err(anyhow!(tip(
+            format!("unknown audit: {ident}", ident = ident.green()),
+            format!(
+                "pass one of: {idents}",
+                idents = explanations
+                    .iter()
+                    .map(|e| e.ident)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        )));
+    };
+
+    println!(
+        "{ident}: {desc}",
+        ident = explanation.ident.bold(),
+        desc = explanation.desc
+    );
+    println!("{url}", url = explanation.url.bright_blue());
+    println!();
+    println!("{rationale}", rationale = explanation.rationale());
+    println!();
+    println!("{}", "Example:".bold());
+    println!();
+    for line in explanation.example().lines() {
+        println!("    {line}");
+    }
+    println!();
+    println!("{}", "Remediation:".bold());
+    println!();
+    println!("{remediation}", remediation = explanation.remediation());
+
+    Ok(())
+}
+
+/// Prints the exit codes that `zizmor` uses to summarize a run.
+fn explain_exit_codes() {
+    println!(
+        "{code:>4}  successful audit; no findings to report",
+        code = 0
+    );
+    println!("{code:>4}  error during audit; consult output", code = 1);
+    for severity in Severity::value_variants() {
+        println!(
+            "{code:>4}  one or more findings; highest finding is {severity}",
+            code = severity.exit_code(),
+            severity = severity.to_possible_value().unwrap().get_name().bold(),
+        );
+    }
+    println!(
+        "{code:>4}  added to the above when one or more inputs can't be collected",
+        code = ERRORED_INPUTS_EXIT_BIT
+    );
+    println!();
+    println!(
+        "Codes {min} through {max} only count findings at or above --fail-on, if given.",
+        min = Severity::Unknown.exit_code(),
+        max = Severity::High.exit_code(),
+    );
+    println!("They're never produced with --no-exit-codes or --format=sarif.");
+    println!(
+        "Code {bit} is added regardless of either.",
+        bit = ERRORED_INPUTS_EXIT_BIT
+    );
+}
+
+/// Prints each input that couldn't be collected, along with the reason
+/// it failed, to `stderr`.
+fn report_errored_inputs(registry: &InputRegistry) {
+    let errored = registry.errored_inputs();
+
+    eprintln!(
+        "{title} ({nerrored})",
+        title = "errored inputs".red().bold(),
+        nerrored = errored.len(),
+    );
+    for err in errored.values() {
+        eprintln!();
+        eprintln!("{err:?}");
+    }
+}
+
+/// Returns a single note summarizing the audits that were skipped,
+/// grouped by the reason they were skipped.
+fn skipped_audits_note(app: &App, skipped: &[(&'static str, String)]) -> Option<String> {
+    if skipped.is_empty() {
+        return None;
+    }
+
+    let mut reasons = IndexMap::<&str, Vec<&str>>::new();
+    for (ident, reason) in skipped {
+        reasons.entry(reason).or_default().push(ident);
+    }
+
+    let title = match skipped.len() {
+        1 => "skipped 1 audit".to_string(),
+        n => format!("skipped {n} audits"),
+    };
+
+    let details = reasons
+        .iter()
+        .map(|(reason, idents)| format!("{reason}: {idents}", idents = idents.join(", ")))
+        .collect::<Vec<_>>();
+
+    let help = if app.offline {
+        Some(format!(
+            "try removing {offline} and passing {gh_token} to run these audits",
+            offline = "--offline".yellow(),
+            gh_token = "--gh-token <TOKEN>".yellow(),
+        ))
+    } else if app.no_online_audits {
+        Some(format!(
+            "try removing {no_online_audits} to run these audits",
+            no_online_audits = "--no-online-audits".yellow(),
+        ))
+    } else if app.gh_token.is_none() {
+        Some(format!(
+            "try passing {gh_token} to run these audits",
+            gh_token = "--gh-token <TOKEN>".yellow(),
+        ))
+    } else {
+        None
+    };
+
+    let mut message = Level::Note.title(&title);
+    for detail in &details {
+        message = message.footer(Level::Note.title(detail));
+    }
+    if let Some(help) = &help {
+        message = message.footer(Level::Help.title(help));
+    }
+
+    let renderer = Renderer::styled();
+    Some(format!("{}", renderer.render(message)))
+}
+
+/// Returns a one-line summary of the audit run: how many inputs were
+/// audited, with how many audits, how long it took, and the reported
+/// findings by severity.
+fn run_summary(results: &FindingRegistry) -> String {
+    let count = |severity| results.reported_count(Some(severity));
+
+    let ninputs = results.inputs_audited();
+    let nfindings = results.reported_count(None);
+    format!(
+        "audited {ninputs} input{s} with {naudits} audits in {elapsed:.2}s: \
+         {nfindings} finding{fs} ({nunknown} unknown, {ninformational} informational, \
+         {nlow} low, {nmedium} medium, {nhigh} high)",
+        s = if ninputs == 1 { "" } else { "s" },
+        naudits = results.audits_run(),
+        elapsed = results.elapsed().as_secs_f64(),
+        fs = if nfindings == 1 { "" } else { "s" },
+        nunknown = count(Severity::Unknown),
+        ninformational = count(Severity::Informational),
+        nlow = count(Severity::Low),
+        nmedium = count(Severity::Medium),
+        nhigh = count(Severity::High),
+    )
+}
+
+/// Returns a one-line description of the GitHub API's rate limit status.
+fn rate_limit_status(rate_limit: &RateLimit) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let minutes = rate_limit.reset.saturating_sub(now).div_ceil(60);
+
+    format!(
+        "GitHub API rate limit: {remaining} request{s} remaining, resets in {minutes} minute{ms}",
+        remaining = rate_limit.remaining,
+        s = if rate_limit.remaining == 1 { "" } else { "s" },
+        ms = if minutes == 1 { "" } else { "s" },
+    )
+}
+
+/// Aborts the process with an error once `timeout` has elapsed,
+/// regardless of what the run is currently doing.
+fn start_watchdog(timeout: Duration) {
+    thread::spawn(move || {
+        thread::sleep(timeout);
+
+        eprintln!(
+            "{}",
+            tip(
+                format!(
+                    "run didn't complete within {secs}s",
+                    secs = timeout.as_secs()
+                ),
+                format!(
+                    "try passing a larger {timeout}",
+                    timeout = "--timeout".yellow()
+                ),
+            )
+        );
+        std::process::exit(1);
+    });
+}
+
+/// Returns whether `path` is excluded from collection by the input root's
+/// `.zizmorignore`.
+fn is_ignored(top_dir: &Utf8Path, path: &Utf8Path, ignore: &ZizmorIgnore) -> bool {
+    let Ok(relative) = path.strip_prefix(top_dir) else {
+        return false;
+    };
+
+    let ignored = ignore.is_ignored(relative, path.is_dir());
+    if ignored {
+        tracing::debug!("skipping {path}: excluded by .zizmorignore");
+    }
+    ignored
+}
+
+/// Returns whether `path` is a submodule of the input root.
+fn is_submodule(top_dir: &Utf8Path, path: &Utf8Path, submodules: &Submodules) -> bool {
+    let Ok(relative) = path.strip_prefix(top_dir) else {
+        return false;
+    };
+
+    let submodule = submodules.is_submodule(relative);
+    if submodule {
+        tracing::debug!("skipping {path}: git submodule");
+    }
+    submodule
+}
+
+/// Records the input at `path` as errored if `result` is an error, unless
+/// `strict` is set, in which case the error is returned as-is.
+fn tolerate(
+    registry: &mut InputRegistry,
+    path: &Utf8Path,
+    strict: bool,
+    result: Result<()>,
+) -> Result<()> {
+    match result {
+        Err(err) if !strict => {
+            registry.register_errored(path, err);
+            Ok(())
+        }
+        result => result,
+    }
+}
+
+#[instrument(skip(mode, ignore, submodules, registry))]
 fn collect_from_repo_dir(
     top_dir: &Utf8Path,
     current_dir: &Utf8Path,
     mode: &CollectionMode,
+    ignore: &ZizmorIgnore,
+    submodules: &Submodules,
+    strict: bool,
     registry: &mut InputRegistry,
 ) -> Result<()> {
     // The workflow directory might not exist if we're collecting from
@@ -182,11 +645,16 @@ fn collect_from_repo_dir(
             for entry in workflow_dir.read_dir_utf8()? {
                 let entry = entry?;
                 let input_path = entry.path();
+                if is_ignored(top_dir, input_path, ignore) {
+                    continue;
+                }
+
                 match input_path.extension() {
                     Some(ext) if ext == "yml" || ext == "yaml" => {
-                        registry
+                        let result = registry
                             .register_by_path(input_path, Some(top_dir))
-                            .with_context(|| format!("failed to register input: {input_path}"))?;
+                            .with_context(|| format!("failed to register input: {input_path}"));
+                        tolerate(registry, input_path, strict, result)?;
                     }
                     _ => continue,
                 }
@@ -201,14 +669,28 @@ fn collect_from_repo_dir(
             let entry = entry?;
             let entry_path = entry.path();
 
+            if is_ignored(top_dir, entry_path, ignore) {
+                continue;
+            }
+
             if entry_path.is_file()
                 && matches!(entry_path.file_name(), Some("action.yml" | "action.yaml"))
             {
-                let action = Action::from_file(entry_path, Some(top_dir))?;
-                registry.register_input(action.into())?;
-            } else if entry_path.is_dir() {
+                let result = Action::from_file(entry_path, Some(top_dir))
+                    .and_then(|action| registry.register_input(action.into()))
+                    .with_context(|| format!("failed to register input: {entry_path}"));
+                tolerate(registry, entry_path, strict, result)?;
+            } else if entry_path.is_dir() && !is_submodule(top_dir, entry_path, submodules) {
                 // Recurse and limit the collection mode to only actions.
-                collect_from_repo_dir(top_dir, entry_path, &CollectionMode::ActionsOnly, registry)?;
+                collect_from_repo_dir(
+                    top_dir,
+                    entry_path,
+                    &CollectionMode::ActionsOnly,
+                    ignore,
+                    submodules,
+                    strict,
+                    registry,
+                )?;
             }
         }
     }
@@ -262,7 +744,7 @@ fn collect_from_repo_slug(
             registry.register_input(workflow.into())?;
         }
     } else {
-        let inputs = client.fetch_audit_inputs(&slug).with_context(|| {
+        let mut inputs = client.fetch_audit_inputs(&slug).with_context(|| {
             tip(
                 format!(
                     "couldn't collect inputs from https://github.com/{owner}/{repo}",
@@ -273,6 +755,10 @@ fn collect_from_repo_slug(
             )
         })?;
 
+        // The full download includes every kind of input, so we need to
+        // filter it down to the ones that this mode collects.
+        inputs.retain(|input| mode.includes(input));
+
         tracing::info!(
             "collected {len} inputs from {owner}/{repo}",
             len = inputs.len(),
@@ -288,24 +774,141 @@ fn collect_from_repo_slug(
     Ok(())
 }
 
+fn collect_from_glob(pattern: &str, strict: bool, registry: &mut InputRegistry) -> Result<()> {
+    let paths = glob::glob(pattern).with_context(|| format!("invalid glob pattern: {pattern}"))?;
+
+    let mut matched = false;
+    for path in paths {
+        let path = Utf8PathBuf::try_from(path?)?;
+
+        // Like with individual files, we don't know which part of the
+        // matched path is the prefix.
+        if path.is_file() {
+            let result = registry
+                .register_by_path(&path, None)
+                .with_context(|| format!("failed to register input: {path}"));
+            tolerate(registry, &path, strict, result)?;
+            matched = true;
+        }
+    }
+
+    if !matched {
+        return Err(anyhow!(tip(
+            format!("no files matched: {pattern}", pattern = pattern.green()),
+            "pass a pattern that matches one or more workflow or action files",
+        )));
+    }
+
+    Ok(())
+}
+
+/// Registers each file listed in the given manifest, i.e. a file with one
+/// input path per line.
+fn collect_from_manifest(
+    manifest: &Utf8Path,
+    strict: bool,
+    registry: &mut InputRegistry,
+) -> Result<()> {
+    let contents = std::fs::read_to_string(manifest)
+        .with_context(|| format!("failed to read input manifest: {manifest}"))?;
+
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        // Changed-file lists include deleted files, which there's
+        // nothing left to audit for.
+        let path = Utf8Path::new(line);
+        if !path.exists() {
+            tracing::warn!("skipping {path} from {manifest}: no such file");
+            continue;
+        }
+
+        if !path.is_file() {
+            return Err(anyhow!(tip(
+                format!("input manifest entries must be files, not {path}"),
+                "list each workflow or action file individually",
+            )));
+        }
+
+        // Like with individual files, we don't know which part of the
+        // path is the prefix.
+        let result = registry
+            .register_by_path(path, None)
+            .with_context(|| format!("failed to register input: {path}"));
+        tolerate(registry, path, strict, result)?;
+    }
+
+    Ok(())
+}
+
 #[instrument(skip_all)]
-fn collect_inputs(
-    inputs: &[String],
-    mode: &CollectionMode,
-    state: &AuditState,
-) -> Result<InputRegistry> {
+fn collect_inputs(app: &App, state: &AuditState) -> Result<InputRegistry> {
+    let input_filename = app.input_filename.as_deref();
+    let mode = &app.collect;
+    let strict = app.strict_collection;
+
     let mut registry = InputRegistry::new();
+    if let Some(repo_root) = &app.repo_root {
+        registry.set_repo_root(repo_root.clone());
+    }
 
-    for input in inputs {
+    for input in &app.inputs {
         let input_path = Utf8Path::new(input);
-        if input_path.is_file() {
+        if input == "-" {
+            let mut contents = String::new();
+            std::io::stdin()
+                .read_to_string(&mut contents)
+                .context("failed to read input from stdin")?;
+
+            let key = InputKey::local(input_filename.unwrap_or(Utf8Path::new(STDIN_NAME)), None)?;
+            registry
+                .register_by_contents(contents, key)
+                .context("failed to register input: stdin")?;
+        } else if let Some(input_filename) = input_filename {
+            if !input_path.is_file() {
+                return Err(anyhow!(
+                    "--input-filename requires a file or stdin input, not {input}"
+                ));
+            }
+
+            let contents = std::fs::read_to_string(input_path)
+                .with_context(|| format!("failed to read input: {input_path}"))?;
+
+            let key = InputKey::local(input_filename, None)?;
+            registry
+                .register_by_contents(contents, key)
+                .with_context(|| format!("failed to register input: {input_path}"))?;
+        } else if input_path.is_file() {
             // When collecting individual files, we don't know which part
             // of the input path is the prefix.
-            registry
+            let result = registry
                 .register_by_path(input_path, None)
-                .with_context(|| format!("failed to register input: {input_path}"))?;
+                .with_context(|| format!("failed to register input: {input_path}"));
+            tolerate(&mut registry, input_path, strict, result)?;
         } else if input_path.is_dir() {
-            collect_from_repo_dir(input_path, input_path, mode, &mut registry)?;
+            // Each directory input can have its own `.zizmorignore`,
+            // which is honored regardless of any `.gitignore`.
+            let ignore = ZizmorIgnore::load(input_path)?;
+            let submodules = match app.include_submodules {
+                true => Submodules::default(),
+                false => Submodules::load(input_path)?,
+            };
+            collect_from_repo_dir(
+                input_path,
+                input_path,
+                mode,
+                &ignore,
+                &submodules,
+                strict,
+                &mut registry,
+            )?;
+        } else if input.contains(['*', '?', '[']) {
+            // If this input looks like a glob, expand it ourselves, since
+            // not every environment we're run from has a shell to do so.
+            collect_from_glob(input, strict, &mut registry)?;
         } else {
             // If this input isn't a file or directory, it's probably an
             // `owner/repo(@ref)?` slug.
@@ -313,8 +916,30 @@ fn collect_inputs(
         }
     }
 
-    if registry.len() == 0 {
-        return Err(anyhow!("no inputs collected"));
+    if let Some(manifest) = &app.inputs_from {
+        collect_from_manifest(manifest, strict, &mut registry)?;
+    }
+
+    // A manifest can legitimately list nothing to audit, e.g. when a change
+    // doesn't touch any workflows or only deletes them. That's an empty
+    // run rather than an error, so long as nothing failed to collect.
+    if registry.is_empty() && app.inputs_from.is_some() && registry.errored_inputs().is_empty() {
+        return Ok(registry);
+    }
+
+    if registry.is_empty() {
+        // If every input failed, there's nothing left to audit, so we
+        // fail with the first error rather than an empty run.
+        let mut errored = registry.into_errored_inputs().into_values();
+        return match errored.next() {
+            Some(err) => {
+                for err in errored {
+                    tracing::error!("{err:#}");
+                }
+                Err(err)
+            }
+            None => Err(anyhow!("no inputs collected")),
+        };
     }
 
     Ok(registry)
@@ -325,12 +950,36 @@ fn run() -> Result<ExitCode> {
 
     let mut app = App::parse();
 
+    if let Some(ident) = &app.explain {
+        explain(ident)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if app.explain_exit_codes {
+        explain_exit_codes();
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    // The watchdog is started before anything else, so that the timeout
+    // covers input collection as well as auditing.
+    if let Some(timeout) = app.timeout {
+        start_watchdog(Duration::from_secs(timeout));
+    }
+
     // `--pedantic` is a shortcut for `--persona=pedantic`.
     if app.pedantic {
         app.persona = Persona::Pedantic;
     }
 
-    let indicatif_layer = IndicatifLayer::new();
+    let color = app.color.apply();
+
+    // Log lines are routed through the progress bars' writer (when there are
+    // progress bars), so that the two don't clobber each other.
+    let indicatif_layer = (!app.no_progress).then(IndicatifLayer::new);
+    let writer = match &indicatif_layer {
+        Some(layer) => BoxMakeWriter::new(layer.get_stderr_writer()),
+        None => BoxMakeWriter::new(std::io::stderr),
+    };
 
     let filter = EnvFilter::builder()
         .with_default_directive(app.verbose.tracing_level_filter().into())
@@ -339,89 +988,181 @@ fn run() -> Result<ExitCode> {
     tracing_subscriber::registry()
         .with(
             tracing_subscriber::fmt::layer()
-                .with_ansi(std::io::stderr().is_terminal())
-                .with_writer(indicatif_layer.get_stderr_writer()),
+                .with_ansi(color)
+                .with_writer(writer),
         )
         .with(filter)
         .with(indicatif_layer)
         .init();
 
-    let audit_state = AuditState::new(&app);
-    let registry = collect_inputs(&app.inputs, &app.collect, &audit_state)?;
+    if app.fix && app.gh_token.is_none() {
+        return Err(anyhow!(tip(
+            "can't apply fixes without a GitHub API token",
+            format!("try passing {gh_token}", gh_token = "--gh-token".yellow())
+        )));
+    }
 
-    let config = Config::new(&app)?;
+    if app.input_filename.is_some() && app.inputs.len() != 1 {
+        return Err(anyhow!(tip(
+            "can't use --input-filename with more than one input",
+            "audit each input separately to give each its own filename"
+        )));
+    }
 
-    let mut audit_registry = AuditRegistry::new();
-    macro_rules! register_audit {
-        ($rule:path) => {{
-            // HACK: https://github.com/rust-lang/rust/issues/48067
-            use $rule as base;
+    if app.fix && app.inputs.iter().any(|input| input == "-") {
+        return Err(anyhow!(tip(
+            "can't apply fixes to stdin",
+            "pass the file to fix as an input instead"
+        )));
+    }
 
-            use crate::audit::AuditCore as _;
-            match base::new(audit_state.clone()) {
-                Ok(audit) => audit_registry.register_audit(base::ident(), Box::new(audit)),
-                Err(e) => tracing::info!("skipping {audit}: {e}", audit = base::ident()),
-            }
-        }};
-    }
-
-    register_audit!(audit::artipacked::Artipacked);
-    register_audit!(audit::excessive_permissions::ExcessivePermissions);
-    register_audit!(audit::dangerous_triggers::DangerousTriggers);
-    register_audit!(audit::impostor_commit::ImpostorCommit);
-    register_audit!(audit::ref_confusion::RefConfusion);
-    register_audit!(audit::use_trusted_publishing::UseTrustedPublishing);
-    register_audit!(audit::template_injection::TemplateInjection);
-    register_audit!(audit::hardcoded_container_credentials::HardcodedContainerCredentials);
-    register_audit!(audit::self_hosted_runner::SelfHostedRunner);
-    register_audit!(audit::known_vulnerable_actions::KnownVulnerableActions);
-    register_audit!(audit::unpinned_uses::UnpinnedUses);
-    register_audit!(audit::insecure_commands::InsecureCommands);
-    register_audit!(audit::github_env::GitHubEnv);
-    register_audit!(audit::cache_poisoning::CachePoisoning);
-    register_audit!(audit::secrets_inherit::SecretsInherit);
-    register_audit!(audit::bot_conditions::BotConditions);
-    register_audit!(audit::overprovisioned_secrets::OverprovisionedSecrets);
-
-    let mut results = FindingRegistry::new(&app, &config);
-    {
-        // Note: block here so that we drop the span here at the right time.
-        let span = info_span!("audit");
-        span.pb_set_length((registry.len() * audit_registry.len()) as u64);
-        span.pb_set_style(
-            &ProgressStyle::with_template("[{elapsed_precise}] {bar:!30.cyan/blue} {msg}").unwrap(),
-        );
+    // Fixes are written back to each finding's input path, which
+    // --input-filename replaces with the reported name.
+    if app.fix && app.input_filename.is_some() {
+        return Err(anyhow!(tip(
+            "can't apply fixes with --input-filename",
+            "pass the file to fix as an input, without --input-filename"
+        )));
+    }
 
-        let _guard = span.enter();
+    // Streamed findings aren't kept around, so there's nothing to fix.
+    if app.fix && matches!(app.format, OutputFormat::Ndjson) {
+        return Err(anyhow!(tip(
+            "can't apply fixes with --format=ndjson",
+            "use a different output format with --fix"
+        )));
+    }
 
-        for (_, input) in registry.iter_inputs() {
-            Span::current().pb_set_message(input.key().filename());
-            for (name, audit) in audit_registry.iter_audits() {
-                results.extend(audit.audit(input).with_context(|| {
-                    format!("{name} failed on {input}", input = input.key().filename())
-                })?);
-                Span::current().pb_inc(1);
-            }
-            tracing::info!(
-                "🌈 completed {input}",
-                input = input.key().best_effort_relative_path()
-            );
-        }
+    // Every other format is made of findings, with no summary to fall
+    // back on.
+    if app.severity_counts_only && !matches!(app.format, OutputFormat::Plain | OutputFormat::Json) {
+        return Err(anyhow!(tip(
+            "--severity-counts-only only supports the plain and JSON formats",
+            "use --format=plain or --format=json with --severity-counts-only"
+        )));
     }
 
+    let config = if app.no_config {
+        Config::default()
+    } else if app.config_from_env {
+        Config::from_env()?
+    } else {
+        Config::new(app.config.as_deref())?
+    };
+
+    let audit_state = AuditState {
+        no_online_audits: app.no_online_audits,
+        fix: app.fix,
+        persona: app.persona,
+        cache_dir: app
+            .cache_dir
+            .as_ref()
+            .map(|dir| dir.as_std_path().to_path_buf())
+            .unwrap_or_else(AuditState::default_cache_dir),
+        gh_token: app.gh_token.clone(),
+        gh_hostname: app.gh_hostname.clone(),
+        config,
+        advisory_db: app
+            .advisory_db
+            .as_deref()
+            .map(AdvisoryDb::load)
+            .transpose()?,
+        rate_limit: Default::default(),
+        request_timeout: Duration::from_secs(app.request_timeout),
+        ca_bundle: app.ca_bundle.as_deref().map(CaBundle::load).transpose()?,
+    };
+    tracing::debug!("using cache directory: {:?}", audit_state.cache_dir);
+    let rate_limit = audit_state.rate_limit.clone();
+
+    let registry = collect_inputs(&app, &audit_state)?;
+
+    let mut zizmor = Zizmor::new().state(audit_state);
+    if let Some(min_severity) = app.min_severity {
+        zizmor = zizmor.min_severity(min_severity);
+    }
+    if let Some(min_confidence) = app.min_confidence {
+        zizmor = zizmor.min_confidence(min_confidence);
+    }
+    if let Some(fail_on) = app.fail_on {
+        zizmor = zizmor.fail_on(fail_on);
+    }
+    if app.correlate {
+        zizmor = zizmor.correlate();
+    }
+    for fingerprint in &app.ignore_finding {
+        zizmor = zizmor.ignore_finding(fingerprint);
+    }
+
+    // NDJSON is streamed as each input is audited, so that large runs
+    // don't need to hold every finding in memory at once. Every other
+    // format is rendered once all inputs have been audited.
+    let results = match app.format {
+        OutputFormat::Ndjson => {
+            let mut stdout = stdout().lock();
+            zizmor.audit_registry_streaming(&registry, |findings| {
+                output::json::output_ndjson(&mut stdout, &findings)
+            })?
+        }
+        _ => zizmor.audit_registry(&registry)?,
+    };
+
     match app.format {
         OutputFormat::Plain => render::render_findings(&app, &registry, &results),
-        OutputFormat::Json => serde_json::to_writer_pretty(stdout(), &results.findings())?,
-        OutputFormat::Sarif => {
-            serde_json::to_writer_pretty(stdout(), &sarif::build(results.findings()))?
+        OutputFormat::Json if app.severity_counts_only => {
+            output::json::output_summary(stdout(), &results)?
         }
+        OutputFormat::Json => output::json::output(stdout(), &results)?,
+        OutputFormat::JsonLegacy => output::json::output_legacy(stdout(), &results)?,
+        // Already emitted above.
+        OutputFormat::Ndjson => {}
+        OutputFormat::Sarif => serde_json::to_writer_pretty(
+            stdout(),
+            &sarif::build(results.findings(), &app.sarif_category),
+        )?,
+        OutputFormat::Csv => output::csv::output(&app, stdout(), &results)?,
+        OutputFormat::Github => output::github::output(&app, stdout(), &results)?,
     };
 
-    if app.no_exit_codes || matches!(app.format, OutputFormat::Sarif) {
-        Ok(ExitCode::SUCCESS)
+    // Only the plain format is meant for humans; the other formats are
+    // typically consumed by other tools, which don't need the noise.
+    if matches!(app.format, OutputFormat::Plain)
+        && app.verbose.tracing_level_filter() >= LevelFilter::INFO
+    {
+        if let Some(note) = skipped_audits_note(&app, results.skipped_audits()) {
+            eprintln!("{note}");
+        }
+    }
+
+    // Unlike the note above, the summary is printed for every format,
+    // since machine-readable formats otherwise leave CI logs empty.
+    if app.verbose.tracing_level_filter() >= LevelFilter::INFO {
+        eprintln!("{summary}", summary = run_summary(&results).dimmed());
+
+        // The rate limit is only recorded when an API request was actually
+        // made, i.e. when online audits or collection ran without a cache hit.
+        if let Some(rate_limit) = *rate_limit.lock().unwrap() {
+            eprintln!("{status}", status = rate_limit_status(&rate_limit).dimmed());
+        }
+    }
+
+    if app.fix {
+        fix::apply_fixes(&registry, &results)?;
+    }
+
+    let mut exit_code = if app.no_exit_codes || matches!(app.format, OutputFormat::Sarif) {
+        0
     } else {
-        Ok(results.into())
+        results.exit_code()
+    };
+
+    // Errored inputs are signaled regardless of the findings, since
+    // they mean that the audit is incomplete.
+    if !registry.errored_inputs().is_empty() {
+        report_errored_inputs(&registry);
+        exit_code |= ERRORED_INPUTS_EXIT_BIT;
     }
+
+    Ok(ExitCode::from(exit_code))
 }
 
 fn main() -> ExitCode {
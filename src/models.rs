@@ -15,11 +15,12 @@ use github_actions_models::workflow::{self, job, job::StepBody, Trigger};
 use github_actions_models::{action, common};
 use indexmap::IndexMap;
 use line_index::LineIndex;
+use serde::{de::IntoDeserializer as _, Deserialize as _};
 use serde_json::{json, Value};
 use terminal_link::Link;
 
 use crate::finding::{Route, SymbolicLocation};
-use crate::registry::InputKey;
+use crate::registry::{InputKey, LocalWorkflows};
 use crate::utils::{self, extract_expressions};
 
 pub(crate) mod coordinate;
@@ -35,7 +36,7 @@ pub(crate) enum StepBodyCommon<'s> {
         run: &'s str,
         _working_directory: Option<&'s str>,
         _shell: Option<&'s str>,
-        _env: &'s LoE<Env>,
+        env: &'s LoE<Env>,
     },
 }
 
@@ -50,9 +51,21 @@ pub(crate) trait StepCommon<'s> {
     /// Composite action steps have no strategy.
     fn strategy(&self) -> Option<&Strategy>;
 
+    /// Returns the declared type of the given `inputs.name` input, if known.
+    ///
+    /// Composite action inputs are untyped, so this is always `None` for
+    /// composite action steps.
+    fn input_type(&self, name: &str) -> Option<&str>;
+
     /// Returns a [`StepBodyCommon`] for this step.
     fn body(&self) -> StepBodyCommon;
 
+    /// Returns the shell used by this step, or `None` if the shell
+    /// can't be statically inferred.
+    ///
+    /// Invariant: panics if the step is not a `run:` step.
+    fn shell(&self) -> Option<&str>;
+
     /// Returns a [`SymbolicLocation`] for this step.
     fn location(&self) -> SymbolicLocation<'s>;
 }
@@ -61,7 +74,7 @@ pub(crate) trait StepCommon<'s> {
 ///
 /// This type implements [`Deref`] for [`workflow::Workflow`],
 /// providing access to the underlying data model.
-pub(crate) struct Workflow {
+pub struct Workflow {
     /// This workflow's unique key into zizmor's runtime registry.
     pub(crate) key: InputKey,
     /// A clickable (OSC 8) link to this workflow, if remote.
@@ -93,7 +106,7 @@ impl Deref for Workflow {
 
 impl Workflow {
     /// Load a workflow from a buffer, with an assigned name.
-    pub(crate) fn from_string(contents: String, key: InputKey) -> Result<Self> {
+    pub fn from_string(contents: String, key: InputKey) -> Result<Self> {
         let inner = serde_yaml::from_str(&contents)
             .with_context(|| format!("invalid GitHub Actions workflow: {key}"))?;
 
@@ -119,7 +132,7 @@ impl Workflow {
     }
 
     /// Load a workflow from the given file on disk.
-    pub(crate) fn from_file<P: AsRef<Utf8Path>>(path: P, prefix: Option<P>) -> Result<Self> {
+    pub fn from_file<P: AsRef<Utf8Path>>(path: P, prefix: Option<P>) -> Result<Self> {
         let contents = std::fs::read_to_string(path.as_ref())?;
         Self::from_string(contents, InputKey::local(path, prefix)?)
     }
@@ -135,6 +148,41 @@ impl Workflow {
         }
     }
 
+    /// Returns a [`SymbolicLocation`] for the given event within this
+    /// workflow's `on:`, or `None` if the workflow isn't triggered by it.
+    pub(crate) fn trigger_location(&self, event: &str) -> Option<SymbolicLocation<'_>> {
+        let location = self.location().with_keys(&["on".into()]);
+
+        match &self.on {
+            Trigger::BareEvent(bare) => (Self::bare_event(event)? == *bare).then_some(location),
+            Trigger::BareEvents(bares) => {
+                let event = Self::bare_event(event)?;
+                bares
+                    .iter()
+                    .position(|bare| *bare == event)
+                    .map(|idx| location.with_keys(&[idx.into()]))
+            }
+            // NOTE: The event models are one field per event, so we look the
+            // event's key up in the document rather than mapping names to
+            // fields.
+            Trigger::Events(_) => {
+                let query = yamlpath::QueryBuilder::new().key("on").key(event).build();
+
+                self.document
+                    .query(&query)
+                    .is_ok()
+                    .then(|| location.with_keys(&[event.to_string().into()]))
+            }
+        }
+    }
+
+    /// Parses the given event name into a [`BareEvent`], if it is one.
+    fn bare_event(event: &str) -> Option<BareEvent> {
+        BareEvent::deserialize(event.into_deserializer())
+            .map_err(|_: serde::de::value::Error| ())
+            .ok()
+    }
+
     /// A [`Jobs`] iterator over this workflow's constituent [`Job`]s.
     pub(crate) fn jobs(&self) -> Jobs<'_> {
         Jobs::new(self)
@@ -149,6 +197,39 @@ impl Workflow {
         }
     }
 
+    /// Whether this workflow is triggered by pull_request.
+    pub(crate) fn has_pull_request(&self) -> bool {
+        match &self.on {
+            Trigger::BareEvent(event) => *event == BareEvent::PullRequest,
+            Trigger::BareEvents(events) => events.contains(&BareEvent::PullRequest),
+            Trigger::Events(events) => !matches!(events.pull_request, OptionalBody::Missing),
+        }
+    }
+
+    /// Whether this workflow is triggered by an event that can run for
+    /// pull requests from forks, i.e. pull_request, pull_request_review,
+    /// or pull_request_review_comment.
+    pub(crate) fn has_fork_pull_request(&self) -> bool {
+        let is_fork_event = |event: &BareEvent| {
+            matches!(
+                event,
+                BareEvent::PullRequest
+                    | BareEvent::PullRequestReview
+                    | BareEvent::PullRequestReviewComment
+            )
+        };
+
+        match &self.on {
+            Trigger::BareEvent(event) => is_fork_event(event),
+            Trigger::BareEvents(events) => events.iter().any(is_fork_event),
+            Trigger::Events(events) => {
+                !matches!(events.pull_request, OptionalBody::Missing)
+                    || !matches!(events.pull_request_review, OptionalBody::Missing)
+                    || !matches!(events.pull_request_review_comment, OptionalBody::Missing)
+            }
+        }
+    }
+
     /// Whether this workflow is triggered by workflow_run.
     pub(crate) fn has_workflow_run(&self) -> bool {
         match &self.on {
@@ -158,6 +239,41 @@ impl Workflow {
         }
     }
 
+    /// Returns the declared type of the given `workflow_dispatch` or
+    /// `workflow_call` input, if this workflow declares it.
+    ///
+    /// Untyped `workflow_dispatch` inputs are strings. If both triggers
+    /// declare the input with different types, `string` is returned as the
+    /// least constrained of the two.
+    pub(crate) fn input_type(&self, name: &str) -> Option<&str> {
+        let Trigger::Events(events) = &self.on else {
+            return None;
+        };
+
+        let dispatch_type = match &events.workflow_dispatch {
+            OptionalBody::Body(body) => body
+                .inputs
+                .iter()
+                .find(|(input, _)| input.eq_ignore_ascii_case(name))
+                .map(|(_, input)| input.r#type.as_deref().unwrap_or("string")),
+            _ => None,
+        };
+
+        let call_type = match &events.workflow_call {
+            OptionalBody::Body(body) => body
+                .inputs
+                .iter()
+                .find(|(input, _)| input.eq_ignore_ascii_case(name))
+                .map(|(_, input)| input.r#type.as_str()),
+            _ => None,
+        };
+
+        match (dispatch_type, call_type) {
+            (Some(dispatch_type), Some(call_type)) if dispatch_type != call_type => Some("string"),
+            (dispatch_type, call_type) => dispatch_type.or(call_type),
+        }
+    }
+
     /// Whether this workflow is triggered by workflow_call.
     pub(crate) fn has_workflow_call(&self) -> bool {
         match &self.on {
@@ -275,6 +391,8 @@ pub(crate) struct ReusableWorkflowCallJob<'w> {
     inner: &'w job::ReusableWorkflowCallJob,
     /// The job's parent [`Workflow`].
     parent: &'w Workflow,
+    /// The called [`Workflow`], if resolved.
+    callee: Option<&'w Workflow>,
 }
 
 impl<'w> ReusableWorkflowCallJob<'w> {
@@ -283,7 +401,28 @@ impl<'w> ReusableWorkflowCallJob<'w> {
         inner: &'w job::ReusableWorkflowCallJob,
         parent: &'w Workflow,
     ) -> Self {
-        Self { id, inner, parent }
+        Self {
+            id,
+            inner,
+            parent,
+            callee: None,
+        }
+    }
+
+    /// Resolves this job's callee against the given local workflows,
+    /// if it calls a local reusable workflow.
+    pub(crate) fn with_callee(mut self, workflows: &LocalWorkflows<'w>) -> Self {
+        if let common::Uses::Local(uses) = &self.inner.uses {
+            self.callee = workflows.resolve(self.parent, uses);
+        }
+
+        self
+    }
+
+    /// Returns the workflow that this job calls, if it's a local reusable
+    /// workflow that's also being audited.
+    pub(crate) fn callee(&self) -> Option<&'w Workflow> {
+        self.callee
     }
 }
 
@@ -400,6 +539,18 @@ impl<'w> Matrix<'w> {
         }
     }
 
+    /// Checks whether the given `matrix.*` context always expands to a
+    /// static value, i.e. whether the matrix isn't generated by an
+    /// expression and no expanded value contains one.
+    pub(crate) fn is_static(&self, context: &str) -> bool {
+        match self.inner {
+            // The matrix is generated by an expression, meaning
+            // that it's trivially not static.
+            LoE::Expr(_) => false,
+            LoE::Literal(_) => self.expands_to_static_values(context),
+        }
+    }
+
     /// Checks whether some expanded path leads to an expression
     pub(crate) fn expands_to_static_values(&self, context: &str) -> bool {
         let expands_to_expression = self.expanded_values.iter().any(|(path, expansion)| {
@@ -568,6 +719,10 @@ impl<'s> StepCommon<'s> for Step<'s> {
         self.job().strategy.as_ref()
     }
 
+    fn input_type(&self, name: &str) -> Option<&str> {
+        self.workflow().input_type(name)
+    }
+
     fn body(&self) -> StepBodyCommon {
         match &self.body {
             StepBody::Uses { uses, with } => StepBodyCommon::Uses { uses, with },
@@ -580,11 +735,15 @@ impl<'s> StepCommon<'s> for Step<'s> {
                 run,
                 _working_directory: working_directory.as_deref(),
                 _shell: shell.as_deref(),
-                _env: env,
+                env,
             },
         }
     }
 
+    fn shell(&self) -> Option<&str> {
+        self.shell()
+    }
+
     fn location(&self) -> SymbolicLocation<'s> {
         self.location()
     }
@@ -707,7 +866,7 @@ impl<'w> Iterator for Steps<'w> {
 ///
 /// This type implements [`Deref`] for [`action::Action`], providing
 /// access to the underlying data model.
-pub(crate) struct Action {
+pub struct Action {
     /// This action's unique key into zizmor's runtime registry.
     pub(crate) key: InputKey,
     pub(crate) link: Option<String>,
@@ -738,14 +897,14 @@ impl Debug for Action {
 
 impl Action {
     /// Load an action from the given file on disk.
-    pub(crate) fn from_file<P: AsRef<Utf8Path>>(path: P, prefix: Option<P>) -> Result<Self> {
+    pub fn from_file<P: AsRef<Utf8Path>>(path: P, prefix: Option<P>) -> Result<Self> {
         let contents =
             std::fs::read_to_string(path.as_ref()).with_context(|| "couldn't read action file")?;
         Self::from_string(contents, InputKey::local(path, prefix)?)
     }
 
     /// Load a workflow from a buffer, with an assigned name.
-    pub(crate) fn from_string(contents: String, key: InputKey) -> Result<Self> {
+    pub fn from_string(contents: String, key: InputKey) -> Result<Self> {
         let inner: action::Action = serde_yaml::from_str(&contents)
             .with_context(|| format!("invalid GitHub Actions definition: {key}"))?;
 
@@ -859,6 +1018,10 @@ impl<'s> StepCommon<'s> for CompositeStep<'s> {
         None
     }
 
+    fn input_type(&self, _name: &str) -> Option<&str> {
+        None
+    }
+
     fn body(&self) -> StepBodyCommon {
         match &self.body {
             action::StepBody::Uses { uses, with } => StepBodyCommon::Uses { uses, with },
@@ -871,11 +1034,20 @@ impl<'s> StepCommon<'s> for CompositeStep<'s> {
                 run,
                 _working_directory: working_directory.as_deref(),
                 _shell: Some(shell),
-                _env: env,
+                env,
             },
         }
     }
 
+    fn shell(&self) -> Option<&str> {
+        let action::StepBody::Run { shell, .. } = &self.body else {
+            panic!("API misuse: can't call shell() on a uses: step")
+        };
+
+        // Composite action steps must always specify their own shell.
+        Some(shell)
+    }
+
     fn location(&self) -> SymbolicLocation<'s> {
         self.location()
     }
@@ -186,6 +186,10 @@ mod tests {
             unimplemented!()
         }
 
+        fn input_type(&self, _name: &str) -> Option<&str> {
+            unimplemented!()
+        }
+
         fn body(&self) -> super::StepBodyCommon {
             match &self.body {
                 github_actions_models::workflow::job::StepBody::Uses { uses, with } => {
@@ -200,11 +204,15 @@ mod tests {
                     run,
                     _working_directory: working_directory.as_deref(),
                     _shell: shell.as_deref(),
-                    _env: env,
+                    env,
                 },
             }
         }
 
+        fn shell(&self) -> Option<&str> {
+            unimplemented!()
+        }
+
         fn location(&self) -> crate::models::SymbolicLocation<'s> {
             unimplemented!()
         }
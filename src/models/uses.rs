@@ -23,6 +23,15 @@ pub(crate) trait RepositoryUsesExt {
     /// returns true.
     fn ref_is_commit(&self) -> bool;
 
+    /// Returns whether this `uses:` clause has a `git` ref that looks like
+    /// an abbreviated commit SHA, i.e. between 7 and 39 hex digits with
+    /// at least one `a` through `f`.
+    ///
+    /// For example, `foo/bar@abc1234` returns true while `foo/bar@v1`,
+    /// `foo/bar@20240115` (more likely a date-based tag), and
+    /// `foo/bar@1234...` (a full commit SHA) return false.
+    fn ref_is_abbreviated_commit(&self) -> bool;
+
     /// Returns the `git` ref for this `uses:`, if present.
     fn commit_ref(&self) -> Option<&str>;
 
@@ -58,6 +67,17 @@ impl RepositoryUsesExt for RepositoryUses {
         }
     }
 
+    fn ref_is_abbreviated_commit(&self) -> bool {
+        match &self.git_ref {
+            Some(git_ref) => {
+                (7..40).contains(&git_ref.len())
+                    && git_ref.chars().all(|c| c.is_ascii_hexdigit())
+                    && git_ref.chars().any(|c| c.is_ascii_alphabetic())
+            }
+            None => false,
+        }
+    }
+
     fn commit_ref(&self) -> Option<&str> {
         match &self.git_ref {
             Some(git_ref) if self.ref_is_commit() => Some(git_ref),
@@ -109,7 +129,7 @@ mod tests {
 
     use github_actions_models::common::Uses;
 
-    use crate::models::uses::RepositoryUsesExt;
+    use crate::models::uses::{RepositoryUsesExt, UsesExt};
 
     #[test]
     fn test_repositoryuses_matches() {
@@ -144,4 +164,83 @@ mod tests {
             assert_eq!(uses.matches(template), matches)
         }
     }
+
+    #[test]
+    fn test_repositoryuses_ref_is_commit() {
+        for (uses, is_commit, is_abbreviated_commit) in [
+            ("actions/checkout", false, false),
+            ("actions/checkout@v4", false, false),
+            ("actions/checkout@main", false, false),
+            (
+                "actions/checkout@11bd71901bbe5b1630ceea73d27597364c9af683",
+                true,
+                false,
+            ),
+            (
+                "actions/checkout@11BD71901BBE5B1630CEEA73D27597364C9AF683",
+                true,
+                false,
+            ),
+            // Abbreviated commit SHAs.
+            ("actions/checkout@11bd719", false, true),
+            ("actions/checkout@11bd71901bbe", false, true),
+            (
+                "actions/checkout@11bd71901bbe5b1630ceea73d27597364c9af68",
+                false,
+                true,
+            ),
+            // Too short to be an abbreviated commit SHA.
+            ("actions/checkout@abc123", false, false),
+            // Too long, or not hex.
+            (
+                "actions/checkout@11bd71901bbe5b1630ceea73d27597364c9af6831",
+                false,
+                false,
+            ),
+            ("actions/checkout@11bd71g", false, false),
+            // All digits, which is more likely a date or version tag.
+            ("actions/checkout@20240115", false, false),
+            ("actions/checkout@1234567890", false, false),
+        ] {
+            let Ok(Uses::Repository(uses)) = Uses::from_str(uses) else {
+                panic!();
+            };
+
+            assert_eq!(uses.ref_is_commit(), is_commit, "failed: {uses:?}");
+            assert_eq!(
+                uses.ref_is_abbreviated_commit(),
+                is_abbreviated_commit,
+                "failed: {uses:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_uses_pinning() {
+        for (uses, unpinned, unhashed) in [
+            ("actions/checkout", true, true),
+            ("actions/checkout@v4", false, true),
+            (
+                "actions/checkout@11bd71901bbe5b1630ceea73d27597364c9af683",
+                false,
+                false,
+            ),
+            // Docker `uses:` are pinned by tag and hashed by digest.
+            ("docker://ubuntu", true, true),
+            ("docker://ubuntu:24.04", false, true),
+            ("docker://ghcr.io/pypa/gh-action-pypi-publish", true, true),
+            (
+                "docker://ubuntu@sha256:80dd3c3b9c6cecb9f1667e9290b3bc61b78c2678c02cbdae5f0fea92cc6734ab",
+                false,
+                false,
+            ),
+            // Local `uses:` never have a ref.
+            ("./.github/actions/foo", true, false),
+        ] {
+            let uses = Uses::from_str(uses).unwrap();
+
+            assert_eq!(uses.unpinned(), unpinned, "failed: {uses:?}");
+            assert_eq!(uses.unhashed(), unhashed, "failed: {uses:?}");
+        }
+    }
 }
@@ -0,0 +1,86 @@
+//! APIs for rendering CSV outputs.
+
+use std::io::Write;
+
+use anyhow::Result;
+use serde::Serialize;
+
+use zizmor::{
+    finding::{Confidence, Finding, Persona, Severity},
+    registry::FindingRegistry,
+};
+
+use crate::App;
+
+/// A single CSV row, corresponding to a single finding.
+#[derive(Serialize)]
+struct Row<'a> {
+    ident: &'static str,
+    severity: Severity,
+    confidence: Confidence,
+    persona: Persona,
+    path: &'a str,
+    start_line: Option<usize>,
+    end_line: Option<usize>,
+    annotation: Option<&'a str>,
+}
+
+impl<'a> From<&'a Finding<'a>> for Row<'a> {
+    fn from(finding: &'a Finding<'a>) -> Self {
+        // Every finding has at least one location, and usually exactly one
+        // primary location. We fall back to the first location if the
+        // finding somehow doesn't have a primary one.
+        let primary = finding
+            .locations
+            .iter()
+            .find(|l| l.symbolic.primary)
+            .or_else(|| finding.locations.first());
+
+        Self {
+            ident: finding.ident,
+            severity: finding.determinations.severity,
+            confidence: finding.determinations.confidence,
+            persona: finding.determinations.persona,
+            path: primary
+                .map(|l| l.symbolic.key.best_effort_relative_path())
+                .unwrap_or_default(),
+            start_line: primary.map(|l| l.concrete.location.start_point.row + 1),
+            end_line: primary.map(|l| l.concrete.location.end_point.row + 1),
+            annotation: primary.map(|l| l.symbolic.annotation.as_str()),
+        }
+    }
+}
+
+/// Writes the given findings as CSV to `sink`, one row per finding.
+///
+/// Like the plain output, ignored findings are excluded unless the
+/// auditor persona is in effect.
+pub(crate) fn output(app: &App, sink: impl Write, findings: &FindingRegistry) -> Result<()> {
+    // NOTE: `serialize` only emits a header row once it's seen a record,
+    // so we write it explicitly to produce a header even with no findings.
+    let mut writer = ::csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(sink);
+    writer.write_record([
+        "ident",
+        "severity",
+        "confidence",
+        "persona",
+        "path",
+        "start_line",
+        "end_line",
+        "annotation",
+    ])?;
+
+    let ignored = match app.persona {
+        Persona::Auditor => findings.ignored(),
+        _ => &[],
+    };
+
+    for finding in findings.findings().iter().chain(ignored) {
+        writer.serialize(Row::from(finding))?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
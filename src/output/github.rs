@@ -0,0 +1,125 @@
+//! APIs for rendering GitHub Actions workflow command outputs.
+//!
+//! See: <https://docs.github.com/en/actions/writing-workflows/choosing-what-your-workflow-does/workflow-commands-for-github-actions>
+
+use std::io::Write;
+
+use anyhow::Result;
+
+use zizmor::{
+    finding::{Finding, Persona, Severity},
+    registry::FindingRegistry,
+};
+
+use crate::App;
+
+/// Returns the workflow command that corresponds to the given severity.
+fn command(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Unknown | Severity::Informational | Severity::Low => "notice",
+        Severity::Medium => "warning",
+        Severity::High => "error",
+    }
+}
+
+/// Escapes a workflow command's message.
+fn escape_data(data: &str) -> String {
+    data.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escapes a workflow command's property value.
+fn escape_property(property: &str) -> String {
+    escape_data(property)
+        .replace(':', "%3A")
+        .replace(',', "%2C")
+}
+
+fn write_finding(mut sink: impl Write, finding: &Finding) -> Result<()> {
+    // Like with CSV, we fall back to the first location if the finding
+    // somehow doesn't have a primary one.
+    let Some(primary) = finding
+        .locations
+        .iter()
+        .find(|l| l.symbolic.primary)
+        .or_else(|| finding.locations.first())
+    else {
+        return Ok(());
+    };
+
+    let message = if primary.symbolic.annotation.is_empty() {
+        finding.desc.to_string()
+    } else {
+        format!(
+            "{desc}: {annotation}",
+            desc = finding.desc,
+            annotation = primary.symbolic.annotation
+        )
+    };
+
+    let (start, end) = (
+        &primary.concrete.location.start_point,
+        &primary.concrete.location.end_point,
+    );
+
+    // Columns are only reliable when the span is contained within a single
+    // line, so we only emit them in that case. GitHub's lines and columns
+    // are both 1-based, and its end column is inclusive. Our end column is
+    // 0-based and exclusive, so it's already the 1-based inclusive column.
+    let columns = if start.row == end.row {
+        format!(
+            ",col={col},endColumn={end_col}",
+            col = start.column + 1,
+            end_col = end.column
+        )
+    } else {
+        String::new()
+    };
+
+    writeln!(
+        sink,
+        "::{command} file={file},line={line},endLine={end_line}{columns},title={title}::{message}",
+        command = command(finding.determinations.severity),
+        file = escape_property(primary.symbolic.key.sarif_path()),
+        line = start.row + 1,
+        end_line = end.row + 1,
+        title = escape_property(finding.ident),
+        message = escape_data(&message),
+    )?;
+
+    Ok(())
+}
+
+/// Writes the given findings to `sink` as GitHub Actions workflow commands,
+/// one command per finding.
+///
+/// Like the plain output, ignored findings are excluded unless the
+/// auditor persona is in effect.
+pub(crate) fn output(app: &App, mut sink: impl Write, findings: &FindingRegistry) -> Result<()> {
+    let ignored = match app.persona {
+        Persona::Auditor => findings.ignored(),
+        _ => &[],
+    };
+
+    for finding in findings.findings().iter().chain(ignored) {
+        write_finding(&mut sink, finding)?;
+    }
+
+    sink.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escape_data, escape_property};
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape_data("100%\r\ndone: a, b"), "100%25%0D%0Adone: a, b");
+        assert_eq!(
+            escape_property("100%\r\ndone: a, b"),
+            "100%25%0D%0Adone%3A a%2C b"
+        );
+    }
+}
@@ -0,0 +1,124 @@
+//! APIs for rendering JSON outputs.
+
+use std::{collections::BTreeMap, io::Write};
+
+use anyhow::Result;
+use serde::Serialize;
+
+use zizmor::{
+    finding::{Finding, Severity},
+    registry::{AuditCounts, FindingRegistry},
+};
+
+/// The JSON schema that describes [`Output`].
+///
+/// This must be updated whenever the JSON output changes in a
+/// backwards-incompatible way.
+const SCHEMA_URL: &str = "https://woodruffw.github.io/zizmor/schemas/output-v1.json";
+
+/// Counts of findings by severity.
+#[derive(Default, Serialize)]
+struct SeverityCounts {
+    unknown: usize,
+    informational: usize,
+    low: usize,
+    medium: usize,
+    high: usize,
+}
+
+/// A summary of an audit's results.
+#[derive(Serialize)]
+struct Summary {
+    /// Counts of reported (i.e. non-ignored, non-suppressed) findings by severity.
+    severities: SeverityCounts,
+    /// The number of findings that were ignored.
+    ignored: usize,
+    /// The number of findings that were suppressed.
+    suppressed: usize,
+    /// The number of findings that were below the minimum severity
+    /// or confidence.
+    filtered: usize,
+    /// Counts of findings by the ident of the audit that produced them.
+    audits: BTreeMap<&'static str, AuditCounts>,
+}
+
+/// The top-level JSON output.
+#[derive(Serialize)]
+struct Output<'a> {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    zizmor_version: &'static str,
+    findings: &'a [Finding<'a>],
+    summary: Summary,
+}
+
+impl From<&FindingRegistry<'_>> for Summary {
+    fn from(findings: &FindingRegistry) -> Self {
+        let mut severities = SeverityCounts::default();
+        for finding in findings.findings() {
+            let count = match finding.determinations.severity {
+                Severity::Unknown => &mut severities.unknown,
+                Severity::Informational => &mut severities.informational,
+                Severity::Low => &mut severities.low,
+                Severity::Medium => &mut severities.medium,
+                Severity::High => &mut severities.high,
+            };
+            *count += 1;
+        }
+
+        Self {
+            severities,
+            ignored: findings.ignored().len(),
+            suppressed: findings.suppressed().len(),
+            filtered: findings.filtered().len(),
+            audits: findings.counts_by_audit(),
+        }
+    }
+}
+
+impl<'a> From<&'a FindingRegistry<'a>> for Output<'a> {
+    fn from(findings: &'a FindingRegistry<'a>) -> Self {
+        Self {
+            schema: SCHEMA_URL,
+            zizmor_version: env!("CARGO_PKG_VERSION"),
+            findings: findings.findings(),
+            summary: Summary::from(findings),
+        }
+    }
+}
+
+/// Writes the given findings to `sink` as zizmor's JSON envelope.
+pub(crate) fn output(sink: impl Write, findings: &FindingRegistry) -> Result<()> {
+    serde_json::to_writer_pretty(sink, &Output::from(findings))?;
+    Ok(())
+}
+
+/// Writes only the summary of the given findings to `sink`, i.e. the
+/// `summary` object of zizmor's JSON envelope.
+pub(crate) fn output_summary(sink: impl Write, findings: &FindingRegistry) -> Result<()> {
+    serde_json::to_writer_pretty(sink, &Summary::from(findings))?;
+    Ok(())
+}
+
+/// Writes the given findings to `sink` as a bare JSON array.
+///
+/// This is the JSON format used by `zizmor` 1.3.0 and earlier, and will
+/// be removed in a future release.
+pub(crate) fn output_legacy(sink: impl Write, findings: &FindingRegistry) -> Result<()> {
+    serde_json::to_writer_pretty(sink, findings.findings())?;
+    Ok(())
+}
+
+/// Writes the given findings to `sink` as newline-delimited JSON, i.e.
+/// one finding object per line.
+///
+/// Unlike the other JSON formats, this can be called repeatedly on the
+/// same `sink`, e.g. once per audited input.
+pub(crate) fn output_ndjson(mut sink: impl Write, findings: &[Finding]) -> Result<()> {
+    for finding in findings {
+        serde_json::to_writer(&mut sink, finding)?;
+        sink.write_all(b"\n")?;
+    }
+    sink.flush()?;
+    Ok(())
+}
@@ -0,0 +1,5 @@
+//! Additional output formats.
+
+pub(crate) mod csv;
+pub(crate) mod github;
+pub(crate) mod json;
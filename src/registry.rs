@@ -1,37 +1,55 @@
 //! Functionality for registering and managing the lifecycles of
 //! audits.
 
-use std::{fmt::Display, process::ExitCode};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    fmt::Display,
+    process::ExitCode,
+    time::Duration,
+};
 
 use anyhow::{anyhow, Context, Result};
 use camino::{Utf8Path, Utf8PathBuf};
-use github_actions_models::common::RepositoryUses;
+use github_actions_models::common::{LocalUses, RepositoryUses};
 use indexmap::IndexMap;
 use serde::Serialize;
 use tracing::instrument;
 
 use crate::{
-    audit::{Audit, AuditInput},
+    audit::{self, Audit, AuditInput, Explanation},
     config::Config,
     finding::{Confidence, Finding, Persona, Severity},
     models::{Action, Workflow},
-    App,
+    state::AuditState,
 };
 
 #[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize)]
-pub(crate) struct LocalKey {
+pub struct LocalKey {
     /// The path's nondeterministic prefix, if any.
     prefix: Option<Utf8PathBuf>,
     /// The given path to the input. This can be absolute or relative.
     given_path: Utf8PathBuf,
+    /// The given path with its prefix (if any) stripped, and with `/`
+    /// as its only separator.
+    #[serde(skip)]
+    relative_path: String,
+    /// The input's path relative to the root of the repository that
+    /// contains it, with `/` as its only separator. This is the same as
+    /// `relative_path` if the input isn't in a repository.
+    #[serde(skip)]
+    repo_relative_path: String,
 }
 
 #[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize)]
-pub(crate) struct RemoteKey {
-    owner: String,
-    repo: String,
-    git_ref: Option<String>,
-    path: Utf8PathBuf,
+pub struct RemoteKey {
+    /// The owner of the remote repository.
+    pub(crate) owner: String,
+    /// The name of the remote repository.
+    pub(crate) repo: String,
+    /// The git reference that the input was fetched at, if any.
+    pub(crate) git_ref: Option<String>,
+    /// The input's path within the remote repository.
+    pub(crate) path: Utf8PathBuf,
 }
 
 /// A unique identifying "key" for a workflow file in a given run of zizmor.
@@ -40,7 +58,7 @@ pub(crate) struct RemoteKey {
 /// are just canonical paths to files on disk, while remote keys are
 /// relative paths within a referenced GitHub repository.
 #[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize)]
-pub(crate) enum InputKey {
+pub enum InputKey {
     Local(LocalKey),
     Remote(RemoteKey),
 }
@@ -65,18 +83,90 @@ impl Display for InputKey {
 }
 
 impl InputKey {
-    pub(crate) fn local<P: AsRef<Utf8Path>>(path: P, prefix: Option<P>) -> Result<Self> {
+    pub fn local<P: AsRef<Utf8Path>>(path: P, prefix: Option<P>) -> Result<Self> {
         // All keys must have a filename component.
         if path.as_ref().file_name().is_none() {
             return Err(anyhow!("invalid local input: no filename component"));
         }
 
+        let relative_path = Self::strip_prefix(
+            path.as_ref().as_str(),
+            prefix.as_ref().map(|p| p.as_ref().as_str()),
+        );
+
+        let repo_relative_path = Self::discover_repo_root(path.as_ref())
+            .and_then(|root| Self::repo_relative_path(path.as_ref(), &root))
+            .unwrap_or_else(|| relative_path.clone());
+
         Ok(Self::Local(LocalKey {
             prefix: prefix.map(|p| p.as_ref().to_path_buf()),
             given_path: path.as_ref().to_path_buf(),
+            relative_path,
+            repo_relative_path,
         }))
     }
 
+    /// Returns the root of the repository containing `path`, i.e. the
+    /// nearest ancestor directory that contains a `.git` entry.
+    ///
+    /// `.git` can be a file rather than a directory, e.g. in worktrees
+    /// and submodules.
+    fn discover_repo_root(path: &Utf8Path) -> Option<Utf8PathBuf> {
+        let path = std::path::absolute(path).ok()?;
+
+        path.ancestors()
+            .skip(1)
+            .find(|dir| dir.join(".git").exists())
+            .and_then(|dir| Utf8PathBuf::from_path_buf(dir.into()).ok())
+    }
+
+    /// Returns `path` relative to `repo_root`, if it's under `repo_root`.
+    fn repo_relative_path(path: &Utf8Path, repo_root: &Utf8Path) -> Option<String> {
+        let path = std::path::absolute(path).ok()?;
+        let repo_root = std::path::absolute(repo_root).ok()?;
+        let relative = path.strip_prefix(repo_root).ok()?;
+
+        Some(Self::strip_prefix(
+            Utf8Path::from_path(relative)?.as_str(),
+            None,
+        ))
+    }
+
+    /// Makes this key's [`InputKey::sarif_path`] relative to `repo_root`,
+    /// rather than to the repository discovered from its path.
+    ///
+    /// This has no effect on remote keys, or on local keys that aren't
+    /// under `repo_root`.
+    pub(crate) fn set_repo_root(&mut self, repo_root: &Utf8Path) {
+        if let InputKey::Local(local) = self {
+            if let Some(path) = Self::repo_relative_path(&local.given_path, repo_root) {
+                local.repo_relative_path = path;
+            }
+        }
+    }
+
+    /// Strips `prefix` from `path`, normalizing both to `/` separators
+    /// first so that Windows-style paths (and prefixes with or without
+    /// a trailing separator) strip cleanly.
+    ///
+    /// Returns the normalized `path` if it isn't under `prefix`.
+    fn strip_prefix(path: &str, prefix: Option<&str>) -> String {
+        let path = path.replace('\\', "/");
+        let path = path.trim_end_matches('/');
+
+        let Some(prefix) = prefix.map(|p| p.replace('\\', "/")) else {
+            return path.into();
+        };
+        let prefix = prefix.trim_end_matches('/');
+
+        match path.strip_prefix(prefix) {
+            // Only strip whole components, i.e. `/foo` isn't a prefix
+            // of `/foobar/baz.yml`.
+            Some(rest) if rest.starts_with('/') => rest.trim_start_matches('/').into(),
+            _ => path.into(),
+        }
+    }
+
     pub(crate) fn remote(slug: &RepositoryUses, path: String) -> Result<Self> {
         if Utf8Path::new(&path).file_name().is_none() {
             return Err(anyhow!("invalid remote input: no filename component"));
@@ -94,47 +184,105 @@ impl InputKey {
     ///
     /// This will always be a relative path for remote keys,
     /// and will be a "best-effort" relative path for local keys.
-    pub(crate) fn best_effort_relative_path(&self) -> &str {
+    ///
+    /// Local paths always use `/` as their separator, even when given
+    /// in Windows style.
+    pub fn best_effort_relative_path(&self) -> &str {
+        match self {
+            InputKey::Local(local) => &local.relative_path,
+            InputKey::Remote(remote) => remote.path.as_str(),
+        }
+    }
+
+    /// Returns the path for this [`InputKey`] that's suitable for SARIF
+    /// and GitHub annotations, i.e. relative to the root of its repository.
+    ///
+    /// For local keys, the repository root is the nearest ancestor of the
+    /// input that contains a `.git` directory, unless overridden with
+    /// `--repo-root`. Local keys that aren't in a repository fall back to
+    /// [`InputKey::best_effort_relative_path`].
+    ///
+    /// Like [`InputKey::best_effort_relative_path`], this always uses `/`
+    /// as its separator.
+    pub fn sarif_path(&self) -> &str {
         match self {
-            InputKey::Local(local) => local
-                .prefix
-                .as_ref()
-                .and_then(|pfx| local.given_path.strip_prefix(pfx).ok())
-                .unwrap_or_else(|| &local.given_path)
-                .as_str(),
+            InputKey::Local(local) => &local.repo_relative_path,
             InputKey::Remote(remote) => remote.path.as_str(),
         }
     }
 
+    /// Returns the owner of the repository that this [`InputKey`] belongs
+    /// to, if known.
+    ///
+    /// This is only known for remote keys, since a local input's path
+    /// doesn't say anything about where it's hosted.
+    pub fn owner(&self) -> Option<&str> {
+        match self {
+            InputKey::Local(_) => None,
+            InputKey::Remote(remote) => Some(&remote.owner),
+        }
+    }
+
+    /// Returns the on-disk path for this [`InputKey`], if it's a local key.
+    pub fn local_path(&self) -> Option<&Utf8Path> {
+        match self {
+            InputKey::Local(local) => Some(&local.given_path),
+            InputKey::Remote(_) => None,
+        }
+    }
+
     /// Returns the filename component of this [`InputKey`].
-    pub(crate) fn filename(&self) -> &str {
+    pub fn filename(&self) -> &str {
         // NOTE: Safe unwraps, since the presence of a filename component
         // is a construction invariant of all `InputKey` variants.
         match self {
-            InputKey::Local(local) => local.given_path.file_name().unwrap(),
+            // NOTE: We use the normalized relative path here, since
+            // `Utf8Path` only understands the host platform's separators.
+            InputKey::Local(local) => local.relative_path.rsplit('/').next().unwrap(),
             InputKey::Remote(remote) => remote.path.file_name().unwrap(),
         }
     }
 }
 
-pub(crate) struct InputRegistry {
+#[derive(Default)]
+pub struct InputRegistry {
     pub(crate) inputs: IndexMap<InputKey, AuditInput>,
+    errored: IndexMap<Utf8PathBuf, anyhow::Error>,
+    /// An explicit repository root for local inputs, overriding the one
+    /// discovered from each input's path.
+    repo_root: Option<Utf8PathBuf>,
 }
 
 impl InputRegistry {
-    pub(crate) fn new() -> Self {
+    pub fn new() -> Self {
         Self {
             inputs: Default::default(),
+            errored: Default::default(),
+            repo_root: None,
         }
     }
 
-    pub(crate) fn len(&self) -> usize {
+    /// Sets the repository root that [`InputKey::sarif_path`] is
+    /// relative to for each subsequently registered local input.
+    pub fn set_repo_root(&mut self, repo_root: Utf8PathBuf) {
+        self.repo_root = Some(repo_root);
+    }
+
+    pub fn len(&self) -> usize {
         self.inputs.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty()
+    }
+
     /// Registers an already-loaded workflow or action definition.
     #[instrument(skip(self))]
-    pub(crate) fn register_input(&mut self, input: AuditInput) -> Result<()> {
+    pub fn register_input(&mut self, mut input: AuditInput) -> Result<()> {
+        if let Some(repo_root) = &self.repo_root {
+            input.key_mut().set_repo_root(repo_root);
+        }
+
         if self.inputs.contains_key(input.key()) {
             return Err(anyhow!(
                 "can't register {key} more than once",
@@ -149,11 +297,7 @@ impl InputRegistry {
 
     /// Registers a workflow or action definition from its path on disk.
     #[instrument(skip(self))]
-    pub(crate) fn register_by_path(
-        &mut self,
-        path: &Utf8Path,
-        prefix: Option<&Utf8Path>,
-    ) -> Result<()> {
+    pub fn register_by_path(&mut self, path: &Utf8Path, prefix: Option<&Utf8Path>) -> Result<()> {
         match Workflow::from_file(path, prefix) {
             Ok(workflow) => self.register_input(workflow.into()),
             Err(we) => match Action::from_file(path, prefix) {
@@ -165,32 +309,231 @@ impl InputRegistry {
         }
     }
 
-    pub(crate) fn iter_inputs(&self) -> indexmap::map::Iter<'_, InputKey, AuditInput> {
+    /// Registers a workflow or action definition from a buffer, under the
+    /// given key.
+    #[instrument(skip(self, contents))]
+    pub fn register_by_contents(&mut self, contents: String, key: InputKey) -> Result<()> {
+        match Workflow::from_string(contents.clone(), key.clone()) {
+            Ok(workflow) => self.register_input(workflow.into()),
+            Err(we) => match Action::from_string(contents, key) {
+                Ok(action) => self.register_input(action.into()),
+                Err(ae) => Err(anyhow!("failed to register input as workflow or action"))
+                    .with_context(|| format!("{ae:?}"))
+                    .with_context(|| format!("{we:?}")),
+            },
+        }
+    }
+
+    /// Records an input that couldn't be registered, e.g. because it
+    /// failed to parse, along with the reason it failed.
+    pub fn register_errored(&mut self, path: &Utf8Path, error: anyhow::Error) {
+        self.errored.insert(path.into(), error);
+    }
+
+    /// Returns each input that couldn't be registered, along with the
+    /// reason it failed.
+    pub fn errored_inputs(&self) -> &IndexMap<Utf8PathBuf, anyhow::Error> {
+        &self.errored
+    }
+
+    /// Consumes this registry, returning each input that couldn't be
+    /// registered.
+    pub fn into_errored_inputs(self) -> IndexMap<Utf8PathBuf, anyhow::Error> {
+        self.errored
+    }
+
+    pub fn iter_inputs(&self) -> indexmap::map::Iter<'_, InputKey, AuditInput> {
         self.inputs.iter()
     }
 
-    pub(crate) fn get_input(&self, key: &InputKey) -> &AuditInput {
+    pub fn get_input(&self, key: &InputKey) -> &AuditInput {
         self.inputs
             .get(key)
             .expect("API misuse: requested an un-registered input")
     }
 }
 
-pub(crate) struct AuditRegistry {
+/// A lookup of the local workflows being audited, keyed by their
+/// canonical paths on disk.
+///
+/// This is used to resolve local reusable workflow calls (like
+/// `uses: ./.github/workflows/reusable.yml`) to their callees.
+pub(crate) struct LocalWorkflows<'a> {
+    workflows: HashMap<Utf8PathBuf, &'a Workflow>,
+}
+
+impl<'a> LocalWorkflows<'a> {
+    pub(crate) fn new(inputs: impl Iterator<Item = &'a AuditInput>) -> Self {
+        let workflows = inputs
+            .filter_map(|input| match input {
+                AuditInput::Workflow(workflow) => {
+                    let path = workflow.key.local_path()?.canonicalize_utf8().ok()?;
+                    Some((path, workflow))
+                }
+                AuditInput::Action(_) => None,
+            })
+            .collect();
+
+        Self { workflows }
+    }
+
+    /// Returns the workflow at the given local path, if it's being audited.
+    pub(crate) fn get(&self, path: &Utf8Path) -> Option<&'a Workflow> {
+        let path = path.canonicalize_utf8().ok()?;
+        self.workflows.get(&path).copied()
+    }
+
+    /// Resolves a local reusable workflow call within `caller` to its
+    /// callee, if the callee is being audited.
+    ///
+    /// Local `uses:` paths are relative to the root of the caller's
+    /// repository, i.e. the parent of its `.github/` directory.
+    pub(crate) fn resolve(&self, caller: &Workflow, uses: &LocalUses) -> Option<&'a Workflow> {
+        let root = caller
+            .key
+            .local_path()?
+            .ancestors()
+            .find(|p| p.file_name() == Some(".github"))?
+            .parent()?;
+
+        self.get(&root.join(&uses.path))
+    }
+}
+
+/// Invokes the given macro with the type of each audit, in the order
+/// that audits are run.
+macro_rules! for_each_audit {
+    ($m:ident) => {
+        $m!(audit::artipacked::Artipacked);
+        $m!(audit::excessive_permissions::ExcessivePermissions);
+        $m!(audit::dangerous_triggers::DangerousTriggers);
+        $m!(audit::impostor_commit::ImpostorCommit);
+        $m!(audit::ref_confusion::RefConfusion);
+        $m!(audit::use_trusted_publishing::UseTrustedPublishing);
+        $m!(audit::template_injection::TemplateInjection);
+        $m!(audit::hardcoded_container_credentials::HardcodedContainerCredentials);
+        $m!(audit::self_hosted_runner::SelfHostedRunner);
+        $m!(audit::known_vulnerable_actions::KnownVulnerableActions);
+        $m!(audit::unpinned_uses::UnpinnedUses);
+        $m!(audit::insecure_commands::InsecureCommands);
+        $m!(audit::github_env::GitHubEnv);
+        $m!(audit::cache_poisoning::CachePoisoning);
+        $m!(audit::secrets_inherit::SecretsInherit);
+        $m!(audit::bot_conditions::BotConditions);
+        $m!(audit::overprovisioned_secrets::OverprovisionedSecrets);
+        $m!(audit::secret_in_url::SecretInUrl);
+        $m!(audit::bundled_script_args::BundledScriptArgs);
+        $m!(audit::untrusted_checkout_secrets::UntrustedCheckoutSecrets);
+        $m!(audit::unused_id_token::UnusedIdToken);
+        $m!(audit::unpinned_images::UnpinnedImages);
+        $m!(audit::hashfiles_args::HashFilesArgs);
+        $m!(audit::unpinned_reusable_workflows::UnpinnedReusableWorkflows);
+        $m!(audit::pull_request_target_checkout::PullRequestTargetCheckout);
+        $m!(audit::controllable_conditions::ControllableConditions);
+        $m!(audit::secret_in_args::SecretInArgs);
+        $m!(audit::checkout_pat::CheckoutPat);
+        $m!(audit::env_case_collision::EnvCaseCollision);
+        $m!(audit::legacy_commands::LegacyCommands);
+        $m!(audit::dynamic_runs_on::DynamicRunsOn);
+        $m!(audit::artifact_poisoning::ArtifactPoisoning);
+        $m!(audit::actor_conditions::ActorConditions);
+        $m!(audit::fork_pr_token::ForkPrToken);
+        $m!(audit::github_output::GitHubOutput);
+        $m!(audit::secret_in_inputs::SecretInInputs);
+        $m!(audit::secret_exfiltration::SecretExfiltration);
+        $m!(audit::duplicate_step_ids::DuplicateStepIds);
+        $m!(audit::stale_branch_workflows::StaleBranchWorkflows);
+        $m!(audit::checkout_submodules::CheckoutSubmodules);
+        $m!(audit::controllable_concurrency::ControllableConcurrency);
+        $m!(audit::insecure_transport::InsecureTransport);
+        $m!(audit::auto_merge::AutoMerge);
+        $m!(audit::tainted_env::TaintedEnv);
+        $m!(audit::secret_in_logs::SecretInLogs);
+        $m!(audit::cross_org_reusable_workflows::CrossOrgReusableWorkflows);
+        $m!(audit::controllable_setup_inputs::ControllableSetupInputs);
+        $m!(audit::unknown_permissions::UnknownPermissions);
+        $m!(audit::reducible_permissions::ReduciblePermissions);
+        $m!(audit::unnecessary_sudo::UnnecessarySudo);
+    };
+}
+
+pub struct AuditRegistry {
     pub(crate) workflow_audits: IndexMap<&'static str, Box<dyn Audit>>,
+    skipped: Vec<(&'static str, String)>,
 }
 
 impl AuditRegistry {
     pub(crate) fn new() -> Self {
         Self {
             workflow_audits: Default::default(),
+            skipped: Default::default(),
         }
     }
 
-    pub(crate) fn len(&self) -> usize {
+    /// Creates a new [`AuditRegistry`] containing every audit that
+    /// can be constructed with the given [`AuditState`].
+    ///
+    /// Audits that can't be constructed (e.g. online audits when
+    /// no GitHub API token is available) are skipped.
+    pub fn default_audits(state: &AuditState) -> Self {
+        let mut registry = Self::new();
+
+        macro_rules! register_audit {
+            ($rule:path) => {{
+                // HACK: https://github.com/rust-lang/rust/issues/48067
+                use $rule as base;
+
+                use crate::audit::AuditCore as _;
+                match base::new(state.clone()) {
+                    Ok(audit) => registry.register_audit(base::ident(), Box::new(audit)),
+                    Err(e) => {
+                        tracing::debug!("skipping {audit}: {e}", audit = base::ident());
+                        registry.skipped.push((base::ident(), e.to_string()));
+                    }
+                }
+            }};
+        }
+
+        for_each_audit!(register_audit);
+
+        registry
+    }
+
+    /// Returns an [`Explanation`] for every audit, regardless of whether
+    /// it can be constructed, in the order that audits are run.
+    pub fn explanations() -> Vec<Explanation> {
+        let mut explanations = vec![];
+
+        macro_rules! explain_audit {
+            ($rule:path) => {{
+                use $rule as base;
+
+                use crate::audit::AuditCore as _;
+                explanations.push(base::explanation());
+            }};
+        }
+
+        for_each_audit!(explain_audit);
+
+        explanations
+    }
+
+    /// Returns the number of registered audits.
+    pub fn len(&self) -> usize {
         self.workflow_audits.len()
     }
 
+    /// Returns whether no audits are registered.
+    pub fn is_empty(&self) -> bool {
+        self.workflow_audits.is_empty()
+    }
+
+    /// Returns each audit that couldn't be constructed, along with
+    /// the reason it was skipped.
+    pub fn skipped(&self) -> &[(&'static str, String)] {
+        &self.skipped
+    }
+
     pub(crate) fn register_audit(&mut self, ident: &'static str, audit: Box<dyn Audit>) {
         self.workflow_audits.insert(ident, audit);
     }
@@ -200,49 +543,169 @@ impl AuditRegistry {
     }
 }
 
+/// Counts of the findings produced by a single audit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct AuditCounts {
+    /// The number of reported (i.e. non-ignored, non-suppressed) findings.
+    pub reported: usize,
+    /// The number of findings that were ignored.
+    pub ignored: usize,
+    /// The number of findings that were suppressed.
+    pub suppressed: usize,
+}
+
 /// A registry of all findings discovered during a `zizmor` run.
-pub(crate) struct FindingRegistry<'a> {
+pub struct FindingRegistry<'a> {
     config: &'a Config,
     minimum_severity: Option<Severity>,
     minimum_confidence: Option<Confidence>,
+    fail_on: Option<Severity>,
     persona: Persona,
+    correlate: bool,
+    ignored_fingerprints: &'a HashSet<String>,
+    skipped_audits: Vec<(&'static str, String)>,
+    inputs_audited: usize,
+    audits_run: usize,
+    elapsed: Duration,
     suppressed: Vec<Finding<'a>>,
     ignored: Vec<Finding<'a>>,
+    filtered: Vec<Finding<'a>>,
     findings: Vec<Finding<'a>>,
+    /// Counts of reported findings that were drained by
+    /// [`FindingRegistry::drain_findings`], by severity.
+    drained: BTreeMap<Severity, usize>,
     highest_seen_severity: Option<Severity>,
 }
 
 impl<'a> FindingRegistry<'a> {
-    pub(crate) fn new(app: &App, config: &'a Config) -> Self {
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(
+        config: &'a Config,
+        persona: Persona,
+        minimum_severity: Option<Severity>,
+        minimum_confidence: Option<Confidence>,
+        fail_on: Option<Severity>,
+        correlate: bool,
+        ignored_fingerprints: &'a HashSet<String>,
+        skipped_audits: Vec<(&'static str, String)>,
+    ) -> Self {
         Self {
             config,
-            minimum_severity: app.min_severity,
-            minimum_confidence: app.min_confidence,
-            persona: app.persona,
+            minimum_severity,
+            minimum_confidence,
+            fail_on,
+            persona,
+            correlate,
+            ignored_fingerprints,
+            skipped_audits,
+            inputs_audited: 0,
+            audits_run: 0,
+            elapsed: Duration::ZERO,
             suppressed: Default::default(),
             ignored: Default::default(),
+            filtered: Default::default(),
             findings: Default::default(),
+            drained: Default::default(),
             highest_seen_severity: None,
         }
     }
 
+    /// Returns whether the given finding is suppressed by the current
+    /// persona.
+    fn suppresses(&self, finding: &Finding) -> bool {
+        match (self.persona, finding.determinations.persona) {
+            // The developer persona only wants the pedantic findings
+            // that we're reasonably confident in.
+            (Persona::Developer, Persona::Pedantic) => {
+                finding.determinations.confidence < Confidence::Medium
+            }
+            (persona, finding_persona) => persona > finding_persona,
+        }
+    }
+
+    /// Returns whether the given finding is below the minimum severity
+    /// or confidence.
+    fn filters(&self, finding: &Finding) -> bool {
+        self.minimum_severity
+            .is_some_and(|min| min > finding.determinations.severity)
+            || self
+                .minimum_confidence
+                .is_some_and(|min| min > finding.determinations.confidence)
+    }
+
+    /// Returns whether the given finding is ignored, whether inline, by
+    /// configuration, or by fingerprint.
+    fn ignores(&self, finding: &Finding) -> bool {
+        finding.ignored
+            || self.config.ignores(finding)
+            || self.ignored_fingerprints.contains(&finding.fingerprint)
+    }
+
+    /// Raises the confidence of each finding whose primary location
+    /// overlaps the primary location of a finding from a different audit.
+    ///
+    /// Only findings that would be reported (i.e. aren't ignored or
+    /// suppressed) can corroborate other findings.
+    fn correlate(&self, results: &mut [Finding<'a>]) {
+        let primaries = results
+            .iter()
+            .map(|finding| {
+                let primary = finding.primary_location();
+                let reportable = !self.suppresses(finding) && !self.ignores(finding);
+
+                (
+                    finding.ident,
+                    primary.symbolic.key,
+                    primary.concrete.location.offset_span.clone(),
+                    reportable,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        for (finding, (ident, key, span, _)) in results.iter_mut().zip(&primaries) {
+            let corroborating = primaries
+                .iter()
+                .filter(|(other_ident, other_key, other_span, reportable)| {
+                    *reportable
+                        && other_ident != ident
+                        && other_key == key
+                        && other_span.start < span.end
+                        && span.start < other_span.end
+                })
+                .map(|(other_ident, ..)| *other_ident)
+                .collect::<BTreeSet<_>>();
+
+            if !corroborating.is_empty() {
+                finding.corroborate(&corroborating.into_iter().collect::<Vec<_>>());
+            }
+        }
+    }
+
     /// Adds one or more findings to the current findings set,
     /// filtering with the configuration in the process.
-    pub(crate) fn extend(&mut self, results: Vec<Finding<'a>>) {
+    ///
+    /// When correlating, `results` should contain every finding for a
+    /// single input, since only findings added together are correlated.
+    ///
+    /// Each finding that isn't reported lands in exactly one bucket, checked
+    /// in order: findings below the minimum severity or confidence are
+    /// filtered, regardless of persona; then findings outside of the current
+    /// persona are suppressed; then any remaining findings that are ignored
+    /// are ignored. This means that a finding that's both outside of the
+    /// current persona and ignored is counted as suppressed, not ignored.
+    pub(crate) fn extend(&mut self, mut results: Vec<Finding<'a>>) {
+        if self.correlate {
+            self.correlate(&mut results);
+        }
+
         // TODO: is it faster to iterate like this, or do `find_by_max`
         // and then `extend`?
         for finding in results {
-            if self.persona > finding.determinations.persona {
+            if self.filters(&finding) {
+                self.filtered.push(finding);
+            } else if self.suppresses(&finding) {
                 self.suppressed.push(finding);
-            } else if finding.ignored
-                || self
-                    .minimum_severity
-                    .is_some_and(|min| min > finding.determinations.severity)
-                || self
-                    .minimum_confidence
-                    .is_some_and(|min| min > finding.determinations.confidence)
-                || self.config.ignores(&finding)
-            {
+            } else if self.ignores(&finding) {
                 self.ignored.push(finding);
             } else {
                 if self
@@ -257,39 +720,157 @@ impl<'a> FindingRegistry<'a> {
         }
     }
 
+    /// Records the scope and duration of the audit run that produced
+    /// these findings.
+    pub(crate) fn record_run(
+        &mut self,
+        inputs_audited: usize,
+        audits_run: usize,
+        elapsed: Duration,
+    ) {
+        self.inputs_audited = inputs_audited;
+        self.audits_run = audits_run;
+        self.elapsed = elapsed;
+    }
+
+    /// Sorts the current findings into a stable order: by input, then
+    /// by the start of each finding's primary location, then by audit.
+    ///
+    /// Findings that compare equal keep the order they were added in.
+    pub(crate) fn sort(&mut self) {
+        self.findings.sort_by_cached_key(|finding| {
+            let primary = finding.primary_location();
+
+            (
+                primary.symbolic.key.to_string(),
+                primary.concrete.location.offset_span.start,
+                finding.ident,
+            )
+        });
+    }
+
+    /// Removes and returns the current findings, sorted as with
+    /// [`FindingRegistry::sort`].
+    ///
+    /// Drained findings are still counted by [`FindingRegistry::count`],
+    /// [`FindingRegistry::reported_count`], and the exit code, which
+    /// allows findings to be emitted as they're produced rather than
+    /// held until the end of the run.
+    pub(crate) fn drain_findings(&mut self) -> Vec<Finding<'a>> {
+        self.sort();
+
+        for finding in &self.findings {
+            *self
+                .drained
+                .entry(finding.determinations.severity)
+                .or_default() += 1;
+        }
+
+        std::mem::take(&mut self.findings)
+    }
+
     /// The total count of all findings, regardless of status.
-    pub(crate) fn count(&self) -> usize {
-        self.findings.len() + self.ignored.len() + self.suppressed.len()
+    pub fn count(&self) -> usize {
+        self.reported_count(None) + self.ignored.len() + self.suppressed.len() + self.filtered.len()
     }
 
-    /// All non-ignored and non-suppressed findings.
-    pub(crate) fn findings(&self) -> &[Finding<'a>] {
+    /// The count of reported findings, including any that were drained,
+    /// optionally limited to a single severity.
+    pub fn reported_count(&self, severity: Option<Severity>) -> usize {
+        let current = self
+            .findings
+            .iter()
+            .filter(|f| severity.map_or(true, |sev| f.determinations.severity == sev))
+            .count();
+        let drained = self
+            .drained
+            .iter()
+            .filter(|(sev, _)| severity.map_or(true, |severity| **sev == severity))
+            .map(|(_, count)| count)
+            .sum::<usize>();
+
+        current + drained
+    }
+
+    /// All non-ignored, non-suppressed, and non-filtered findings.
+    pub fn findings(&self) -> &[Finding<'a>] {
         &self.findings
     }
 
-    /// All ignored findings.
-    pub(crate) fn ignored(&self) -> &[Finding<'a>] {
+    /// All ignored findings, i.e. those ignored inline, by configuration,
+    /// or by fingerprint.
+    pub fn ignored(&self) -> &[Finding<'a>] {
         &self.ignored
     }
 
+    /// All findings below the minimum severity or confidence.
+    pub fn filtered(&self) -> &[Finding<'a>] {
+        &self.filtered
+    }
+
     /// All persona-suppressed findings.
-    pub(crate) fn suppressed(&self) -> &[Finding<'a>] {
+    pub fn suppressed(&self) -> &[Finding<'a>] {
         &self.suppressed
     }
+
+    /// Counts of findings by the ident of the audit that produced them.
+    ///
+    /// Filtered findings aren't counted.
+    pub fn counts_by_audit(&self) -> BTreeMap<&'static str, AuditCounts> {
+        let mut counts = BTreeMap::<_, AuditCounts>::new();
+
+        for finding in &self.findings {
+            counts.entry(finding.ident).or_default().reported += 1;
+        }
+        for finding in &self.ignored {
+            counts.entry(finding.ident).or_default().ignored += 1;
+        }
+        for finding in &self.suppressed {
+            counts.entry(finding.ident).or_default().suppressed += 1;
+        }
+
+        counts
+    }
+
+    /// All audits that were skipped, along with the reason each was skipped.
+    pub fn skipped_audits(&self) -> &[(&'static str, String)] {
+        &self.skipped_audits
+    }
+
+    /// The number of inputs that were audited.
+    pub fn inputs_audited(&self) -> usize {
+        self.inputs_audited
+    }
+
+    /// The number of audits that were run against each input.
+    pub fn audits_run(&self) -> usize {
+        self.audits_run
+    }
+
+    /// How long the audit run took.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Returns the exit code that summarizes these findings.
+    pub fn exit_code(&self) -> u8 {
+        // If a failure threshold is set, findings below it are still
+        // reported but don't affect the exit code.
+        self.highest_seen_severity
+            .filter(|sev| self.fail_on.map_or(true, |fail_on| *sev >= fail_on))
+            .map_or(0, Severity::exit_code)
+    }
+
+    /// Consumes this registry, returning all non-ignored, non-suppressed,
+    /// and non-filtered findings.
+    pub fn into_findings(self) -> Vec<Finding<'a>> {
+        self.findings
+    }
 }
 
 impl From<FindingRegistry<'_>> for ExitCode {
     fn from(value: FindingRegistry<'_>) -> Self {
-        match value.highest_seen_severity {
-            Some(sev) => match sev {
-                Severity::Unknown => ExitCode::from(10),
-                Severity::Informational => ExitCode::from(11),
-                Severity::Low => ExitCode::from(12),
-                Severity::Medium => ExitCode::from(13),
-                Severity::High => ExitCode::from(14),
-            },
-            None => ExitCode::SUCCESS,
-        }
+        ExitCode::from(value.exit_code())
     }
 }
 
@@ -297,9 +878,170 @@ impl From<FindingRegistry<'_>> for ExitCode {
 mod tests {
     use std::str::FromStr;
 
-    use github_actions_models::common::Uses;
+    use camino::{Utf8Path, Utf8PathBuf};
+    use github_actions_models::common::{LocalUses, Uses};
 
-    use super::InputKey;
+    use super::{AuditRegistry, FindingRegistry, InputKey, LocalWorkflows};
+    use crate::{
+        audit::AuditInput,
+        config::Config,
+        finding::{Confidence, Determinations, Finding, Persona, Severity},
+        models::Workflow,
+    };
+
+    fn finding(
+        severity: Severity,
+        confidence: Confidence,
+        persona: Persona,
+        ignored: bool,
+    ) -> Finding<'static> {
+        Finding {
+            ident: "test",
+            desc: "test",
+            url: "https://example.com",
+            determinations: Determinations {
+                confidence,
+                severity,
+                persona,
+            },
+            locations: vec![],
+            span: None,
+            ignored,
+            acknowledgment: None,
+            fixes: vec![],
+            fingerprint: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_finding_buckets() {
+        let config = Config::default();
+        let ignored_fingerprints = Default::default();
+        let mut registry = FindingRegistry::new(
+            &config,
+            Persona::Regular,
+            Some(Severity::Medium),
+            Some(Confidence::Medium),
+            None,
+            false,
+            &ignored_fingerprints,
+            vec![],
+        );
+
+        registry.extend(vec![
+            // Reported.
+            finding(Severity::High, Confidence::High, Persona::Regular, false),
+            // Filtered, by severity and by confidence, regardless of
+            // persona or ignores.
+            finding(Severity::Low, Confidence::High, Persona::Regular, false),
+            finding(Severity::High, Confidence::Low, Persona::Regular, false),
+            finding(Severity::Low, Confidence::High, Persona::Auditor, false),
+            finding(Severity::Low, Confidence::High, Persona::Regular, true),
+            // Suppressed, even if also ignored.
+            finding(Severity::High, Confidence::High, Persona::Auditor, false),
+            finding(Severity::High, Confidence::High, Persona::Pedantic, true),
+            // Ignored.
+            finding(Severity::High, Confidence::High, Persona::Regular, true),
+        ]);
+
+        assert_eq!(registry.findings().len(), 1);
+        assert_eq!(registry.filtered().len(), 4);
+        assert_eq!(registry.suppressed().len(), 2);
+        assert_eq!(registry.ignored().len(), 1);
+        assert_eq!(registry.count(), 8);
+    }
+
+    #[test]
+    fn test_finding_buckets_auditor() {
+        let config = Config::default();
+        let ignored_fingerprints = Default::default();
+        let mut registry = FindingRegistry::new(
+            &config,
+            Persona::Auditor,
+            None,
+            Some(Confidence::Medium),
+            None,
+            false,
+            &ignored_fingerprints,
+            vec![],
+        );
+
+        registry.extend(vec![
+            finding(Severity::High, Confidence::Low, Persona::Auditor, false),
+            finding(Severity::High, Confidence::Medium, Persona::Auditor, false),
+            finding(Severity::High, Confidence::Medium, Persona::Pedantic, true),
+        ]);
+
+        // With the auditor persona nothing is suppressed, so below-threshold
+        // findings are only ever filtered.
+        assert_eq!(registry.findings().len(), 1);
+        assert_eq!(registry.filtered().len(), 1);
+        assert_eq!(registry.suppressed().len(), 0);
+        assert_eq!(registry.ignored().len(), 1);
+    }
+
+    #[test]
+    fn test_finding_buckets_developer() {
+        let config = Config::default();
+        let ignored_fingerprints = Default::default();
+        let mut registry = FindingRegistry::new(
+            &config,
+            Persona::Developer,
+            None,
+            None,
+            None,
+            false,
+            &ignored_fingerprints,
+            vec![],
+        );
+
+        registry.extend(vec![
+            // Reported: regular findings, developer findings regardless of
+            // confidence, and pedantic findings with medium or higher
+            // confidence.
+            finding(Severity::Low, Confidence::Low, Persona::Regular, false),
+            finding(Severity::Low, Confidence::Low, Persona::Developer, false),
+            finding(Severity::Low, Confidence::Medium, Persona::Pedantic, false),
+            finding(Severity::Low, Confidence::High, Persona::Pedantic, false),
+            // Suppressed: low-confidence pedantic findings, and auditor
+            // findings regardless of confidence.
+            finding(Severity::Low, Confidence::Low, Persona::Pedantic, false),
+            finding(Severity::Low, Confidence::Unknown, Persona::Pedantic, false),
+            finding(Severity::Low, Confidence::High, Persona::Auditor, false),
+        ]);
+
+        assert_eq!(registry.findings().len(), 4);
+        assert_eq!(registry.filtered().len(), 0);
+        assert_eq!(registry.suppressed().len(), 3);
+        assert_eq!(registry.ignored().len(), 0);
+    }
+
+    #[test]
+    fn test_explanations() {
+        let explanations = AuditRegistry::explanations();
+        let mut idents = explanations.iter().map(|e| e.ident).collect::<Vec<_>>();
+        idents.sort();
+        idents.dedup();
+        assert_eq!(idents.len(), explanations.len());
+
+        for explanation in &explanations {
+            let ident = explanation.ident;
+            for text in [
+                explanation.rationale(),
+                explanation.example(),
+                explanation.remediation(),
+            ] {
+                assert!(!text.is_empty(), "empty explanation for {ident}");
+                assert!(
+                    !text.starts_with(char::is_whitespace),
+                    "bad indentation for {ident}"
+                );
+            }
+
+            serde_yaml::from_str::<serde_yaml::Value>(&explanation.example())
+                .unwrap_or_else(|e| panic!("invalid example for {ident}: {e}"));
+        }
+    }
 
     #[test]
     fn test_input_key_display() {
@@ -347,5 +1089,117 @@ mod tests {
             local.best_effort_relative_path(),
             ".github/workflows/baz.yml"
         );
+
+        // Prefixes only match whole components.
+        let local = InputKey::local("/foobar/baz.yml", Some("/foo")).unwrap();
+        assert_eq!(local.best_effort_relative_path(), "/foobar/baz.yml");
+    }
+
+    #[test]
+    fn test_input_key_windows_paths() {
+        for (path, prefix, relative) in [
+            (
+                r"C:\repo\.github\workflows\baz.yml",
+                None,
+                "C:/repo/.github/workflows/baz.yml",
+            ),
+            (
+                r"C:\repo\.github\workflows\baz.yml",
+                Some(r"C:\repo"),
+                ".github/workflows/baz.yml",
+            ),
+            (
+                r"C:\repo\.github\workflows\baz.yml",
+                Some(r"C:\repo\"),
+                ".github/workflows/baz.yml",
+            ),
+            // Mixed separators, e.g. from joining a glob match onto a prefix.
+            (
+                r"C:\repo/.github/workflows/baz.yml",
+                Some(r"C:\repo"),
+                ".github/workflows/baz.yml",
+            ),
+            (
+                r"C:/repo/.github/workflows/baz.yml",
+                Some(r"C:\repo\"),
+                ".github/workflows/baz.yml",
+            ),
+            (r"D:\other\baz.yml", Some(r"C:\repo"), "D:/other/baz.yml"),
+        ] {
+            let local = InputKey::local(path, prefix).unwrap();
+            assert_eq!(
+                local.best_effort_relative_path(),
+                relative,
+                "failed: {path}"
+            );
+            assert_eq!(local.filename(), "baz.yml", "failed: {path}");
+        }
+    }
+
+    #[test]
+    fn test_input_key_sarif_path() {
+        let repo = std::env::temp_dir().join(format!("zizmor-sarif-path-{}", std::process::id()));
+        let repo = Utf8PathBuf::try_from(repo).unwrap();
+        let workflows = repo.join(".github/workflows");
+        std::fs::create_dir_all(repo.join(".git")).unwrap();
+        std::fs::create_dir_all(&workflows).unwrap();
+
+        let path = workflows.join("baz.yml");
+
+        // Absolute paths are made relative to the discovered repository.
+        let local = InputKey::local(path.as_path(), None).unwrap();
+        assert_eq!(local.best_effort_relative_path(), path.as_str());
+        assert_eq!(local.sarif_path(), ".github/workflows/baz.yml");
+
+        // ...regardless of the prefix.
+        let local = InputKey::local(path.as_path(), Some(workflows.as_path())).unwrap();
+        assert_eq!(local.best_effort_relative_path(), "baz.yml");
+        assert_eq!(local.sarif_path(), ".github/workflows/baz.yml");
+
+        // An explicit repository root overrides the discovered one.
+        let mut local = InputKey::local(path.as_path(), None).unwrap();
+        local.set_repo_root(&repo.join(".github"));
+        assert_eq!(local.sarif_path(), "workflows/baz.yml");
+
+        // ...unless the input isn't under it.
+        local.set_repo_root(Utf8Path::new("/nonexistent"));
+        assert_eq!(local.sarif_path(), "workflows/baz.yml");
+
+        std::fs::remove_dir_all(&repo).unwrap();
+
+        // Inputs outside of any repository fall back to the relative path.
+        let local = InputKey::local("/foo/bar/baz.yml", Some("/foo")).unwrap();
+        assert_eq!(local.sarif_path(), "bar/baz.yml");
+
+        // Remote keys are always relative to their repository.
+        let Uses::Repository(slug) = Uses::from_str("foo/bar").unwrap() else {
+            panic!()
+        };
+        let remote = InputKey::remote(&slug, ".github/workflows/baz.yml".into()).unwrap();
+        assert_eq!(remote.sarif_path(), ".github/workflows/baz.yml");
+    }
+
+    #[test]
+    fn test_local_workflows_resolve() {
+        let dir = "tests/test-data/secrets-inherit-local/.github/workflows";
+        let inputs = ["caller.yml", "no-secrets.yml"].map(|name| {
+            AuditInput::from(Workflow::from_file(format!("{dir}/{name}"), None).unwrap())
+        });
+        let workflows = LocalWorkflows::new(inputs.iter());
+
+        let AuditInput::Workflow(caller) = &inputs[0] else {
+            panic!()
+        };
+
+        let uses = LocalUses::from_str("./.github/workflows/no-secrets.yml").unwrap();
+        let callee = workflows.resolve(caller, &uses).unwrap();
+        assert_eq!(callee.key.filename(), "no-secrets.yml");
+
+        // Exists on disk, but isn't one of the inputs.
+        let uses = LocalUses::from_str("./.github/workflows/some-secrets.yml").unwrap();
+        assert!(workflows.resolve(caller, &uses).is_none());
+
+        let uses = LocalUses::from_str("./.github/workflows/missing.yml").unwrap();
+        assert!(workflows.resolve(caller, &uses).is_none());
     }
 }
@@ -2,83 +2,214 @@
 
 use std::collections::{hash_map::Entry, HashMap};
 
-use annotate_snippets::{Level, Renderer, Snippet};
+use annotate_snippets::{Annotation, Level, Renderer, Snippet};
 use anstream::{eprintln, print, println};
+use indexmap::IndexMap;
 use owo_colors::OwoColorize;
 use terminal_link::Link;
 
-use crate::{
+use zizmor::{
     finding::{Finding, Location, Severity},
     registry::{FindingRegistry, InputKey, InputRegistry},
-    App,
 };
 
-impl From<&Severity> for Level {
-    fn from(sev: &Severity) -> Self {
-        match sev {
-            Severity::Unknown => Level::Note,
-            Severity::Informational => Level::Info,
-            Severity::Low => Level::Help,
-            Severity::Medium => Level::Warning,
-            Severity::High => Level::Error,
+use crate::App;
+
+fn level(sev: &Severity) -> Level {
+    match sev {
+        Severity::Unknown => Level::Note,
+        Severity::Informational => Level::Info,
+        Severity::Low => Level::Help,
+        Severity::Medium => Level::Warning,
+        Severity::High => Level::Error,
+    }
+}
+
+/// Returns the byte offset of the start of each line in `source`.
+fn line_starts(source: &str) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(source.match_indices('\n').map(|(idx, _)| idx + 1))
+        .collect()
+}
+
+/// Groups the given locations into clusters that are close enough to share
+/// `context` lines of surrounding source, returning the (inclusive) range
+/// of lines that each cluster covers along with its locations.
+fn location_clusters<'a, 'w>(
+    mut locations: Vec<&'a Location<'w>>,
+    context: usize,
+    nlines: usize,
+) -> Vec<(usize, usize, Vec<&'a Location<'w>>)> {
+    locations.sort_by_key(|loc| loc.concrete.location.start_point.row);
+
+    let mut clusters: Vec<(usize, usize, Vec<_>)> = vec![];
+    for location in locations {
+        let start = location.concrete.location.start_point.row;
+        let end = location.concrete.location.end_point.row;
+
+        match clusters.last_mut() {
+            // Merge with the previous cluster if their contexts would overlap.
+            Some((_, last_end, cluster)) if start <= *last_end + 2 * context + 1 => {
+                *last_end = (*last_end).max(end);
+                cluster.push(location);
+            }
+            _ => clusters.push((start, end, vec![location])),
         }
     }
+
+    // The renderer reports the first annotation of the first snippet as the
+    // finding's location, so the primary location needs to come first.
+    for (_, _, cluster) in &mut clusters {
+        cluster.sort_by_key(|loc| !loc.symbolic.primary);
+    }
+    clusters.sort_by_key(|(_, _, cluster)| !cluster[0].symbolic.primary);
+
+    clusters
+        .into_iter()
+        .map(|(start, end, cluster)| {
+            (
+                start.saturating_sub(context),
+                (end + context).min(nlines.saturating_sub(1)),
+                cluster,
+            )
+        })
+        .collect()
+}
+
+/// Returns an annotation for each of the given locations, relative to
+/// a snippet that starts at `base` and is `len` bytes long.
+fn annotations<'w>(
+    finding: &'w Finding<'w>,
+    locations: Vec<&'w Location<'w>>,
+    base: usize,
+    len: usize,
+) -> impl Iterator<Item = Annotation<'w>> {
+    locations.into_iter().map(move |loc| {
+        let annotation = match loc.symbolic.link {
+            Some(ref link) => link,
+            None => &loc.symbolic.annotation,
+        };
+
+        let span = &loc.concrete.location.offset_span;
+        level(&finding.determinations.severity)
+            .span((span.start - base).min(len)..(span.end - base).min(len))
+            .label(annotation)
+    })
 }
 
 pub(crate) fn finding_snippet<'w>(
     registry: &'w InputRegistry,
     finding: &'w Finding<'w>,
+    context: Option<usize>,
 ) -> Vec<Snippet<'w>> {
     // Our finding might span multiple workflows, so we need to group locations
     // by their enclosing workflow to generate each snippet correctly.
-    let mut locations_by_workflow: HashMap<&InputKey, Vec<&Location<'w>>> = HashMap::new();
+    let mut locations_by_workflow: IndexMap<&InputKey, Vec<&Location<'w>>> = IndexMap::new();
     for location in &finding.locations {
-        match locations_by_workflow.entry(location.symbolic.key) {
-            Entry::Occupied(mut e) => {
-                e.get_mut().push(location);
-            }
-            Entry::Vacant(e) => {
-                e.insert(vec![location]);
-            }
-        }
+        locations_by_workflow
+            .entry(location.symbolic.key)
+            .or_default()
+            .push(location);
     }
 
     let mut snippets = vec![];
     for (input_key, locations) in locations_by_workflow {
         let input = registry.get_input(input_key);
+        let source = input.document().source();
+        let origin = input
+            .link()
+            .unwrap_or(input_key.best_effort_relative_path());
 
-        snippets.push(
-            Snippet::source(input.document().source())
-                .fold(true)
-                .line_start(1)
-                .origin(
-                    input
-                        .link()
-                        .unwrap_or(input_key.best_effort_relative_path()),
-                )
-                .annotations(locations.iter().map(|loc| {
-                    let annotation = match loc.symbolic.link {
-                        Some(ref link) => link,
-                        None => &loc.symbolic.annotation,
-                    };
-
-                    Level::from(&finding.determinations.severity)
-                        .span(
-                            loc.concrete.location.offset_span.start
-                                ..loc.concrete.location.offset_span.end,
-                        )
-                        .label(annotation)
-                })),
-        );
+        let Some(context) = context else {
+            snippets.push(
+                Snippet::source(source)
+                    .fold(true)
+                    .line_start(1)
+                    .origin(origin)
+                    .annotations(annotations(finding, locations, 0, source.len())),
+            );
+            continue;
+        };
+
+        // With explicit context, we slice out each cluster of annotated
+        // lines (plus context) ourselves, since `fold` would discard the
+        // surrounding lines.
+        let line_starts = line_starts(source);
+        for (first, last, cluster) in location_clusters(locations, context, line_starts.len()) {
+            let start = line_starts[first];
+            let end = line_starts
+                .get(last + 1)
+                .map_or(source.len(), |next| next - 1);
+            let slice = &source[start..end];
+
+            snippets.push(
+                Snippet::source(slice)
+                    .line_start(first + 1)
+                    .origin(origin)
+                    .annotations(annotations(finding, cluster, start, slice.len())),
+            );
+        }
     }
 
     snippets
 }
 
-pub(crate) fn render_findings(app: &App, registry: &InputRegistry, findings: &FindingRegistry) {
+/// Groups findings by the input that their primary location belongs to,
+/// in the order that each input was first seen.
+fn findings_by_input<'a, 'w>(
+    findings: &'a FindingRegistry<'w>,
+) -> IndexMap<&'w InputKey, Vec<&'a Finding<'w>>> {
+    let mut findings_by_input: IndexMap<_, Vec<_>> = IndexMap::new();
     for finding in findings.findings() {
-        render_finding(registry, finding);
-        println!();
+        // Every finding has a primary location; see `FindingBuilder::build`.
+        let Some(primary) = finding.locations.iter().find(|l| l.symbolic.primary) else {
+            continue;
+        };
+
+        findings_by_input
+            .entry(primary.symbolic.key)
+            .or_default()
+            .push(finding);
+    }
+
+    findings_by_input
+}
+
+fn input_origin<'w>(registry: &'w InputRegistry, input_key: &'w InputKey) -> &'w str {
+    registry
+        .get_input(input_key)
+        .link()
+        .unwrap_or(input_key.best_effort_relative_path())
+}
+
+fn nfindings(count: usize) -> String {
+    format!("{count} finding{s}", s = if count == 1 { "" } else { "s" })
+}
+
+pub(crate) fn render_findings(app: &App, registry: &InputRegistry, findings: &FindingRegistry) {
+    let findings_by_input = findings_by_input(findings);
+
+    // Per-input headers and rollups are only useful when there's more
+    // than one input to tell apart.
+    let grouped = findings_by_input.len() > 1;
+
+    // With --severity-counts-only, only the tallies below are rendered.
+    if !app.severity_counts_only {
+        for (input_key, input_findings) in &findings_by_input {
+            if grouped {
+                println!(
+                    "{origin} ({nfindings})",
+                    origin = input_origin(registry, input_key).bold(),
+                    nfindings = nfindings(input_findings.len()),
+                );
+                println!();
+            }
+
+            for finding in input_findings {
+                render_finding(app, registry, finding);
+                println!();
+            }
+        }
     }
 
     let mut qualifiers = vec![];
@@ -94,6 +225,12 @@ pub(crate) fn render_findings(app: &App, registry: &InputRegistry, findings: &Fi
             nsuppressed = findings.suppressed().len().bright_yellow()
         ));
     }
+    if !findings.filtered().is_empty() {
+        qualifiers.push(format!(
+            "{nfiltered} filtered",
+            nfiltered = findings.filtered().len().bright_yellow()
+        ));
+    }
 
     if findings.findings().is_empty() {
         if qualifiers.is_empty() {
@@ -146,10 +283,46 @@ pub(crate) fn render_findings(app: &App, registry: &InputRegistry, findings: &Fi
             nmedium = findings_by_severity.get(&Severity::Medium).unwrap_or(&0).yellow(),
             nhigh = findings_by_severity.get(&Severity::High).unwrap_or(&0).red(),
         );
+
+        if grouped {
+            for (input_key, input_findings) in &findings_by_input {
+                println!(
+                    "  {origin}: {nfindings}",
+                    origin = input_origin(registry, input_key),
+                    nfindings = nfindings(input_findings.len()),
+                );
+            }
+        }
+
+        // Like the per-input rollup, the per-audit tally is only useful
+        // when more than one audit produced findings.
+        let counts_by_audit = findings.counts_by_audit();
+        if !app.severity_counts_only && counts_by_audit.len() > 1 {
+            println!("findings by audit:");
+            for (ident, counts) in &counts_by_audit {
+                let mut qualifiers = vec![];
+                if counts.ignored > 0 {
+                    qualifiers.push(format!("{} ignored", counts.ignored));
+                }
+                if counts.suppressed > 0 {
+                    qualifiers.push(format!("{} suppressed", counts.suppressed));
+                }
+
+                if qualifiers.is_empty() {
+                    println!("  {ident}: {}", nfindings(counts.reported));
+                } else {
+                    println!(
+                        "  {ident}: {nfindings} ({qualifiers})",
+                        nfindings = nfindings(counts.reported),
+                        qualifiers = qualifiers.join(", "),
+                    );
+                }
+            }
+        }
     }
 }
 
-fn render_finding(registry: &InputRegistry, finding: &Finding) {
+fn render_finding(app: &App, registry: &InputRegistry, finding: &Finding) {
     let link = Link::new(finding.ident, finding.url).to_string();
     let confidence = format!(
         "audit confidence → {:?}",
@@ -157,12 +330,34 @@ fn render_finding(registry: &InputRegistry, finding: &Finding) {
     );
     let confidence_footer = Level::Note.title(&confidence);
 
-    let message = Level::from(&finding.determinations.severity)
+    let acknowledgment = finding
+        .acknowledgment
+        .as_ref()
+        .map(|ack| match ack.as_str() {
+            "" => "acknowledged".to_string(),
+            ack => format!("acknowledged: {ack}"),
+        });
+
+    let fixes = finding
+        .fixes
+        .iter()
+        .map(|fix| format!("fix: {title}", title = fix.title))
+        .collect::<Vec<_>>();
+
+    let mut message = level(&finding.determinations.severity)
         .title(finding.desc)
         .id(&link)
-        .snippets(finding_snippet(registry, finding))
+        .snippets(finding_snippet(registry, finding, app.context))
         .footer(confidence_footer);
 
+    if let Some(acknowledgment) = &acknowledgment {
+        message = message.footer(Level::Note.title(acknowledgment));
+    }
+
+    for fix in &fixes {
+        message = message.footer(Level::Help.title(fix));
+    }
+
     let renderer = Renderer::styled();
     println!("{}", renderer.render(message));
 }
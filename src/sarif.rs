@@ -3,48 +3,45 @@
 use std::collections::HashSet;
 
 use serde_sarif::sarif::{
-    ArtifactContent, ArtifactLocation, Location as SarifLocation, LogicalLocation, Message,
-    PhysicalLocation, PropertyBag, Region, ReportingDescriptor, Result as SarifResult, ResultKind,
-    ResultLevel, Run, Sarif, Tool, ToolComponent,
+    ArtifactChange, ArtifactContent, ArtifactLocation, Fix as SarifFix, Location as SarifLocation,
+    LogicalLocation, Message, PhysicalLocation, PropertyBag, Region,
+    Replacement as SarifReplacement, ReportingDescriptor, Result as SarifResult, ResultKind,
+    ResultLevel, Run, RunAutomationDetails, Sarif, Tool, ToolComponent,
 };
 
-use crate::finding::{Finding, Location, Severity};
-
-impl From<Severity> for ResultKind {
-    fn from(value: Severity) -> Self {
-        // TODO: Does this mapping make sense?
-        match value {
-            Severity::Unknown => ResultKind::Review,
-            Severity::Informational => ResultKind::Review,
-            Severity::Low => ResultKind::Fail,
-            Severity::Medium => ResultKind::Fail,
-            Severity::High => ResultKind::Fail,
-        }
+use zizmor::finding::{Finding, Location, Replacement, Severity};
+
+fn result_kind(value: Severity) -> ResultKind {
+    // TODO: Does this mapping make sense?
+    match value {
+        Severity::Unknown => ResultKind::Review,
+        Severity::Informational => ResultKind::Review,
+        Severity::Low => ResultKind::Fail,
+        Severity::Medium => ResultKind::Fail,
+        Severity::High => ResultKind::Fail,
     }
 }
 
-impl From<Severity> for ResultLevel {
-    fn from(value: Severity) -> Self {
-        // TODO: Does this mapping make sense?
-        match value {
-            Severity::Unknown => ResultLevel::None,
-            Severity::Informational => ResultLevel::Note,
-            Severity::Low => ResultLevel::Warning,
-            Severity::Medium => ResultLevel::Warning,
-            Severity::High => ResultLevel::Error,
-        }
+fn result_level(value: Severity) -> ResultLevel {
+    // TODO: Does this mapping make sense?
+    match value {
+        Severity::Unknown => ResultLevel::None,
+        Severity::Informational => ResultLevel::Note,
+        Severity::Low => ResultLevel::Warning,
+        Severity::Medium => ResultLevel::Warning,
+        Severity::High => ResultLevel::Error,
     }
 }
 
-pub(crate) fn build(findings: &[Finding]) -> Sarif {
+pub(crate) fn build(findings: &[Finding], category: &str) -> Sarif {
     Sarif::builder()
         .version("2.1.0")
         .schema("https://docs.oasis-open.org/sarif/sarif/v2.1.0/os/schemas/sarif-schema-2.1.0.json")
-        .runs([build_run(findings)])
+        .runs([build_run(findings, category)])
         .build()
 }
 
-fn build_run(findings: &[Finding]) -> Run {
+fn build_run(findings: &[Finding], category: &str) -> Run {
     Run::builder()
         .tool(
             Tool::builder()
@@ -61,6 +58,7 @@ fn build_run(findings: &[Finding]) -> Run {
                 .build(),
         )
         .results(build_results(findings))
+        .automation_details(RunAutomationDetails::builder().id(category).build())
         .build()
 }
 
@@ -86,25 +84,82 @@ fn build_results(findings: &[Finding]) -> Vec<SarifResult> {
 }
 
 fn build_result(finding: &Finding<'_>) -> SarifResult {
-    SarifResult::builder()
+    let mut result = SarifResult::builder()
         .message(finding.desc)
         .rule_id(finding.ident)
         .locations(build_locations(
             finding.locations.iter().filter(|l| l.symbolic.primary),
         ))
-        .related_locations(build_locations(
-            finding.locations.iter().filter(|l| !l.symbolic.primary),
-        ))
         // TODO: https://github.com/psastras/sarif-rs/pull/770
         .level(
-            serde_json::to_value(ResultLevel::from(finding.determinations.severity))
+            serde_json::to_value(result_level(finding.determinations.severity))
                 .expect("failed to serialize SARIF result level"),
         )
         .kind(
-            serde_json::to_value(ResultKind::from(finding.determinations.severity))
+            serde_json::to_value(result_kind(finding.determinations.severity))
                 .expect("failed to serialize SARIF result kind"),
         )
-        .build()
+        .partial_fingerprints([("zizmor/v1".to_string(), finding.fingerprint.clone())])
+        .build();
+
+    // Supporting locations become related locations, each of which
+    // should have an ID that's unique within the result.
+    let mut related_locations =
+        build_locations(finding.locations.iter().filter(|l| !l.symbolic.primary));
+    for (id, location) in related_locations.iter_mut().enumerate() {
+        location.id = Some(id as i64 + 1);
+    }
+
+    if !related_locations.is_empty() {
+        result.related_locations = Some(related_locations);
+    }
+
+    if !finding.fixes.is_empty() {
+        result.fixes = Some(build_fixes(&finding.fixes));
+    }
+
+    if let Some(acknowledgment) = &finding.acknowledgment {
+        result.properties = Some(
+            PropertyBag::builder()
+                .additional_properties([("acknowledgment".into(), acknowledgment.as_str().into())])
+                .build(),
+        );
+    }
+
+    result
+}
+
+fn build_fixes(fixes: &[Replacement]) -> Vec<SarifFix> {
+    fixes
+        .iter()
+        .map(|fix| {
+            let location = &fix.location;
+
+            SarifFix::builder()
+                .description(Message::builder().text(&fix.title).build())
+                .artifact_changes([ArtifactChange::builder()
+                    .artifact_location(
+                        ArtifactLocation::builder()
+                            .uri_base_id("%SRCROOT%")
+                            .uri(fix.key.sarif_path())
+                            .build(),
+                    )
+                    .replacements([SarifReplacement::builder()
+                        .deleted_region(
+                            Region::builder()
+                                // NOTE: SARIF lines/columns are 1-based.
+                                .start_line((location.start_point.row as i64) + 1)
+                                .end_line((location.end_point.row as i64) + 1)
+                                .start_column((location.start_point.column as i64) + 1)
+                                .end_column((location.end_point.column as i64) + 1)
+                                .build(),
+                        )
+                        .inserted_content(ArtifactContent::builder().text(&fix.text).build())
+                        .build()])
+                    .build()])
+                .build()
+        })
+        .collect()
 }
 
 fn build_locations<'a>(locations: impl Iterator<Item = &'a Location<'a>>) -> Vec<SarifLocation> {
@@ -126,7 +181,7 @@ fn build_locations<'a>(locations: impl Iterator<Item = &'a Location<'a>>) -> Vec
                         .artifact_location(
                             ArtifactLocation::builder()
                                 .uri_base_id("%SRCROOT%")
-                                .uri(location.symbolic.key.best_effort_relative_path())
+                                .uri(location.symbolic.key.sarif_path())
                                 .build(),
                         )
                         .region(
@@ -162,14 +217,14 @@ fn build_locations<'a>(locations: impl Iterator<Item = &'a Location<'a>>) -> Vec
 
 #[cfg(test)]
 mod tests {
-    use serde_sarif::sarif::ResultKind;
+    use zizmor::finding::Severity;
 
-    use crate::finding::Severity;
+    use super::result_kind;
 
     #[test]
     fn test_resultkind_from_severity() {
         assert_eq!(
-            serde_json::to_string(&ResultKind::from(Severity::High)).unwrap(),
+            serde_json::to_string(&result_kind(Severity::High)).unwrap(),
             "\"fail\""
         );
     }
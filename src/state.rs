@@ -1,52 +1,94 @@
 //! zizmor's runtime state, including application-level caching.
 
-use std::path::PathBuf;
+use std::{path::PathBuf, sync::Arc, time::Duration};
 
 use etcetera::{choose_app_strategy, AppStrategy, AppStrategyArgs};
 
 use crate::{
-    github_api::{Client, GitHubHost},
-    App,
+    advisory_db::AdvisoryDb,
+    config::Config,
+    finding::Persona,
+    github_api::{CaBundle, Client, GitHubHost, SharedRateLimit},
 };
 
 #[derive(Clone)]
-pub(crate) struct AuditState {
-    pub(crate) no_online_audits: bool,
-    pub(crate) cache_dir: PathBuf,
-    pub(crate) gh_token: Option<String>,
-    pub(crate) gh_hostname: GitHubHost,
+pub struct AuditState {
+    /// Whether to skip audits that require network access.
+    pub no_online_audits: bool,
+    /// Whether audits should compute fixes for their findings.
+    pub fix: bool,
+    /// The persona being audited with. Audits can use this to skip
+    /// expensive work for findings that the persona won't show.
+    pub persona: Persona,
+    /// The directory to use for HTTP caching.
+    pub cache_dir: PathBuf,
+    /// The GitHub API token to use, if any.
+    pub gh_token: Option<String>,
+    /// The GitHub host to use for API requests.
+    pub gh_hostname: GitHubHost,
+    /// The loaded configuration, including any audit-specific settings.
+    pub config: Config,
+    /// A local advisory database, used by `known-vulnerable-actions`
+    /// when online audits aren't available.
+    pub advisory_db: Option<AdvisoryDb>,
+    /// The GitHub API's rate limit status, as of the most recent API
+    /// response. This is shared between every client created from this
+    /// state.
+    pub rate_limit: SharedRateLimit,
+    /// The timeout for connecting to, and for each read from, the
+    /// GitHub API.
+    pub request_timeout: Duration,
+    /// Additional root certificates to trust for GitHub API requests.
+    pub ca_bundle: Option<CaBundle>,
 }
 
-impl AuditState {
-    pub(crate) fn new(app: &App) -> Self {
-        let cache_dir = match &app.cache_dir {
-            Some(cache_dir) => cache_dir.as_std_path().to_path_buf(),
-            None => choose_app_strategy(AppStrategyArgs {
-                top_level_domain: "io.github".into(),
-                author: "woodruffw".into(),
-                app_name: "zizmor".into(),
-            })
-            // NOTE: no point in failing gracefully here.
-            .expect("failed to determine default cache directory")
-            .cache_dir(),
-        };
-
-        tracing::debug!("using cache directory: {cache_dir:?}");
-
+impl Default for AuditState {
+    fn default() -> Self {
         Self {
-            no_online_audits: app.no_online_audits,
-            cache_dir,
-            gh_token: app.gh_token.clone(),
-            gh_hostname: app.gh_hostname.clone(),
+            no_online_audits: false,
+            fix: false,
+            persona: Persona::default(),
+            cache_dir: Self::default_cache_dir(),
+            gh_token: None,
+            gh_hostname: GitHubHost::Standard("github.com".into()),
+            config: Config::default(),
+            advisory_db: None,
+            rate_limit: Arc::default(),
+            request_timeout: Self::DEFAULT_REQUEST_TIMEOUT,
+            ca_bundle: None,
         }
     }
+}
+
+impl AuditState {
+    /// The default value for [`AuditState::request_timeout`].
+    pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+    /// Returns the host-appropriate user-caching directory for zizmor.
+    pub fn default_cache_dir() -> PathBuf {
+        choose_app_strategy(AppStrategyArgs {
+            top_level_domain: "io.github".into(),
+            author: "woodruffw".into(),
+            app_name: "zizmor".into(),
+        })
+        // NOTE: no point in failing gracefully here.
+        .expect("failed to determine default cache directory")
+        .cache_dir()
+    }
 
     /// Return a cache-configured GitHub API client, if
     /// a GitHub API token is present.
     /// If gh_hostname is also present, set it as api_base for client.
-    pub(crate) fn github_client(&self) -> Option<Client> {
-        self.gh_token
-            .as_ref()
-            .map(|token| Client::new(&self.gh_hostname, token, &self.cache_dir))
+    pub fn github_client(&self) -> Option<Client> {
+        self.gh_token.as_ref().map(|token| {
+            Client::new(
+                &self.gh_hostname,
+                token,
+                &self.cache_dir,
+                self.rate_limit.clone(),
+                self.request_timeout,
+                self.ca_bundle.as_ref(),
+            )
+        })
     }
 }
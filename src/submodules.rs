@@ -0,0 +1,95 @@
+//! Support for git submodules in local directory inputs.
+//!
+//! Submodules are detected via the `.gitmodules` file at the root of a
+//! local directory input. Each `path = ...` entry in that file names a
+//! submodule directory, relative to the input root.
+//!
+//! By default, submodule directories are treated as external to the
+//! input, like any other third-party repository, and are skipped during
+//! input collection.
+
+use std::collections::HashSet;
+
+use anyhow::{Context, Result};
+use camino::{Utf8Path, Utf8PathBuf};
+
+/// The name of the submodules file, relative to the input root.
+const FILENAME: &str = ".gitmodules";
+
+/// The submodule directories declared by a `.gitmodules` file.
+#[derive(Default)]
+pub(crate) struct Submodules {
+    paths: HashSet<Utf8PathBuf>,
+}
+
+impl Submodules {
+    /// Loads the `.gitmodules` at the given input root, if present.
+    ///
+    /// If the input root has no `.gitmodules`, the returned submodules
+    /// are empty.
+    pub(crate) fn load(root: &Utf8Path) -> Result<Self> {
+        let path = root.join(FILENAME);
+        if !path.is_file() {
+            return Ok(Self::default());
+        }
+
+        let contents =
+            std::fs::read_to_string(&path).with_context(|| format!("couldn't read {path}"))?;
+        Ok(Self::parse(&contents))
+    }
+
+    fn parse(contents: &str) -> Self {
+        let paths = contents
+            .lines()
+            .filter_map(|line| {
+                let (key, value) = line.split_once('=')?;
+                (key.trim() == "path").then(|| value.trim().trim_end_matches('/').into())
+            })
+            .collect();
+
+        Self { paths }
+    }
+
+    /// Returns whether the given path, relative to the input root,
+    /// is a submodule directory.
+    pub(crate) fn is_submodule(&self, path: &Utf8Path) -> bool {
+        self.paths.contains(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use camino::Utf8Path;
+
+    use super::Submodules;
+
+    #[test]
+    fn test_is_submodule() {
+        let submodules = Submodules::parse(
+            r#"
+[submodule "vendor/some-action"]
+	path = vendor/some-action
+	url = https://github.com/example/some-action
+[submodule "docs"]
+	path=docs/theme/
+	url = https://github.com/example/theme
+	branch = main
+"#,
+        );
+
+        for (path, expected) in [
+            ("vendor/some-action", true),
+            ("docs/theme", true),
+            ("vendor", false),
+            ("vendor/some-action/nested", false),
+            ("docs", false),
+            ("main", false),
+        ] {
+            assert_eq!(
+                submodules.is_submodule(Utf8Path::new(path)),
+                expected,
+                "failed: {path}"
+            );
+        }
+    }
+}
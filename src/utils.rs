@@ -8,6 +8,79 @@ use github_actions_models::common::{
     Env,
 };
 
+use crate::expr::{Context, Expr};
+
+/// Contexts that are believed to be always safe.
+pub(crate) const SAFE_CONTEXTS: &[&str] = &[
+    // The action path is always safe.
+    "github.action_path",
+    // The GitHub event name (i.e. trigger) is itself safe.
+    "github.event_name",
+    // Safe keys within the otherwise generally unsafe github.event context.
+    "github.event.after",  // hexadecimal SHA ref
+    "github.event.before", // hexadecimal SHA ref
+    "github.event.issue.number",
+    "github.event.merge_group.base_sha",
+    "github.event.number",
+    "github.event.pull_request.base.sha",
+    "github.event.pull_request.commits", // number of commits in PR
+    "github.event.pull_request.number",  // the PR's own number
+    "github.event.workflow_run.id",
+    // Information about the GitHub repository
+    "github.repository",
+    "github.repository_id",
+    "github.repositoryUrl",
+    // Information about the GitHub repository owner (account/org or ID)
+    "github.repository_owner",
+    "github.repository_owner_id",
+    // Unique numbers assigned by GitHub for workflow runs
+    "github.run_attempt",
+    "github.run_id",
+    "github.run_number",
+    // Typically something like `https://github.com`; you have bigger problems if
+    // this is attacker-controlled.
+    "github.server_url",
+    // Always a 40-char SHA-1 reference.
+    "github.sha",
+    // Like `secrets.*`: not safe to expose, but safe to interpolate.
+    "github.token",
+    // GitHub Actions-controlled local directory.
+    "github.workspace",
+    // GitHub Actions-controller runner architecture.
+    "runner.arch",
+    // Debug logging is (1) or is not (0) enabled on GitHub Actions runner.
+    "runner.debug",
+    // GitHub Actions runner operating system.
+    "runner.os",
+    // GitHub Actions temporary directory, value controlled by the runner itself.
+    "runner.temp",
+    // GitHub Actions cached tool directory, value controlled by the runner itself.
+    "runner.tool_cache",
+];
+
+/// Keys within `github.event` that are safe to use in conditions, since
+/// they only take on a small set of values that GitHub controls.
+const SAFE_CONDITION_CONTEXTS: &[&str] = &[
+    "github.event.action",
+    "github.event.pull_request.draft",
+    "github.event.pull_request.merged",
+    "github.event.pull_request.state",
+    "github.event.review.state",
+];
+
+/// Keys within `github.event` that only the repository's maintainers can
+/// set (or that GitHub sets based on the actor's relationship with the
+/// repository), which makes them usable as security gates in conditions.
+pub(crate) const TRUSTED_CONDITION_CONTEXTS: &[&str] = &[
+    "github.event.comment.author_association",
+    "github.event.issue.author_association",
+    "github.event.label.name",
+    "github.event.pull_request.author_association",
+    "github.event.pull_request.head.repo.fork",
+    "github.event.pull_request.labels.*.name",
+    "github.event.review.author_association",
+];
+
 /// Convenience trait for inline transformations of `Self`.
 ///
 /// This is similar to the `tap` crate's `Pipe` trait, except that
@@ -50,16 +123,27 @@ pub(crate) fn split_patterns(patterns: &str) -> impl Iterator<Item = &str> {
 fn extract_expression(text: &str, offset: usize) -> Option<(ExplicitExpr, Range<usize>)> {
     let view = &text[offset..];
     let start = view.find("${{")?;
+    let bytes = view.as_bytes();
 
+    // The expression ends at the first `}}` that isn't within a string
+    // literal or closing a brace opened within the expression itself.
+    // String literals escape quotes by doubling them (`'it''s'`),
+    // so toggling on each quote is sufficient to track them.
     let mut end = None;
     let mut in_string = false;
+    let mut depth = 0usize;
 
-    for (idx, char) in view.bytes().enumerate().skip(start) {
-        if char == b'\'' {
-            in_string = !in_string;
-        } else if !in_string && view.as_bytes()[idx] == b'}' && view.as_bytes()[idx - 1] == b'}' {
-            end = Some(idx);
-            break;
+    for idx in start + 3..bytes.len() {
+        match bytes[idx] {
+            b'\'' => in_string = !in_string,
+            _ if in_string => continue,
+            b'{' => depth += 1,
+            b'}' if depth > 0 => depth -= 1,
+            b'}' if bytes.get(idx + 1) == Some(&b'}') => {
+                end = Some(idx + 1);
+                break;
+            }
+            _ => continue,
         }
     }
 
@@ -89,6 +173,81 @@ pub(crate) fn extract_expressions(text: &str) -> Vec<(ExplicitExpr, Range<usize>
     exprs
 }
 
+/// Returns a copy of `text` with each `${{ ... }}` expression replaced by
+/// `x`s.
+///
+/// Expressions can contain whitespace, quotes, and other syntax that would
+/// confuse an analysis of the surrounding text. Masking preserves byte
+/// offsets, so spans into the masked text are valid for `text` too.
+pub(crate) fn mask_expressions(text: &str) -> String {
+    let mut masked = text.to_string();
+    for (_, span) in extract_expressions(text) {
+        masked.replace_range(span.clone(), &"x".repeat(span.len()));
+    }
+
+    masked
+}
+
+/// Returns whether the given context may be attacker-controllable.
+pub(crate) fn is_controllable(ctx: &Context) -> bool {
+    (ctx.child_of("github.event") || ctx == "github.head_ref")
+        && !SAFE_CONTEXTS
+            .iter()
+            .chain(SAFE_CONDITION_CONTEXTS)
+            .chain(TRUSTED_CONDITION_CONTEXTS)
+            .any(|safe| *ctx == **safe)
+}
+
+/// Returns whether the given `==` or `!=` operands compare an
+/// attacker-controllable context against a trusted one, e.g.
+/// `github.event.pull_request.head.repo.full_name == github.repository`.
+///
+/// These comparisons are safe, since the attacker can't control both
+/// sides.
+pub(crate) fn is_trusted_comparison(lhs: &Expr, rhs: &Expr) -> bool {
+    match (lhs, rhs) {
+        (Expr::Context(lhs), Expr::Context(rhs)) => is_controllable(lhs) != is_controllable(rhs),
+        _ => false,
+    }
+}
+
+/// Returns each context that's expanded in `text`'s `${{ ... }}`
+/// expressions and satisfies `filter`.
+pub(crate) fn expanded_contexts(text: &str, filter: impl Fn(&Context) -> bool) -> Vec<String> {
+    let mut contexts = vec![];
+
+    for (expr, _) in extract_expressions(text) {
+        let Ok(parsed) = Expr::parse(expr.as_bare()) else {
+            tracing::warn!("couldn't parse expression: {expr}", expr = expr.as_bare());
+            continue;
+        };
+
+        contexts.extend(
+            parsed
+                .contexts()
+                .into_iter()
+                .filter(|ctx| filter(ctx))
+                .map(|ctx| ctx.as_str().to_string()),
+        );
+    }
+
+    contexts
+}
+
+/// Returns each attacker-controllable context that's expanded in
+/// `text`'s `${{ ... }}` expressions.
+pub(crate) fn controllable_contexts(text: &str) -> Vec<String> {
+    expanded_contexts(text, is_controllable)
+}
+
+/// Returns each `secrets` context that's expanded into the given text.
+///
+/// This includes the bare `secrets` context (e.g. in `toJSON(secrets)`),
+/// which expands every secret at once.
+pub(crate) fn secret_contexts(text: &str) -> Vec<String> {
+    expanded_contexts(text, |ctx| ctx.child_of("secrets"))
+}
+
 /// Returns whether the given `env.name` environment access is "static,"
 /// i.e. is not influenced by another expression.
 pub(crate) fn env_is_static(name: &str, envs: &[&LoE<Env>]) -> bool {
@@ -127,7 +286,10 @@ pub(crate) fn normalize_shell(shell: &str) -> &str {
 
 #[cfg(test)]
 mod tests {
-    use crate::utils::{extract_expression, extract_expressions, normalize_shell};
+    use crate::utils::{
+        controllable_contexts, extract_expression, extract_expressions, mask_expressions,
+        normalize_shell, secret_contexts,
+    };
 
     #[test]
     fn split_patterns() {
@@ -173,6 +335,14 @@ mod tests {
                 8..31,
             ),
             ("${{ 'es''cape' }}", "'es''cape'", 0..17),
+            ("${{}}", "", 0..5),
+            // Closing braces within strings don't end the expression.
+            ("${{ format('{0}', '}}') }}", "format('{0}', '}}')", 0..26),
+            ("${{ 'it''s }}' }} }}", "'it''s }}'", 0..17),
+            // Braces opened within the expression are closed first.
+            ("${{ {a}}} trailing", "{a}", 0..9),
+            // A stray closing brace after the expression isn't included.
+            ("${{ foo }}}", "foo", 0..10),
         ];
 
         for (text, expected_expr, expected_span) in exprs {
@@ -200,6 +370,130 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_parse_expressions_spans() {
+        for (text, expected) in [
+            // Adjacent expressions.
+            (
+                "${{ a }}${{ b }}",
+                vec![("${{ a }}", 0..8), ("${{ b }}", 8..16)],
+            ),
+            // Adjacent expressions, with closing braces in a string.
+            (
+                "${{ '}}' }}${{ b }}",
+                vec![("${{ '}}' }}", 0..11), ("${{ b }}", 11..19)],
+            ),
+            // Opening delimiters in a string don't start a new expression.
+            (
+                "x ${{ format('${{ {0} }}', a) }} y ${{ b }}",
+                vec![
+                    ("${{ format('${{ {0} }}', a) }}", 2..32),
+                    ("${{ b }}", 35..43),
+                ],
+            ),
+            // Unterminated expressions are skipped entirely.
+            ("${{ 'unterminated }}", vec![]),
+        ] {
+            let actual = extract_expressions(text)
+                .into_iter()
+                .map(|(expr, span)| (expr.as_curly().to_string(), span))
+                .collect::<Vec<_>>();
+            let expected = expected
+                .into_iter()
+                .map(|(expr, span)| (expr.to_string(), span))
+                .collect::<Vec<_>>();
+
+            assert_eq!(actual, expected, "failed: {text}");
+            for (expr, span) in &actual {
+                assert_eq!(&text[span.clone()], expr, "bad span in: {text}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_mask_expressions() {
+        for (text, expected) in &[
+            ("echo hello", "echo hello"),
+            ("echo ${{ foo }}", "echo xxxxxxxxxx"),
+            (
+                "curl \"${{ a }}\" -d '${{ 'b c' }}'",
+                "curl \"xxxxxxxx\" -d 'xxxxxxxxxxxx'",
+            ),
+            ("${{ a }}${{ b }}", "xxxxxxxxxxxxxxxx"),
+            ("é ${{ 'ü' }} é", "é xxxxxxxxxxx é"),
+        ] {
+            let masked = mask_expressions(text);
+            assert_eq!(masked, *expected, "failed: {text}");
+            assert_eq!(masked.len(), text.len());
+        }
+    }
+
+    #[test]
+    fn test_controllable_contexts() {
+        for (text, expected) in &[
+            ("static", vec![]),
+            ("${{ matrix.node }}", vec![]),
+            ("${{ inputs.version }}", vec![]),
+            ("${{ github.sha }}", vec![]),
+            ("${{ github.workflow }}-${{ github.ref }}", vec![]),
+            ("${{ github.event.pull_request.number }}", vec![]),
+            ("${{ github.event.pull_request.merged }}", vec![]),
+            (
+                "${{ runner.os }}-cargo-${{ hashFiles('**/Cargo.lock') }}",
+                vec![],
+            ),
+            ("cache-${{ github.head_ref }}", vec!["github.head_ref"]),
+            (
+                "${{ github.event.inputs.version }}",
+                vec!["github.event.inputs.version"],
+            ),
+            (
+                "3.${{ github.event.pull_request.title }}",
+                vec!["github.event.pull_request.title"],
+            ),
+            (
+                "${{ runner.os }}-\n${{ GitHub.Event.Pull_Request.Title }}-",
+                vec!["GitHub.Event.Pull_Request.Title"],
+            ),
+            (
+                "${{ github.head_ref || github.run_id }}",
+                vec!["github.head_ref"],
+            ),
+            (
+                "deploy-${{ github.event.pull_request.head.ref }}",
+                vec!["github.event.pull_request.head.ref"],
+            ),
+        ] {
+            assert_eq!(&controllable_contexts(text), expected, "failed: {text}");
+        }
+    }
+
+    #[test]
+    fn test_secret_contexts() {
+        for (text, expected) in &[
+            ("echo hello", vec![]),
+            ("echo ${{ github.sha }}", vec![]),
+            ("${{ github.token }}", vec![]),
+            ("${{ inputs.secrets-file }}", vec![]),
+            ("${{ secrets.FOO }}", vec!["secrets.FOO"]),
+            (
+                "make TOKEN=${{ secrets.FOO }} KEY=${{ secrets.BAR || 'x' }}",
+                vec!["secrets.FOO", "secrets.BAR"],
+            ),
+            (
+                "token=${{ secrets.A }},${{ secrets['B'] }}",
+                vec!["secrets.A", "secrets['B']"],
+            ),
+            ("${{ toJSON(secrets) }}", vec!["secrets"]),
+            (
+                "${{ vars.USE_PAT && secrets.MY_PAT || github.token }}",
+                vec!["secrets.MY_PAT"],
+            ),
+        ] {
+            assert_eq!(&secret_contexts(text), expected, "failed: {text}");
+        }
+    }
+
     #[test]
     fn test_normalize_shell() {
         for (actual, expected) in &[
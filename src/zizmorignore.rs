@@ -0,0 +1,204 @@
+//! Support for `.zizmorignore` files.
+//!
+//! A `.zizmorignore` file lives at the root of a local directory input,
+//! and excludes matching paths from input collection. It uses a subset
+//! of `.gitignore` syntax:
+//!
+//! * Blank lines and lines beginning with `#` are ignored;
+//! * A leading `!` negates a pattern, re-including anything excluded by
+//!   an earlier pattern. The last matching pattern wins;
+//! * A trailing `/` makes a pattern match only directories;
+//! * A pattern with a `/` anywhere else is anchored to the input root,
+//!   while a pattern without one matches a file or directory name at
+//!   any depth;
+//! * Patterns are otherwise globs, with `**` matching across directories.
+//!
+//! Like with `.gitignore`, a path can't be re-included if one of its
+//! parent directories is excluded.
+
+use anyhow::{Context, Result};
+use camino::Utf8Path;
+use glob::{MatchOptions, Pattern};
+
+/// The name of the ignore file, relative to the input root.
+const FILENAME: &str = ".zizmorignore";
+
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+struct Rule {
+    pattern: Pattern,
+    negated: bool,
+    dir_only: bool,
+    anchored: bool,
+}
+
+impl Rule {
+    fn parse(line: &str) -> Result<Option<Self>> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(None);
+        }
+
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(line) => (true, line),
+            None => (false, line.strip_prefix('\\').unwrap_or(line)),
+        };
+
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(line) => (true, line),
+            None => (false, line),
+        };
+
+        let anchored = line.contains('/');
+        let line = line.strip_prefix('/').unwrap_or(line);
+
+        let pattern =
+            Pattern::new(line).with_context(|| format!("invalid ignore pattern: {line}"))?;
+
+        Ok(Some(Self {
+            pattern,
+            negated,
+            dir_only,
+            anchored,
+        }))
+    }
+
+    /// Returns whether this rule matches the given root-relative path.
+    fn matches(&self, path: &str, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+
+        if self.anchored {
+            self.pattern.matches_with(path, MATCH_OPTIONS)
+        } else {
+            let name = path.rsplit('/').next().unwrap_or(path);
+            self.pattern.matches_with(name, MATCH_OPTIONS)
+        }
+    }
+}
+
+/// A parsed `.zizmorignore` file.
+#[derive(Default)]
+pub(crate) struct ZizmorIgnore {
+    rules: Vec<Rule>,
+}
+
+impl ZizmorIgnore {
+    /// Loads the `.zizmorignore` at the given input root, if present.
+    ///
+    /// If the input root has no `.zizmorignore`, the returned ignore file
+    /// excludes nothing.
+    pub(crate) fn load(root: &Utf8Path) -> Result<Self> {
+        let path = root.join(FILENAME);
+        if !path.is_file() {
+            return Ok(Self::default());
+        }
+
+        let contents =
+            std::fs::read_to_string(&path).with_context(|| format!("couldn't read {path}"))?;
+        Self::parse(&contents).with_context(|| format!("invalid ignore file: {path}"))
+    }
+
+    fn parse(contents: &str) -> Result<Self> {
+        let mut rules = vec![];
+        for line in contents.lines() {
+            rules.extend(Rule::parse(line)?);
+        }
+
+        Ok(Self { rules })
+    }
+
+    /// Returns whether the given root-relative path matches, ignoring
+    /// its parents.
+    fn matches(&self, path: &str, is_dir: bool) -> bool {
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| rule.matches(path, is_dir))
+            .is_some_and(|rule| !rule.negated)
+    }
+
+    /// Returns whether the given path, relative to the input root,
+    /// should be excluded from collection.
+    pub(crate) fn is_ignored(&self, path: &Utf8Path, is_dir: bool) -> bool {
+        if self.rules.is_empty() {
+            return false;
+        }
+
+        let components = path.components().map(|c| c.as_str()).collect::<Vec<_>>();
+        let mut prefix = String::new();
+        for (idx, component) in components.iter().enumerate() {
+            if !prefix.is_empty() {
+                prefix.push('/');
+            }
+            prefix.push_str(component);
+
+            let last = idx == components.len() - 1;
+            if self.matches(&prefix, !last || is_dir) {
+                return true;
+            }
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use camino::Utf8Path;
+
+    use super::ZizmorIgnore;
+
+    #[test]
+    fn test_is_ignored() {
+        let ignore = ZizmorIgnore::parse(
+            r#"
+# generated workflows
+.github/workflows/generated-*.yml
+!.github/workflows/generated-keep.yml
+
+examples/
+action.yaml
+/vendor/**/action.yml
+"#,
+        )
+        .unwrap();
+
+        for (path, is_dir, ignored) in [
+            (".github/workflows/ci.yml", false, false),
+            (".github/workflows/generated-foo.yml", false, true),
+            (".github/workflows/generated-keep.yml", false, false),
+            // Anchored patterns don't match at other depths.
+            ("sub/.github/workflows/generated-foo.yml", false, false),
+            // Directory-only patterns exclude everything beneath them.
+            ("examples", true, true),
+            ("examples/action.yml", false, true),
+            ("docs/examples/action.yml", false, true),
+            ("examples", false, false),
+            // Unanchored patterns match names at any depth.
+            ("action.yaml", false, true),
+            ("deep/nested/action.yaml", false, true),
+            ("deep/nested/action.yml", false, false),
+            ("vendor/foo/action.yml", false, true),
+            ("vendor/foo/bar/action.yml", false, true),
+            ("other/vendor/foo/action.yml", false, false),
+        ] {
+            assert_eq!(
+                ignore.is_ignored(Utf8Path::new(path), is_dir),
+                ignored,
+                "failed: {path}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_empty() {
+        let ignore = ZizmorIgnore::parse("\n# just a comment\n").unwrap();
+        assert!(!ignore.is_ignored(Utf8Path::new(".github/workflows/ci.yml"), false));
+    }
+}
@@ -36,6 +36,96 @@ fn catches_inlined_ignore() -> anyhow::Result<()> {
 
     assert_eq!(execution.status.code(), Some(0));
 
+    let output: Value = serde_json::from_slice(&execution.stdout)?;
+
+    assert_eq!(output["findings"], Value::Array(vec![]));
+    assert_eq!(output["summary"]["ignored"], 5);
+
+    Ok(())
+}
+
+#[test]
+fn ignores_finding_by_fingerprint() -> anyhow::Result<()> {
+    let auditable = workflow_under_test("artipacked.yml");
+
+    let execution = zizmor().args([&auditable]).output()?;
+    let output: Value = serde_json::from_slice(&execution.stdout)?;
+    let findings = output["findings"].as_array().unwrap();
+    let fingerprint = findings[0]["fingerprint"].as_str().unwrap();
+
+    let execution = zizmor()
+        .args(["--ignore-finding", fingerprint, &auditable])
+        .output()?;
+    let ignored: Value = serde_json::from_slice(&execution.stdout)?;
+
+    assert_eq!(
+        ignored["findings"].as_array().unwrap().len(),
+        findings.len() - 1
+    );
+    assert!(ignored["findings"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|f| f["fingerprint"] != fingerprint));
+    assert_eq!(ignored["summary"]["ignored"], 1);
+
+    Ok(())
+}
+
+#[test]
+fn fingerprints_dont_depend_on_input_path() -> anyhow::Result<()> {
+    let fingerprints = |input: &str| -> anyhow::Result<Vec<Value>> {
+        let execution = zizmor().args([input]).output()?;
+        let output: Value = serde_json::from_slice(&execution.stdout)?;
+
+        Ok(output["findings"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| f["fingerprint"].clone())
+            .collect())
+    };
+
+    let absolute = fingerprints(&workflow_under_test("artipacked.yml"))?;
+    assert!(!absolute.is_empty());
+    assert_eq!(absolute, fingerprints("tests/test-data/artipacked.yml")?);
+
+    Ok(())
+}
+
+#[test]
+fn audits_stdin_with_input_filename() -> anyhow::Result<()> {
+    let auditable = std::fs::read_to_string(workflow_under_test("artipacked.yml"))?;
+
+    let execution = zizmor()
+        .args(["--input-filename", ".github/workflows/artipacked.yml", "-"])
+        .write_stdin(auditable)
+        .output()?;
+
+    let output: Value = serde_json::from_slice(&execution.stdout)?;
+    let findings = output["findings"].as_array().unwrap();
+    assert!(!findings.is_empty());
+
+    assert_value_match(
+        &output,
+        "$.findings[0].locations[0].symbolic.key.Local.given_path",
+        ".github/workflows/artipacked.yml",
+    );
+
+    Ok(())
+}
+
+#[test]
+fn emits_legacy_json() -> anyhow::Result<()> {
+    let auditable = workflow_under_test("inlined-ignores.yml");
+
+    // Unlike the other tests, we need a format other than `json` here.
+    let execution = Command::cargo_bin("zizmor")?
+        .args(["--offline", "--format", "json-legacy", &auditable])
+        .output()?;
+
+    assert_eq!(execution.status.code(), Some(0));
+
     let findings = String::from_utf8(execution.stdout)?;
 
     assert_eq!(&findings, "[]");
@@ -54,16 +144,34 @@ fn audit_artipacked() -> anyhow::Result<()> {
 
     let findings = serde_json::from_slice(&execution.stdout)?;
 
-    assert_value_match(&findings, "$[0].determinations.confidence", "Low");
+    assert_value_match(&findings, "$.findings[0].determinations.confidence", "Low");
     assert_value_match(
         &findings,
-        "$[0].locations[0].concrete.feature",
+        "$.findings[0].locations[0].concrete.feature",
         "uses: actions/checkout@11bd71901bbe5b1630ceea73d27597364c9af683",
     );
 
     Ok(())
 }
 
+#[test]
+fn fail_on_threshold() -> anyhow::Result<()> {
+    let auditable = workflow_under_test("artipacked.yml");
+
+    // artipacked.yml's highest finding is medium, so failing on high
+    // succeeds, while still reporting every finding.
+    let execution = zizmor().args([&auditable, "--fail-on=high"]).output()?;
+    assert_eq!(execution.status.code(), Some(0));
+
+    let findings = serde_json::from_slice(&execution.stdout)?;
+    assert_value_match(&findings, "$.findings[0].ident", "artipacked");
+
+    let execution = zizmor().args([&auditable, "--fail-on=medium"]).output()?;
+    assert_eq!(execution.status.code(), Some(13));
+
+    Ok(())
+}
+
 #[test]
 fn audit_excessive_permission() -> anyhow::Result<()> {
     let auditable = workflow_under_test("excessive-permissions.yml");
@@ -75,16 +183,43 @@ fn audit_excessive_permission() -> anyhow::Result<()> {
 
     let findings = serde_json::from_slice(&execution.stdout)?;
 
-    assert_value_match(&findings, "$[0].determinations.confidence", "High");
+    assert_value_match(&findings, "$.findings[0].determinations.confidence", "High");
     assert_value_match(
         &findings,
-        "$[0].locations[0].concrete.feature",
+        "$.findings[0].locations[0].concrete.feature",
         "permissions: write-all",
     );
 
     Ok(())
 }
 
+#[test]
+fn audit_excessive_permission_explicit_scopes() -> anyhow::Result<()> {
+    let auditable = workflow_under_test("excessive-permissions/workflow-write-scopes.yml");
+    let cli_args = [&auditable];
+
+    let execution = zizmor().args(cli_args).output()?;
+
+    assert_eq!(execution.status.code(), Some(14));
+
+    let findings: Value = serde_json::from_slice(&execution.stdout)?;
+
+    // Only the `write` scopes are flagged, one finding each.
+    assert_eq!(findings["findings"].as_array().unwrap().len(), 2);
+    assert_value_match(
+        &findings,
+        "$.findings[0].locations[0].symbolic.annotation",
+        "contents: write is overly broad at the workflow level",
+    );
+    assert_value_match(
+        &findings,
+        "$.findings[1].locations[0].symbolic.annotation",
+        "issues: write is overly broad at the workflow level",
+    );
+
+    Ok(())
+}
+
 #[test]
 fn audit_hardcoded_credentials() -> anyhow::Result<()> {
     let auditable = workflow_under_test("hardcoded-credentials.yml");
@@ -96,10 +231,11 @@ fn audit_hardcoded_credentials() -> anyhow::Result<()> {
 
     let findings = serde_json::from_slice(&execution.stdout)?;
 
-    assert_value_match(&findings, "$[0].determinations.confidence", "High");
+    // Findings are sorted by location, so the unpinned image comes first.
+    assert_value_match(&findings, "$.findings[1].determinations.confidence", "High");
     assert_value_match(
         &findings,
-        "$[0].locations[0].concrete.feature",
+        "$.findings[1].locations[0].concrete.feature",
         "password: hackme",
     );
 
@@ -117,10 +253,10 @@ fn audit_template_injection() -> anyhow::Result<()> {
 
     let findings = serde_json::from_slice(&execution.stdout)?;
 
-    assert_value_match(&findings, "$[0].determinations.confidence", "High");
+    assert_value_match(&findings, "$.findings[0].determinations.confidence", "High");
     assert_value_match(
         &findings,
-        "$[0].locations[0].concrete.feature",
+        "$.findings[0].locations[0].concrete.feature",
         "${{ github.event.issue.title }}",
     );
 
@@ -138,16 +274,151 @@ fn audit_use_trusted_publishing() -> anyhow::Result<()> {
 
     let findings = serde_json::from_slice(&execution.stdout)?;
 
-    assert_value_match(&findings, "$[0].determinations.confidence", "High");
+    assert_value_match(&findings, "$.findings[0].determinations.confidence", "High");
     assert_value_match(
         &findings,
-        "$[0].locations[0].concrete.feature",
+        "$.findings[0].locations[0].concrete.feature",
         "uses: pypa/gh-action-pypi-publish@release/v1",
     );
 
     Ok(())
 }
 
+#[test]
+fn finding_span() -> anyhow::Result<()> {
+    let auditable = workflow_under_test("env-case-collision.yml");
+
+    let cli_args = ["--persona=pedantic", &auditable];
+
+    let execution = zizmor().args(cli_args).output()?;
+
+    assert_eq!(execution.status.code(), Some(12));
+
+    let output: Value = serde_json::from_slice(&execution.stdout)?;
+
+    // The first finding's locations are the job-level `PATH` (primary)
+    // and the earlier workflow-level `Path`, so its span runs from the
+    // start of the latter to the end of the former.
+    let finding = &output["findings"][0];
+    let locations = finding["locations"].as_array().unwrap();
+    assert_eq!(
+        finding["span"]["offset_span"]["start"],
+        locations[1]["concrete"]["location"]["offset_span"]["start"]
+    );
+    assert_eq!(
+        finding["span"]["offset_span"]["end"],
+        locations[0]["concrete"]["location"]["offset_span"]["end"]
+    );
+    assert_eq!(finding["span"]["start_point"]["row"], 7);
+    assert_eq!(finding["span"]["end_point"]["row"], 14);
+
+    Ok(())
+}
+
+#[test]
+fn sarif_related_locations() -> anyhow::Result<()> {
+    let auditable = workflow_under_test("pull-request-target-checkout.yml");
+
+    // Unlike the other tests, we need a format other than `json` here.
+    let execution = Command::cargo_bin("zizmor")?
+        .args(["--offline", "--format", "sarif", &auditable])
+        .output()?;
+
+    assert_eq!(execution.status.code(), Some(0));
+
+    let sarif: Value = serde_json::from_slice(&execution.stdout)?;
+
+    // The first result is a `dangerous-triggers` finding, which has
+    // no supporting locations.
+    let results = &sarif["runs"][0]["results"];
+    assert_eq!(results[0]["ruleId"], "dangerous-triggers");
+    assert!(results[0].get("relatedLocations").is_none());
+
+    // The primary location is the result's location, and each
+    // supporting location is a related location with an ID.
+    assert_eq!(results[1]["ruleId"], "pull-request-target-checkout");
+    assert_value_match(
+        &sarif,
+        "$.runs[0].results[1].locations[0].message.text",
+        "repository is checked out in a pull_request_target workflow",
+    );
+    assert_value_match(&sarif, "$.runs[0].results[1].relatedLocations[0].id", "1");
+    assert_value_match(
+        &sarif,
+        "$.runs[0].results[1].relatedLocations[0].message.text",
+        "checked-out code is built or run here",
+    );
+    assert_value_match(&sarif, "$.runs[0].results[1].relatedLocations[1].id", "2");
+    assert_value_match(
+        &sarif,
+        "$.runs[0].results[1].relatedLocations[1].message.text",
+        "privileged trigger",
+    );
+
+    Ok(())
+}
+
+#[test]
+fn sarif_fixes() -> anyhow::Result<()> {
+    let auditable = workflow_under_test("use-trusted-publishing-release-gem.yml");
+
+    // Unlike the other tests, we need a format other than `json` here.
+    let execution = Command::cargo_bin("zizmor")?
+        .args(["--offline", "--format", "sarif", &auditable])
+        .output()?;
+
+    assert_eq!(execution.status.code(), Some(0));
+
+    let sarif: Value = serde_json::from_slice(&execution.stdout)?;
+
+    // The fix replaces the `setup-trusted-publisher: false` input.
+    let results = &sarif["runs"][0]["results"];
+    assert_eq!(results[0]["ruleId"], "use-trusted-publishing");
+
+    let replacement = &results[0]["fixes"][0]["artifactChanges"][0]["replacements"][0];
+    assert_eq!(replacement["deletedRegion"]["startLine"], 11);
+    assert_eq!(replacement["deletedRegion"]["startColumn"], 11);
+    assert_eq!(replacement["deletedRegion"]["endColumn"], 41);
+    assert_eq!(
+        replacement["insertedContent"]["text"],
+        "setup-trusted-publisher: true"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn sarif_category() -> anyhow::Result<()> {
+    let auditable = workflow_under_test("pull-request-target-checkout.yml");
+
+    // By default, runs are tagged with the `zizmor` category.
+    let execution = Command::cargo_bin("zizmor")?
+        .args(["--offline", "--format", "sarif", &auditable])
+        .output()?;
+
+    let sarif: Value = serde_json::from_slice(&execution.stdout)?;
+    assert_eq!(sarif["runs"][0]["automationDetails"]["id"], "zizmor");
+
+    let execution = Command::cargo_bin("zizmor")?
+        .args([
+            "--offline",
+            "--format",
+            "sarif",
+            "--sarif-category",
+            "zizmor/workflows",
+            &auditable,
+        ])
+        .output()?;
+
+    let sarif: Value = serde_json::from_slice(&execution.stdout)?;
+    assert_eq!(
+        sarif["runs"][0]["automationDetails"]["id"],
+        "zizmor/workflows"
+    );
+
+    Ok(())
+}
+
 #[test]
 fn audit_self_hosted() -> anyhow::Result<()> {
     let auditable = workflow_under_test("self-hosted.yml");
@@ -161,10 +432,10 @@ fn audit_self_hosted() -> anyhow::Result<()> {
 
     let findings = serde_json::from_slice(&execution.stdout)?;
 
-    assert_value_match(&findings, "$[0].determinations.confidence", "High");
+    assert_value_match(&findings, "$.findings[0].determinations.confidence", "High");
     assert_value_match(
         &findings,
-        "$[0].locations[0].concrete.feature",
+        "$.findings[0].locations[0].concrete.feature",
         "runs-on: [self-hosted, my-ubuntu-box]",
     );
 
@@ -183,26 +454,26 @@ fn audit_unpinned_uses() -> anyhow::Result<()> {
 
     let findings = serde_json::from_slice(&execution.stdout)?;
 
-    assert_value_match(&findings, "$[0].determinations.confidence", "High");
-    assert_value_match(&findings, "$[0].determinations.severity", "Medium");
+    assert_value_match(&findings, "$.findings[0].determinations.confidence", "High");
+    assert_value_match(&findings, "$.findings[0].determinations.severity", "Medium");
     assert_value_match(
         &findings,
-        "$[0].locations[0].concrete.feature",
+        "$.findings[0].locations[0].concrete.feature",
         "uses: actions/checkout",
     );
     assert_value_match(
         &findings,
-        "$[1].locations[0].concrete.feature",
+        "$.findings[1].locations[0].concrete.feature",
         "uses: github/codeql-action/upload-sarif",
     );
     assert_value_match(
         &findings,
-        "$[2].locations[0].concrete.feature",
+        "$.findings[2].locations[0].concrete.feature",
         "uses: docker://ubuntu",
     );
     assert_value_match(
         &findings,
-        "$[3].locations[0].concrete.feature",
+        "$.findings[3].locations[0].concrete.feature",
         "uses: docker://ghcr.io/pypa/gh-action-pypi-publish",
     );
 
@@ -221,10 +492,10 @@ fn audit_insecure_commands_allowed() -> anyhow::Result<()> {
 
     let findings = serde_json::from_slice(&execution.stdout)?;
 
-    assert_value_match(&findings, "$[0].determinations.confidence", "High");
+    assert_value_match(&findings, "$.findings[0].determinations.confidence", "High");
     assert_value_match(
         &findings,
-        "$[0].locations[0].concrete.feature",
+        "$.findings[0].locations[0].concrete.feature",
         "ACTIONS_ALLOW_UNSECURE_COMMANDS",
     );
 
@@ -243,10 +514,10 @@ fn audit_github_env_injection() -> anyhow::Result<()> {
 
     let findings = serde_json::from_slice(&execution.stdout)?;
 
-    assert_value_match(&findings, "$[0].determinations.confidence", "Low");
+    assert_value_match(&findings, "$.findings[0].determinations.confidence", "Low");
     assert_value_match(
         &findings,
-        "$[0].locations[0].concrete.feature",
+        "$.findings[0].locations[0].concrete.feature",
         "GITHUB_ENV",
     );
 
@@ -265,8 +536,334 @@ fn audit_cache_poisoning() -> anyhow::Result<()> {
 
     let findings = serde_json::from_slice(&execution.stdout)?;
 
-    assert_value_match(&findings, "$[0].determinations.confidence", "Low");
-    assert_value_match(&findings, "$[0].locations[0].concrete.feature", "release");
+    assert_value_match(&findings, "$.findings[0].determinations.confidence", "Low");
+    assert_value_match(
+        &findings,
+        "$.findings[0].locations[0].concrete.feature",
+        "release",
+    );
+
+    Ok(())
+}
+
+#[test]
+fn collects_glob_inputs() -> anyhow::Result<()> {
+    let auditable = workflow_under_test("cache-poisoning/caching-opt-in-*.yml");
+
+    let cli_args = [&auditable];
+
+    let execution = zizmor().args(cli_args).output()?;
+
+    assert_eq!(execution.status.code(), Some(14));
+
+    let output: Value = serde_json::from_slice(&execution.stdout)?;
+
+    // One cache-poisoning finding from each of the four matched workflows,
+    // plus a secret-in-args finding from one of them.
+    assert_eq!(output["findings"].as_array().map(Vec::len), Some(5));
+
+    Ok(())
+}
+
+#[test]
+fn rejects_glob_without_matches() -> anyhow::Result<()> {
+    let auditable = workflow_under_test("does-not-exist/*.yml");
+
+    let cli_args = [&auditable];
+
+    let execution = zizmor().args(cli_args).output()?;
+
+    assert_eq!(execution.status.code(), Some(1));
+
+    let stderr = String::from_utf8(execution.stderr)?;
+
+    assert!(stderr.contains("no files matched"));
+
+    Ok(())
+}
+
+#[test]
+fn loads_ca_bundle() -> anyhow::Result<()> {
+    let auditable = workflow_under_test("artipacked.yml");
+    let ca_bundle = workflow_under_test("ca-bundle.pem");
+
+    let execution = zizmor()
+        .args(["--ca-bundle", &ca_bundle, &auditable])
+        .output()?;
+
+    assert_eq!(execution.status.code(), Some(13));
+
+    Ok(())
+}
+
+#[test]
+fn rejects_invalid_ca_bundle() -> anyhow::Result<()> {
+    let auditable = workflow_under_test("artipacked.yml");
+
+    // Missing.
+    let execution = zizmor()
+        .args(["--ca-bundle", "does-not-exist.pem", &auditable])
+        .output()?;
+
+    assert_eq!(execution.status.code(), Some(1));
+    let stderr = String::from_utf8(execution.stderr)?;
+    assert!(stderr.contains("couldn't read CA bundle: does-not-exist.pem"));
+
+    // Not a PEM bundle.
+    let execution = zizmor()
+        .args(["--ca-bundle", &auditable, &auditable])
+        .output()?;
+
+    assert_eq!(execution.status.code(), Some(1));
+    let stderr = String::from_utf8(execution.stderr)?;
+    assert!(stderr.contains("invalid CA bundle"));
+    assert!(stderr.contains("no PEM-encoded certificates found"));
+
+    Ok(())
+}
+
+#[test]
+fn deterministic_json_output() -> anyhow::Result<()> {
+    let auditable = workflow_under_test("cache-poisoning/*.yml");
+
+    let cli_args = [auditable.as_str(), "--persona=auditor"];
+
+    let first = zizmor().args(cli_args).output()?;
+    let second = zizmor().args(cli_args).output()?;
+
+    assert_eq!(first.status.code(), Some(14));
+    assert_eq!(first.stdout, second.stdout);
+
+    // Findings are ordered by input, then by their primary location.
+    let output: Value = serde_json::from_slice(&first.stdout)?;
+    let keys = output["findings"]
+        .as_array()
+        .expect("findings should be an array")
+        .iter()
+        .map(|finding| {
+            let primary = finding["locations"]
+                .as_array()
+                .and_then(|locations| {
+                    locations
+                        .iter()
+                        .find(|l| l["symbolic"]["primary"].as_bool() == Some(true))
+                })
+                .expect("finding should have a primary location");
+
+            (
+                primary["symbolic"]["key"]["Local"]["given_path"]
+                    .as_str()
+                    .map(str::to_string),
+                primary["concrete"]["location"]["offset_span"]["start"].as_u64(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert!(keys.len() > 1);
+    assert!(keys.windows(2).all(|pair| pair[0] <= pair[1]));
+
+    Ok(())
+}
+
+#[test]
+fn continues_past_errored_inputs() -> anyhow::Result<()> {
+    let auditable = workflow_under_test("errored-inputs");
+
+    let execution = zizmor().args([&auditable]).output()?;
+
+    // The valid input is still audited, and the invalid one is signaled
+    // with its own exit code bit: 13 (medium) | 32 (errored inputs).
+    assert_eq!(execution.status.code(), Some(45));
+
+    let output: Value = serde_json::from_slice(&execution.stdout)?;
+    assert_value_match(&output, "$.findings[0].ident", "legacy-commands");
+
+    let stderr = String::from_utf8(execution.stderr)?;
+    assert!(stderr.contains("errored inputs (1)"));
+    assert!(stderr.contains("invalid.yml"));
+
+    // With `--strict-collection`, the first invalid input fails the run.
+    let execution = zizmor()
+        .args(["--strict-collection", &auditable])
+        .output()?;
+
+    assert_eq!(execution.status.code(), Some(1));
+    assert!(execution.stdout.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn emits_ndjson() -> anyhow::Result<()> {
+    let execution = Command::cargo_bin("zizmor")?
+        .args([
+            "--offline",
+            "--format",
+            "ndjson",
+            &workflow_under_test("artipacked.yml"),
+            &workflow_under_test("hardcoded-credentials.yml"),
+        ])
+        .output()?;
+
+    // Exit codes still reflect the streamed findings.
+    assert_eq!(execution.status.code(), Some(14));
+
+    let stdout = String::from_utf8(execution.stdout)?;
+    let findings = stdout
+        .lines()
+        .map(serde_json::from_str::<Value>)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    assert_eq!(findings.len(), 3);
+    assert_eq!(findings[0]["ident"], "artipacked");
+    assert_eq!(findings[2]["ident"], "hardcoded-container-credentials");
+
+    Ok(())
+}
+
+#[test]
+fn sarif_repo_relative_paths() -> anyhow::Result<()> {
+    // NOTE: This is an absolute path.
+    let auditable = workflow_under_test("pull-request-target-checkout.yml");
+    let repo_root = format!("{}/tests", env!("CARGO_MANIFEST_DIR"));
+
+    // Inputs are reported relative to the repository root.
+    let execution = Command::cargo_bin("zizmor")?
+        .args([
+            "--offline",
+            "--format",
+            "sarif",
+            "--repo-root",
+            &repo_root,
+            &auditable,
+        ])
+        .output()?;
+
+    let sarif: Value = serde_json::from_slice(&execution.stdout)?;
+    assert_eq!(
+        sarif["runs"][0]["results"][0]["locations"][0]["physicalLocation"]["artifactLocation"]
+            ["uri"],
+        "test-data/pull-request-target-checkout.yml"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn audits_inputs_from_manifest() -> anyhow::Result<()> {
+    let execution = zizmor()
+        .args(["--inputs-from", "tests/test-data/inputs-from.txt"])
+        .output()?;
+
+    // Both listed files are audited, and the missing one is skipped.
+    assert_eq!(execution.status.code(), Some(14));
+
+    let output: Value = serde_json::from_slice(&execution.stdout)?;
+    let mut paths = output["findings"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|finding| {
+            finding["locations"][0]["symbolic"]["key"]["Local"]["given_path"]
+                .as_str()
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+    paths.sort();
+    paths.dedup();
+    assert_eq!(
+        paths,
+        [
+            "tests/test-data/bot-conditions.yml",
+            "tests/test-data/insecure-commands.yml",
+        ]
+    );
+
+    let stderr = String::from_utf8(execution.stderr)?;
+    assert!(stderr.contains("skipping tests/test-data/does-not-exist.yml"));
+
+    Ok(())
+}
+
+#[test]
+fn audits_empty_inputs_from_manifest() -> anyhow::Result<()> {
+    // A manifest that only lists deleted files is an empty run, not an error.
+    let execution = zizmor()
+        .args(["--inputs-from", "tests/test-data/inputs-from-deleted.txt"])
+        .output()?;
+
+    assert_eq!(execution.status.code(), Some(0));
+
+    let output: Value = serde_json::from_slice(&execution.stdout)?;
+    assert!(output["findings"].as_array().unwrap().is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn loads_config_from_env() -> anyhow::Result<()> {
+    use base64::{prelude::BASE64_STANDARD, Engine as _};
+
+    let auditable = workflow_under_test("insecure-commands.yml");
+    let config = "rules:\n  insecure-commands:\n    ignore:\n      - insecure-commands.yml\n";
+
+    // Without the config, the workflow has a finding.
+    let execution = zizmor().args([&auditable]).output()?;
+    assert_eq!(execution.status.code(), Some(14));
+
+    for value in [config.to_string(), BASE64_STANDARD.encode(config)] {
+        let execution = zizmor()
+            .env("ZIZMOR_CONFIG", value)
+            .args(["--config-from-env", &auditable])
+            .output()?;
+        assert_eq!(execution.status.code(), Some(0));
+    }
+
+    let execution = zizmor()
+        .env_remove("ZIZMOR_CONFIG")
+        .args(["--config-from-env", &auditable])
+        .output()?;
+    assert_eq!(execution.status.code(), Some(1));
+    let stderr = String::from_utf8(execution.stderr)?;
+    assert!(stderr.contains("couldn't read config from $ZIZMOR_CONFIG"));
+
+    // The config can only come from one place.
+    let execution = zizmor()
+        .args(["--config-from-env", "--config", "zizmor.yml", &auditable])
+        .output()?;
+    assert_eq!(execution.status.code(), Some(2));
+
+    Ok(())
+}
+
+#[test]
+fn color_mode() -> anyhow::Result<()> {
+    let auditable = workflow_under_test("insecure-commands.yml");
+
+    let plain = |color: &str| -> anyhow::Result<(String, String)> {
+        // Unlike the other tests, we need the plain format here.
+        let execution = Command::cargo_bin("zizmor")?
+            .env_remove("NO_COLOR")
+            .args(["--offline", "--no-progress", "--color", color, &auditable])
+            .output()?;
+        assert_eq!(execution.status.code(), Some(14));
+
+        Ok((
+            String::from_utf8(execution.stdout)?,
+            String::from_utf8(execution.stderr)?,
+        ))
+    };
+
+    // Output isn't a terminal, so it's only colorized when forced.
+    for color in ["auto", "never"] {
+        let (stdout, stderr) = plain(color)?;
+        assert!(!stdout.contains('\x1b'));
+        assert!(!stderr.contains('\x1b'));
+    }
+
+    let (stdout, stderr) = plain("always")?;
+    assert!(stdout.contains('\x1b'));
+    assert!(stderr.contains('\x1b'));
 
     Ok(())
 }
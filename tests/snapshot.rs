@@ -138,6 +138,124 @@ fn test_invalid_inputs() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_quiet_stderr() -> Result<()> {
+    // Auditing with `--quiet` shouldn't produce any stderr output at all,
+    // e.g. from leftover debugging statements.
+    for workflow in ["overprovisioned-secrets.yml", "secret-in-url.yml"] {
+        assert_eq!(
+            zizmor()
+                .output(OutputMode::Stderr)
+                .workflow(workflow_under_test(workflow))
+                .args(["--quiet"])
+                .run()?,
+            ""
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_skipped_audits_note() -> Result<()> {
+    // Skipped audits are summarized once on stderr, but only for the
+    // human-readable output format.
+    let stderr = zizmor()
+        .output(OutputMode::Stderr)
+        .workflow(workflow_under_test("secret-in-url.yml"))
+        .run()?;
+    assert_eq!(stderr.matches("skipped 3 audits").count(), 1);
+    assert!(stderr.contains(
+        "can't run without a GitHub API token: impostor-commit, ref-confusion, known-vulnerable-actions"
+    ));
+    assert!(stderr.contains("try removing --offline and passing --gh-token <TOKEN>"));
+
+    for format in ["--format=json", "--format=sarif"] {
+        let stderr = zizmor()
+            .output(OutputMode::Stderr)
+            .workflow(workflow_under_test("secret-in-url.yml"))
+            .args([format])
+            .run()?;
+        assert!(!stderr.contains("skipped"));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_run_summary() -> Result<()> {
+    // Every format gets a one-line summary of the run on stderr.
+    for format in ["--format=plain", "--format=json", "--format=sarif"] {
+        let stderr = zizmor()
+            .output(OutputMode::Stderr)
+            .workflow(workflow_under_test("bot-conditions.yml"))
+            .args([format])
+            .run()?;
+        assert_eq!(stderr.matches("audited 1 input with").count(), 1);
+        assert!(stderr.contains("5 findings (0 unknown, 0 informational, 0 low, 0 medium, 5 high)"));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_grouped_output() -> Result<()> {
+    // Findings from more than one input are grouped under per-input
+    // headers, with a per-input rollup after the summary.
+    let first = workflow_under_test("grouped-output/first.yml");
+    insta::assert_snapshot!(zizmor()
+        .offline(true)
+        .args([first.as_str()])
+        .workflow(workflow_under_test("grouped-output/second.yml"))
+        .run()?
+        .replace(&workflow_under_test(""), ""));
+
+    Ok(())
+}
+
+#[test]
+fn test_github_output() -> Result<()> {
+    // NOTE: Annotations are relative to the repository root, which we
+    // pin here so that they don't depend on where the tests are run from.
+    let repo_root = env!("CARGO_MANIFEST_DIR");
+
+    insta::assert_snapshot!(zizmor()
+        .offline(true)
+        .workflow(workflow_under_test("insecure-commands.yml"))
+        .args(["--format=github", "--repo-root", repo_root])
+        .run()?);
+
+    insta::assert_snapshot!(zizmor()
+        .offline(true)
+        .workflow(workflow_under_test("bot-conditions.yml"))
+        .args(["--format=github", "--repo-root", repo_root])
+        .run()?);
+
+    Ok(())
+}
+
+#[test]
+fn test_context_lines() -> Result<()> {
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("pull-request-target-checkout.yml"))
+        .args(["--context=1"])
+        .run()?);
+
+    Ok(())
+}
+
+#[test]
+fn test_filtered_summary() -> Result<()> {
+    // Findings below `--min-severity` are counted as filtered, rather
+    // than ignored.
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("unpinned-uses.yml"))
+        .args(["--pedantic", "--min-severity=medium"])
+        .run()?);
+
+    Ok(())
+}
+
 #[test]
 fn artipacked() -> Result<()> {
     insta::assert_snapshot!(zizmor()
@@ -159,6 +277,15 @@ fn artipacked() -> Result<()> {
         .args(["--persona=auditor"])
         .run()?);
 
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("artipacked/workspace-upload.yml"))
+        .args(["--persona=auditor"])
+        .run()?);
+
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("artipacked/checkout-push.yml"))
+        .run()?);
+
     Ok(())
 }
 
@@ -214,6 +341,20 @@ fn self_hosted() -> Result<()> {
         .args(["--persona=auditor"])
         .run()?);
 
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test(
+            "self-hosted/self-hosted-matrix-include-runner.yml"
+        ))
+        .args(["--persona=auditor"])
+        .run()?);
+
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test(
+            "self-hosted/self-hosted-matrix-github-hosted.yml"
+        ))
+        .args(["--persona=auditor"])
+        .run()?);
+
     Ok(())
 }
 
@@ -238,6 +379,15 @@ fn unpinned_uses() -> Result<()> {
         .args(["--pedantic"])
         .run()?);
 
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("unpinned-uses/docker-tags.yml"))
+        .args(["--pedantic"])
+        .run()?);
+
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("unpinned-uses/abbreviated-sha.yml"))
+        .run()?);
+
     Ok(())
 }
 
@@ -306,6 +456,26 @@ fn template_injection() -> Result<()> {
         ))
         .run()?);
 
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("template-injection/typed-inputs.yml"))
+        .run()?);
+
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test(
+            "template-injection/github-script-code-position.yml"
+        ))
+        .run()?);
+
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("template-injection/trigger-trace.yml"))
+        .run()?);
+
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test(
+            "template-injection/composite-shells/action.yml"
+        ))
+        .run()?);
+
     Ok(())
 }
 
@@ -387,6 +557,19 @@ fn cache_poisoning() -> Result<()> {
         .workflow(workflow_under_test("cache-poisoning/issue-378-repro.yml"))
         .run()?);
 
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test(
+            "cache-poisoning/controllable-cache-key.yml"
+        ))
+        .run()?);
+
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test(
+            "cache-poisoning/key-without-hashfiles.yml"
+        ))
+        .args(["--pedantic"])
+        .run()?);
+
     Ok(())
 }
 
@@ -466,6 +649,12 @@ fn excessive_permissions() -> Result<()> {
         ))
         .run()?);
 
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test(
+            "excessive-permissions/pull-request-write.yml"
+        ))
+        .run()?);
+
     Ok(())
 }
 
@@ -483,6 +672,10 @@ fn github_env() -> Result<()> {
         .workflow(workflow_under_test("github-env/issue-397-repro.yml"))
         .run()?);
 
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("github-env/sensitive-vars.yml"))
+        .run()?);
+
     Ok(())
 }
 
@@ -492,6 +685,15 @@ fn secrets_inherit() -> Result<()> {
         .workflow(workflow_under_test("secrets-inherit.yml"))
         .run()?);
 
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("secrets-inherit-local"))
+        .run()?);
+
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("secrets-inherit.yml"))
+        .args(["--pedantic"])
+        .run()?);
+
     Ok(())
 }
 
@@ -512,3 +714,502 @@ fn overprovisioned_secrets() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn secret_in_url() -> Result<()> {
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("secret-in-url.yml"))
+        .run()?);
+
+    Ok(())
+}
+
+#[test]
+fn bundled_script_args() -> Result<()> {
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("bundled-script-args/action.yml"))
+        .run()?);
+
+    Ok(())
+}
+
+#[test]
+fn untrusted_checkout_secrets() -> Result<()> {
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("untrusted-checkout-secrets.yml"))
+        .run()?);
+
+    Ok(())
+}
+
+#[test]
+fn unused_id_token() -> Result<()> {
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("unused-id-token/unused-id-token.yml"))
+        .args(["--pedantic"])
+        .run()?);
+
+    // With `example/oidc-login` configured as an additional OIDC consumer.
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("unused-id-token/unused-id-token.yml"))
+        .args([
+            "--pedantic",
+            "--config",
+            &workflow_under_test("unused-id-token/zizmor.yml"),
+        ])
+        .run()?);
+
+    Ok(())
+}
+
+#[test]
+fn unpinned_images() -> Result<()> {
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("unpinned-images.yml"))
+        .run()?);
+
+    Ok(())
+}
+
+#[test]
+fn hashfiles_args() -> Result<()> {
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("hashfiles-args.yml"))
+        .args(["--pedantic"])
+        .run()?);
+
+    Ok(())
+}
+
+#[test]
+fn unpinned_reusable_workflows() -> Result<()> {
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("unpinned-reusable-workflows.yml"))
+        .run()?);
+
+    Ok(())
+}
+
+#[test]
+fn test_zizmorignore() -> Result<()> {
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("zizmorignore"))
+        .args(["--pedantic"])
+        .run()?);
+
+    Ok(())
+}
+
+#[test]
+fn test_submodules() -> Result<()> {
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("submodules"))
+        .args(["--pedantic"])
+        .run()?);
+
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("submodules"))
+        .args(["--pedantic", "--include-submodules"])
+        .run()?);
+
+    Ok(())
+}
+
+#[test]
+fn pull_request_target_checkout() -> Result<()> {
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("pull-request-target-checkout.yml"))
+        .run()?);
+
+    Ok(())
+}
+
+#[test]
+fn controllable_conditions() -> Result<()> {
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("controllable-conditions.yml"))
+        .args(["--pedantic"])
+        .run()?);
+
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("controllable-conditions.yml"))
+        .args(["--persona=developer"])
+        .run()?);
+
+    Ok(())
+}
+
+#[test]
+fn secret_in_args() -> Result<()> {
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("secret-in-args.yml"))
+        .run()?);
+
+    Ok(())
+}
+
+#[test]
+fn known_vulnerable_actions_offline() -> Result<()> {
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test(
+            "known-vulnerable-actions/offline-advisory-db.yml"
+        ))
+        .args([
+            "--advisory-db",
+            &workflow_under_test("known-vulnerable-actions/advisories.json"),
+        ])
+        .run()?);
+
+    Ok(())
+}
+
+#[test]
+fn checkout_pat() -> Result<()> {
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("checkout-pat/pull-request.yml"))
+        .run()?);
+
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("checkout-pat/push.yml"))
+        .run()?);
+
+    Ok(())
+}
+
+#[test]
+fn env_case_collision() -> Result<()> {
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("env-case-collision.yml"))
+        .args(["--persona=pedantic"])
+        .run()?);
+
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("env-case-collision.yml"))
+        .run()?);
+
+    Ok(())
+}
+
+#[test]
+fn legacy_commands() -> Result<()> {
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("legacy-commands.yml"))
+        .run()?);
+
+    Ok(())
+}
+
+#[test]
+fn correlate() -> Result<()> {
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("correlate.yml"))
+        .args(["--correlate"])
+        .run()?);
+
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("correlate.yml"))
+        .run()?);
+
+    Ok(())
+}
+
+#[test]
+fn dynamic_runs_on() -> Result<()> {
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("dynamic-runs-on.yml"))
+        .run()?);
+
+    Ok(())
+}
+
+#[test]
+fn ignore_ranges() -> Result<()> {
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("ignore-ranges.yml"))
+        .run()?);
+
+    Ok(())
+}
+
+#[test]
+fn artifact_poisoning() -> Result<()> {
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("artifact-poisoning.yml"))
+        .run()?);
+
+    Ok(())
+}
+
+#[test]
+fn actor_conditions() -> Result<()> {
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("actor-conditions.yml"))
+        .args(["--persona=pedantic"])
+        .run()?);
+
+    Ok(())
+}
+
+#[test]
+fn fork_pr_token() -> Result<()> {
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("fork-pr-token.yml"))
+        .run()?);
+
+    Ok(())
+}
+
+#[test]
+fn github_output_audit() -> Result<()> {
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("github-output.yml"))
+        .run()?);
+
+    Ok(())
+}
+
+#[test]
+fn secret_in_inputs() -> Result<()> {
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("secret-in-inputs.yml"))
+        .run()?);
+
+    Ok(())
+}
+
+#[test]
+fn secret_exfiltration() -> Result<()> {
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test(
+            "secret-exfiltration/secret-exfiltration.yml"
+        ))
+        .args(["--persona=auditor"])
+        .run()?);
+
+    // With `example.com` configured as an allowed host.
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test(
+            "secret-exfiltration/secret-exfiltration.yml"
+        ))
+        .args([
+            "--persona=auditor",
+            "--config",
+            &workflow_under_test("secret-exfiltration/zizmor.yml"),
+        ])
+        .run()?);
+
+    Ok(())
+}
+
+#[test]
+fn duplicate_step_ids() -> Result<()> {
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("duplicate-step-ids.yml"))
+        .run()?);
+
+    Ok(())
+}
+
+#[test]
+fn stale_branch_workflows() -> Result<()> {
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("stale-branch-workflows.yml"))
+        .args(["--pedantic"])
+        .run()?);
+
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("stale-branch-workflows-ignore.yml"))
+        .args(["--pedantic"])
+        .run()?);
+
+    Ok(())
+}
+
+#[test]
+fn checkout_submodules() -> Result<()> {
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("checkout-submodules/pull-request.yml"))
+        .run()?);
+
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("checkout-submodules/push.yml"))
+        .run()?);
+
+    Ok(())
+}
+
+#[test]
+fn controllable_concurrency() -> Result<()> {
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("controllable-concurrency.yml"))
+        .run()?);
+
+    Ok(())
+}
+
+#[test]
+fn insecure_transport() -> Result<()> {
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("insecure-transport.yml"))
+        .run()?);
+
+    Ok(())
+}
+
+#[test]
+fn auto_merge() -> Result<()> {
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("auto-merge/auto-merge.yml"))
+        .args(["--persona=auditor"])
+        .run()?);
+
+    // With an extra action and command configured.
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("auto-merge/auto-merge.yml"))
+        .args([
+            "--persona=auditor",
+            "--config",
+            &workflow_under_test("auto-merge/zizmor.yml"),
+        ])
+        .run()?);
+
+    // Auditor-only.
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("auto-merge/auto-merge.yml"))
+        .run()?);
+
+    Ok(())
+}
+
+#[test]
+fn tainted_env() -> Result<()> {
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("tainted-env.yml"))
+        .run()?);
+
+    Ok(())
+}
+
+#[test]
+fn secret_in_logs() -> Result<()> {
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("secret-in-logs.yml"))
+        .run()?);
+
+    Ok(())
+}
+
+#[test]
+fn cross_org_reusable_workflows() -> Result<()> {
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("cross-org-reusable-workflows.yml"))
+        .args(["--pedantic"])
+        .run()?);
+
+    Ok(())
+}
+
+#[test]
+fn controllable_setup_inputs() -> Result<()> {
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("controllable-setup-inputs.yml"))
+        .run()?);
+
+    Ok(())
+}
+
+#[test]
+fn unknown_permissions() -> Result<()> {
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("unknown-permissions.yml"))
+        .run()?);
+
+    Ok(())
+}
+
+#[test]
+fn reducible_permissions() -> Result<()> {
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test(
+            "reducible-permissions/reducible-permissions.yml"
+        ))
+        .args(["--persona=auditor"])
+        .run()?);
+
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test(
+            "reducible-permissions/reducible-permissions.yml"
+        ))
+        .args([
+            "--persona=auditor",
+            "--config",
+            &workflow_under_test("reducible-permissions/zizmor.yml"),
+        ])
+        .run()?);
+
+    Ok(())
+}
+
+#[test]
+fn unnecessary_sudo() -> Result<()> {
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("unnecessary-sudo/unnecessary-sudo.yml"))
+        .args(["--pedantic"])
+        .run()?);
+
+    insta::assert_snapshot!(zizmor()
+        .workflow(workflow_under_test("unnecessary-sudo/unnecessary-sudo.yml"))
+        .args([
+            "--pedantic",
+            "--config",
+            &workflow_under_test("unnecessary-sudo/zizmor.yml"),
+        ])
+        .run()?);
+
+    Ok(())
+}
+
+#[test]
+fn explain() -> Result<()> {
+    insta::assert_snapshot!(zizmor().args(["--explain", "artipacked"]).run()?);
+
+    let stderr = zizmor()
+        .output(OutputMode::Stderr)
+        .args(["--explain", "not-an-audit"])
+        .run()?;
+    assert!(stderr.contains("unknown audit: not-an-audit"));
+
+    Ok(())
+}
+
+#[test]
+fn explain_exit_codes() -> Result<()> {
+    insta::assert_snapshot!(zizmor().args(["--explain-exit-codes"]).run()?);
+
+    Ok(())
+}
+
+#[test]
+fn severity_counts_only() -> Result<()> {
+    insta::assert_snapshot!(zizmor()
+        .offline(true)
+        .workflow(workflow_under_test("bot-conditions.yml"))
+        .args(["--severity-counts-only"])
+        .run()?);
+
+    insta::assert_snapshot!(zizmor()
+        .offline(true)
+        .workflow(workflow_under_test("bot-conditions.yml"))
+        .args(["--severity-counts-only", "--format=json"])
+        .run()?);
+
+    let stderr = zizmor()
+        .output(OutputMode::Stderr)
+        .offline(true)
+        .workflow(workflow_under_test("bot-conditions.yml"))
+        .args(["--severity-counts-only", "--format=sarif"])
+        .run()?;
+    assert!(stderr.contains("--severity-counts-only only supports the plain and JSON formats"));
+
+    Ok(())
+}